@@ -0,0 +1,79 @@
+use crate::db::Db;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DEFAULT_SWAPS_LIMIT: u32 = 100;
+const MAX_SWAPS_LIMIT: u32 = 1000;
+
+/**
+ * Serves the JSON API over the local model, read only:
+ * - GET /pools, the indexed pools with their last price, tick and indexed state version
+ * - GET /pools/{pool}, one of them
+ * - GET /pools/{pool}/positions, the open positions
+ * - GET /pools/{pool}/ticks, the used ticks
+ * - GET /pools/{pool}/swaps?limit={n}, the latest swaps, 100 by default and at most 1000
+ * - GET /pools/{pool}/volumes, the daily volumes and fees by input resource
+ */
+pub fn serve(listen_addr: &str, db: Db) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let server = Server::http(listen_addr)?;
+    for request in server.incoming_requests() {
+        let (status, body) = route(&db, &request);
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+        if let Err(err) = request.respond(response) {
+            eprintln!("Failed to respond: {}", err);
+        }
+    }
+    Ok(())
+}
+
+fn route(db: &Db, request: &Request) -> (u16, String) {
+    if *request.method() != Method::Get {
+        return error(405, "Method not allowed");
+    }
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let result = match segments.as_slice() {
+        ["pools"] => db.pools().map(|pools| json(&pools)),
+        ["pools", pool] => db.pool(pool).map(|pool| match pool {
+            Some(pool) => json(&pool),
+            None => error(404, "Pool not indexed"),
+        }),
+        ["pools", pool, "positions"] => db.positions(pool).map(|positions| json(&positions)),
+        ["pools", pool, "ticks"] => db.ticks(pool).map(|ticks| json(&ticks)),
+        ["pools", pool, "swaps"] => db.swaps(pool, swaps_limit(query)).map(|swaps| json(&swaps)),
+        ["pools", pool, "volumes"] => db.volumes(pool).map(|volumes| json(&volumes)),
+        _ => Ok(error(404, "Not found")),
+    };
+    result.unwrap_or_else(|err| error(500, &err.to_string()))
+}
+
+fn swaps_limit(query: &str) -> u32 {
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("limit="))
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(DEFAULT_SWAPS_LIMIT)
+        .min(MAX_SWAPS_LIMIT)
+}
+
+fn json<T: Serialize>(value: &T) -> (u16, String) {
+    (200, serde_json::to_string(value).unwrap())
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swaps_limit() {
+        assert_eq!(DEFAULT_SWAPS_LIMIT, swaps_limit(""));
+        assert_eq!(10, swaps_limit("from=1&limit=10"));
+        assert_eq!(MAX_SWAPS_LIMIT, swaps_limit("limit=5000"));
+    }
+}
@@ -0,0 +1,470 @@
+use crate::gateway::{PoolEvent, PositionChange};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use radix_engine_common::math::Decimal;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use std::str::FromStr;
+
+//the decimals are stored as TEXT, their exact string representation, and summed by the indexer, not by SQLite
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS pools (
+        pool TEXT PRIMARY KEY,
+        state_version INTEGER NOT NULL,
+        sqrt_price TEXT,
+        price TEXT,
+        tick INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS positions (
+        pool TEXT NOT NULL,
+        pos_id TEXT NOT NULL,
+        low_tick INTEGER NOT NULL,
+        high_tick INTEGER NOT NULL,
+        liq TEXT NOT NULL,
+        amount0 TEXT NOT NULL,
+        amount1 TEXT NOT NULL,
+        fees0 TEXT NOT NULL,
+        fees1 TEXT NOT NULL,
+        PRIMARY KEY (pool, pos_id)
+    );
+    CREATE TABLE IF NOT EXISTS ticks (
+        pool TEXT NOT NULL,
+        tick INTEGER NOT NULL,
+        liq_net TEXT NOT NULL,
+        liq_gross TEXT NOT NULL,
+        crossings INTEGER NOT NULL,
+        PRIMARY KEY (pool, tick)
+    );
+    CREATE TABLE IF NOT EXISTS swaps (
+        pool TEXT NOT NULL,
+        state_version INTEGER NOT NULL,
+        confirmed_at TEXT,
+        resource_in TEXT NOT NULL,
+        amount_in TEXT NOT NULL,
+        resource_out TEXT NOT NULL,
+        amount_out TEXT NOT NULL,
+        sqrt_price TEXT NOT NULL,
+        effective_price TEXT NOT NULL,
+        fee_amount TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS swaps_by_pool ON swaps (pool, state_version);
+    CREATE TABLE IF NOT EXISTS volumes (
+        pool TEXT NOT NULL,
+        day TEXT NOT NULL,
+        resource TEXT NOT NULL,
+        swaps INTEGER NOT NULL,
+        volume TEXT NOT NULL,
+        fees TEXT NOT NULL,
+        PRIMARY KEY (pool, day, resource)
+    );
+";
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct PoolRow {
+    pub pool: String,
+    pub state_version: u64,
+    pub sqrt_price: Option<String>,
+    pub price: Option<String>,
+    pub tick: Option<i32>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct PositionRow {
+    pub pos_id: String,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub liq: String,
+    pub amount0: String,
+    pub amount1: String,
+    pub fees0: String,
+    pub fees1: String,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct TickRow {
+    pub tick: i32,
+    pub price: String,
+    pub liq_net: String,
+    pub liq_gross: String,
+    pub crossings: u64,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct SwapRow {
+    pub state_version: u64,
+    pub confirmed_at: Option<String>,
+    pub resource_in: String,
+    pub amount_in: String,
+    pub resource_out: String,
+    pub amount_out: String,
+    pub sqrt_price: String,
+    pub effective_price: String,
+    pub fee_amount: String,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct VolumeRow {
+    pub day: String,
+    pub resource: String,
+    pub swaps: u64,
+    pub volume: String,
+    pub fees: String,
+}
+
+/**
+ * The local SQLite model of the indexed pools: the positions, the used ticks, the swaps, the daily volumes by input resource and the
+ * last price of each pool, together with the last indexed state version
+ */
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open(path: &str) -> rusqlite::Result<Db> {
+        let conn = Connection::open(path)?;
+        //the API reads while the indexer writes
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Db { conn })
+    }
+
+    /**
+     * Adds the pool to the indexed pools, if not already there
+     */
+    pub fn add_pool(&self, pool: &str) -> rusqlite::Result<()> {
+        self.conn.execute("INSERT OR IGNORE INTO pools (pool, state_version) VALUES (?1, 0)", params![pool])?;
+        Ok(())
+    }
+
+    /**
+     * The last state version indexed for the pool
+     */
+    pub fn state_version(&self, pool: &str) -> rusqlite::Result<u64> {
+        self.conn.query_row("SELECT state_version FROM pools WHERE pool = ?1", params![pool], |row| row.get(0))
+    }
+
+    /**
+     * Applies the events of a committed transaction to the model of the pool and records its state version, atomically
+     */
+    pub fn apply_transaction(
+        &mut self,
+        pool: &str,
+        state_version: u64,
+        confirmed_at: Option<&str>,
+        events: &[PoolEvent],
+    ) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        for event in events {
+            match event {
+                PoolEvent::Swap {
+                    resource_in_addr,
+                    amount_in,
+                    resource_out_addr,
+                    amount_out,
+                    sqrt_price,
+                    effective_price,
+                    fee_amount,
+                } => {
+                    tx.execute(
+                        "INSERT INTO swaps VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            pool,
+                            state_version,
+                            confirmed_at,
+                            resource_in_addr,
+                            amount_in.to_string(),
+                            resource_out_addr,
+                            amount_out.to_string(),
+                            sqrt_price.to_string(),
+                            effective_price.to_string(),
+                            fee_amount.to_string(),
+                        ],
+                    )?;
+                    let tick = tick_math::tick_at_sqrt_price(SqrtPrice(*sqrt_price)).0;
+                    tx.execute(
+                        "UPDATE pools SET sqrt_price = ?2, price = ?3, tick = ?4 WHERE pool = ?1",
+                        params![pool, sqrt_price.to_string(), (*sqrt_price * *sqrt_price).to_string(), tick],
+                    )?;
+                    //the confirmation time is an RFC 3339 timestamp, its date is the day
+                    let day = confirmed_at.map(|time| time.chars().take(10).collect()).unwrap_or_default();
+                    add_volume(&tx, pool, &day, resource_in_addr, *amount_in, *fee_amount)?;
+                }
+                PoolEvent::Position(change) => {
+                    apply_position_change(&tx, pool, change)?;
+                    update_tick(&tx, pool, change.low_tick, change.liq_delta, change.liq_delta)?;
+                    update_tick(&tx, pool, change.high_tick, -change.liq_delta, change.liq_delta)?;
+                }
+                PoolEvent::TickCrossed { tick, .. } => {
+                    tx.execute(
+                        "UPDATE ticks SET crossings = crossings + 1 WHERE pool = ?1 AND tick = ?2",
+                        params![pool, tick],
+                    )?;
+                }
+            }
+        }
+        tx.execute("UPDATE pools SET state_version = ?2 WHERE pool = ?1", params![pool, state_version])?;
+        tx.commit()
+    }
+
+    pub fn pools(&self) -> rusqlite::Result<Vec<PoolRow>> {
+        let mut stmt = self.conn.prepare("SELECT pool, state_version, sqrt_price, price, tick FROM pools ORDER BY pool")?;
+        let rows = stmt.query_map([], pool_row)?;
+        rows.collect()
+    }
+
+    pub fn pool(&self, pool: &str) -> rusqlite::Result<Option<PoolRow>> {
+        self.conn
+            .query_row(
+                "SELECT pool, state_version, sqrt_price, price, tick FROM pools WHERE pool = ?1",
+                params![pool],
+                pool_row,
+            )
+            .optional()
+    }
+
+    /**
+     * The positions of the pool with liquidity, the closed ones excluded
+     */
+    pub fn positions(&self, pool: &str) -> rusqlite::Result<Vec<PositionRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pos_id, low_tick, high_tick, liq, amount0, amount1, fees0, fees1 FROM positions \
+             WHERE pool = ?1 AND liq != '0' ORDER BY pos_id",
+        )?;
+        let rows = stmt.query_map(params![pool], |row| {
+            Ok(PositionRow {
+                pos_id: row.get(0)?,
+                low_tick: row.get(1)?,
+                high_tick: row.get(2)?,
+                liq: row.get(3)?,
+                amount0: row.get(4)?,
+                amount1: row.get(5)?,
+                fees0: row.get(6)?,
+                fees1: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /**
+     * The used ticks of the pool, in ascending order, with their price of token0 vs token1
+     */
+    pub fn ticks(&self, pool: &str) -> rusqlite::Result<Vec<TickRow>> {
+        let mut stmt =
+            self.conn.prepare("SELECT tick, liq_net, liq_gross, crossings FROM ticks WHERE pool = ?1 ORDER BY tick")?;
+        let rows = stmt.query_map(params![pool], |row| {
+            let tick: i32 = row.get(0)?;
+            Ok(TickRow {
+                tick,
+                price: tick_math::price_at_tick(Tick(tick)).to_string(),
+                liq_net: row.get(1)?,
+                liq_gross: row.get(2)?,
+                crossings: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /**
+     * The latest swaps of the pool, the newest first
+     */
+    pub fn swaps(&self, pool: &str, limit: u32) -> rusqlite::Result<Vec<SwapRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT state_version, confirmed_at, resource_in, amount_in, resource_out, amount_out, sqrt_price, effective_price, \
+             fee_amount FROM swaps WHERE pool = ?1 ORDER BY state_version DESC, rowid DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![pool, limit], |row| {
+            Ok(SwapRow {
+                state_version: row.get(0)?,
+                confirmed_at: row.get(1)?,
+                resource_in: row.get(2)?,
+                amount_in: row.get(3)?,
+                resource_out: row.get(4)?,
+                amount_out: row.get(5)?,
+                sqrt_price: row.get(6)?,
+                effective_price: row.get(7)?,
+                fee_amount: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /**
+     * The daily swapped amounts and fees of the pool by input resource, the newest day first
+     */
+    pub fn volumes(&self, pool: &str) -> rusqlite::Result<Vec<VolumeRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT day, resource, swaps, volume, fees FROM volumes WHERE pool = ?1 ORDER BY day DESC, resource",
+        )?;
+        let rows = stmt.query_map(params![pool], |row| {
+            Ok(VolumeRow {
+                day: row.get(0)?,
+                resource: row.get(1)?,
+                swaps: row.get(2)?,
+                volume: row.get(3)?,
+                fees: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+fn pool_row(row: &rusqlite::Row) -> rusqlite::Result<PoolRow> {
+    Ok(PoolRow {
+        pool: row.get(0)?,
+        state_version: row.get(1)?,
+        sqrt_price: row.get(2)?,
+        price: row.get(3)?,
+        tick: row.get(4)?,
+    })
+}
+
+fn decimal(value: String) -> Decimal {
+    Decimal::from_str(&value).unwrap()
+}
+
+fn add_volume(tx: &Transaction, pool: &str, day: &str, resource: &str, amount: Decimal, fee: Decimal) -> rusqlite::Result<()> {
+    let existing: Option<(u64, String, String)> = tx
+        .query_row(
+            "SELECT swaps, volume, fees FROM volumes WHERE pool = ?1 AND day = ?2 AND resource = ?3",
+            params![pool, day, resource],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let (swaps, volume, fees) = match existing {
+        Some((swaps, volume, fees)) => (swaps + 1, decimal(volume) + amount, decimal(fees) + fee),
+        None => (1, amount, fee),
+    };
+    tx.execute(
+        "INSERT OR REPLACE INTO volumes VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![pool, day, resource, swaps, volume.to_string(), fees.to_string()],
+    )?;
+    Ok(())
+}
+
+fn apply_position_change(tx: &Transaction, pool: &str, change: &PositionChange) -> rusqlite::Result<()> {
+    let existing: Option<(String, String, String, String)> = tx
+        .query_row(
+            "SELECT amount0, amount1, fees0, fees1 FROM positions WHERE pool = ?1 AND pos_id = ?2",
+            params![pool, change.pos_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?;
+    let (amount0, amount1, fees0, fees1) = match existing {
+        Some((amount0, amount1, fees0, fees1)) => (
+            decimal(amount0) + change.amount0,
+            decimal(amount1) + change.amount1,
+            decimal(fees0) + change.fees0,
+            decimal(fees1) + change.fees1,
+        ),
+        None => (change.amount0, change.amount1, change.fees0, change.fees1),
+    };
+    tx.execute(
+        "INSERT OR REPLACE INTO positions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            pool,
+            change.pos_id,
+            change.low_tick,
+            change.high_tick,
+            change.pos_liq.to_string(),
+            amount0.to_string(),
+            amount1.to_string(),
+            fees0.to_string(),
+            fees1.to_string(),
+        ],
+    )?;
+    Ok(())
+}
+
+//as TickState::update of the pool: the net liquidity changes by the liquidity added to the positions starting at the tick and
+//subtracted by the one added to the positions ending at it, the gross one by the liquidity added, and the tick is removed once no
+//position uses it
+fn update_tick(
+    tx: &Transaction,
+    pool: &str,
+    tick: i32,
+    liq_net_delta: Decimal,
+    liq_gross_delta: Decimal,
+) -> rusqlite::Result<()> {
+    if liq_gross_delta == Decimal::zero() {
+        return Ok(());
+    }
+    let existing: Option<(String, String, u64)> = tx
+        .query_row(
+            "SELECT liq_net, liq_gross, crossings FROM ticks WHERE pool = ?1 AND tick = ?2",
+            params![pool, tick],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let (liq_net, liq_gross, crossings) = match existing {
+        Some((liq_net, liq_gross, crossings)) => (decimal(liq_net), decimal(liq_gross), crossings),
+        None => (Decimal::zero(), Decimal::zero(), 0),
+    };
+    let liq_net = liq_net + liq_net_delta;
+    let liq_gross = liq_gross + liq_gross_delta;
+    if liq_gross <= Decimal::zero() {
+        tx.execute("DELETE FROM ticks WHERE pool = ?1 AND tick = ?2", params![pool, tick])?;
+    } else {
+        tx.execute(
+            "INSERT OR REPLACE INTO ticks VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![pool, tick, liq_net.to_string(), liq_gross.to_string(), crossings],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position_change(liq_delta: i32, amount: i32, pos_liq: i32) -> PoolEvent {
+        PoolEvent::Position(PositionChange {
+            pos_id: "#1#".to_string(),
+            low_tick: -100,
+            high_tick: 100,
+            liq_delta: Decimal::from(liq_delta),
+            amount0: Decimal::from(amount),
+            amount1: Decimal::from(amount),
+            fees0: Decimal::zero(),
+            fees1: Decimal::zero(),
+            pos_liq: Decimal::from(pos_liq),
+        })
+    }
+
+    #[test]
+    fn test_apply_transactions() {
+        let mut db = Db::open(":memory:").unwrap();
+        db.add_pool("component_pool").unwrap();
+        db.apply_transaction("component_pool", 10, Some("2026-01-02T10:00:00Z"), &[position_change(50, 100, 50)]).unwrap();
+        assert_eq!(10, db.state_version("component_pool").unwrap());
+        assert_eq!("50", db.ticks("component_pool").unwrap()[0].liq_net);
+        assert_eq!("-50", db.ticks("component_pool").unwrap()[1].liq_net);
+
+        let swap = PoolEvent::Swap {
+            resource_in_addr: "resource_moj".to_string(),
+            amount_in: Decimal::from(10),
+            resource_out_addr: "resource_usdt".to_string(),
+            amount_out: Decimal::from(9),
+            sqrt_price: Decimal::one(),
+            effective_price: Decimal::from_str("0.9").unwrap(),
+            fee_amount: Decimal::from_str("0.03").unwrap(),
+        };
+        db.apply_transaction("component_pool", 11, Some("2026-01-02T11:00:00Z"), &[swap.clone()]).unwrap();
+        db.apply_transaction("component_pool", 12, Some("2026-01-02T12:00:00Z"), &[swap]).unwrap();
+        let volumes = db.volumes("component_pool").unwrap();
+        assert_eq!(
+            vec![VolumeRow {
+                day: "2026-01-02".to_string(),
+                resource: "resource_moj".to_string(),
+                swaps: 2,
+                volume: "20".to_string(),
+                fees: "0.06".to_string(),
+            }],
+            volumes
+        );
+        assert_eq!(12, db.swaps("component_pool", 1).unwrap()[0].state_version);
+        assert_eq!(Some(0), db.pool("component_pool").unwrap().unwrap().tick);
+
+        db.apply_transaction("component_pool", 13, None, &[position_change(-50, -100, 0)]).unwrap();
+        assert!(db.positions("component_pool").unwrap().is_empty());
+        assert!(db.ticks("component_pool").unwrap().is_empty());
+    }
+}
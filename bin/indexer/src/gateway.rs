@@ -0,0 +1,272 @@
+use radix_engine_common::math::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/**
+ * A page of the committed transactions affecting the indexed pools, in ascending ledger order, see Gateway::stream_transactions
+ */
+#[derive(Deserialize, Debug)]
+pub struct TransactionsPage {
+    pub items: Vec<Transaction>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Transaction {
+    pub state_version: u64,
+    pub confirmed_at: Option<String>,
+    pub receipt: Option<Receipt>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Receipt {
+    pub status: Option<String>,
+    pub events: Option<Vec<Event>>,
+}
+
+/**
+ * An event of a transaction receipt, its data in the programmatic SBOR JSON of the Gateway API
+ */
+#[derive(Deserialize, Debug)]
+pub struct Event {
+    pub name: String,
+    pub emitter: Value,
+    pub data: Value,
+}
+
+impl Event {
+    /**
+     * The address of the component that emitted the event, if it was emitted by a component method
+     */
+    pub fn emitter_addr(&self) -> Option<&str> {
+        self.emitter["entity"]["entity_address"].as_str()
+    }
+}
+
+/**
+ * The client of the transaction stream of the Radix Gateway API
+ */
+pub struct Gateway {
+    client: reqwest::blocking::Client,
+    url: String,
+}
+
+impl Gateway {
+    pub fn new(url: &str) -> Gateway {
+        Gateway {
+            client: reqwest::blocking::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /**
+     * Returns the next page of the committed transactions affecting the given pool, starting at the given state version, with their
+     * receipt events. The cursor of the previous page, if any, continues the same stream.
+     */
+    pub fn stream_transactions(
+        &self,
+        pool_addr: &str,
+        from_state_version: u64,
+        cursor: Option<&str>,
+    ) -> Result<TransactionsPage, reqwest::Error> {
+        let mut request = json!({
+            "affected_global_entities_filter": [pool_addr],
+            "kind_filter": "User",
+            "order": "Asc",
+            "from_ledger_state": { "state_version": from_state_version },
+            "limit_per_page": 100,
+            "opt_ins": { "receipt_events": true },
+        });
+        if let Some(cursor) = cursor {
+            request["cursor"] = json!(cursor);
+        }
+        self.client
+            .post(format!("{}/stream/transactions", self.url))
+            .json(&request)
+            .send()?
+            .error_for_status()?
+            .json()
+    }
+}
+
+/**
+ * A change of a position: the liquidity added, negative if removed, the amounts deposited, negative if withdrawn, the fees collected
+ * and the resulting position liquidity
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionChange {
+    pub pos_id: String,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub liq_delta: Decimal,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub pos_liq: Decimal,
+}
+
+/**
+ * The pool events the indexer keeps a model of, decoded from the receipt events
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolEvent {
+    Swap {
+        resource_in_addr: String,
+        amount_in: Decimal,
+        resource_out_addr: String,
+        amount_out: Decimal,
+        sqrt_price: Decimal,
+        effective_price: Decimal,
+        fee_amount: Decimal,
+    },
+    Position(PositionChange),
+    TickCrossed {
+        tick: i32,
+        up: bool,
+    },
+}
+
+impl PoolEvent {
+    /**
+     * Decodes a SwapEvent, PositionAddedEvent, LiquidityAddedEvent, LiquidityRemovedEvent, FeesCollectedEvent or TickCrossedEvent.
+     * Returns None for the other events of the pool.
+     */
+    pub fn decode(event: &Event) -> Option<PoolEvent> {
+        let data = &event.data;
+        let position = |liq_delta: Decimal, amount_sign: Decimal| -> Option<PositionChange> {
+            Some(PositionChange {
+                pos_id: string_field(data, "pos_id")?,
+                low_tick: i32_field(data, "low_tick")?,
+                high_tick: i32_field(data, "high_tick")?,
+                liq_delta,
+                amount0: decimal_field(data, "amount0").unwrap_or(Decimal::zero()) * amount_sign,
+                amount1: decimal_field(data, "amount1").unwrap_or(Decimal::zero()) * amount_sign,
+                fees0: decimal_field(data, "fees0").unwrap_or(Decimal::zero()),
+                fees1: decimal_field(data, "fees1").unwrap_or(Decimal::zero()),
+                pos_liq: decimal_field(data, "pos_liq").or(decimal_field(data, "liq"))?,
+            })
+        };
+        match event.name.as_str() {
+            "SwapEvent" => Some(PoolEvent::Swap {
+                resource_in_addr: string_field(data, "resource_in_addr")?,
+                amount_in: decimal_field(data, "amount_in")?,
+                resource_out_addr: string_field(data, "resource_out_addr")?,
+                amount_out: decimal_field(data, "amount_out")?,
+                sqrt_price: decimal_field(data, "sqrt_price")?,
+                effective_price: decimal_field(data, "effective_price")?,
+                fee_amount: decimal_field(data, "fee_amount")?,
+            }),
+            //the fees added to the liquidity aren't collected, they're part of the deposited amounts
+            "PositionAddedEvent" | "LiquidityAddedEvent" => {
+                let mut change = position(decimal_field(data, "liq")?, Decimal::one())?;
+                change.fees0 = Decimal::zero();
+                change.fees1 = Decimal::zero();
+                Some(PoolEvent::Position(change))
+            }
+            "LiquidityRemovedEvent" => Some(PoolEvent::Position(position(-decimal_field(data, "liq")?, -Decimal::one())?)),
+            "FeesCollectedEvent" => Some(PoolEvent::Position(position(Decimal::zero(), Decimal::one())?)),
+            "TickCrossedEvent" => Some(PoolEvent::TickCrossed {
+                tick: i32_field(data, "tick")?,
+                up: field(data, "direction")?["variant_name"].as_str()? == "Up",
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn field<'a>(data: &'a Value, name: &str) -> Option<&'a Value> {
+    data["fields"].as_array()?.iter().find(|field| field["field_name"] == name)
+}
+
+fn string_field(data: &Value, name: &str) -> Option<String> {
+    field(data, name)?["value"].as_str().map(String::from)
+}
+
+fn i32_field(data: &Value, name: &str) -> Option<i32> {
+    field(data, name)?["value"].as_str()?.parse().ok()
+}
+
+//a newtype, e.g. SqrtPrice, is a tuple of one field
+fn decimal_field(data: &Value, name: &str) -> Option<Decimal> {
+    let field = field(data, name)?;
+    let value = if field["kind"] == "Tuple" { &field["fields"][0]["value"] } else { &field["value"] };
+    Decimal::from_str(value.as_str()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, data: Value) -> Event {
+        Event {
+            name: name.to_string(),
+            emitter: json!({ "type": "Method", "entity": { "entity_address": "component_pool" } }),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_decode_swap_event() {
+        let event = event(
+            "SwapEvent",
+            json!({ "kind": "Tuple", "type_name": "SwapEvent", "fields": [
+                { "kind": "Reference", "field_name": "resource_in_addr", "value": "resource_moj" },
+                { "kind": "Decimal", "field_name": "amount_in", "value": "100" },
+                { "kind": "Reference", "field_name": "resource_out_addr", "value": "resource_usdt" },
+                { "kind": "Decimal", "field_name": "amount_out", "value": "98.5" },
+                { "kind": "Tuple", "type_name": "SqrtPrice", "field_name": "sqrt_price", "fields": [{ "kind": "Decimal", "value": "0.99" }] },
+                { "kind": "Decimal", "field_name": "effective_price", "value": "0.985" },
+                { "kind": "Decimal", "field_name": "fee_amount", "value": "0.3" },
+            ]}),
+        );
+        assert_eq!(Some("component_pool"), event.emitter_addr());
+        assert_eq!(
+            Some(PoolEvent::Swap {
+                resource_in_addr: "resource_moj".to_string(),
+                amount_in: Decimal::from(100),
+                resource_out_addr: "resource_usdt".to_string(),
+                amount_out: Decimal::from_str("98.5").unwrap(),
+                sqrt_price: Decimal::from_str("0.99").unwrap(),
+                effective_price: Decimal::from_str("0.985").unwrap(),
+                fee_amount: Decimal::from_str("0.3").unwrap(),
+            }),
+            PoolEvent::decode(&event)
+        );
+    }
+
+    #[test]
+    fn test_decode_position_events() {
+        let fields = json!([
+            { "kind": "NonFungibleLocalId", "field_name": "pos_id", "value": "#1#" },
+            { "kind": "I32", "field_name": "low_tick", "value": "-100" },
+            { "kind": "I32", "field_name": "high_tick", "value": "100" },
+            { "kind": "Decimal", "field_name": "amount0", "value": "10" },
+            { "kind": "Decimal", "field_name": "amount1", "value": "20" },
+            { "kind": "Decimal", "field_name": "fees0", "value": "1" },
+            { "kind": "Decimal", "field_name": "fees1", "value": "2" },
+            { "kind": "Decimal", "field_name": "liq", "value": "5" },
+            { "kind": "Decimal", "field_name": "pos_liq", "value": "15" },
+        ]);
+        let removed = PoolEvent::decode(&event("LiquidityRemovedEvent", json!({ "kind": "Tuple", "fields": fields }))).unwrap();
+        assert_eq!(
+            PoolEvent::Position(PositionChange {
+                pos_id: "#1#".to_string(),
+                low_tick: -100,
+                high_tick: 100,
+                liq_delta: Decimal::from(-5),
+                amount0: Decimal::from(-10),
+                amount1: Decimal::from(-20),
+                fees0: Decimal::one(),
+                fees1: Decimal::from(2),
+                pos_liq: Decimal::from(15),
+            }),
+            removed
+        );
+        let added = PoolEvent::decode(&event("LiquidityAddedEvent", json!({ "kind": "Tuple", "fields": fields }))).unwrap();
+        let PoolEvent::Position(added) = added else { panic!("Position change expected") };
+        assert_eq!((Decimal::from(5), Decimal::from(10), Decimal::zero()), (added.liq_delta, added.amount0, added.fees0));
+        assert_eq!(None, PoolEvent::decode(&event("ProtocolFeeSwitchEvent", json!({ "kind": "Tuple", "fields": [] }))));
+    }
+}
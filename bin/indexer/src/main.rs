@@ -0,0 +1,142 @@
+mod api;
+mod db;
+mod gateway;
+
+use db::Db;
+use gateway::{Gateway, PoolEvent};
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: indexer [--gateway <url>] [--db <path>] [--listen <addr>] [--poll-secs <secs>] <pool_addr>...";
+
+/**
+ * The command line options, see USAGE
+ */
+struct Config {
+    gateway_url: String,
+    db_path: String,
+    listen_addr: String,
+    poll_interval: Duration,
+    pools: Vec<String>,
+}
+
+impl Config {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+        let mut config = Config {
+            gateway_url: "https://mainnet.radixdlt.com".to_string(),
+            db_path: "indexer.db".to_string(),
+            listen_addr: "127.0.0.1:8080".to_string(),
+            poll_interval: Duration::from_secs(5),
+            pools: vec![],
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--gateway" => config.gateway_url = option_value(&mut args, &arg)?,
+                "--db" => config.db_path = option_value(&mut args, &arg)?,
+                "--listen" => config.listen_addr = option_value(&mut args, &arg)?,
+                "--poll-secs" => {
+                    let secs = option_value(&mut args, &arg)?.parse().map_err(|_| "Invalid --poll-secs".to_string())?;
+                    config.poll_interval = Duration::from_secs(secs);
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ => config.pools.push(arg),
+            }
+        }
+        if config.pools.is_empty() {
+            return Err("No pool to index".to_string());
+        }
+        Ok(config)
+    }
+}
+
+fn option_value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next().ok_or(format!("Missing the value of {}", option))
+}
+
+/**
+ * The reference indexer of the pools: follows the committed transactions of the given pools through the Gateway API, keeps a local
+ * SQLite model of their positions, ticks, swaps, volumes and prices from the pool events and serves it as a JSON API, see api::serve.
+ * The indexing resumes from the last indexed state version of each pool.
+ */
+fn main() {
+    let config = Config::from_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        std::process::exit(1);
+    });
+
+    let mut db = Db::open(&config.db_path).expect("Failed to open the database");
+    for pool in &config.pools {
+        db.add_pool(pool).expect("Failed to add the pool");
+    }
+
+    let api_db = Db::open(&config.db_path).expect("Failed to open the database");
+    let listen_addr = config.listen_addr.clone();
+    thread::spawn(move || {
+        if let Err(err) = api::serve(&listen_addr, api_db) {
+            eprintln!("The API stopped: {}", err);
+            std::process::exit(1);
+        }
+    });
+    println!("Serving the API on {}", config.listen_addr);
+
+    let gateway = Gateway::new(&config.gateway_url);
+    loop {
+        for pool in &config.pools {
+            if let Err(err) = index_pool(&gateway, &mut db, pool) {
+                eprintln!("Failed to index {}: {}", pool, err);
+            }
+        }
+        thread::sleep(config.poll_interval);
+    }
+}
+
+/**
+ * Indexes the transactions of the pool committed since its last indexed state version, page by page. The failed transactions and the
+ * events emitted by other components, e.g. the hooks or the router, are skipped.
+ */
+fn index_pool(gateway: &Gateway, db: &mut Db, pool: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let from_state_version = db.state_version(pool)? + 1;
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = gateway.stream_transactions(pool, from_state_version, cursor.as_deref())?;
+        for tx in &page.items {
+            let events: Vec<PoolEvent> = match &tx.receipt {
+                Some(receipt) if receipt.status.as_deref() == Some("CommittedSuccess") => receipt
+                    .events
+                    .iter()
+                    .flatten()
+                    .filter(|event| event.emitter_addr() == Some(pool))
+                    .filter_map(PoolEvent::decode)
+                    .collect(),
+                _ => vec![],
+            };
+            db.apply_transaction(pool, tx.state_version, tx.confirmed_at.as_deref(), &events)?;
+        }
+        match page.next_cursor {
+            Some(next_cursor) if !page.items.is_empty() => cursor = Some(next_cursor),
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> impl Iterator<Item = String> + '_ {
+        args.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn test_config_from_args() {
+        let config = Config::from_args(args("--db pools.db --poll-secs 2 component_a component_b")).unwrap();
+        assert_eq!("pools.db", config.db_path);
+        assert_eq!(Duration::from_secs(2), config.poll_interval);
+        assert_eq!(vec!["component_a", "component_b"], config.pools);
+        assert_eq!("https://mainnet.radixdlt.com", config.gateway_url);
+
+        assert!(Config::from_args(args("--db pools.db")).is_err());
+        assert!(Config::from_args(args("--db")).is_err());
+        assert!(Config::from_args(args("--verbose component_a")).is_err());
+    }
+}
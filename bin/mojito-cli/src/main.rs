@@ -0,0 +1,255 @@
+use mojitoswap_pool::manifests::{self, NewPool};
+use mojitoswap_pool::tick_math;
+use radix_engine_common::prelude::*;
+use std::str::FromStr;
+use transaction::manifest::decompile;
+use transaction::model::TransactionManifestV1;
+
+const USAGE: &str = "Usage: mojito-cli [--network mainnet|stokenet|simulator] <command> --account <addr> [--fee-lock <xrd>] <options>
+
+Prints a ready to sign transaction manifest. The commands and their options:
+  create-pool      --package <addr> --resource0 <addr> --resource1 <addr> --fee <fee> --price <price> --admin-badge <addr>
+                   --low-price <price> --high-price <price> --amount0 <amount> --amount1 <amount>
+  add-position     --pool <addr> --resource0 <addr> --resource1 <addr> --amount0 <amount> --amount1 <amount>
+                   --low-price <price> --high-price <price> [--tick-spacing <spacing>]
+  remove-position  --pool <addr> --resource0 <addr> --resource1 <addr> --position-nft <addr> --position-id <id>
+                   [--min-amount0 <amount>] [--min-amount1 <amount>]
+  collect-fees     --pool <addr> --resource0 <addr> --resource1 <addr> --position-nft <addr> --position-id <id>
+  swap             --pool <addr> --resource-in <addr> --amount <amount> --resource-out <addr>
+                   (--min-out <amount> | --expected-out <amount> --slippage <fraction>)
+
+The prices are of resource0 vs resource1, the position ids as in the wallet, e.g. #1#.";
+
+/**
+ * A position of a pool: the pool, its resources and the position NFT
+ */
+struct PoolPosition {
+    pool_addr: ComponentAddress,
+    resource0_addr: ResourceAddress,
+    resource1_addr: ResourceAddress,
+    pos_nft_id: NonFungibleGlobalId,
+}
+
+/**
+ * The usable tick of the given price of token0 vs token1, see tick_math::nearest_usable_tick
+ */
+fn price_to_tick(price: Decimal, tick_spacing: i32) -> i32 {
+    tick_math::nearest_usable_tick(tick_math::tick_at_price(price), tick_spacing).0
+}
+
+/**
+ * The options of a command, by name, with their typed parsing, the addresses being decoded for the network of the manifest
+ */
+struct Options {
+    values: BTreeMap<String, String>,
+    network: NetworkDefinition,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<(String, Options), String> {
+        let mut values = BTreeMap::new();
+        let mut command = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args.next().ok_or(format!("Missing the value of {}", arg))?;
+                    values.insert(name.to_string(), value.clone());
+                }
+                None if command.is_none() => command = Some(arg.clone()),
+                None => return Err(format!("Unexpected argument {}", arg)),
+            }
+        }
+        let network = match values.remove("network").as_deref() {
+            None | Some("mainnet") => NetworkDefinition::mainnet(),
+            Some("stokenet") => NetworkDefinition::stokenet(),
+            Some("simulator") => NetworkDefinition::simulator(),
+            Some(network) => return Err(format!("Unknown network {}", network)),
+        };
+        Ok((command.ok_or("No command")?, Options { values, network }))
+    }
+
+    fn get(&self, name: &str) -> Result<&str, String> {
+        self.values.get(name).map(String::as_str).ok_or(format!("Missing --{}", name))
+    }
+
+    fn decimal(&self, name: &str) -> Result<Decimal, String> {
+        Decimal::from_str(self.get(name)?).map_err(|_| format!("Invalid --{}", name))
+    }
+
+    fn decimal_or(&self, name: &str, default: Decimal) -> Result<Decimal, String> {
+        if self.values.contains_key(name) {
+            self.decimal(name)
+        } else {
+            Ok(default)
+        }
+    }
+
+    fn component(&self, name: &str) -> Result<ComponentAddress, String> {
+        ComponentAddress::try_from_bech32(&AddressBech32Decoder::new(&self.network), self.get(name)?)
+            .ok_or(format!("Invalid --{}", name))
+    }
+
+    fn resource(&self, name: &str) -> Result<ResourceAddress, String> {
+        ResourceAddress::try_from_bech32(&AddressBech32Decoder::new(&self.network), self.get(name)?)
+            .ok_or(format!("Invalid --{}", name))
+    }
+
+    fn package(&self, name: &str) -> Result<PackageAddress, String> {
+        PackageAddress::try_from_bech32(&AddressBech32Decoder::new(&self.network), self.get(name)?)
+            .ok_or(format!("Invalid --{}", name))
+    }
+
+    fn position(&self) -> Result<PoolPosition, String> {
+        let position_id =
+            NonFungibleLocalId::from_str(self.get("position-id")?).map_err(|_| "Invalid --position-id".to_string())?;
+        Ok(PoolPosition {
+            pool_addr: self.component("pool")?,
+            resource0_addr: self.resource("resource0")?,
+            resource1_addr: self.resource("resource1")?,
+            pos_nft_id: NonFungibleGlobalId::new(self.resource("position-nft")?, position_id),
+        })
+    }
+}
+
+/**
+ * Builds the manifest of the given command, locking the fee from the account
+ */
+fn build_manifest(command: &str, options: &Options) -> Result<TransactionManifestV1, String> {
+    let account_addr = options.component("account")?;
+    let manifest = match command {
+        "create-pool" => {
+            let pool = NewPool {
+                resource0_addr: options.resource("resource0")?,
+                resource1_addr: options.resource("resource1")?,
+                fee: options.decimal("fee")?,
+                price: options.decimal("price")?,
+                admin_badge_addr: options.resource("admin-badge")?,
+                low_price: options.decimal("low-price")?,
+                high_price: options.decimal("high-price")?,
+                amount0: options.decimal("amount0")?,
+                amount1: options.decimal("amount1")?,
+            };
+            manifests::build_new_pool_manifest(options.package("package")?, account_addr, &pool)
+        }
+        "add-position" => {
+            let tick_spacing: i32 = match options.values.get("tick-spacing") {
+                Some(spacing) => spacing.parse().map_err(|_| "Invalid --tick-spacing".to_string())?,
+                None => 1,
+            };
+            let low_tick = price_to_tick(options.decimal("low-price")?, tick_spacing);
+            let high_tick = price_to_tick(options.decimal("high-price")?, tick_spacing);
+            if low_tick >= high_tick {
+                return Err("The price range is narrower than the tick spacing".to_string());
+            }
+            manifests::build_add_pos_manifest(
+                options.component("pool")?,
+                account_addr,
+                (options.resource("resource0")?, options.decimal("amount0")?),
+                (options.resource("resource1")?, options.decimal("amount1")?),
+                (low_tick, high_tick),
+            )
+        }
+        "remove-position" => {
+            let position = options.position()?;
+            manifests::build_remove_pos_manifest(
+                position.pool_addr,
+                account_addr,
+                position.pos_nft_id,
+                (position.resource0_addr, options.decimal_or("min-amount0", Decimal::zero())?),
+                (position.resource1_addr, options.decimal_or("min-amount1", Decimal::zero())?),
+            )
+        }
+        "collect-fees" => {
+            let position = options.position()?;
+            manifests::build_collect_fees_manifest(
+                position.pool_addr,
+                account_addr,
+                position.pos_nft_id,
+                (position.resource0_addr, Decimal::zero()),
+                (position.resource1_addr, Decimal::zero()),
+            )
+        }
+        "swap" => {
+            let min_out = if options.values.contains_key("min-out") {
+                options.decimal("min-out")?
+            } else {
+                manifests::min_out_with_slippage(options.decimal("expected-out")?, options.decimal("slippage")?)
+            };
+            manifests::build_swap_manifest(
+                options.component("pool")?,
+                account_addr,
+                options.resource("resource-in")?,
+                options.decimal("amount")?,
+                options.resource("resource-out")?,
+                min_out,
+            )
+        }
+        _ => return Err(format!("Unknown command {}", command)),
+    };
+    Ok(manifests::with_fee_lock(manifest, account_addr, options.decimal_or("fee-lock", dec!("10"))?))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = Options::parse(&args).and_then(|(command, options)| {
+        let manifest = build_manifest(&command, &options)?;
+        decompile(&manifest.instructions, &options.network).map_err(|err| format!("{:?}", err))
+    });
+    match result {
+        Ok(manifest) => println!("{}", manifest),
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let (command, options) = Options::parse(&args("--network stokenet swap --amount 10 --slippage 0.01")).unwrap();
+        assert_eq!("swap", command);
+        assert_eq!(NetworkDefinition::stokenet().id, options.network.id);
+        assert_eq!(dec!("10"), options.decimal("amount").unwrap());
+        assert_eq!(dec!("10"), options.decimal_or("fee-lock", dec!("10")).unwrap());
+        assert!(options.decimal("min-out").is_err());
+
+        assert!(Options::parse(&args("swap --amount")).is_err());
+        assert!(Options::parse(&args("swap remove-position")).is_err());
+        assert!(Options::parse(&args("--network localnet swap")).is_err());
+        assert!(Options::parse(&args("--amount 10")).is_err());
+    }
+
+    #[test]
+    fn test_price_to_tick() {
+        assert_eq!(0, price_to_tick(Decimal::one(), 10));
+        assert_eq!(tick_math::tick_at_price(dec!("2")).0, price_to_tick(dec!("2"), 1));
+        assert_eq!(0, price_to_tick(dec!("2"), 60) % 60);
+    }
+
+    #[test]
+    fn test_build_swap_manifest() {
+        let encoder = AddressBech32Encoder::new(&NetworkDefinition::simulator());
+        let faucet = encoder.encode(FAUCET.as_ref()).unwrap();
+        let xrd = encoder.encode(XRD.as_ref()).unwrap();
+        let (command, options) = Options::parse(&args(&format!(
+            "--network simulator swap --account {0} --pool {0} --resource-in {1} --amount 10 --resource-out {1} --expected-out 20 \
+             --slippage 0.01",
+            faucet, xrd
+        )))
+        .unwrap();
+        let manifest = build_manifest(&command, &options).unwrap();
+        let rtm = decompile(&manifest.instructions, &options.network).unwrap();
+        assert!(rtm.contains("Decimal(\"19.8\")"));
+        assert!(rtm.find("\"lock_fee\"").unwrap() < rtm.find("\"swap\"").unwrap());
+        assert!(build_manifest("unknown", &options).is_err());
+    }
+}
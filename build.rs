@@ -0,0 +1,317 @@
+/*!
+ * Generates the pre-computed sqrt prices used by the tick math, from the √1.0001 definition:
+ * - $OUT_DIR/price_constants.rs: the Decimal constants of tick_math
+ * - $OUT_DIR/precise_price_constants.rs: the PreciseDecimal constants of precise_tick_math
+ * - $OUT_DIR/q128_price_constants.rs: the Q128.128 fixed point constants of q128_math
+ *
+ * For each power of 2, 2^k with k in [0, 19], we generate PRICE_0X<2^k> = √1.0001^(2^k) and INV_PRICE_0X<2^k> = 1 / √1.0001^(2^k),
+ * truncated to the precision of the target type, and the PRICES, INV_PRICES arrays indexed by k.
+ *
+ * The values are computed with a small big integer implementation, in fixed point with 72 decimals, which is enough to truncate
+ * them exactly to 36 decimals.
+ */
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const POWERS: usize = 20;
+const SCALE: usize = 72;
+
+/**
+ * An unsigned big integer, stored as base 10^9 limbs, least significant first, without the most significant zero limbs
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BigUint(Vec<u64>);
+
+const LIMB_BASE: u64 = 1_000_000_000;
+const LIMB_DIGITS: usize = 9;
+
+impl BigUint {
+    fn from_u64(mut value: u64) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push(value % LIMB_BASE);
+            value /= LIMB_BASE;
+        }
+        BigUint(limbs)
+    }
+
+    fn pow10(exp: usize) -> Self {
+        let mut limbs = vec![0; exp / LIMB_DIGITS];
+        limbs.push(10u64.pow((exp % LIMB_DIGITS) as u32));
+        BigUint(limbs)
+    }
+
+    fn normalized(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint(limbs)
+    }
+
+    fn add(&self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.0.len().max(other.0.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.0.len().max(other.0.len()) {
+            let sum = self.0.get(i).unwrap_or(&0) + other.0.get(i).unwrap_or(&0) + carry;
+            limbs.push(sum % LIMB_BASE);
+            carry = sum / LIMB_BASE;
+        }
+        limbs.push(carry);
+        BigUint::normalized(limbs)
+    }
+
+    fn mul(&self, other: &BigUint) -> BigUint {
+        let mut acc = vec![0u128; self.0.len() + other.0.len() + 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                acc[i + j] += (*a as u128) * (*b as u128);
+            }
+        }
+        let mut limbs = Vec::with_capacity(acc.len());
+        let mut carry = 0u128;
+        for value in acc {
+            let sum = value + carry;
+            limbs.push((sum % LIMB_BASE as u128) as u64);
+            carry = sum / LIMB_BASE as u128;
+        }
+        BigUint::normalized(limbs)
+    }
+
+    /**
+     * Returns the quotient and the remainder of the division by a divisor up to 2^64
+     */
+    fn div_rem_small(&self, divisor: u128) -> (BigUint, u128) {
+        let mut limbs = vec![0; self.0.len()];
+        let mut rem = 0u128;
+        for i in (0..self.0.len()).rev() {
+            let current = rem * LIMB_BASE as u128 + self.0[i] as u128;
+            limbs[i] = (current / divisor) as u64;
+            rem = current % divisor;
+        }
+        (BigUint::normalized(limbs), rem)
+    }
+
+    fn div_pow10(&self, exp: usize) -> BigUint {
+        let dropped = (exp / LIMB_DIGITS).min(self.0.len());
+        let shifted = BigUint(self.0[dropped..].to_vec());
+        shifted.div_rem_small(10u128.pow((exp % LIMB_DIGITS) as u32)).0
+    }
+
+    /**
+     * The floor of the square root, computed decimal digit by decimal digit, from the most significant one
+     */
+    fn isqrt(&self) -> BigUint {
+        let digits = self.to_string().len();
+        let mut root = BigUint(Vec::new());
+        for pos in (0..(digits + 1) / 2).rev() {
+            for digit in (1..10).rev() {
+                let candidate = root.add(&BigUint::pow10(pos).mul(&BigUint::from_u64(digit)));
+                if candidate.mul(&candidate).cmp(self) != Ordering::Greater {
+                    root = candidate;
+                    break;
+                }
+            }
+        }
+        root
+    }
+
+    fn to_u64_digits(&self, count: usize) -> Vec<u64> {
+        let mut value = self.clone();
+        let mut digits = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (quotient, rem) = value.div_rem_small(1u128 << 64);
+            digits.push(rem as u64);
+            value = quotient;
+        }
+        assert!(value.0.is_empty(), "Value doesn't fit in {} u64 digits.", count);
+        digits
+    }
+
+    fn to_decimal_string(&self, decimals: usize) -> String {
+        let digits = format!("{:0>width$}", self.to_string(), width = decimals + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals);
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.split_last() {
+            None => write!(f, "0"),
+            Some((most_significant, rest)) => {
+                write!(f, "{}", most_significant)?;
+                for limb in rest.iter().rev() {
+                    write!(f, "{:09}", limb)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/**
+ * Squares a value scaled by 10^SCALE, truncating the result to SCALE decimals
+ */
+fn square(value: &BigUint) -> BigUint {
+    value.mul(value).div_pow10(SCALE)
+}
+
+/**
+ * √1.0001^(2^k) and 1 / √1.0001^(2^k) for k in [0, POWERS), scaled by 10^SCALE and truncated.
+ *
+ * For k >= 1 the values are the powers of 1.0001 and of 1 / 1.0001, computed by squaring starting from the exact 1.0001 and from
+ * 1 / 1.0001, so that the values with less than SCALE decimals, e.g. 1.0001^2, are exact.
+ */
+fn sqrt_price_powers() -> (Vec<BigUint>, Vec<BigUint>) {
+    let mut prices = vec![BigUint::from_u64(10001).mul(&BigUint::pow10(2 * SCALE - 4)).isqrt()];
+    let mut inv_prices = vec![BigUint::pow10(2 * SCALE + 4).div_rem_small(10001).0.isqrt()];
+
+    prices.push(BigUint::from_u64(10001).mul(&BigUint::pow10(SCALE - 4)));
+    inv_prices.push(BigUint::pow10(SCALE + 4).div_rem_small(10001).0);
+    for k in 2..POWERS {
+        prices.push(square(&prices[k - 1]));
+        inv_prices.push(square(&inv_prices[k - 1]));
+    }
+    (prices, inv_prices)
+}
+
+/**
+ * The Decimal prices of tick_math. They are computed as the table was originally computed: √1.0001 truncated to 18 decimals and
+ * squared with the Decimal truncation, except 1.0001 and 1.00020001, which are exact. The inverse prices are truncated to 18
+ * decimals directly.
+ */
+fn decimal_prices(prices: &[BigUint]) -> Vec<BigUint> {
+    let mut squared_price = prices[0].div_pow10(SCALE - 18);
+    let mut decimal_prices = vec![squared_price.clone()];
+    for price in &prices[1..] {
+        squared_price = squared_price.mul(&squared_price).div_pow10(18);
+        if decimal_prices.len() <= 2 {
+            decimal_prices.push(price.div_pow10(SCALE - 18));
+        } else {
+            decimal_prices.push(squared_price.clone());
+        }
+    }
+    decimal_prices
+}
+
+/**
+ * A generated constant: its value in the comment and the raw integer of the fixed point number
+ */
+struct Constant {
+    value: String,
+    raw: BigUint,
+}
+
+/**
+ * The fixed point number type of the generated constants, e.g. Decimal(bnum_integer::I192::from_digits([...]))
+ */
+struct NumberType {
+    name: &'static str,
+    integer: &'static str,
+    u64_digits: usize,
+}
+
+fn write_constants(path: &Path, number_type: &NumberType, prices: &[Constant], inv_prices: &[Constant]) {
+    let mut code = String::from("// Generated by build.rs, don't edit.\n");
+    for (prefix, comment, constants) in [("PRICE", "", prices), ("INV_PRICE", "1 / ", inv_prices)] {
+        for (k, constant) in constants.iter().enumerate() {
+            let digits: Vec<String> = constant
+                .raw
+                .to_u64_digits(number_type.u64_digits)
+                .iter()
+                .map(|digit| digit.to_string())
+                .collect();
+            code += &format!(
+                "//{} = {}√1.0001^(2^{})\nconst {}_0X{:X}: {} = {}({}::from_digits([{}]));\n",
+                constant.value,
+                comment,
+                k,
+                prefix,
+                1u32 << k,
+                number_type.name,
+                number_type.name,
+                number_type.integer,
+                digits.join(", ")
+            );
+        }
+    }
+    for (prefix, array) in [("PRICE", "PRICES"), ("INV_PRICE", "INV_PRICES")] {
+        code += &format!("const {}: [{}; {}] = [\n", array, number_type.name, POWERS);
+        for k in 0..POWERS {
+            code += &format!("    {}_0X{:X},\n", prefix, 1u32 << k);
+        }
+        code += "];\n";
+    }
+    fs::write(path, code).unwrap();
+}
+
+/**
+ * The values truncated to the given decimals, as constants of a decimal type
+ */
+fn decimal_constants(values: &[BigUint], decimals: usize) -> Vec<Constant> {
+    values
+        .iter()
+        .map(|value| Constant { value: value.to_decimal_string(decimals), raw: value.clone() })
+        .collect()
+}
+
+/**
+ * The values as Q128.128 fixed point constants: value * 2^128, truncated
+ */
+fn q128_constants(values: &[BigUint]) -> Vec<Constant> {
+    let two_pow_64 = BigUint::from_u64(u64::MAX).add(&BigUint::from_u64(1));
+    values
+        .iter()
+        .map(|value| Constant {
+            value: format!("{}...", value.div_pow10(SCALE - 36).to_decimal_string(36)),
+            raw: value.mul(&two_pow_64).mul(&two_pow_64).div_pow10(SCALE),
+        })
+        .collect()
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let (prices, inv_prices) = sqrt_price_powers();
+
+    let truncated = |values: &[BigUint], decimals: usize| -> Vec<BigUint> {
+        values.iter().map(|value| value.div_pow10(SCALE - decimals)).collect()
+    };
+
+    write_constants(
+        &Path::new(&out_dir).join("price_constants.rs"),
+        &NumberType { name: "Decimal", integer: "bnum_integer::I192", u64_digits: 3 },
+        &decimal_constants(&decimal_prices(&prices), 18),
+        &decimal_constants(&truncated(&inv_prices, 18), 18),
+    );
+    write_constants(
+        &Path::new(&out_dir).join("precise_price_constants.rs"),
+        &NumberType { name: "PreciseDecimal", integer: "bnum_integer::I256", u64_digits: 4 },
+        &decimal_constants(&truncated(&prices, 36), 36),
+        &decimal_constants(&truncated(&inv_prices, 36), 36),
+    );
+    write_constants(
+        &Path::new(&out_dir).join("q128_price_constants.rs"),
+        &NumberType { name: "Q128", integer: "U256", u64_digits: 4 },
+        &q128_constants(&prices),
+        &q128_constants(&inv_prices),
+    );
+}
@@ -0,0 +1,24 @@
+//the stable interface of the pool for the DEX aggregators and other integrators: the methods below keep their names and arguments
+//ordering across the package versions and the pool emits a SwapEvent for each swap
+
+pub use crate::interface::{PoolVersion, SwapEvent};
+
+/**
+ * Declares the Pool external blueprint of the given package address, see extern_mojitoswap_pool_interface!, whose methods include
+ * the aggregator interface:
+ * - swap(bucket) = swaps the bucket for the other resource, returns the output and the remainder of the bucket, if the liquidity
+ *   runs out
+ * - quote_swap(resource_addr, amount) = the output and the remainder of the swap, without executing it
+ * - get_resource_addresses() = the addresses of resource0 and resource1
+ * - get_price() = the current price of token0 vs token1
+ * - get_fee() = the pool fee, a percentage of the swapped amount
+ * - get_version() = the package version of the pool and its supported features
+ *
+ * E.g. in an integrator blueprint: extern_mojitoswap_pool!("package_rdx1..."); and then let pool: Global<Pool> = pool_addr.into();
+ */
+#[macro_export]
+macro_rules! extern_mojitoswap_pool {
+    ($package_address:literal) => {
+        $crate::extern_mojitoswap_pool_interface!($package_address);
+    };
+}
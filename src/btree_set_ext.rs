@@ -5,6 +5,10 @@ pub fn previous_elem<T: Ord>(tree: &BTreeSet<T>, val: T) -> Option<&T> {
     tree.range((Unbounded, Excluded(val))).next_back()
 }
 
+pub fn previous_or_equal_elem<T: Ord>(tree: &BTreeSet<T>, val: T) -> Option<&T> {
+    tree.range((Unbounded, Included(val))).next_back()
+}
+
 pub fn next_elem<T: Ord>(tree: &BTreeSet<T>, val: T) -> Option<&T> {
     tree.range((Excluded(val), Unbounded)).next()
 }
@@ -35,6 +39,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn previous_or_equal_elem_retrieval() {
+        let mut btree_set = BTreeSet::new();
+
+        btree_set.insert(32);
+        btree_set.insert(40);
+        btree_set.insert(18);
+
+        assert_eq!(
+            previous_or_equal_elem(&btree_set, 39),
+            Option::from(&32)
+        );
+        assert_eq!(
+            previous_or_equal_elem(&btree_set, 32),
+            Option::from(&32)
+        );
+        assert_eq!(
+            previous_or_equal_elem(&btree_set, 18),
+            Option::from(&18)
+        );
+        assert_eq!(previous_or_equal_elem(&btree_set, 17), None);
+    }
+
     #[test]
     fn next_elem_retrieval() {
         let mut btree_set = BTreeSet::new();
@@ -0,0 +1,242 @@
+use scrypto::prelude::*;
+
+use crate::pool::pool_blueprint::Pool;
+
+/**
+ * A dollar cost averaging order: the input left to swap, in parts of amount_per_swap swapped every interval seconds, and the output
+ * swapped so far and not yet claimed. The parts are swapped only if their output is at least min_output_rate per input unit.
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct DcaOrder {
+    pub input_resource_addr: ResourceAddress,
+    pub input_amount: Decimal,
+    pub amount_per_swap: Decimal,
+    pub interval: i64,
+    pub min_output_rate: Decimal,
+    pub next_swap_time: i64,
+    pub output_amount: Decimal,
+}
+
+/**
+ * The data of the NFT received for a DCA order, claiming its output and cancelling it
+ */
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct DcaReceipt {
+    pub order_id: u64,
+}
+
+/**
+ * Dollar cost averaging on top of a pool: the users deposit one of the pool tokens with a schedule and the deposit is swapped in small
+ * parts, periodically, for the other token, which they claim as it accumulates. The parts are swapped by keepers, e.g. bots or the
+ * users themselves, calling the permissionless execute_order method once an order is due, see get_due_orders.
+ *
+ * The keepers choose when to swap within the schedule, so each order sets a minimum output rate, guarding its parts against the
+ * swaps done at manipulated prices.
+ */
+#[blueprint]
+mod dca_vault_blueprint {
+    struct DcaVault {
+        pool: Global<Pool>,
+        vault0: Vault,
+        vault1: Vault,
+        receipt_resource_manager: ResourceManager,
+        receipt_minter_badge: Vault,
+        orders: HashMap<u64, DcaOrder>,
+        order_count: u64,
+    }
+
+    impl DcaVault {
+        /**
+         * Creates a new DCA vault swapping through the given pool
+         */
+        pub fn new(pool_addr: ComponentAddress) -> Global<DcaVault> {
+            let pool: Global<Pool> = pool_addr.into();
+            let (resource0_addr, resource1_addr) = pool.get_resource_addresses();
+
+            let receipt_minter_badge: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .mint_initial_supply(1)
+                .into();
+            let receipt_resource_manager = ResourceBuilder::new_ruid_non_fungible::<DcaReceipt>(OwnerRole::None)
+                .mint_roles(mint_roles! {
+                    minter => rule!(require(receipt_minter_badge.resource_address()));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(receipt_minter_badge.resource_address()));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            Self {
+                pool,
+                vault0: Vault::new(resource0_addr),
+                vault1: Vault::new(resource1_addr),
+                receipt_resource_manager,
+                receipt_minter_badge: Vault::with_bucket(receipt_minter_badge),
+                orders: HashMap::new(),
+                order_count: 0,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /**
+         * Creates an order swapping the provided tokens in parts of amount_per_swap, every interval seconds, the first part being due
+         * right away. The parts are swapped only if their output is at least min_output_rate per input unit, zero for any rate.
+         *
+         * Returns the NFT of the order, see claim_output and cancel_order
+         */
+        pub fn create_order(
+            &mut self,
+            input: Bucket,
+            amount_per_swap: Decimal,
+            interval: i64,
+            min_output_rate: Decimal,
+        ) -> Bucket {
+            assert!(!input.is_empty(), "No input sent. Create order op aborted.");
+            assert!(amount_per_swap > Decimal::zero(), "Invalid amount per swap, should be positive. Create order op aborted.");
+            assert!(interval > 0, "Invalid interval, should be positive. Create order op aborted.");
+            assert!(
+                min_output_rate >= Decimal::zero(),
+                "Invalid min output rate, should be positive or zero. Create order op aborted."
+            );
+
+            let order_id = self.order_count;
+            self.orders.insert(
+                order_id,
+                DcaOrder {
+                    input_resource_addr: input.resource_address(),
+                    input_amount: input.amount(),
+                    amount_per_swap,
+                    interval,
+                    min_output_rate,
+                    next_swap_time: Self::current_timestamp(),
+                    output_amount: Decimal::zero(),
+                },
+            );
+            self.order_count += 1;
+            self.vault_mut(input.resource_address()).put(input);
+
+            self.receipt_minter_badge.as_fungible().authorize_with_amount(1, || {
+                self.receipt_resource_manager.mint_ruid_non_fungible(DcaReceipt { order_id })
+            })
+        }
+
+        /**
+         * Swaps the next part of the given order through the pool, if the order is due. Anyone can call it.
+         */
+        pub fn execute_order(&mut self, order_id: u64) {
+            let now = Self::current_timestamp();
+            let mut order = self.orders.get(&order_id).expect("Order not found. Execute order op aborted.").clone();
+            assert!(order.input_amount > Decimal::zero(), "Order completed. Execute order op aborted.");
+            assert!(now >= order.next_swap_time, "Order not due. Execute order op aborted.");
+
+            let amount = order.amount_per_swap.min(order.input_amount);
+            let part = self.vault_mut(order.input_resource_addr).take(amount);
+            let (output, remainder) = self.pool.swap(part);
+            let swapped_amount = amount - remainder.amount();
+            assert!(
+                output.amount() >= swapped_amount * order.min_output_rate,
+                "Output rate under the order minimum. Execute order op aborted."
+            );
+
+            order.input_amount -= swapped_amount;
+            order.output_amount += output.amount();
+            order.next_swap_time = now + order.interval;
+            self.vault_mut(remainder.resource_address()).put(remainder);
+            self.vault_mut(output.resource_address()).put(output);
+            self.orders.insert(order_id, order);
+        }
+
+        /**
+         * Returns the output swapped so far by the order of the provided receipt
+         */
+        pub fn claim_output(&mut self, receipt: Proof) -> Bucket {
+            let order_id = self.check_receipt(receipt);
+            let order = self.orders.get_mut(&order_id).unwrap();
+            let output_amount = order.output_amount;
+            order.output_amount = Decimal::zero();
+            let output_resource_addr = self.output_resource_addr(order_id);
+            self.vault_mut(output_resource_addr).take(output_amount)
+        }
+
+        /**
+         * Cancels the order of the provided receipt, which is burned.
+         *
+         * Returns the input left to swap and the output not yet claimed
+         */
+        pub fn cancel_order(&mut self, receipt: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                receipt.resource_address() == self.receipt_resource_manager.address() && receipt.amount() == Decimal::one(),
+                "Wrong receipt sent. Cancel order op aborted."
+            );
+            let order_id = receipt.as_non_fungible().non_fungible::<DcaReceipt>().data().order_id;
+            let output_resource_addr = self.output_resource_addr(order_id);
+            let order = self.orders.remove(&order_id).unwrap();
+
+            self.receipt_minter_badge
+                .as_fungible()
+                .authorize_with_amount(1, || receipt.burn());
+            let input = self.vault_mut(order.input_resource_addr).take(order.input_amount);
+            let output = self.vault_mut(output_resource_addr).take(order.output_amount);
+            (input, output)
+        }
+
+        /**
+         * Returns the given order
+         */
+        pub fn get_order(&self, order_id: u64) -> DcaOrder {
+            self.orders.get(&order_id).expect("Order not found.").clone()
+        }
+
+        /**
+         * Returns the ids of the orders that can be executed now, in ascending order
+         */
+        pub fn get_due_orders(&self) -> Vec<u64> {
+            let now = Self::current_timestamp();
+            let mut order_ids: Vec<u64> = self
+                .orders
+                .iter()
+                .filter(|(_, order)| order.input_amount > Decimal::zero() && now >= order.next_swap_time)
+                .map(|(order_id, _)| *order_id)
+                .collect();
+            order_ids.sort();
+            order_ids
+        }
+
+        /**
+         * Returns the NFT resource of the order receipts
+         */
+        pub fn get_receipt_resource_addr(&self) -> ResourceAddress {
+            self.receipt_resource_manager.address()
+        }
+
+        fn check_receipt(&self, receipt: Proof) -> u64 {
+            let checked_receipt = receipt.check(self.receipt_resource_manager.address());
+            checked_receipt.as_non_fungible().non_fungible::<DcaReceipt>().data().order_id
+        }
+
+        fn output_resource_addr(&self, order_id: u64) -> ResourceAddress {
+            if self.orders.get(&order_id).unwrap().input_resource_addr == self.vault0.resource_address() {
+                self.vault1.resource_address()
+            } else {
+                self.vault0.resource_address()
+            }
+        }
+
+        fn vault_mut(&mut self, resource_addr: ResourceAddress) -> &mut Vault {
+            if resource_addr == self.vault0.resource_address() {
+                &mut self.vault0
+            } else {
+                assert!(resource_addr == self.vault1.resource_address(), "Wrong resource type sent. Op aborted.");
+                &mut self.vault1
+            }
+        }
+
+        fn current_timestamp() -> i64 {
+            Clock::current_time_rounded_to_minutes().seconds_since_unix_epoch
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use scrypto::prelude::*;
+
+use crate::pool::pool_blueprint::Pool;
+use crate::pool::{PoolConfig, MAX_FEE_BPS};
+use crate::tick_math::SqrtPrice;
+
+/**
+ * The fee tiers enabled when the factory is created, each bound to its tick spacing: 0.01%, 0.05%, 0.3% and 1%. The spacing grows
+ * with the fee, as the pairs with larger fees are more volatile and need less granular ranges.
+ */
+const STANDARD_FEE_TIERS: [(&str, i32); 4] = [("0.0001", 1), ("0.0005", 10), ("0.003", 60), ("0.01", 200)];
+
+/**
+ * The stable fee tier enabled when the factory is created, 0.005% with a tick spacing of 1, for the stable pairs, e.g. USDT/xUSDC.
+ * Its pools are created as stable pools, with the guards tuned for the stable pairs, see PoolConfig.
+ */
+const STABLE_FEE_TIER: (&str, i32) = ("0.00005", 1);
+
+/**
+ * A pool created by the factory, see PoolFactory::list_pools
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolInfo {
+    pub pool_addr: ComponentAddress,
+    pub resource0_addr: ResourceAddress,
+    pub resource1_addr: ResourceAddress,
+    pub fee: Decimal,
+    pub creation_epoch: u64,
+    pub curated: bool,
+    pub deprecated: bool,
+}
+
+/**
+ * Emitted by PoolFactory::create_pool for each new pool, the discovery standard of the Mojito pools: the aggregators and the
+ * indexers listening to the factory events can list the pools as they are created, without polling PoolFactory::list_pools. The
+ * pools created directly with the Pool functions, outside the factory, have no such event.
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct PoolCreatedEvent {
+    pub pool_addr: ComponentAddress,
+    pub resource0_addr: ResourceAddress,
+    pub resource1_addr: ResourceAddress,
+    pub fee: Decimal,
+    pub tick_spacing: i32,
+    pub position_nft_addr: ResourceAddress,
+}
+
+/**
+ * The best route for a swap, see PoolFactory::best_quote: the pool to swap in, the expected output amount and the remainder of the
+ * input amount, if the pool liquidity runs out
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub pool_addr: ComponentAddress,
+    pub amount_out: Decimal,
+    pub remainder: Decimal,
+}
+
+/**
+ * Creates the pools and keeps track of them, so there is at most one pool for each pair of resources and fee, and aggregators can
+ * discover the pools. The pools are created only with the enabled fee tiers, each fee having a fixed tick spacing.
+ *
+ * Anyone can create a pool by paying the creation fee, which is kept in the protocol treasury of the factory. The admin can mark the
+ * pools as curated, e.g. for the frontends to highlight them. The deprecated pools are flagged, see flag_deprecated, and left out of the
+ * quotes.
+ *
+ * The admin badge can be rotated, e.g. to a multisig controlled badge, with a two step handover, see propose_admin.
+ *
+ * For the launch phase, the factory can be created in allowlist mode, where only the resources allowlisted by the admin can be used in
+ * new pools. The allowlist mode can be disabled only once, permanently.
+ */
+#[blueprint]
+#[events(PoolCreatedEvent)]
+mod pool_factory_blueprint {
+
+    enable_method_auth! {
+        roles {
+            admin => updatable_by: [SELF];
+            pending_admin => updatable_by: [SELF];
+        },
+        methods {
+            create_pool => PUBLIC;
+            enable_fee_tier => restrict_to: [admin];
+            set_creation_fee => restrict_to: [admin];
+            withdraw_creation_fees => restrict_to: [admin];
+            set_curated => restrict_to: [admin];
+            flag_deprecated => PUBLIC;
+            add_to_allowlist => restrict_to: [admin];
+            remove_from_allowlist => restrict_to: [admin];
+            disable_allowlist => restrict_to: [admin];
+            propose_admin => restrict_to: [admin];
+            accept_admin => restrict_to: [pending_admin];
+            get_admin_badge_addr => PUBLIC;
+            is_allowed => PUBLIC;
+            get_creation_fee => PUBLIC;
+            get_tick_spacing => PUBLIC;
+            is_stable_fee_tier => PUBLIC;
+            get_pool => PUBLIC;
+            list_pools => PUBLIC;
+            best_quote => PUBLIC;
+        }
+    }
+
+    struct PoolFactory {
+        pools: KeyValueStore<(ResourceAddress, ResourceAddress, Decimal), ComponentAddress>,
+        pool_infos: KeyValueStore<u64, PoolInfo>,
+        pair_pools: KeyValueStore<(ResourceAddress, ResourceAddress), Vec<ComponentAddress>>,
+        pool_indexes: KeyValueStore<ComponentAddress, u64>,
+        pool_count: u64,
+        fee_tiers: KeyValueStore<Decimal, i32>,
+        stable_fee_tiers: KeyValueStore<Decimal, ()>,
+        creation_fee_resource_addr: ResourceAddress,
+        creation_fee: Decimal,
+        treasury: KeyValueStore<ResourceAddress, Vault>,
+        allowlist_mode: bool,
+        allowlist: KeyValueStore<ResourceAddress, ()>,
+        admin_badge_addr: ResourceAddress,
+        pending_admin_badge_addr: Option<ResourceAddress>,
+    }
+
+    impl PoolFactory {
+        /**
+         * Creates a new pool factory. Where:
+         * - admin_badge_addr = the badge of the factory admin, it is also the admin badge of the created pools
+         * - creation_fee_resource_addr, creation_fee = the resource, e.g. XRD or MOJ, and the amount paid to create a pool
+         * - allowlist_mode = whether only the allowlisted resources can be used in new pools, the allowlist being empty initially
+         *
+         * The standard fee tiers and the stable fee tier are enabled.
+         */
+        pub fn new(
+            admin_badge_addr: ResourceAddress,
+            creation_fee_resource_addr: ResourceAddress,
+            creation_fee: Decimal,
+            allowlist_mode: bool,
+        ) -> Global<PoolFactory> {
+            assert!(creation_fee >= Decimal::zero(), "Invalid creation fee, should be positive or zero.");
+
+            let fee_tiers = KeyValueStore::new();
+            for (fee, tick_spacing) in STANDARD_FEE_TIERS {
+                fee_tiers.insert(fee.parse::<Decimal>().unwrap(), tick_spacing);
+            }
+            let (stable_fee, stable_tick_spacing) = STABLE_FEE_TIER;
+            let stable_fee_tiers = KeyValueStore::new();
+            fee_tiers.insert(stable_fee.parse::<Decimal>().unwrap(), stable_tick_spacing);
+            stable_fee_tiers.insert(stable_fee.parse::<Decimal>().unwrap(), ());
+
+            Self {
+                pools: KeyValueStore::new(),
+                pool_infos: KeyValueStore::new(),
+                pair_pools: KeyValueStore::new(),
+                pool_indexes: KeyValueStore::new(),
+                pool_count: 0,
+                fee_tiers,
+                stable_fee_tiers,
+                creation_fee_resource_addr,
+                creation_fee,
+                treasury: KeyValueStore::new(),
+                allowlist_mode,
+                allowlist: KeyValueStore::new(),
+                admin_badge_addr,
+                pending_admin_badge_addr: None,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                admin => rule!(require(admin_badge_addr));
+                pending_admin => rule!(deny_all);
+            ))
+            .globalize()
+        }
+
+        /**
+         * Creates a new pool with the given fee, sqrt price and initial position range, see PoolConfig. The fee must be an enabled fee
+         * tier and the pool uses its tick spacing. The pools of the stable fee tier are created as stable pools. It aborts if a pool
+         * with the same resources, in any order, and fee already exists.
+         *
+         * The creation fee is taken from creation_fee_bucket and kept in the treasury. A PoolCreatedEvent is emitted for the new pool.
+         *
+         * Returns the new pool, the NFT of the initial position and the remainders of bucket0, bucket1 and creation_fee_bucket.
+         */
+        pub fn create_pool(
+            &mut self,
+            resource0_addr: ResourceAddress,
+            resource1_addr: ResourceAddress,
+            fee: Decimal,
+            sqrt_price: SqrtPrice,
+            low_sqrt_price: SqrtPrice,
+            high_sqrt_price: SqrtPrice,
+            bucket0: Bucket,
+            bucket1: Bucket,
+            mut creation_fee_bucket: Bucket,
+        ) -> (Global<Pool>, Bucket, Bucket, Bucket, Bucket) {
+            assert!(
+                creation_fee_bucket.resource_address() == self.creation_fee_resource_addr,
+                "Invalid creation fee resource. Create pool op aborted."
+            );
+            assert!(
+                creation_fee_bucket.amount() >= self.creation_fee,
+                "Insufficient creation fee. Create pool op aborted."
+            );
+            assert!(
+                self.is_allowed(resource0_addr) && self.is_allowed(resource1_addr),
+                "Resource not allowlisted. Create pool op aborted."
+            );
+            assert!(
+                self.get_pool(resource0_addr, resource1_addr, fee).is_none(),
+                "A pool with the same resources and fee already exists. Create pool op aborted."
+            );
+            let tick_spacing = match self.get_tick_spacing(fee) {
+                Some(tick_spacing) => tick_spacing,
+                None => panic!("Fee tier not enabled. Create pool op aborted."),
+            };
+
+            let config = PoolConfig {
+                tick_spacing,
+                initial_position: Some((low_sqrt_price, high_sqrt_price)),
+                stable: self.is_stable_fee_tier(fee),
+                ..PoolConfig::new(fee, sqrt_price, self.admin_badge_addr)
+            };
+            let (pool, mut pos_nfts, remaining_bucket0, remaining_bucket1) =
+                Pool::new(resource0_addr, resource1_addr, config, bucket0, bucket1);
+            let pos_nft = pos_nfts.remove(0);
+            self.pools.insert((resource0_addr, resource1_addr, fee), pool.address());
+            self.pool_infos.insert(
+                self.pool_count,
+                PoolInfo {
+                    pool_addr: pool.address(),
+                    resource0_addr,
+                    resource1_addr,
+                    fee,
+                    creation_epoch: Runtime::current_epoch().number(),
+                    curated: false,
+                    deprecated: false,
+                },
+            );
+            self.pool_indexes.insert(pool.address(), self.pool_count);
+            let pair = PoolFactory::pair_key(resource0_addr, resource1_addr);
+            let has_pair_pools = self.pair_pools.get(&pair).is_some();
+            if has_pair_pools {
+                self.pair_pools.get_mut(&pair).unwrap().push(pool.address());
+            } else {
+                self.pair_pools.insert(pair, vec![pool.address()]);
+            }
+            self.pool_count += 1;
+
+            self.deposit_to_treasury(creation_fee_bucket.take(self.creation_fee));
+
+            Runtime::emit_event(PoolCreatedEvent {
+                pool_addr: pool.address(),
+                resource0_addr,
+                resource1_addr,
+                fee,
+                tick_spacing,
+                position_nft_addr: pos_nft.resource_address(),
+            });
+
+            debug!("### Pool {:?} created.", pool.address());
+
+            (pool, pos_nft, remaining_bucket0, remaining_bucket1, creation_fee_bucket)
+        }
+
+        /**
+         * Proposes a new admin badge, e.g. moving from a founder badge to a multisig controlled one, the first step of the admin
+         * handover: the current admin badge stays the admin until a holder of the new badge accepts it with accept_admin. A new
+         * proposal replaces the pending one.
+         */
+        pub fn propose_admin(&mut self, new_badge_addr: ResourceAddress) {
+            self.pending_admin_badge_addr = Some(new_badge_addr);
+            Runtime::global_component().set_role("pending_admin", rule!(require(new_badge_addr)));
+        }
+
+        /**
+         * Makes the proposed admin badge the admin of the factory and of the pools created from now on, the second step of the admin
+         * handover. The existing pools keep their admin badge, handed over with their own propose_admin and accept_admin.
+         */
+        pub fn accept_admin(&mut self) {
+            let new_badge_addr = self
+                .pending_admin_badge_addr
+                .take()
+                .expect("No admin badge proposed. Accept admin op aborted.");
+            self.admin_badge_addr = new_badge_addr;
+            let component = Runtime::global_component();
+            component.set_role("admin", rule!(require(new_badge_addr)));
+            component.set_role("pending_admin", rule!(deny_all));
+        }
+
+        /**
+         * Returns the admin badge of the factory
+         */
+        pub fn get_admin_badge_addr(&self) -> ResourceAddress {
+            self.admin_badge_addr
+        }
+
+        /**
+         * Sets the resource and the amount paid to create a pool
+         */
+        pub fn set_creation_fee(&mut self, creation_fee_resource_addr: ResourceAddress, creation_fee: Decimal) {
+            assert!(creation_fee >= Decimal::zero(), "Invalid creation fee, should be positive or zero.");
+
+            self.creation_fee_resource_addr = creation_fee_resource_addr;
+            self.creation_fee = creation_fee;
+        }
+
+        /**
+         * Returns the resource and the amount paid to create a pool
+         */
+        pub fn get_creation_fee(&self) -> (ResourceAddress, Decimal) {
+            (self.creation_fee_resource_addr, self.creation_fee)
+        }
+
+        /**
+         * Withdraws the creation fees of the given resource from the treasury
+         */
+        pub fn withdraw_creation_fees(&mut self, resource_addr: ResourceAddress) -> Bucket {
+            match self.treasury.get_mut(&resource_addr) {
+                Some(mut vault) => vault.take_all(),
+                None => Bucket::new(resource_addr),
+            }
+        }
+
+        /**
+         * Marks the given factory pool as curated or not
+         */
+        pub fn set_curated(&mut self, pool_addr: ComponentAddress, curated: bool) {
+            let index = match self.pool_indexes.get(&pool_addr) {
+                Some(index) => *index,
+                None => panic!("Unknown pool."),
+            };
+            self.pool_infos.get_mut(&index).unwrap().curated = curated;
+        }
+
+        /**
+         * Flags the given factory pool as deprecated, once its admin deprecated it, see Pool::deprecate. Anyone can flag a deprecated
+         * pool, as the pool state is checked.
+         */
+        pub fn flag_deprecated(&mut self, pool_addr: ComponentAddress) {
+            let index = match self.pool_indexes.get(&pool_addr) {
+                Some(index) => *index,
+                None => panic!("Unknown pool."),
+            };
+            let pool: Global<Pool> = pool_addr.into();
+            assert!(pool.is_deprecated(), "The pool isn't deprecated.");
+            self.pool_infos.get_mut(&index).unwrap().deprecated = true;
+        }
+
+        /**
+         * Enables a new fee tier, of at most MAX_FEE_BPS, bound to the given tick spacing. The enabled fee tiers can't be changed or
+         * disabled, as the existing pools use them.
+         */
+        pub fn enable_fee_tier(&mut self, fee: Decimal, tick_spacing: i32) {
+            assert!(
+                fee >= Decimal::zero() && fee <= Decimal::from(MAX_FEE_BPS) / 10000,
+                "Invalid fee, should be 0 <= fee <= {} bps.",
+                MAX_FEE_BPS
+            );
+            assert!(tick_spacing > 0, "Invalid tick spacing, should be positive.");
+            assert!(self.fee_tiers.get(&fee).is_none(), "Fee tier already enabled.");
+
+            self.fee_tiers.insert(fee, tick_spacing);
+        }
+
+        /**
+         * Allows the given resource to be used in new pools, in allowlist mode
+         */
+        pub fn add_to_allowlist(&mut self, resource_addr: ResourceAddress) {
+            self.allowlist.insert(resource_addr, ());
+        }
+
+        /**
+         * Removes the given resource from the allowlist, the existing pools of the resource are not affected
+         */
+        pub fn remove_from_allowlist(&mut self, resource_addr: ResourceAddress) {
+            self.allowlist.remove(&resource_addr);
+        }
+
+        /**
+         * Disables the allowlist mode permanently, any resource can be used in new pools afterwards
+         */
+        pub fn disable_allowlist(&mut self) {
+            self.allowlist_mode = false;
+        }
+
+        /**
+         * Returns whether the given resource can be used in new pools
+         */
+        pub fn is_allowed(&self, resource_addr: ResourceAddress) -> bool {
+            !self.allowlist_mode || self.allowlist.get(&resource_addr).is_some()
+        }
+
+        /**
+         * Returns the tick spacing of the given fee tier, if it is enabled
+         */
+        pub fn get_tick_spacing(&self, fee: Decimal) -> Option<i32> {
+            self.fee_tiers.get(&fee).map(|tick_spacing| *tick_spacing)
+        }
+
+        /**
+         * Whether the given fee tier is a stable one, its pools being created as stable pools, see PoolConfig
+         */
+        pub fn is_stable_fee_tier(&self, fee: Decimal) -> bool {
+            self.stable_fee_tiers.get(&fee).is_some()
+        }
+
+        /**
+         * Returns the address of the pool of the given resources, in any order, and fee, if it exists
+         */
+        pub fn get_pool(&self, resource_a_addr: ResourceAddress, resource_b_addr: ResourceAddress, fee: Decimal) -> Option<ComponentAddress> {
+            self.pools
+                .get(&(resource_a_addr, resource_b_addr, fee))
+                .or_else(|| self.pools.get(&(resource_b_addr, resource_a_addr, fee)))
+                .map(|pool_addr| *pool_addr)
+        }
+
+        /**
+         * Returns at most limit pools created by the factory, in their creation order, starting with the pool at the cursor index,
+         * and the cursor of the next page, if there are more pools. The first page starts at cursor 0.
+         */
+        pub fn list_pools(&self, cursor: u64, limit: u64) -> (Vec<PoolInfo>, Option<u64>) {
+            let end = cursor.saturating_add(limit).min(self.pool_count);
+            let pool_infos = (cursor..end)
+                .map(|index| self.pool_infos.get(&index).unwrap().clone())
+                .collect();
+            let next_cursor = if end < self.pool_count { Some(end) } else { None };
+            (pool_infos, next_cursor)
+        }
+
+        /**
+         * Quotes the swap of the given amount of token_in for token_out in all the factory pools of the pair and returns the best
+         * route: the pool with the largest output amount, if any pool of the pair exists. The deprecated pools aren't quoted. The
+         * pools state is not modified.
+         */
+        pub fn best_quote(&self, token_in: ResourceAddress, token_out: ResourceAddress, amount: Decimal) -> Option<SwapQuote> {
+            let mut best: Option<SwapQuote> = None;
+            for pool_addr in self.get_pair_pools(token_in, token_out) {
+                if self.is_flagged_deprecated(pool_addr) {
+                    continue;
+                }
+                let pool: Global<Pool> = pool_addr.into();
+                let (amount_out, remainder) = pool.quote_swap(token_in, amount);
+                if best.as_ref().map_or(true, |best| amount_out > best.amount_out) {
+                    best = Some(SwapQuote {
+                        pool_addr,
+                        amount_out,
+                        remainder,
+                    });
+                }
+            }
+            best
+        }
+
+        fn get_pair_pools(&self, resource_a_addr: ResourceAddress, resource_b_addr: ResourceAddress) -> Vec<ComponentAddress> {
+            self.pair_pools
+                .get(&PoolFactory::pair_key(resource_a_addr, resource_b_addr))
+                .map(|pool_addrs| pool_addrs.clone())
+                .unwrap_or_default()
+        }
+
+        //the pools of a pair are kept under the same key whatever the order of their resources, so all of them are quoted
+        fn pair_key(resource_a_addr: ResourceAddress, resource_b_addr: ResourceAddress) -> (ResourceAddress, ResourceAddress) {
+            if resource_a_addr < resource_b_addr {
+                (resource_a_addr, resource_b_addr)
+            } else {
+                (resource_b_addr, resource_a_addr)
+            }
+        }
+
+        fn is_flagged_deprecated(&self, pool_addr: ComponentAddress) -> bool {
+            let index = *self.pool_indexes.get(&pool_addr).unwrap();
+            self.pool_infos.get(&index).unwrap().deprecated
+        }
+
+        fn deposit_to_treasury(&mut self, bucket: Bucket) {
+            let resource_addr = bucket.resource_address();
+            let has_vault = self.treasury.get(&resource_addr).is_some();
+            if has_vault {
+                self.treasury.get_mut(&resource_addr).unwrap().put(bucket);
+            } else {
+                self.treasury.insert(resource_addr, Vault::with_bucket(bucket));
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use scrypto::prelude::*;
+
+use crate::pool::{SwapContext, SwapEvent, MAX_FEE_BPS};
+
+/**
+ * A dynamic fee manager of a pool, see ParameterChange::SetFeeManager: the fee of a swap is the base fee plus a fee proportional to
+ * the recent volatility of the pool price, capped at a maximum fee, so the liquidity providers are paid more when their positions lose
+ * more to the price moves.
+ *
+ * The volatility is the sum of the relative sqrt price moves of the past swaps, halving every half_life seconds. The pool reports its
+ * swaps to the manager registered as one of its hooks, see ParameterChange::RegisterHook. Only the pool can report them.
+ */
+#[blueprint]
+mod fee_manager_blueprint {
+
+    enable_method_auth! {
+        roles {
+            pool => updatable_by: [];
+        },
+        methods {
+            get_swap_fee => PUBLIC;
+            get_volatility => PUBLIC;
+            before_swap => PUBLIC;
+            after_swap => restrict_to: [pool];
+        }
+    }
+
+    struct FeeManager {
+        base_fee: Decimal,
+        max_fee: Decimal,
+        volatility_fee: Decimal,
+        half_life: i64,
+        volatility: Decimal,
+        last_sqrt_price: Option<Decimal>,
+        last_timestamp: i64,
+    }
+
+    impl FeeManager {
+        /**
+         * Creates a new fee manager of the given pool. Where:
+         * - base_fee = the fee charged when the price doesn't move, 0 <= base_fee <= max_fee
+         * - max_fee = the maximum fee charged, at most MAX_FEE_BPS like the static fee of the pool
+         * - volatility_fee = the fee added for each unit of volatility, e.g. 0.1 adds 0.001 for a 1% sqrt price move
+         * - half_life = the seconds in which the volatility halves
+         */
+        pub fn new(
+            pool_addr: ComponentAddress,
+            base_fee: Decimal,
+            max_fee: Decimal,
+            volatility_fee: Decimal,
+            half_life: i64,
+        ) -> Global<FeeManager> {
+            assert!(
+                base_fee >= Decimal::zero() && base_fee <= max_fee && max_fee <= Decimal::from(MAX_FEE_BPS) / 10000,
+                "Invalid fees, should be 0 <= base_fee <= max_fee <= {} bps.",
+                MAX_FEE_BPS
+            );
+            assert!(volatility_fee >= Decimal::zero(), "Invalid volatility fee, should be positive or zero.");
+            assert!(half_life > 0, "Invalid half life, should be positive.");
+
+            Self {
+                base_fee,
+                max_fee,
+                volatility_fee,
+                half_life,
+                volatility: Decimal::zero(),
+                last_sqrt_price: None,
+                last_timestamp: Self::current_timestamp(),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                pool => rule!(require(global_caller(pool_addr)));
+            ))
+            .globalize()
+        }
+
+        /**
+         * Returns the fee of the given swap: base_fee + volatility_fee * volatility, at most max_fee
+         */
+        pub fn get_swap_fee(&self, _context: SwapContext) -> Decimal {
+            let fee = self.base_fee + self.volatility_fee * self.get_volatility();
+            if fee > self.max_fee {
+                self.max_fee
+            } else {
+                fee
+            }
+        }
+
+        /**
+         * Returns the current volatility of the pool price
+         */
+        pub fn get_volatility(&self) -> Decimal {
+            self.decayed_volatility().0
+        }
+
+        pub fn before_swap(&mut self, _context: SwapContext) {}
+
+        /**
+         * Adds the relative sqrt price move of the swap to the volatility
+         */
+        pub fn after_swap(&mut self, event: SwapEvent) {
+            let (mut volatility, decay_timestamp) = self.decayed_volatility();
+            if let Some(last_sqrt_price) = self.last_sqrt_price {
+                let price_move = event.sqrt_price.0 / last_sqrt_price - Decimal::one();
+                volatility += if price_move < Decimal::zero() { -price_move } else { price_move };
+            }
+            self.volatility = volatility;
+            self.last_sqrt_price = Some(event.sqrt_price.0);
+            self.last_timestamp = decay_timestamp;
+        }
+
+        /**
+         * Returns the volatility halved for each half life passed since the last decay and the timestamp of the new last decay, so
+         * the frequent swaps don't delay the decay
+         */
+        fn decayed_volatility(&self) -> (Decimal, i64) {
+            let now = Self::current_timestamp();
+            let half_lives = (now - self.last_timestamp) / self.half_life;
+            if half_lives >= 64 {
+                return (Decimal::zero(), now);
+            }
+            (
+                self.volatility / Decimal::from(1u64 << half_lives),
+                self.last_timestamp + half_lives * self.half_life,
+            )
+        }
+
+        fn current_timestamp() -> i64 {
+            Clock::current_time_rounded_to_minutes().seconds_since_unix_epoch
+        }
+    }
+}
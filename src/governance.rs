@@ -0,0 +1,234 @@
+use scrypto::prelude::*;
+
+/**
+ * A proposal to call an admin gated method, e.g. PoolFactory::enable_fee_tier or Pool::schedule_change, with the given arguments,
+ * encoded with scrypto_args!. The called method must not return any bucket, its output, e.g. the id of the change scheduled by
+ * Pool::schedule_change, being discarded.
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Proposal {
+    pub description: String,
+    pub component_addr: ComponentAddress,
+    pub method: String,
+    pub args: Vec<u8>,
+    pub votes_for: Decimal,
+    pub votes_against: Decimal,
+    pub voting_end: i64,
+    pub executed: bool,
+}
+
+/**
+ * The data of the NFT received for the votes on a proposal, redeemed for the voting tokens once the voting ends
+ */
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct VoteReceipt {
+    pub proposal_id: u64,
+    pub amount: Decimal,
+    pub support: bool,
+}
+
+/**
+ * A lightweight governance of the pools and factories administered by the admin badge it holds: the holders of the voting resource,
+ * e.g. MOJ or veMOJ, vote on proposals calling the admin gated methods, e.g. to enable a new fee tier. A passed proposal can be
+ * executed by anyone, the governance presenting the admin badge.
+ *
+ * The tokens voting on a proposal are locked until its voting ends, so they can't vote twice.
+ */
+#[blueprint]
+mod governance_blueprint {
+    struct Governance {
+        admin_badge: Vault,
+        vote_resource_addr: ResourceAddress,
+        locked_votes: Vault,
+        vote_receipt_resource_manager: ResourceManager,
+        vote_receipt_minter_badge: Vault,
+        proposals: KeyValueStore<u64, Proposal>,
+        proposal_count: u64,
+        voting_period: i64,
+        quorum: Decimal,
+        proposal_threshold: Decimal,
+    }
+
+    impl Governance {
+        /**
+         * Creates a new governance. Where:
+         * - admin_badge = the admin badge of the governed pools and factories, held by the governance
+         * - vote_resource_addr = the fungible resource voting on the proposals, one vote for each token
+         * - voting_period = the seconds a proposal can be voted
+         * - quorum = the minimum votes, for and against, for a proposal to pass
+         * - proposal_threshold = the minimum tokens a proposer must hold
+         */
+        pub fn new(
+            admin_badge: Bucket,
+            vote_resource_addr: ResourceAddress,
+            voting_period: i64,
+            quorum: Decimal,
+            proposal_threshold: Decimal,
+        ) -> Global<Governance> {
+            assert!(voting_period > 0, "Invalid voting period, should be positive.");
+            assert!(quorum > Decimal::zero(), "Invalid quorum, should be positive.");
+            assert!(proposal_threshold >= Decimal::zero(), "Invalid proposal threshold, should be positive or zero.");
+
+            let vote_receipt_minter_badge: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .mint_initial_supply(1)
+                .into();
+            let vote_receipt_resource_manager = ResourceBuilder::new_ruid_non_fungible::<VoteReceipt>(OwnerRole::None)
+                .mint_roles(mint_roles! {
+                    minter => rule!(require(vote_receipt_minter_badge.resource_address()));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(vote_receipt_minter_badge.resource_address()));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            Self {
+                admin_badge: Vault::with_bucket(admin_badge),
+                vote_resource_addr,
+                locked_votes: Vault::new(vote_resource_addr),
+                vote_receipt_resource_manager,
+                vote_receipt_minter_badge: Vault::with_bucket(vote_receipt_minter_badge),
+                proposals: KeyValueStore::new(),
+                proposal_count: 0,
+                voting_period,
+                quorum,
+                proposal_threshold,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /**
+         * Creates a proposal to call the given method of the given component with the given arguments, encoded with scrypto_args!.
+         * The proof must be of at least proposal_threshold voting tokens. The voting starts right away.
+         *
+         * Returns the id of the proposal
+         */
+        pub fn propose(
+            &mut self,
+            proof: Proof,
+            description: String,
+            component_addr: ComponentAddress,
+            method: String,
+            args: Vec<u8>,
+        ) -> u64 {
+            let checked_proof = proof.check(self.vote_resource_addr);
+            assert!(
+                checked_proof.amount() >= self.proposal_threshold,
+                "Not enough tokens to propose. Propose op aborted."
+            );
+
+            let proposal_id = self.proposal_count;
+            self.proposals.insert(
+                proposal_id,
+                Proposal {
+                    description,
+                    component_addr,
+                    method,
+                    args,
+                    votes_for: Decimal::zero(),
+                    votes_against: Decimal::zero(),
+                    voting_end: Self::current_timestamp() + self.voting_period,
+                    executed: false,
+                },
+            );
+            self.proposal_count += 1;
+            proposal_id
+        }
+
+        /**
+         * Votes for or against the given proposal with the provided tokens, locked until the voting ends.
+         *
+         * Returns the NFT redeeming the tokens, see withdraw_votes
+         */
+        pub fn vote(&mut self, proposal_id: u64, votes: Bucket, support: bool) -> Bucket {
+            assert!(votes.resource_address() == self.vote_resource_addr, "Wrong resource type sent. Vote op aborted.");
+            assert!(votes.amount() > Decimal::zero(), "No votes sent. Vote op aborted.");
+
+            let mut proposal = self.proposals.get_mut(&proposal_id).expect("Proposal not found. Vote op aborted.");
+            assert!(Self::current_timestamp() < proposal.voting_end, "The voting ended. Vote op aborted.");
+            let amount = votes.amount();
+            if support {
+                proposal.votes_for += amount;
+            } else {
+                proposal.votes_against += amount;
+            }
+            self.locked_votes.put(votes);
+
+            self.vote_receipt_minter_badge.as_fungible().authorize_with_amount(1, || {
+                self.vote_receipt_resource_manager
+                    .mint_ruid_non_fungible(VoteReceipt { proposal_id, amount, support })
+            })
+        }
+
+        /**
+         * Returns the tokens voting with the given vote receipt, once the voting of its proposal ended
+         */
+        pub fn withdraw_votes(&mut self, vote_receipt: Bucket) -> Bucket {
+            assert!(
+                vote_receipt.resource_address() == self.vote_receipt_resource_manager.address(),
+                "Wrong resource type sent. Withdraw op aborted."
+            );
+            let mut amount = Decimal::zero();
+            for receipt in vote_receipt.as_non_fungible().non_fungibles::<VoteReceipt>() {
+                let receipt_data = receipt.data();
+                let proposal = self.proposals.get(&receipt_data.proposal_id).unwrap();
+                assert!(
+                    Self::current_timestamp() >= proposal.voting_end,
+                    "The voting hasn't ended. Withdraw op aborted."
+                );
+                amount += receipt_data.amount;
+            }
+
+            self.vote_receipt_minter_badge
+                .as_fungible()
+                .authorize_with_amount(1, || vote_receipt.burn());
+            self.locked_votes.take(amount)
+        }
+
+        /**
+         * Executes the given proposal, once its voting ended, if it passed: more votes for than against and at least quorum votes
+         */
+        pub fn execute(&mut self, proposal_id: u64) {
+            let mut proposal = self.proposals.get_mut(&proposal_id).expect("Proposal not found. Execute op aborted.");
+            assert!(!proposal.executed, "Proposal already executed. Execute op aborted.");
+            assert!(
+                Self::current_timestamp() >= proposal.voting_end,
+                "The voting hasn't ended. Execute op aborted."
+            );
+            assert!(
+                proposal.votes_for + proposal.votes_against >= self.quorum,
+                "The proposal didn't reach the quorum. Execute op aborted."
+            );
+            assert!(proposal.votes_for > proposal.votes_against, "The proposal didn't pass. Execute op aborted.");
+
+            proposal.executed = true;
+            let component: Global<AnyComponent> = Global::from(proposal.component_addr);
+            //the outputs of any type are decoded, so any method can be called
+            self.admin_badge.as_fungible().authorize_with_amount(1, || {
+                component.call_raw::<ScryptoValue>(&proposal.method, proposal.args.clone());
+            });
+        }
+
+        /**
+         * Returns the given proposal
+         */
+        pub fn get_proposal(&self, proposal_id: u64) -> Proposal {
+            self.proposals.get(&proposal_id).expect("Proposal not found.").clone()
+        }
+
+        /**
+         * Returns the number of proposals, the ids being 0..count
+         */
+        pub fn get_proposal_count(&self) -> u64 {
+            self.proposal_count
+        }
+
+        fn current_timestamp() -> i64 {
+            Clock::current_time_rounded_to_minutes().seconds_since_unix_epoch
+        }
+    }
+}
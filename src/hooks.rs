@@ -0,0 +1,44 @@
+use scrypto::prelude::*;
+
+use crate::pool::{SwapContext, SwapEvent};
+
+/**
+ * An example of a pool hook, see ParameterChange::RegisterHook: it aborts the swaps of amounts larger than a maximum amount, e.g. for
+ * a launch phase, and counts the swaps done. The hook methods are public, so the count is indicative only.
+ */
+#[blueprint]
+mod swap_limit_hook_blueprint {
+    struct SwapLimitHook {
+        max_amount: Decimal,
+        swaps: u64,
+    }
+
+    impl SwapLimitHook {
+        /**
+         * Creates a new hook, aborting the swaps of amounts larger than max_amount
+         */
+        pub fn new(max_amount: Decimal) -> Global<SwapLimitHook> {
+            assert!(max_amount > Decimal::zero(), "Invalid max amount, should be positive.");
+
+            Self { max_amount, swaps: 0 }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        pub fn before_swap(&mut self, context: SwapContext) {
+            assert!(context.amount <= self.max_amount, "Swap amount over the limit. Swap op aborted.");
+        }
+
+        pub fn after_swap(&mut self, _event: SwapEvent) {
+            self.swaps += 1;
+        }
+
+        /**
+         * Returns the number of swaps done
+         */
+        pub fn get_swaps(&self) -> u64 {
+            self.swaps
+        }
+    }
+}
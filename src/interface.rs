@@ -0,0 +1,68 @@
+//the typed interface of the pool for the components of other packages calling it, e.g. vaults, lending markets or routers, so their
+//calls are checked at compile time instead of going through call_raw with the method names and arguments as strings
+
+pub use crate::oracle::Observation;
+pub use crate::pool::{
+    Depth, FeeConfig, PoolState, PoolVersion, Portfolio, PortfolioPosition, Position, SwapEvent, TickState, Tvl,
+};
+pub use crate::tick_math::{SqrtPrice, Tick, TickRange};
+
+/**
+ * Declares the Pool external blueprint of the given package address, with the methods of the pool available to the components of
+ * other packages, a superset of the aggregator interface, see extern_mojitoswap_pool!:
+ * - the positions: add_pos, add_liq, add_accumulated_fees_to_liq, remove_pos, remove_liq, collect_fees, collect_fees_as, rebalance_pos
+ *   and migrate_position, the position NFT being presented as a proof
+ * - the swaps: swap, swap_with_referrer, swap_as_trader, with a proof of the trader badge, and quote_swap
+ * - the queries: the pool state, the positions, the portfolio of a proof of position NFTs, the used ticks, the TVL, the depth, the
+ *   oracle observations and TWAP, the fee and its configuration, the price and the version
+ *
+ * extern_mojitoswap_pool! of the aggregator module expands to this macro, so there is a single declaration of the Pool blueprint.
+ *
+ * E.g. in a vault blueprint: extern_mojitoswap_pool_interface!("package_rdx1..."); and then let pool: Global<Pool> =
+ * pool_addr.into(); let (nft, remainder0, remainder1) = pool.add_pos(bucket0, bucket1, range);
+ */
+#[macro_export]
+macro_rules! extern_mojitoswap_pool_interface {
+    ($package_address:literal) => {
+        extern_blueprint!(
+            $package_address,
+            Pool {
+                fn add_pos(&mut self, bucket0: Bucket, bucket1: Bucket, range: $crate::interface::TickRange) -> (Bucket, Bucket, Bucket);
+                fn add_liq(&mut self, bucket0: Bucket, bucket1: Bucket, auth: Proof) -> (Bucket, Bucket);
+                fn add_accumulated_fees_to_liq(&mut self, auth: Proof);
+                fn remove_pos(&mut self, proof: Proof) -> (Bucket, Bucket);
+                fn remove_liq(&mut self, liq: Decimal, proof: Proof) -> (Bucket, Bucket);
+                fn collect_fees(&mut self, auth: Proof) -> (Bucket, Bucket);
+                fn collect_fees_as(&mut self, auth: Proof, output_resource: ResourceAddress, min_out: Decimal) -> (Bucket, Bucket);
+                fn rebalance_pos(&mut self, proof: Proof, range: $crate::interface::TickRange) -> (Bucket, Bucket, Bucket);
+                fn migrate_position(&mut self, proof: Proof, target_pool_addr: ComponentAddress) -> (Bucket, Bucket, Bucket);
+                fn swap(&mut self, bucket: Bucket) -> (Bucket, Bucket);
+                fn swap_with_referrer(&mut self, bucket: Bucket, referrer_id: u64) -> (Bucket, Bucket);
+                fn swap_as_trader(&mut self, bucket: Bucket, trader_badge: Proof) -> (Bucket, Bucket);
+                fn quote_swap(&self, resource_addr: ResourceAddress, amount: Decimal) -> (Decimal, Decimal);
+                fn amount_to_move_price(&self, target_sqrt_price: $crate::interface::SqrtPrice) -> (ResourceAddress, Decimal);
+                fn get_pool_state(&self) -> $crate::interface::PoolState;
+                fn get_position(&self, pos_id: NonFungibleLocalId) -> Option<$crate::interface::Position>;
+                fn get_positions(&self) -> Vec<(NonFungibleLocalId, $crate::interface::Position)>;
+                fn get_position_amounts_at(
+                    &self,
+                    pos_id: NonFungibleLocalId,
+                    sqrt_price: $crate::interface::SqrtPrice
+                ) -> (Decimal, Decimal);
+                fn get_portfolio(&self, proof: Proof) -> $crate::interface::Portfolio;
+                fn get_tick_states(&self) -> Vec<$crate::interface::TickState>;
+                fn list_initialized_ticks(&self, cursor: Option<i32>, limit: u32) -> Vec<(i32, Decimal, Decimal)>;
+                fn get_tvl(&self) -> $crate::interface::Tvl;
+                fn get_depth(&self, levels: u32, tick_bucket_size: u32) -> $crate::interface::Depth;
+                fn observe(&self, seconds_ago: Vec<u64>) -> Vec<$crate::interface::Observation>;
+                fn twap_tick(&self, window: u64) -> $crate::interface::Tick;
+                fn twap_price(&self, window: u64) -> Decimal;
+                fn get_resource_addresses(&self) -> (ResourceAddress, ResourceAddress);
+                fn get_price(&self) -> Decimal;
+                fn get_fee(&self) -> Decimal;
+                fn get_fee_config(&self) -> $crate::interface::FeeConfig;
+                fn get_version(&self) -> $crate::interface::PoolVersion;
+            }
+        );
+    };
+}
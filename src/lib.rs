@@ -1,5 +1,50 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "blueprint")]
 #[macro_use]
 mod btree_set_ext;
+#[cfg(feature = "blueprint")]
 pub mod pool;
+#[cfg(feature = "blueprint")]
+pub mod factory;
+#[cfg(feature = "blueprint")]
+pub mod router;
+#[cfg(feature = "blueprint")]
+pub mod aggregator;
+#[cfg(feature = "blueprint")]
+pub mod hooks;
+#[cfg(feature = "blueprint")]
+pub mod fee_manager;
+#[cfg(feature = "blueprint")]
+pub mod governance;
+#[cfg(feature = "blueprint")]
+pub mod dca;
+#[cfg(feature = "blueprint")]
+pub mod limit_order;
+#[cfg(feature = "blueprint")]
+pub mod managed_vault;
+#[cfg(feature = "blueprint")]
+pub mod referral;
+#[cfg(feature = "blueprint")]
+pub mod treasury;
+#[cfg(feature = "blueprint")]
+pub mod replay;
+#[cfg(feature = "blueprint")]
+pub mod interface;
 pub mod tick_math;
+#[cfg(feature = "precise")]
+pub mod precise_tick_math;
+#[cfg(feature = "q128")]
+pub mod q128_math;
 pub mod pool_math;
+pub mod swap_math;
+pub mod oracle;
+#[cfg(feature = "simulation")]
+pub mod sim;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "manifests")]
+pub mod manifests;
+
+//the tick range and the sqrt prices at its bounds, so integrators don't hard-code them
+pub use tick_math::{MAX_PRICE, MAX_TICK, MIN_PRICE, MIN_TICK};
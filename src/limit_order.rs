@@ -0,0 +1,228 @@
+use scrypto::prelude::*;
+
+use crate::pool::pool_blueprint::Pool;
+use crate::pool::PoolState;
+use crate::tick_math::{Tick, TickRange};
+
+/**
+ * A limit order: the position selling input_amount of the input resource on the single tick spacing wide range [low_tick,
+ * high_tick], all in the other resource once the pool price moved past the range.
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct LimitOrder {
+    pub pos_id: NonFungibleLocalId,
+    pub input_resource_addr: ResourceAddress,
+    pub input_amount: Decimal,
+    pub low_tick: i32,
+    pub high_tick: i32,
+}
+
+/**
+ * The data of the NFT received for a limit order, redeemed for the order output or cancelling it
+ */
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct LimitOrderReceipt {
+    pub order_id: u64,
+}
+
+/**
+ * Limit orders on top of a pool: the orders are positions on a single tick spacing wide range, out of the current price, opened on
+ * behalf of the users. Selling resource0 the range is above the current tick, selling resource1 below it. Once the pool price moved
+ * past the range the position is all in the other resource, the order is filled and its owner claims it, the position being removed.
+ * Until then, the owner can cancel it, getting back the input and the part already converted.
+ *
+ * A filled order that isn't claimed converts back if the price returns into its range, so the owners or keepers, watching the pool
+ * swap events or get_filled_orders, should claim the filled orders timely.
+ */
+#[blueprint]
+mod limit_order_manager_blueprint {
+    struct LimitOrderManager {
+        pool: Global<Pool>,
+        positions: Vault,
+        receipt_resource_manager: ResourceManager,
+        receipt_minter_badge: Vault,
+        orders: HashMap<u64, LimitOrder>,
+        order_count: u64,
+    }
+
+    impl LimitOrderManager {
+        /**
+         * Creates a new limit order manager of the given pool
+         */
+        pub fn new(pool_addr: ComponentAddress) -> Global<LimitOrderManager> {
+            let pool: Global<Pool> = pool_addr.into();
+            let position_nft_addr = pool.get_pool_state().position_nft_addr;
+
+            let receipt_minter_badge: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .mint_initial_supply(1)
+                .into();
+            let receipt_resource_manager = ResourceBuilder::new_ruid_non_fungible::<LimitOrderReceipt>(OwnerRole::None)
+                .mint_roles(mint_roles! {
+                    minter => rule!(require(receipt_minter_badge.resource_address()));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(receipt_minter_badge.resource_address()));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            Self {
+                pool,
+                positions: Vault::new(position_nft_addr),
+                receipt_resource_manager,
+                receipt_minter_badge: Vault::with_bucket(receipt_minter_badge),
+                orders: HashMap::new(),
+                order_count: 0,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /**
+         * Places an order selling the provided tokens on the range [low_tick, low_tick + tick spacing], which must be above the current
+         * tick when selling resource0 and below it when selling resource1. The low tick must be a multiple of the pool tick spacing.
+         *
+         * Returns the NFT of the order, see claim_filled_order and cancel_order, and the remainders amount0,1 of the provided tokens
+         */
+        pub fn place_order(&mut self, input: Bucket, low_tick: i32) -> (Bucket, Bucket, Bucket) {
+            assert!(!input.is_empty(), "No input sent. Place order op aborted.");
+            let pool_state = self.pool.get_pool_state();
+            let high_tick = low_tick + pool_state.tick_spacing;
+            let range = match TickRange::try_new(Tick(low_tick), Tick(high_tick), pool_state.tick_spacing) {
+                Ok(range) => range,
+                Err(err) => panic!("Invalid order tick: {}. Place order op aborted.", err),
+            };
+
+            let input_resource_addr = input.resource_address();
+            let (bucket0, bucket1) = if input_resource_addr == pool_state.resource0_addr {
+                assert!(
+                    low_tick > pool_state.current_tick.0,
+                    "The order range should be above the current tick. Place order op aborted."
+                );
+                (input, Bucket::new(pool_state.resource1_addr))
+            } else {
+                assert!(
+                    input_resource_addr == pool_state.resource1_addr,
+                    "Wrong resource type sent. Place order op aborted."
+                );
+                assert!(
+                    high_tick <= pool_state.current_tick.0,
+                    "The order range should be below the current tick. Place order op aborted."
+                );
+                (Bucket::new(pool_state.resource0_addr), input)
+            };
+            let input_amount = bucket0.amount() + bucket1.amount();
+            let (pos_nft, remainder0, remainder1) = self.pool.add_pos(bucket0, bucket1, range);
+
+            let order_id = self.order_count;
+            self.orders.insert(
+                order_id,
+                LimitOrder {
+                    pos_id: pos_nft.as_non_fungible().non_fungible_local_id(),
+                    input_resource_addr,
+                    input_amount: input_amount - remainder0.amount() - remainder1.amount(),
+                    low_tick,
+                    high_tick,
+                },
+            );
+            self.order_count += 1;
+            self.positions.put(pos_nft);
+
+            let receipt = self.receipt_minter_badge.as_fungible().authorize_with_amount(1, || {
+                self.receipt_resource_manager.mint_ruid_non_fungible(LimitOrderReceipt { order_id })
+            });
+            (receipt, remainder0, remainder1)
+        }
+
+        /**
+         * Claims the given filled order with its receipt, which is burned, removing its position.
+         *
+         * Returns the amount0,1 of the position: the output, plus the fees earned while the price was in the order range
+         */
+        pub fn claim_filled_order(&mut self, order_id: u64, receipt: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                self.redeem_receipt(receipt) == order_id,
+                "The receipt should be of the claimed order. Claim order op aborted."
+            );
+            assert!(self.is_filled(order_id), "Order not filled. Claim order op aborted.");
+            self.remove_order(order_id)
+        }
+
+        /**
+         * Cancels the order of the provided receipt, which is burned, removing its position.
+         *
+         * Returns the amount0,1 of the position: the input left and the part already converted, plus the fees earned
+         */
+        pub fn cancel_order(&mut self, receipt: Bucket) -> (Bucket, Bucket) {
+            let order_id = self.redeem_receipt(receipt);
+            self.remove_order(order_id)
+        }
+
+        /**
+         * Returns true if the pool price moved past the range of the given order, so its position is all in the other resource
+         */
+        pub fn is_filled(&self, order_id: u64) -> bool {
+            let order = self.orders.get(&order_id).expect("Order not found.");
+            LimitOrderManager::filled_at(order, &self.pool.get_pool_state())
+        }
+
+        /**
+         * Returns the ids of the filled orders not yet claimed, in ascending order
+         */
+        pub fn get_filled_orders(&self) -> Vec<u64> {
+            let pool_state = self.pool.get_pool_state();
+            let mut order_ids: Vec<u64> = self
+                .orders
+                .iter()
+                .filter(|(_, order)| LimitOrderManager::filled_at(order, &pool_state))
+                .map(|(order_id, _)| *order_id)
+                .collect();
+            order_ids.sort();
+            order_ids
+        }
+
+        /**
+         * Returns the given order
+         */
+        pub fn get_order(&self, order_id: u64) -> LimitOrder {
+            self.orders.get(&order_id).expect("Order not found.").clone()
+        }
+
+        /**
+         * Returns the NFT resource of the order receipts
+         */
+        pub fn get_receipt_resource_addr(&self) -> ResourceAddress {
+            self.receipt_resource_manager.address()
+        }
+
+        fn filled_at(order: &LimitOrder, pool_state: &PoolState) -> bool {
+            if order.input_resource_addr == pool_state.resource0_addr {
+                pool_state.current_tick.0 >= order.high_tick
+            } else {
+                pool_state.current_tick.0 < order.low_tick
+            }
+        }
+
+        fn redeem_receipt(&mut self, receipt: Bucket) -> u64 {
+            assert!(
+                receipt.resource_address() == self.receipt_resource_manager.address() && receipt.amount() == Decimal::one(),
+                "Wrong receipt sent. Op aborted."
+            );
+            let order_id = receipt.as_non_fungible().non_fungible::<LimitOrderReceipt>().data().order_id;
+            self.receipt_minter_badge
+                .as_fungible()
+                .authorize_with_amount(1, || receipt.burn());
+            order_id
+        }
+
+        //the empty position NFT stays in the manager, as the pool position NFTs can't be burned
+        fn remove_order(&mut self, order_id: u64) -> (Bucket, Bucket) {
+            let order = self.orders.remove(&order_id).expect("Order not found. Op aborted.");
+            let pos_proof = self.positions.as_non_fungible().create_proof_of_non_fungibles(&indexset!(order.pos_id));
+            self.pool.remove_pos(pos_proof.into())
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use scrypto::prelude::*;
+
+use crate::pool::pool_blueprint::Pool;
+use crate::tick_math::TickRange;
+
+/**
+ * A managed liquidity vault on top of a pool: the user deposits are pooled in a pair of positions, a wide one, always earning some
+ * fees, and a narrow one around the price, earning more while the price stays in its range. The users get fungible shares of the
+ * vault and a strategist, holding the strategy badge, moves the ranges to follow the price with Pool::rebalance_pos, so the passive
+ * users get concentrated liquidity returns.
+ *
+ * The shares are priced on the vault amounts, the idle amounts plus the amounts of the positions at the current price, fees included.
+ * The deposits top up the positions with Pool::add_liq and the withdrawals take their share of the positions liquidity out with
+ * Pool::remove_liq, so the vault keeps the same two position NFTs until the strategist moves the ranges. The amounts that don't fit
+ * in the ranges wait in the vault until the next deposit. In emergency mode the withdrawals remove the whole positions, the only way
+ * out of the pool, and the next deposit opens new ones.
+ */
+#[blueprint]
+mod managed_vault_blueprint {
+
+    enable_method_auth! {
+        roles {
+            strategist => updatable_by: [];
+        },
+        methods {
+            deposit => PUBLIC;
+            withdraw => PUBLIC;
+            rebalance => restrict_to: [strategist];
+            get_ranges => PUBLIC;
+            get_idle_amounts => PUBLIC;
+            get_share_resource_addr => PUBLIC;
+        }
+    }
+
+    struct ManagedVault {
+        pool: Global<Pool>,
+        positions: Vault,
+        pos_ids: Option<(NonFungibleLocalId, NonFungibleLocalId)>,
+        wide_range: TickRange,
+        narrow_range: TickRange,
+        idle0: Vault,
+        idle1: Vault,
+        share_resource_manager: ResourceManager,
+        share_minter_badge: Vault,
+    }
+
+    impl ManagedVault {
+        /**
+         * Creates a new managed vault of the given pool, with the initial wide and narrow ranges. The holders of the strategy badge
+         * can rebalance the ranges.
+         */
+        pub fn new(
+            pool_addr: ComponentAddress,
+            strategy_badge_addr: ResourceAddress,
+            wide_range: TickRange,
+            narrow_range: TickRange,
+        ) -> Global<ManagedVault> {
+            let pool: Global<Pool> = pool_addr.into();
+            let pool_state = pool.get_pool_state();
+            let wide_range = ManagedVault::validate_range(wide_range, pool_state.tick_spacing);
+            let narrow_range = ManagedVault::validate_range(narrow_range, pool_state.tick_spacing);
+
+            let share_minter_badge: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .mint_initial_supply(1)
+                .into();
+            let share_resource_manager = ResourceBuilder::new_fungible(OwnerRole::None)
+                .mint_roles(mint_roles! {
+                    minter => rule!(require(share_minter_badge.resource_address()));
+                    minter_updater => rule!(deny_all);
+                })
+                .burn_roles(burn_roles! {
+                    burner => rule!(require(share_minter_badge.resource_address()));
+                    burner_updater => rule!(deny_all);
+                })
+                .create_with_no_initial_supply();
+
+            Self {
+                pool,
+                positions: Vault::new(pool_state.position_nft_addr),
+                pos_ids: None,
+                wide_range,
+                narrow_range,
+                idle0: Vault::new(pool_state.resource0_addr),
+                idle1: Vault::new(pool_state.resource1_addr),
+                share_resource_manager,
+                share_minter_badge: Vault::with_bucket(share_minter_badge),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                strategist => rule!(require(strategy_badge_addr));
+            ))
+            .globalize()
+        }
+
+        /**
+         * Deposits the provided amount0,1 in the vault, in the ratio of the vault amounts. The first deposit sets the ratio and gets
+         * shares equal to its amount0 + amount1.
+         *
+         * Returns the vault shares and the remainders amount0,1 over the vault ratio
+         */
+        pub fn deposit(&mut self, mut bucket0: Bucket, mut bucket1: Bucket) -> (Bucket, Bucket, Bucket) {
+            assert!(
+                bucket0.resource_address() == self.idle0.resource_address()
+                    && bucket1.resource_address() == self.idle1.resource_address(),
+                "Wrong resource type sent. Deposit op aborted."
+            );
+            let total_supply = self.share_resource_manager.total_supply().unwrap();
+            let shares = if total_supply == Decimal::zero() {
+                let shares = bucket0.amount() + bucket1.amount();
+                self.idle0.put(bucket0.take(bucket0.amount()));
+                self.idle1.put(bucket1.take(bucket1.amount()));
+                shares
+            } else {
+                //the deposit gets the share of the vault amounts it matches in full
+                let (amount0, amount1) = self.vault_amounts();
+                let ratio = match (amount0 > Decimal::zero(), amount1 > Decimal::zero()) {
+                    (true, true) => (bucket0.amount() / amount0).min(bucket1.amount() / amount1),
+                    (true, false) => bucket0.amount() / amount0,
+                    (false, true) => bucket1.amount() / amount1,
+                    (false, false) => panic!("The vault is empty. Deposit op aborted."),
+                };
+                self.idle0.put(bucket0.take(amount0 * ratio));
+                self.idle1.put(bucket1.take(amount1 * ratio));
+                total_supply * ratio
+            };
+            assert!(shares > Decimal::zero(), "Nothing deposited. Deposit op aborted.");
+
+            self.add_positions();
+            let shares = self
+                .share_minter_badge
+                .as_fungible()
+                .authorize_with_amount(1, || self.share_resource_manager.mint(shares));
+            (shares, bucket0, bucket1)
+        }
+
+        /**
+         * Withdraws the amount0,1 of the provided vault shares, which are burned
+         */
+        pub fn withdraw(&mut self, shares: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                shares.resource_address() == self.share_resource_manager.address(),
+                "Wrong resource type sent. Withdraw op aborted."
+            );
+            let fraction = shares.amount() / self.share_resource_manager.total_supply().unwrap();
+
+            //the withdrawals are always allowed, in emergency mode the positions can only be removed whole
+            if self.pool.is_emergency_mode() {
+                self.remove_positions();
+            } else {
+                self.collect_position_fees();
+            }
+            let mut bucket0 = self.idle0.take(self.idle0.amount() * fraction);
+            let mut bucket1 = self.idle1.take(self.idle1.amount() * fraction);
+            if let Some((wide_pos_id, narrow_pos_id)) = self.pos_ids.clone() {
+                for pos_id in [wide_pos_id, narrow_pos_id] {
+                    let liq = self.pool.get_position(pos_id.clone()).unwrap().liq;
+                    let (removed0, removed1) = self.pool.remove_liq(liq * fraction, self.position_proof(&pos_id));
+                    bucket0.put(removed0);
+                    bucket1.put(removed1);
+                }
+                //the last withdrawal empties the positions, which are removed from the pool
+                if self.pool.get_position(self.pos_ids.as_ref().unwrap().0.clone()).is_none() {
+                    self.pos_ids = None;
+                }
+            }
+            self.share_minter_badge.as_fungible().authorize_with_amount(1, || shares.burn());
+            (bucket0, bucket1)
+        }
+
+        /**
+         * Moves the vault positions to the given wide and narrow ranges, see Pool::rebalance_pos. The amounts that don't fit in the
+         * new ranges at the current price wait in the vault until the next deposit or withdrawal.
+         */
+        pub fn rebalance(&mut self, wide_range: TickRange, narrow_range: TickRange) {
+            let tick_spacing = self.pool.get_pool_state().tick_spacing;
+            self.wide_range = ManagedVault::validate_range(wide_range, tick_spacing);
+            self.narrow_range = ManagedVault::validate_range(narrow_range, tick_spacing);
+
+            if let Some((wide_pos_id, narrow_pos_id)) = self.pos_ids.take() {
+                let wide_pos_id = self.rebalance_position(wide_pos_id, self.wide_range);
+                let narrow_pos_id = self.rebalance_position(narrow_pos_id, self.narrow_range);
+                self.pos_ids = Some((wide_pos_id, narrow_pos_id));
+            }
+        }
+
+        /**
+         * Returns the wide and the narrow ranges of the vault positions
+         */
+        pub fn get_ranges(&self) -> (TickRange, TickRange) {
+            (self.wide_range, self.narrow_range)
+        }
+
+        /**
+         * Returns the amount0,1 waiting in the vault, out of the positions
+         */
+        pub fn get_idle_amounts(&self) -> (Decimal, Decimal) {
+            (self.idle0.amount(), self.idle1.amount())
+        }
+
+        /**
+         * Returns the fungible resource of the vault shares
+         */
+        pub fn get_share_resource_addr(&self) -> ResourceAddress {
+            self.share_resource_manager.address()
+        }
+
+        fn validate_range(range: TickRange, tick_spacing: i32) -> TickRange {
+            match range.validate(tick_spacing) {
+                Ok(range) => range,
+                Err(err) => panic!("Invalid tick range: {} Op aborted.", err),
+            }
+        }
+
+        fn position_proof(&self, pos_id: &NonFungibleLocalId) -> Proof {
+            self.positions
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset!(pos_id.clone()))
+                .into()
+        }
+
+        //the idle amounts plus the amounts of the positions at the current price, fees included
+        fn vault_amounts(&self) -> (Decimal, Decimal) {
+            let (mut amount0, mut amount1) = (self.idle0.amount(), self.idle1.amount());
+            if let Some((wide_pos_id, narrow_pos_id)) = self.pos_ids.clone() {
+                let sqrt_price = self.pool.get_pool_state().sqrt_price;
+                for pos_id in [wide_pos_id, narrow_pos_id] {
+                    let (pos_amount0, pos_amount1) = self.pool.get_position_amounts_at(pos_id, sqrt_price);
+                    amount0 += pos_amount0;
+                    amount1 += pos_amount1;
+                }
+            }
+            (amount0, amount1)
+        }
+
+        //the fees of the pool positions are moved to the idle vaults
+        fn collect_position_fees(&mut self) {
+            if let Some((wide_pos_id, narrow_pos_id)) = self.pos_ids.clone() {
+                for pos_id in [wide_pos_id, narrow_pos_id] {
+                    let (bucket0, bucket1) = self.pool.collect_fees(self.position_proof(&pos_id));
+                    self.idle0.put(bucket0);
+                    self.idle1.put(bucket1);
+                }
+            }
+        }
+
+        //the pool positions and their fees are moved to the idle vaults
+        fn remove_positions(&mut self) {
+            if let Some((wide_pos_id, narrow_pos_id)) = self.pos_ids.take() {
+                for pos_id in [wide_pos_id, narrow_pos_id] {
+                    let (bucket0, bucket1) = self.pool.remove_pos(self.position_proof(&pos_id));
+                    self.idle0.put(bucket0);
+                    self.idle1.put(bucket1);
+                }
+            }
+        }
+
+        //half of the idle amounts go to the wide range, the rest to the narrow one, topping up the positions if they are open
+        fn add_positions(&mut self) {
+            if let Some((wide_pos_id, narrow_pos_id)) = self.pos_ids.clone() {
+                let (remainder0, remainder1) = self.pool.add_liq(
+                    self.idle0.take(self.idle0.amount() / 2),
+                    self.idle1.take(self.idle1.amount() / 2),
+                    self.position_proof(&wide_pos_id),
+                );
+                self.idle0.put(remainder0);
+                self.idle1.put(remainder1);
+                let (remainder0, remainder1) = self.pool.add_liq(
+                    self.idle0.take(self.idle0.amount()),
+                    self.idle1.take(self.idle1.amount()),
+                    self.position_proof(&narrow_pos_id),
+                );
+                self.idle0.put(remainder0);
+                self.idle1.put(remainder1);
+                return;
+            }
+
+            let (wide_nft, remainder0, remainder1) = self.pool.add_pos(
+                self.idle0.take(self.idle0.amount() / 2),
+                self.idle1.take(self.idle1.amount() / 2),
+                self.wide_range,
+            );
+            self.idle0.put(remainder0);
+            self.idle1.put(remainder1);
+            let (narrow_nft, remainder0, remainder1) = self.pool.add_pos(
+                self.idle0.take(self.idle0.amount()),
+                self.idle1.take(self.idle1.amount()),
+                self.narrow_range,
+            );
+            self.idle0.put(remainder0);
+            self.idle1.put(remainder1);
+
+            self.pos_ids = Some((
+                wide_nft.as_non_fungible().non_fungible_local_id(),
+                narrow_nft.as_non_fungible().non_fungible_local_id(),
+            ));
+            self.positions.put(wide_nft);
+            self.positions.put(narrow_nft);
+        }
+
+        fn rebalance_position(&mut self, pos_id: NonFungibleLocalId, range: TickRange) -> NonFungibleLocalId {
+            let (pos_nft, remainder0, remainder1) = self.pool.rebalance_pos(self.position_proof(&pos_id), range);
+            self.idle0.put(remainder0);
+            self.idle1.put(remainder1);
+            let new_pos_id = pos_nft.as_non_fungible().non_fungible_local_id();
+            self.positions.put(pos_nft);
+            new_pos_id
+        }
+    }
+}
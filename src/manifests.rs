@@ -0,0 +1,221 @@
+use radix_engine_common::prelude::*;
+use crate::pool_math;
+use crate::tick_math::SqrtPrice;
+use transaction::builder::{ManifestBuilder, ResolvableArguments};
+use transaction::model::TransactionManifestV1;
+
+//the manifests of the pool operations for the integrators, e.g. wallets, bots or other dApps. The outputs are deposited back into the
+//account and the fee isn't locked, as the wallets lock it themselves, see with_fee_lock for the other signers
+
+/**
+ * The parameters of a new pool, see Pool::new: its resources, fee, price of token0 vs token1 and admin badge, and the price range
+ * and the amounts of its initial position
+ */
+#[derive(Clone, Debug)]
+pub struct NewPool {
+    pub resource0_addr: ResourceAddress,
+    pub resource1_addr: ResourceAddress,
+    pub fee: Decimal,
+    pub price: Decimal,
+    pub admin_badge_addr: ResourceAddress,
+    pub low_price: Decimal,
+    pub high_price: Decimal,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+}
+
+//the PoolConfig of a plain pool with an initial position, encoded as a tuple in its field order, as the pool module isn't built
+//with the manifests feature alone. The admin rule, the launch ladder, the price band and the buy cap are always none
+fn pool_config(pool: &NewPool) -> impl ManifestEncode {
+    let sqrt_price = |price: Decimal| SqrtPrice(pool_math::sqrt(price));
+    (
+        pool.fee,
+        sqrt_price(pool.price),
+        1i32,
+        Some(pool.admin_badge_addr),
+        None::<ManifestValue>,
+        Some((sqrt_price(pool.low_price), sqrt_price(pool.high_price))),
+        None::<ManifestValue>,
+        None::<(i32, i32)>,
+        None::<ResourceAddress>,
+        None::<ResourceAddress>,
+        None::<Decimal>,
+        None::<ManifestValue>,
+        false,
+        (
+            None::<String>,
+            None::<String>,
+            None::<Vec<String>>,
+            None::<String>,
+            None::<String>,
+            None::<ComponentAddress>,
+        ),
+    )
+}
+
+fn deposit_all(manifest: ManifestBuilder, account_addr: ComponentAddress) -> TransactionManifestV1 {
+    manifest
+        .call_method(account_addr, "deposit_batch", manifest_args!(ManifestExpression::EntireWorktop))
+        .build()
+}
+
+/**
+ * Creates a pool of the given package with Pool::new, its initial position funded by the account, which gets the position NFT and
+ * the unused amounts. The sqrt prices are computed with pool_math::sqrt, truncated to 18 decimals.
+ */
+pub fn build_new_pool_manifest(
+    package_addr: PackageAddress,
+    account_addr: ComponentAddress,
+    pool: &NewPool,
+) -> TransactionManifestV1 {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account_addr, pool.resource0_addr, pool.amount0)
+        .withdraw_from_account(account_addr, pool.resource1_addr, pool.amount1)
+        .take_from_worktop(pool.resource0_addr, pool.amount0, "bucket0")
+        .take_from_worktop(pool.resource1_addr, pool.amount1, "bucket1")
+        .call_function_with_name_lookup(package_addr, "Pool", "new", |lookup| {
+            (
+                pool.resource0_addr,
+                pool.resource1_addr,
+                pool_config(pool),
+                lookup.bucket("bucket0"),
+                lookup.bucket("bucket1"),
+            )
+        });
+    deposit_all(manifest, account_addr)
+}
+
+/**
+ * Adds a position on [low_tick, high_tick) to the pool with Pool::add_pos, funded with at most the given amounts of resource0 and
+ * resource1 of the account, which gets the position NFT and the unused amounts
+ */
+pub fn build_add_pos_manifest(
+    pool_addr: ComponentAddress,
+    account_addr: ComponentAddress,
+    (resource0_addr, amount0): (ResourceAddress, Decimal),
+    (resource1_addr, amount1): (ResourceAddress, Decimal),
+    (low_tick, high_tick): (i32, i32),
+) -> TransactionManifestV1 {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account_addr, resource0_addr, amount0)
+        .withdraw_from_account(account_addr, resource1_addr, amount1)
+        .take_from_worktop(resource0_addr, amount0, "bucket0")
+        .take_from_worktop(resource1_addr, amount1, "bucket1")
+        .call_method_with_name_lookup(pool_addr, "add_pos", |lookup| {
+            (lookup.bucket("bucket0"), lookup.bucket("bucket1"), (low_tick, high_tick))
+        });
+    deposit_all(manifest, account_addr)
+}
+
+/**
+ * Removes the position of the given NFT, held by the account, with Pool::remove_pos, the transaction failing if the amounts
+ * returned, the fees included, are less than the given minimums of resource0 and resource1
+ */
+pub fn build_remove_pos_manifest(
+    pool_addr: ComponentAddress,
+    account_addr: ComponentAddress,
+    pos_nft_id: NonFungibleGlobalId,
+    (resource0_addr, min_amount0): (ResourceAddress, Decimal),
+    (resource1_addr, min_amount1): (ResourceAddress, Decimal),
+) -> TransactionManifestV1 {
+    let pos_nft_addr = pos_nft_id.resource_address();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_non_fungible(account_addr, pos_nft_id)
+        .create_proof_from_auth_zone_of_amount(pos_nft_addr, Decimal::one(), "proof")
+        .call_method_with_name_lookup(pool_addr, "remove_pos", |lookup| (lookup.proof("proof"),))
+        .assert_worktop_contains(resource0_addr, min_amount0)
+        .assert_worktop_contains(resource1_addr, min_amount1);
+    deposit_all(manifest, account_addr)
+}
+
+/**
+ * Collects the fees of the position of the given NFT, held by the account, with Pool::collect_fees, the transaction failing if
+ * the fees are less than the given minimums of resource0 and resource1
+ */
+pub fn build_collect_fees_manifest(
+    pool_addr: ComponentAddress,
+    account_addr: ComponentAddress,
+    pos_nft_id: NonFungibleGlobalId,
+    (resource0_addr, min_fees0): (ResourceAddress, Decimal),
+    (resource1_addr, min_fees1): (ResourceAddress, Decimal),
+) -> TransactionManifestV1 {
+    let pos_nft_addr = pos_nft_id.resource_address();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_non_fungible(account_addr, pos_nft_id)
+        .create_proof_from_auth_zone_of_amount(pos_nft_addr, Decimal::one(), "proof")
+        .call_method_with_name_lookup(pool_addr, "collect_fees", |lookup| (lookup.proof("proof"),))
+        .assert_worktop_contains(resource0_addr, min_fees0)
+        .assert_worktop_contains(resource1_addr, min_fees1);
+    deposit_all(manifest, account_addr)
+}
+
+/**
+ * Swaps the given amount of the resource, taken from the account, with Pool::swap, the transaction failing if the output of the
+ * other resource is less than min_out, see min_out_with_slippage
+ */
+pub fn build_swap_manifest(
+    pool_addr: ComponentAddress,
+    account_addr: ComponentAddress,
+    resource_addr: ResourceAddress,
+    amount: Decimal,
+    resource_out_addr: ResourceAddress,
+    min_out: Decimal,
+) -> TransactionManifestV1 {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account_addr, resource_addr, amount)
+        .take_from_worktop(resource_addr, amount, "bucket")
+        .call_method_with_name_lookup(pool_addr, "swap", |lookup| (lookup.bucket("bucket"),))
+        .assert_worktop_contains(resource_out_addr, min_out);
+    deposit_all(manifest, account_addr)
+}
+
+/**
+ * The minimum output of a swap quoted to expected_out, e.g. by Pool::quote_swap, with the given slippage tolerance, e.g. 0.005
+ */
+pub fn min_out_with_slippage(expected_out: Decimal, slippage: Decimal) -> Decimal {
+    expected_out * (Decimal::one() - slippage)
+}
+
+/**
+ * Prepends the locking of the given XRD amount from the account for the transaction fee, for the signers other than the wallets
+ */
+pub fn with_fee_lock(manifest: TransactionManifestV1, account_addr: ComponentAddress, amount: Decimal) -> TransactionManifestV1 {
+    let mut locked = ManifestBuilder::new().lock_fee(account_addr, amount).build();
+    locked.instructions.extend(manifest.instructions);
+    locked.blobs = manifest.blobs;
+    locked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transaction::manifest::decompile;
+
+    #[test]
+    fn test_swap_manifest() {
+        let manifest = build_swap_manifest(FAUCET, FAUCET, XRD, dec!("100"), PACKAGE_OWNER_BADGE, dec!("95"));
+        let manifest = with_fee_lock(manifest, FAUCET, dec!("10"));
+        let rtm = decompile(&manifest.instructions, &NetworkDefinition::simulator()).unwrap();
+        assert!(rtm.find("\"lock_fee\"").unwrap() < rtm.find("\"swap\"").unwrap());
+        assert!(rtm.contains("ASSERT_WORKTOP_CONTAINS"));
+        assert!(rtm.contains("Decimal(\"95\")"));
+        assert!(rtm.contains("\"deposit_batch\""));
+    }
+
+    #[test]
+    fn test_remove_pos_manifest() {
+        let pos_nft_id = NonFungibleGlobalId::new(ACCOUNT_OWNER_BADGE, NonFungibleLocalId::integer(1));
+        let manifest =
+            build_remove_pos_manifest(FAUCET, FAUCET, pos_nft_id, (XRD, dec!("1")), (PACKAGE_OWNER_BADGE, dec!("2")));
+        let rtm = decompile(&manifest.instructions, &NetworkDefinition::simulator()).unwrap();
+        assert!(rtm.contains("\"create_proof_of_non_fungibles\""));
+        assert!(rtm.contains("\"remove_pos\""));
+        assert!(rtm.contains("NonFungibleLocalId(\"#1#\")"));
+    }
+
+    #[test]
+    fn test_min_out_with_slippage() {
+        assert_eq!(dec!("99.5"), min_out_with_slippage(dec!("100"), dec!("0.005")));
+        assert_eq!(dec!("100"), min_out_with_slippage(dec!("100"), Decimal::zero()));
+    }
+}
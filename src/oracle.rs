@@ -0,0 +1,170 @@
+use radix_engine_common::prelude::*;
+use crate::tick_math::Tick;
+
+/**
+ * An observation of the pool oracle: the time in seconds since the unix epoch, the tick accumulated up to that time, the sum of the
+ * pool ticks weighted by the seconds they were current, the live (in range) liquidity accumulated the same way, and the seconds
+ * accumulated divided by the live liquidity, the seconds without live liquidity counting as with a liquidity of 1. The time weighted
+ * averages between two observations are the differences of their cumulative values divided by the seconds in between.
+ *
+ * The seconds per liquidity accumulated in between two observations times the liquidity of a position in range all along are the
+ * seconds the position was in range, e.g. for the liquidity mining rewards.
+ */
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub tick_cumulative: i64,
+    pub liquidity_cumulative: Decimal,
+    pub seconds_per_liquidity_cumulative: Decimal,
+}
+
+impl Observation {
+    /**
+     * The observation at the given later timestamp, the pool tick and live liquidity being constant in between
+     */
+    pub fn advance(&self, timestamp: i64, tick: i32, liquidity: Decimal) -> Observation {
+        let seconds = timestamp - self.timestamp;
+        let seconds_per_liquidity =
+            if liquidity > Decimal::zero() { Decimal::from(seconds) / liquidity } else { Decimal::from(seconds) };
+        Observation {
+            timestamp,
+            tick_cumulative: self.tick_cumulative + tick as i64 * seconds,
+            liquidity_cumulative: self.liquidity_cumulative + liquidity * seconds,
+            seconds_per_liquidity_cumulative: self.seconds_per_liquidity_cumulative + seconds_per_liquidity,
+        }
+    }
+
+    /**
+     * The observation at the given timestamp in between this observation and the next one, as the pool tick and live liquidity are
+     * constant in between two consecutive observations
+     */
+    pub fn interpolate(&self, next: &Observation, timestamp: i64) -> Observation {
+        assert!(
+            self.timestamp <= timestamp && timestamp <= next.timestamp,
+            "The timestamp should be in between the observations."
+        );
+        if timestamp == self.timestamp {
+            return *self;
+        }
+        let seconds = next.timestamp - self.timestamp;
+        let tick = (next.tick_cumulative - self.tick_cumulative) / seconds;
+        let liquidity = (next.liquidity_cumulative - self.liquidity_cumulative) / seconds;
+        //interpolated as is, so it matches the next observation, the seconds per liquidity being rounded when accumulated
+        let seconds_per_liquidity =
+            (next.seconds_per_liquidity_cumulative - self.seconds_per_liquidity_cumulative) * (timestamp - self.timestamp) / seconds;
+        Observation {
+            seconds_per_liquidity_cumulative: self.seconds_per_liquidity_cumulative + seconds_per_liquidity,
+            ..self.advance(timestamp, tick as i32, liquidity)
+        }
+    }
+}
+
+/**
+ * The time weighted average tick in between the given observations, rounded towards negative infinity. The tick being the logarithm
+ * of the price in base 1.0001, the price at the average tick is the time weighted geometric mean of the prices, not the arithmetic
+ * one, so a short lived outlier price, e.g. a price manipulated during a block, moves the average far less.
+ */
+pub fn mean_tick(start: &Observation, end: &Observation) -> Tick {
+    assert!(end.timestamp > start.timestamp, "The end observation should be after the start observation.");
+    let mean = (end.tick_cumulative - start.tick_cumulative).div_euclid(end.timestamp - start.timestamp);
+    Tick(mean as i32)
+}
+
+/**
+ * The time weighted average live liquidity in between the given observations, e.g. to weight the rewards of a pool by its depth
+ */
+pub fn mean_liquidity(start: &Observation, end: &Observation) -> Decimal {
+    assert!(end.timestamp > start.timestamp, "The end observation should be after the start observation.");
+    (end.liquidity_cumulative - start.liquidity_cumulative) / (end.timestamp - start.timestamp)
+}
+
+/**
+ * The seconds a position of the given liquidity was in range in between the given observations, the position having been in range
+ * all along, e.g. as the pool tick stayed in its range
+ */
+pub fn seconds_in_range(start: &Observation, end: &Observation, liquidity: Decimal) -> Decimal {
+    assert!(end.timestamp >= start.timestamp, "The end observation should be after the start observation.");
+    (end.seconds_per_liquidity_cumulative - start.seconds_per_liquidity_cumulative) * liquidity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick_math;
+
+    #[test]
+    fn test_advance_and_interpolate() {
+        let observation = Observation {
+            timestamp: 1000,
+            tick_cumulative: 500,
+            liquidity_cumulative: dec!("10000"),
+            seconds_per_liquidity_cumulative: dec!("20"),
+        };
+        let next = observation.advance(1600, -25, dec!("1.5"));
+        assert_eq!(
+            Observation {
+                timestamp: 1600,
+                tick_cumulative: -14500,
+                liquidity_cumulative: dec!("10900"),
+                seconds_per_liquidity_cumulative: dec!("420"),
+            },
+            next
+        );
+        assert_eq!(
+            Observation {
+                timestamp: 1300,
+                tick_cumulative: -7000,
+                liquidity_cumulative: dec!("10450"),
+                seconds_per_liquidity_cumulative: dec!("220"),
+            },
+            observation.interpolate(&next, 1300)
+        );
+        assert_eq!(observation, observation.interpolate(&next, 1000));
+        assert_eq!(next, observation.interpolate(&next, 1600));
+    }
+
+    #[test]
+    fn test_mean_tick() {
+        let start = Observation {
+            timestamp: 0,
+            tick_cumulative: 0,
+            liquidity_cumulative: Decimal::zero(),
+            seconds_per_liquidity_cumulative: Decimal::zero(),
+        };
+        assert_eq!(Tick(-3), mean_tick(&start, &Observation { timestamp: 2, tick_cumulative: -5, ..start }));
+        assert_eq!(Tick(2), mean_tick(&start, &Observation { timestamp: 2, tick_cumulative: 5, ..start }));
+
+        // a price of 1 for 590 seconds and an outlier price of 100 (tick 46054) for 10 seconds: the arithmetic mean would be 2.65
+        let end = start.advance(590, 0, Decimal::zero()).advance(600, 46054, Decimal::zero());
+        assert_eq!(Tick(767), mean_tick(&start, &end));
+        let sqrt_price = tick_math::sqrt_price_at_tick(mean_tick(&start, &end)).0;
+        assert!(sqrt_price * sqrt_price < dec!("1.08"));
+    }
+
+    #[test]
+    fn test_mean_liquidity() {
+        let start = Observation {
+            timestamp: 0,
+            tick_cumulative: 0,
+            liquidity_cumulative: Decimal::zero(),
+            seconds_per_liquidity_cumulative: Decimal::zero(),
+        };
+        let end = start.advance(300, 0, dec!("1000")).advance(600, 0, dec!("3000"));
+        assert_eq!(dec!("2000"), mean_liquidity(&start, &end));
+    }
+
+    #[test]
+    fn test_seconds_in_range() {
+        let start = Observation {
+            timestamp: 0,
+            tick_cumulative: 0,
+            liquidity_cumulative: Decimal::zero(),
+            seconds_per_liquidity_cumulative: Decimal::zero(),
+        };
+        // a position of 1000 liquidity out of 4000 for 300 seconds, then out of 1000 for 300 seconds, and no liquidity for 60 seconds
+        let end = start.advance(300, 0, dec!("4000")).advance(600, 0, dec!("1000"));
+        assert_eq!(dec!("375"), seconds_in_range(&start, &end, dec!("1000")));
+        let later = end.advance(660, 0, Decimal::zero());
+        assert_eq!(dec!("60"), later.seconds_per_liquidity_cumulative - end.seconds_per_liquidity_cumulative);
+    }
+}
@@ -1,24 +1,248 @@
 use scrypto::prelude::*;
 use std::collections::hash_map::Entry;
+use std::fmt;
 
 use crate::btree_set_ext;
+use crate::oracle::{self, Observation};
 use crate::pool_math;
-use crate::tick_math;
+use crate::swap_math;
+use crate::tick_math::{self, SqrtPrice, Tick, TickRange};
+
+/**
+ * How many epochs the pool keeps statistics for. Older statistics are overwritten.
+ */
+pub const EPOCH_STATS_CAPACITY: u64 = 10000;
+
+/**
+ * How many observations the pool oracle keeps initially, see Pool::increase_observation_cardinality. The observations are written at
+ * most once a minute, on swaps, the older ones being overwritten.
+ */
+pub const INITIAL_OBSERVATION_CARDINALITY: u32 = 10;
+
+/**
+ * The maximum number of observations the pool oracle can keep, a bit over 45 days of observations written each minute
+ */
+pub const MAX_OBSERVATION_CARDINALITY: u32 = 65535;
+
+/**
+ * The maximum number of hooks a pool can call on each swap, as their calls add up to the swap cost
+ */
+pub const MAX_HOOKS: usize = 5;
+
+/**
+ * The seconds the parameter changes of a new pool wait before they can be executed, see Pool::schedule_change
+ */
+pub const INITIAL_TIMELOCK_DELAY: i64 = 86400;
+
+/**
+ * The decimal places the amounts of the positions removed in emergency mode are rounded down to, leaving the rounding errors in the
+ * pool, so the last positions can be removed too
+ */
+pub const EMERGENCY_AMOUNT_DECIMALS: i32 = 15;
+
+/**
+ * The maximum fee set with ParameterChange::SetFee, in basis points of the swapped amount
+ */
+pub const MAX_FEE_BPS: u32 = 1000;
+
+/**
+ * The maximum share of the swap fees going to the protocol, set with ParameterChange::SetProtocolFeeShare, in basis points
+ */
+pub const MAX_PROTOCOL_FEE_SHARE_BPS: u32 = 2500;
+
+/**
+ * The maximum share of the swap fees paid to the referrers, set with ParameterChange::SetReferralFeeShare, in basis points
+ */
+pub const MAX_REFERRAL_FEE_SHARE_BPS: u32 = 2500;
+
+/**
+ * The epochs of the trailing volume of a trader, the current one included, see Pool::swap_as_trader
+ */
+pub const VOLUME_WINDOW_EPOCHS: u64 = 30;
+
+/**
+ * The maximum number of volume tiers, set with ParameterChange::SetVolumeTiers
+ */
+pub const MAX_VOLUME_TIERS: usize = 10;
+
+/**
+ * The maximum fee of a stable pool, see PoolConfig, in basis points of the swapped amount
+ */
+pub const MAX_STABLE_FEE_BPS: u32 = 5;
+
+/**
+ * The maximum number of used ticks a single swap of a stable pool can cross, as the positions of the stable pairs are concentrated
+ * on many narrow ranges around the peg and their crossings add up to the swap cost. The swap stops at the last tick it can cross and
+ * gives back the remaining amount, the traders splitting their larger swaps instead.
+ */
+pub const STABLE_MAX_TICKS_CROSSED: u32 = 100;
+
+/**
+ * The maximum single swap price impact of a stable pool when created, in basis points, see ParameterChange::SetMaxSingleSwapPriceImpact
+ */
+pub const STABLE_MAX_PRICE_IMPACT_BPS: u32 = 50;
+
+/**
+ * The maximum number of ticks of a page of Pool::list_initialized_ticks
+ */
+pub const MAX_TICKS_PAGE_SIZE: u32 = 1000;
+
+/**
+ * The features supported by the pool instances of this package version, see Pool::get_version. A feature is never removed from the
+ * list, the new ones being appended.
+ */
+pub const POOL_FEATURES: [&str; 45] = [
+    "tick_spacing",
+    "quote_swap",
+    "swap_event",
+    "aggregator_getters",
+    "migrate_position",
+    "export_state",
+    "oracle",
+    "hooks",
+    "fee_manager",
+    "timelock",
+    "admin_handover",
+    "pause",
+    "emergency_mode",
+    "metadata",
+    "royalties",
+    "permissioned",
+    "rebalance_pos",
+    "set_fee",
+    "protocol_fee",
+    "referral",
+    "volume_tiers",
+    "max_price_impact",
+    "position_nft_metadata",
+    "admin_rule",
+    "fee_ramp",
+    "launch",
+    "buy_cap",
+    "min_liquidity_gate",
+    "deprecation",
+    "position_events",
+    "tick_crossed_events",
+    "swap_event_effective_price",
+    "fee_config",
+    "list_initialized_ticks",
+    "stable_tier",
+    "no_seed_position",
+    "lp_whitelist",
+    "parameter_preview",
+    "portfolio",
+    "max_swap_input",
+    "pool_metadata",
+    "observe_batch",
+    "price_band",
+    "rescue",
+    "remove_liq",
+];
 
 #[blueprint]
+#[events(
+    SwapEvent,
+    PositionAddedEvent,
+    TickCrossedEvent,
+    LiquidityAddedEvent,
+    LiquidityRemovedEvent,
+    FeesCollectedEvent,
+    ParameterChangeScheduledEvent,
+    ProtocolFeeSwitchEvent,
+    PriceBandWidenedEvent
+)]
 mod pool_blueprint {
 
     enable_method_auth! {
         roles {
-            admin => updatable_by: [];
+            admin => updatable_by: [SELF];
+            pending_admin => updatable_by: [SELF];
+            fee_manager => updatable_by: [admin];
+            pauser => updatable_by: [admin];
+            importer => updatable_by: [admin];
+            treasury => updatable_by: [admin];
+            fee_switcher => updatable_by: [admin];
+            member => updatable_by: [];
+            liquidity_provider => updatable_by: [SELF];
         },
         methods {
-            add_pos => PUBLIC;
+            add_pos => restrict_to: [liquidity_provider];
             remove_pos => PUBLIC;
+            remove_liq => PUBLIC;
             add_liq => PUBLIC;
             add_accumulated_fees_to_liq => PUBLIC;
             collect_fees => PUBLIC;
-            swap => PUBLIC;
+            collect_fees_as => restrict_to: [member];
+            migrate_position => PUBLIC;
+            rebalance_pos => restrict_to: [liquidity_provider];
+            swap => restrict_to: [member];
+            swap_with_referrer => restrict_to: [member];
+            swap_as_trader => restrict_to: [member];
+            quote_swap => PUBLIC;
+            get_resource_addresses => PUBLIC;
+            get_price => PUBLIC;
+            get_fee => PUBLIC;
+            get_fee_config => PUBLIC;
+            get_fee_ramp => PUBLIC;
+            get_buy_cap => PUBLIC;
+            get_min_liquidity => PUBLIC;
+            get_price_band => PUBLIC;
+            get_version => PUBLIC;
+            amount_to_move_price => PUBLIC;
+            get_depth => PUBLIC;
+            get_pool_state => PUBLIC;
+            get_tvl => PUBLIC;
+            get_epoch_stats => PUBLIC;
+            observe => PUBLIC;
+            twap_tick => PUBLIC;
+            twap_price => PUBLIC;
+            increase_observation_cardinality => PUBLIC;
+            get_observation_cardinality => PUBLIC;
+            get_position => PUBLIC;
+            get_position_amounts_at => PUBLIC;
+            get_portfolio => PUBLIC;
+            get_positions => PUBLIC;
+            get_tick_states => PUBLIC;
+            list_initialized_ticks => PUBLIC;
+            import_state => restrict_to: [importer];
+            schedule_change => restrict_to: [fee_manager, admin];
+            execute_change => restrict_to: [fee_manager, admin];
+            cancel_change => restrict_to: [fee_manager, admin];
+            get_scheduled_changes => PUBLIC;
+            preview_parameter_change => PUBLIC;
+            propose_admin => restrict_to: [admin];
+            accept_admin => restrict_to: [pending_admin];
+            set_paused => restrict_to: [pauser, admin];
+            is_paused => PUBLIC;
+            enable_emergency_mode => restrict_to: [admin];
+            is_emergency_mode => PUBLIC;
+            deprecate => restrict_to: [admin];
+            is_deprecated => PUBLIC;
+            deposits_allowed => PUBLIC;
+            update_metadata => restrict_to: [admin];
+            set_position_nft_metadata => restrict_to: [admin];
+            claim_royalties => restrict_to: [treasury, admin];
+            set_protocol_fee_switch => restrict_to: [fee_switcher];
+            is_protocol_fee_enabled => PUBLIC;
+            get_protocol_fee_share => PUBLIC;
+            get_referral_registry => PUBLIC;
+            get_referral_fee_share => PUBLIC;
+            get_volume_tiers => PUBLIC;
+            get_trader_volume => PUBLIC;
+            get_max_single_swap_price_impact => PUBLIC;
+            get_max_swap_input_share => PUBLIC;
+            get_max_ticks_crossed => PUBLIC;
+            collect_protocol_fees => restrict_to: [treasury, admin];
+            deposit => PUBLIC;
+            rescue => restrict_to: [admin];
+            get_admin_badge_addr => PUBLIC;
+            get_pending_admin_badge_addr => PUBLIC;
+            get_timelock_delay => PUBLIC;
+            get_hooks => PUBLIC;
+            get_fee_manager => PUBLIC;
+            get_member_badge_addr => PUBLIC;
+            set_lp_badge_addr => restrict_to: [admin];
+            get_lp_badge_addr => PUBLIC;
             destroy => restrict_to: [admin];
         }
     }
@@ -26,10 +250,13 @@ mod pool_blueprint {
     struct Pool {
         vault0: Vault,
         vault1: Vault,
+        divisibility0: u8,
+        divisibility1: u8,
         live_liq: Decimal,
         tick: i32,
         sqrt_price: Decimal,
         fee: Decimal,
+        tick_spacing: i32,
         fee_global0: Decimal,
         fee_global1: Decimal,
         pos_nft_addr_resource_manager: ResourceManager,
@@ -37,56 +264,251 @@ mod pool_blueprint {
         used_ticks: BTreeSet<i32>,
         tick_states: HashMap<i32, TickState>,
         pos_nft_minter_badge: Vault,
-        admin_badge_addr: ResourceAddress,
+        imported_pos_nft_minter_badge: Option<Vault>,
+        admin_badge_addr: Option<ResourceAddress>,
+        pending_admin_badge_addr: Option<ResourceAddress>,
+        epoch_stats: KeyValueStore<u64, EpochStats>,
+        observations: KeyValueStore<u32, Observation>,
+        observation_index: u32,
+        observation_count: u32,
+        observation_cardinality: u32,
+        observation_cardinality_next: u32,
+        hooks: Vec<ComponentAddress>,
+        fee_manager: Option<ComponentAddress>,
+        timelock_delay: i64,
+        scheduled_changes: HashMap<u64, ScheduledChange>,
+        change_count: u64,
+        paused_operations: Vec<PausableOperation>,
+        emergency_mode: bool,
+        deprecated: bool,
+        member_badge_addr: Option<ResourceAddress>,
+        lp_badge_addr: Option<ResourceAddress>,
+        protocol_fee_share: Decimal,
+        protocol_fee_enabled: bool,
+        protocol_fees0: Vault,
+        protocol_fees1: Vault,
+        referral_registry: Option<ComponentAddress>,
+        referral_fee_share: Decimal,
+        volume_tiers: Vec<VolumeTier>,
+        trader_volumes: KeyValueStore<NonFungibleGlobalId, Vec<(u64, Decimal)>>,
+        max_single_swap_price_impact: Option<Decimal>,
+        max_swap_input_share: Option<Decimal>,
+        max_ticks_crossed: Option<u32>,
+        fee_ramp: Option<FeeRamp>,
+        buy_cap: Option<BuyCap>,
+        buy_cap_end_epoch: u64,
+        bought_amounts: KeyValueStore<NonFungibleGlobalId, Decimal>,
+        min_liquidity: Option<Decimal>,
+        price_band: Option<(i32, i32)>,
+        swap_royalty: Decimal,
+        foreign_vaults: KeyValueStore<ResourceAddress, Vault>,
     }
 
     impl Pool {
         /**
-         * Creates a new concentrated liquidity pool. Where:
-         * - resource0_addr, resource1_addr = fungible tokens address.
-         * - fee = pool fee, a percentage of the amount that is swapped, fee >= 0 and fee <= 1
-         * - sqrt_price = square root of the price token0 vs token1 when the pool is created.
-         * - admin_badge_addr = a badge that allows the pool creator to destroy the pool if conditions are met
+         * Creates a new concentrated liquidity pool of the fungible tokens resource0_addr and resource1_addr, set up by the given
+         * PoolConfig, e.g. permissioned, stable or a launch pool, see PoolConfig::new for the defaults of a plain pool. The admin can
+         * delegate the day-to-day operations with the component roles: fee_manager (the fee parameter changes), pauser, treasury and
+         * fee_switcher (the protocol fee switch), none being assigned initially. The importer role lets a pool export its state to
+         * this one, see Pool::import_state.
+         *
+         * The initial positions, if any, are added on behalf of the pool creator, with a one-shot creator badge burned right after,
+         * so the pools gated by badges can't be entered bypassing them: the initial position is funded with bucket0 and bucket1, the
+         * launch ladder with bucket0 only. The initial range doesn't bound the pool price or the other positions, and the initial
+         * position can be moved later, see Pool::rebalance_pos.
+         *
+         * Returns the new pool, the NFTs of the initial positions, in ascending order of their ranges, and the remainders of bucket0
+         * and bucket1
          */
         pub fn new(
             resource0_addr: ResourceAddress,
             resource1_addr: ResourceAddress,
-            fee: Decimal,
-            sqrt_price: Decimal,
-            admin_badge_addr: ResourceAddress,
-            low_sqrt_price: Decimal,
-            high_sqrt_price: Decimal,
+            config: PoolConfig,
             bucket0: Bucket,
             bucket1: Bucket,
-        ) -> (Global<Pool>, Bucket, Bucket, Bucket) {
-            assert!(
-                sqrt_price > Decimal::zero(),
-                "Invalid sqrt price, should be positive."
-            );
-            assert!(
-                sqrt_price >= low_sqrt_price,
-                "Invalid low sqrt price, should be smaller or equal with sqrt price."
+        ) -> (Global<Pool>, Vec<Bucket>, Bucket, Bucket) {
+            let (sqrt_price, tick_spacing) = (config.sqrt_price, config.tick_spacing);
+            let (initial_position, launch_ladder) = (config.initial_position, config.launch_ladder.clone());
+            match (initial_position, &launch_ladder) {
+                (Some((low_sqrt_price, high_sqrt_price)), None) => {
+                    assert!(
+                        sqrt_price >= low_sqrt_price,
+                        "Invalid low sqrt price, should be smaller or equal with sqrt price."
+                    );
+                    assert!(
+                        sqrt_price < high_sqrt_price,
+                        "Invalid high sqrt price, should be greater than sqrt price."
+                    );
+                }
+                (None, Some(launch_ladder)) => launch_ladder.validate(sqrt_price, tick_spacing),
+                (None, None) => {}
+                (Some(_), Some(_)) => panic!("Invalid initial liquidity, should be an initial position or a launch ladder."),
+            }
+
+            let creator_badge: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .burn_roles(burn_roles! {
+                    burner => rule!(allow_all);
+                    burner_updater => rule!(deny_all);
+                })
+                .mint_initial_supply(1)
+                .into();
+            let component = Pool::instantiate(resource0_addr, resource1_addr, config, creator_badge.resource_address());
+
+            let (pos_nfts, remainder0, remainder1) = creator_badge.as_fungible().authorize_with_amount(1, || {
+                match (initial_position, launch_ladder) {
+                    (Some((low_sqrt_price, high_sqrt_price)), _) => {
+                        let range = match TickRange::try_from_sqrt_prices(low_sqrt_price, high_sqrt_price, tick_spacing) {
+                            Ok(range) => range,
+                            Err(err) => panic!("Invalid sqrt price range: {}", err),
+                        };
+                        let (pos_nft, remainder0, remainder1) = component.add_pos(bucket0, bucket1, range);
+                        (vec![pos_nft], remainder0, remainder1)
+                    }
+                    (None, Some(launch_ladder)) => Pool::add_launch_ladder(&component, &launch_ladder, tick_spacing, bucket0, bucket1),
+                    (None, None) => (Vec::new(), bucket0, bucket1),
+                }
+            });
+            creator_badge.burn();
+
+            (component, pos_nfts, remainder0, remainder1)
+        }
+
+        //the launch ladder splits bucket0 evenly in its single sided positions, no resource1 being needed below their ranges
+        fn add_launch_ladder(
+            component: &Global<Pool>,
+            launch_ladder: &LaunchLadder,
+            tick_spacing: i32,
+            mut bucket0: Bucket,
+            bucket1: Bucket,
+        ) -> (Vec<Bucket>, Bucket, Bucket) {
+            let steps = launch_ladder.steps as i32;
+            let step_width = (launch_ladder.high_tick - launch_ladder.low_tick) / steps;
+            let step_amount = pool_math::round_down_to_divisibility(
+                bucket0.amount() / launch_ladder.steps,
+                Pool::fungible_divisibility(bucket0.resource_address()),
             );
+
+            let mut pos_nfts = Vec::new();
+            let mut remainder0 = Bucket::new(bucket0.resource_address());
+            for step in 0..steps {
+                let amount = if step == steps - 1 { bucket0.amount() } else { step_amount };
+                let range = match TickRange::try_new(
+                    Tick(launch_ladder.low_tick + step * step_width),
+                    Tick(launch_ladder.low_tick + (step + 1) * step_width),
+                    tick_spacing,
+                ) {
+                    Ok(range) => range,
+                    Err(err) => panic!("Invalid launch range: {}", err),
+                };
+                let (pos_nft, step_remainder0, step_remainder1) =
+                    component.add_pos(bucket0.take(amount), Bucket::new(bucket1.resource_address()), range);
+                step_remainder1.drop_empty();
+                remainder0.put(step_remainder0);
+                pos_nfts.push(pos_nft);
+            }
+            remainder0.put(bucket0);
+
+            (pos_nfts, remainder0, bucket1)
+        }
+
+        //instantiates and globalizes the pool at the configured price, without any position, see Pool::new. The holders of the
+        //creator badge can add positions too
+        fn instantiate(
+            resource0_addr: ResourceAddress,
+            resource1_addr: ResourceAddress,
+            config: PoolConfig,
+            creator_badge_addr: ResourceAddress,
+        ) -> Global<Pool> {
+            let PoolConfig {
+                fee,
+                sqrt_price,
+                tick_spacing,
+                admin_badge_addr,
+                admin_rule,
+                member_badge_addr,
+                lp_badge_addr,
+                min_liquidity,
+                price_band,
+                buy_cap,
+                stable,
+                metadata,
+                ..
+            } = config;
+            let (admin_rule, admin_badge_addr) = match (admin_rule, admin_badge_addr) {
+                (None, Some(admin_badge_addr)) => (rule!(require(admin_badge_addr)), Some(admin_badge_addr)),
+                (Some(admin_rule), None) => (admin_rule, None),
+                _ => panic!("Invalid admin, should be an admin badge or an admin rule."),
+            };
+            assert!(tick_spacing > 0, "Invalid tick spacing, should be positive.");
             assert!(
-                sqrt_price < high_sqrt_price,
-                "Invalid high sqrt price, should be greater than sqrt price."
+                sqrt_price.0 > Decimal::zero(),
+                "Invalid sqrt price, should be positive."
             );
+            let tick = match tick_math::try_tick_at_sqrt_price(sqrt_price) {
+                Ok(tick) => tick.0,
+                Err(err) => panic!("Invalid sqrt price: {}", err),
+            };
             assert!(
                 fee >= Decimal::zero() && fee <= Decimal::one(),
                 "Invalid fee, should be 0 <= fee <= 1"
             );
+            if stable {
+                assert!(
+                    fee <= Decimal::from(MAX_STABLE_FEE_BPS) / 10000,
+                    "Invalid stable pool fee, should be 0 <= fee <= {} bps.",
+                    MAX_STABLE_FEE_BPS
+                );
+                assert!(tick_spacing == 1, "Invalid stable pool tick spacing, should be 1.");
+            }
+            if let Some(min_liquidity) = min_liquidity {
+                assert!(
+                    min_liquidity > Decimal::zero(),
+                    "Invalid min liquidity, should be positive."
+                );
+            }
+            if let Some((low_tick, high_tick)) = price_band {
+                if let Err(err) = TickRange::try_new(Tick(low_tick), Tick(high_tick), tick_spacing) {
+                    panic!("Invalid price band: {}", err);
+                }
+            }
+            if let Some(buy_cap) = &buy_cap {
+                assert!(
+                    buy_cap.max_output > Decimal::zero() && buy_cap.epochs > 0,
+                    "Invalid buy cap, should be max_output > 0 and epochs > 0."
+                );
+            }
             assert!(
                 resource0_addr != resource1_addr,
                 "Pool resources should be different."
             );
-            Pool::validate_resource_type_is_fungible(resource0_addr);
-            Pool::validate_resource_type_is_fungible(resource1_addr);
+            let divisibility0 = Pool::fungible_divisibility(resource0_addr);
+            let divisibility1 = Pool::fungible_divisibility(resource1_addr);
 
             let pos_nft_minter_badge: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
                 .divisibility(DIVISIBILITY_NONE)
                 .mint_initial_supply(1)
                 .into();
+            let (symbol0, symbol1) = (Pool::resource_symbol(resource0_addr), Pool::resource_symbol(resource1_addr));
+            let fee_percent = fee * dec!("100");
+            //the position NFT metadata is updated with the minter badge, so it stays updatable after a state import, see
+            //set_position_nft_metadata
             let pos_nft_addr_resource_manager = ResourceBuilder::new_ruid_non_fungible::<PositionNFTData>(OwnerRole::None)
+                .metadata(metadata! {
+                    roles {
+                        metadata_setter => rule!(require(pos_nft_minter_badge.resource_address()));
+                        metadata_setter_updater => rule!(deny_all);
+                        metadata_locker => rule!(deny_all);
+                        metadata_locker_updater => rule!(deny_all);
+                    },
+                    init {
+                        "name" => format!("MojitoSwap {}/{} {}% position", symbol0, symbol1, fee_percent), updatable;
+                        "description" => format!(
+                            "A liquidity position of the MojitoSwap pool of {} and {}, with a {}% fee.",
+                            symbol0, symbol1, fee_percent
+                        ), updatable;
+                    }
+                })
                 .mint_roles(mint_roles! {
                     minter => rule!(require(pos_nft_minter_badge.resource_address()));
                     minter_updater => rule!(deny_all);
@@ -101,13 +523,36 @@ mod pool_blueprint {
                 })
                 .create_with_no_initial_supply();
 
-            let component = Self {
+            //the oracle starts observing the pool tick at its creation
+            let observations = KeyValueStore::new();
+            observations.insert(
+                0,
+                Observation {
+                    timestamp: Pool::current_timestamp(),
+                    tick_cumulative: 0,
+                    liquidity_cumulative: Decimal::zero(),
+                    seconds_per_liquidity_cumulative: Decimal::zero(),
+                },
+            );
+
+            //the pool sets its own metadata and royalties, see update_metadata and ParameterChange::SetSwapRoyalty
+            let (address_reservation, component_addr) = Runtime::allocate_component_address(Pool::blueprint_id());
+
+            let member_rule = match member_badge_addr {
+                Some(member_badge_addr) => rule!(require(member_badge_addr)),
+                None => rule!(allow_all),
+            };
+
+            Self {
                 vault0: Vault::new(resource0_addr),
                 vault1: Vault::new(resource1_addr),
+                divisibility0,
+                divisibility1,
                 live_liq: Decimal::zero(),
-                tick: tick_math::tick_at_sqrt_price(sqrt_price),
-                sqrt_price,
+                tick,
+                sqrt_price: sqrt_price.0,
                 fee,
+                tick_spacing,
                 fee_global0: Decimal::zero(),
                 fee_global1: Decimal::zero(),
                 pos_nft_addr_resource_manager,
@@ -115,235 +560,2170 @@ mod pool_blueprint {
                 used_ticks: BTreeSet::new(),
                 tick_states: HashMap::new(),
                 pos_nft_minter_badge: Vault::with_bucket(pos_nft_minter_badge),
+                imported_pos_nft_minter_badge: None,
                 admin_badge_addr,
+                pending_admin_badge_addr: None,
+                epoch_stats: KeyValueStore::new(),
+                observations,
+                observation_index: 0,
+                observation_count: 1,
+                observation_cardinality: INITIAL_OBSERVATION_CARDINALITY,
+                observation_cardinality_next: INITIAL_OBSERVATION_CARDINALITY,
+                hooks: Vec::new(),
+                fee_manager: None,
+                timelock_delay: INITIAL_TIMELOCK_DELAY,
+                scheduled_changes: HashMap::new(),
+                change_count: 0,
+                paused_operations: Vec::new(),
+                emergency_mode: false,
+                deprecated: false,
+                member_badge_addr,
+                lp_badge_addr,
+                protocol_fee_share: Decimal::zero(),
+                protocol_fee_enabled: false,
+                protocol_fees0: Vault::new(resource0_addr),
+                protocol_fees1: Vault::new(resource1_addr),
+                referral_registry: None,
+                referral_fee_share: Decimal::zero(),
+                volume_tiers: Vec::new(),
+                trader_volumes: KeyValueStore::new(),
+                max_single_swap_price_impact: if stable {
+                    Some(Decimal::from(STABLE_MAX_PRICE_IMPACT_BPS) / 10000)
+                } else {
+                    None
+                },
+                max_swap_input_share: None,
+                max_ticks_crossed: if stable { Some(STABLE_MAX_TICKS_CROSSED) } else { None },
+                fee_ramp: None,
+                buy_cap_end_epoch: Runtime::current_epoch().number() + buy_cap.as_ref().map_or(0, |buy_cap| buy_cap.epochs),
+                buy_cap,
+                bought_amounts: KeyValueStore::new(),
+                min_liquidity,
+                price_band,
+                swap_royalty: Decimal::zero(),
+                foreign_vaults: KeyValueStore::new(),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                admin => admin_rule;
+                pending_admin => rule!(deny_all);
+                fee_manager => rule!(deny_all);
+                pauser => rule!(deny_all);
+                importer => rule!(deny_all);
+                treasury => rule!(deny_all);
+                fee_switcher => rule!(deny_all);
+                member => member_rule;
+                liquidity_provider => Pool::lp_rule(member_badge_addr, lp_badge_addr, Some(creator_badge_addr));
+            ))
+            .enable_component_royalties(component_royalties! {
+                roles {
+                    royalty_setter => rule!(require(global_caller(component_addr)));
+                    royalty_setter_updater => rule!(deny_all);
+                    royalty_locker => rule!(deny_all);
+                    royalty_locker_updater => rule!(deny_all);
+                    royalty_claimer => rule!(require(global_caller(component_addr)));
+                    royalty_claimer_updater => rule!(deny_all);
+                },
+                //all the methods are listed, only the swap royalties can be set
+                init {
+                    add_pos => Free, locked;
+                    remove_pos => Free, locked;
+                    remove_liq => Free, locked;
+                    add_liq => Free, locked;
+                    add_accumulated_fees_to_liq => Free, locked;
+                    collect_fees => Free, locked;
+                    collect_fees_as => Free, locked;
+                    migrate_position => Free, locked;
+                    rebalance_pos => Free, locked;
+                    swap => Free, updatable;
+                    swap_with_referrer => Free, updatable;
+                    swap_as_trader => Free, updatable;
+                    quote_swap => Free, locked;
+                    get_resource_addresses => Free, locked;
+                    get_price => Free, locked;
+                    get_fee => Free, locked;
+                    get_fee_config => Free, locked;
+                    get_fee_ramp => Free, locked;
+                    get_buy_cap => Free, locked;
+                    get_min_liquidity => Free, locked;
+                    get_price_band => Free, locked;
+                    get_version => Free, locked;
+                    amount_to_move_price => Free, locked;
+                    get_depth => Free, locked;
+                    get_pool_state => Free, locked;
+                    get_tvl => Free, locked;
+                    get_epoch_stats => Free, locked;
+                    observe => Free, locked;
+                    twap_tick => Free, locked;
+                    twap_price => Free, locked;
+                    increase_observation_cardinality => Free, locked;
+                    get_observation_cardinality => Free, locked;
+                    get_position => Free, locked;
+                    get_position_amounts_at => Free, locked;
+                    get_portfolio => Free, locked;
+                    get_positions => Free, locked;
+                    get_tick_states => Free, locked;
+                    list_initialized_ticks => Free, locked;
+                    import_state => Free, locked;
+                    schedule_change => Free, locked;
+                    execute_change => Free, locked;
+                    cancel_change => Free, locked;
+                    get_scheduled_changes => Free, locked;
+                    preview_parameter_change => Free, locked;
+                    propose_admin => Free, locked;
+                    accept_admin => Free, locked;
+                    set_paused => Free, locked;
+                    is_paused => Free, locked;
+                    enable_emergency_mode => Free, locked;
+                    is_emergency_mode => Free, locked;
+                    deprecate => Free, locked;
+                    is_deprecated => Free, locked;
+                    deposits_allowed => Free, locked;
+                    update_metadata => Free, locked;
+                    set_position_nft_metadata => Free, locked;
+                    claim_royalties => Free, locked;
+                    set_protocol_fee_switch => Free, locked;
+                    is_protocol_fee_enabled => Free, locked;
+                    get_protocol_fee_share => Free, locked;
+                    get_referral_registry => Free, locked;
+                    get_referral_fee_share => Free, locked;
+                    get_volume_tiers => Free, locked;
+                    get_trader_volume => Free, locked;
+                    get_max_single_swap_price_impact => Free, locked;
+                    get_max_swap_input_share => Free, locked;
+                    get_max_ticks_crossed => Free, locked;
+                    collect_protocol_fees => Free, locked;
+                    deposit => Free, locked;
+                    rescue => Free, locked;
+                    get_admin_badge_addr => Free, locked;
+                    get_pending_admin_badge_addr => Free, locked;
+                    get_timelock_delay => Free, locked;
+                    get_hooks => Free, locked;
+                    get_fee_manager => Free, locked;
+                    get_member_badge_addr => Free, locked;
+                    set_lp_badge_addr => Free, locked;
+                    get_lp_badge_addr => Free, locked;
+                    destroy => Free, locked;
+                }
+            })
+            .metadata(metadata! {
+                roles {
+                    metadata_setter => rule!(require(global_caller(component_addr)));
+                    metadata_setter_updater => rule!(deny_all);
+                    metadata_locker => rule!(deny_all);
+                    metadata_locker_updater => rule!(deny_all);
+                },
+                init {
+                    "name" => metadata.name.unwrap_or_else(|| Pool::default_name(&symbol0, &symbol1, fee)), updatable;
+                    "description" => metadata.description.unwrap_or_else(
+                        || Pool::default_description(&symbol0, &symbol1, fee)
+                    ), updatable;
+                    "tags" => metadata.tags.unwrap_or_else(
+                        || vec!["dex".to_owned(), "liquidity-pool".to_owned(), "mojitoswap".to_owned()]
+                    ), updatable;
+                    "pair" => format!("{}/{}", symbol0, symbol1), locked;
+                    "fee_tier" => fee, updatable;
+                    "icon_url" => metadata.icon_url.map(UncheckedUrl::of), updatable;
+                    "info_url" => metadata.info_url.map(UncheckedUrl::of), updatable;
+                    "dapp_definition" => metadata.dapp_definition_addr.map(GlobalAddress::from), updatable;
+                }
+            })
+            .with_address(address_reservation)
+            .globalize()
+        }
+
+        //the LPs must hold both the member badge of a permissioned pool and the whitelist badge, if any. Pool::new adds the initial
+        //positions on behalf of the pool creators with a creator badge
+        fn lp_rule(
+            member_badge_addr: Option<ResourceAddress>,
+            lp_badge_addr: Option<ResourceAddress>,
+            creator_badge_addr: Option<ResourceAddress>,
+        ) -> AccessRule {
+            let badge_addrs: Vec<ResourceAddress> = member_badge_addr.into_iter().chain(lp_badge_addr).collect();
+            match creator_badge_addr {
+                _ if badge_addrs.is_empty() => rule!(allow_all),
+                Some(creator_badge_addr) => rule!(require_all_of(badge_addrs) || require(creator_badge_addr)),
+                None => rule!(require_all_of(badge_addrs)),
+            }
+        }
+
+        /**
+         * Returns the divisibility of the given pool resource, which must be fungible. It's detected once, at the pool creation, the
+         * amounts paid in being rounded up and the amounts paid out rounded down to it, see swap_internal.
+         */
+        fn fungible_divisibility(resource_addr: ResourceAddress) -> u8 {
+            match ResourceManager::from(resource_addr).resource_type() {
+                ResourceType::Fungible { divisibility } => divisibility,
+                _ => panic!("Pool resource0,1 must be of fungible type."),
+            }
+        }
+
+        /**
+         * Adds a new liquidity position in range [low_tick, high_tick] using the resource amounts in bucket0 and bucket1. Depending on the current price, amount0,1 might not be used entirely.
+         *
+         * Returns a NFT representing the position with the newly created liquidity and the remainders amount0,1.
+         */
+        pub fn add_pos(
+            &mut self,
+            mut bucket0: Bucket,
+            mut bucket1: Bucket,
+            range: TickRange,
+        ) -> (Bucket, Bucket, Bucket) {
+            debug!("### Adding a new position...");
+            self.check_allowed(PausableOperation::Deposit);
+            debug!("### Bucket0 resource={:?}", bucket0.resource_address());
+            debug!("### Bucket0={:?}", bucket0.amount());
+            debug!("### Bucket1 resource={:?}", bucket1.resource_address());
+            debug!("### Bucket1={:?}", bucket1.amount());
+            debug!("### Range={:?}", range);
+
+            //the range is decoded from the transaction arguments, so it must be validated again
+            let range = match range.validate(self.tick_spacing) {
+                Ok(range) => range,
+                Err(err) => panic!("Invalid tick range: {} Add position op aborted.", err),
+            };
+            let (low_tick, high_tick) = (range.low().0, range.high().0);
+            if let Some((band_low_tick, band_high_tick)) = self.price_band {
+                assert!(
+                    low_tick >= band_low_tick && high_tick <= band_high_tick,
+                    "Invalid tick range, should be inside the price band [{}, {}]. Add position op aborted.",
+                    band_low_tick,
+                    band_high_tick
+                );
+            }
+
+            // validate the passed resources
+            self.validate_resources(bucket0.resource_address(), bucket1.resource_address());
+
+            self.log_state("### Internal state before adding the new position");
+
+            let low_sqrt_price = tick_math::sqrt_price_at_tick(Tick(low_tick)).0;
+            let high_sqrt_price = tick_math::sqrt_price_at_tick(Tick(high_tick)).0;
+            //compute the liquidty and amount0,1 required for this liquidity, depending on the current tick
+            let (liq, required_amount0, required_amount1) =
+                pool_math::compute_range_liq_given_amounts(
+                    bucket0.amount(),
+                    bucket1.amount(),
+                    self.sqrt_price,
+                    low_sqrt_price,
+                    high_sqrt_price,
+                );
+            //the resources with less than 18 decimals can't pay the exact amounts, they are rounded up, the dust staying in the pool
+            let required_amount0 =
+                pool_math::round_up_to_divisibility(required_amount0, self.divisibility0).min(bucket0.amount());
+            let required_amount1 =
+                pool_math::round_up_to_divisibility(required_amount1, self.divisibility1).min(bucket1.amount());
+
+            //the oracle observes the live liq before it changes
+            self.write_observation();
+
+            //update live liq
+            self.update_live_liq(liq, low_tick, high_tick);
+
+            //update tick states
+            self.tick_states
+                .entry(low_tick)
+                .or_insert(TickState::new(low_tick))
+                .modify_liq(liq, false, self.tick, self.fee_global0, self.fee_global1);
+            self.tick_states
+                .entry(high_tick)
+                .or_insert(TickState::new(high_tick))
+                .modify_liq(liq, true, self.tick, self.fee_global0, self.fee_global1);
+
+            //mark ticks as used
+            self.used_ticks.insert(low_tick);
+            self.used_ticks.insert(high_tick);
+
+            //the fees already accumulated by the range don't belong to the new position, if the ticks were used by other positions
+            let (range_fee0, range_fee1) = pool_math::compute_range_fees(
+                self.tick,
+                self.fee_global0,
+                self.fee_global1,
+                self.tick_states.get(&low_tick).unwrap(),
+                self.tick_states.get(&high_tick).unwrap(),
+            );
+
+            //take the required amounts in the pool vaults
+            self.vault0.put(bucket0.take(required_amount0));
+            self.vault1.put(bucket1.take(required_amount1));
+
+            //mint the nft corresponding to the position with the liqudity on it
+            let pos_nft = self
+                .pos_nft_minter_badge()
+                .as_fungible()
+                .authorize_with_amount(1, || {
+                    self.pos_nft_addr_resource_manager
+                        .mint_ruid_non_fungible(PositionNFTData { liq, range })
+                });
+
+            //save the new position
+            let pos_id = pos_nft.as_non_fungible().non_fungible_local_id();
+            self.positions.insert(
+                pos_id.clone(),
+                Position::new(liq, low_tick, high_tick, range_fee0, range_fee1),
+            );
+            Runtime::emit_event(PositionAddedEvent {
+                pos_id,
+                low_tick,
+                high_tick,
+                amount0: required_amount0,
+                amount1: required_amount1,
+                liq,
+            });
+
+            self.log_state("### Internal state after adding the new position");
+
+            debug!(
+                "### Position id={:?}",
+                pos_nft.as_non_fungible().non_fungible_local_id()
+            );
+            debug!("### Position NFT liq={:?}", liq);
+            debug!("### Bucket0={:?}", bucket0.amount());
+            debug!("### Bucket1={:?}", bucket1.amount());
+            debug!("### Position added.");
+
+            (pos_nft, bucket0, bucket1)
+        }
+
+        /**
+         * Adds more liquidity to an already existing position. The new liquidity is computed from the provided amounts of tokens0,1 depending on the current price. Any fees already accumulated by the position are collected and put to work as new liquidity.
+         *
+         * The caller must present a proof issued from the NFT that identifies the already existing, active position.
+         *
+         * Returns the remainder of the amount0,1, if any.
+         */
+        pub fn add_liq(
+            &mut self,
+            mut bucket0: Bucket,
+            mut bucket1: Bucket,
+            auth: Proof,
+        ) -> (Bucket, Bucket) {
+            debug!("### Adding liquidity...");
+            self.check_allowed(PausableOperation::Deposit);
+            //validate the resources sent in
+            self.validate_resources(bucket0.resource_address(), bucket1.resource_address());
+
+            //add liq
+            let (to_deduct_amount0, to_deduct_amount1) =
+                self.add_liq_internal(bucket0.amount(), bucket1.amount(), auth);
+
+            // take the required amounts in the pool vaults, rounded up to the divisibility of the resources
+            let to_deduct_amount0 =
+                pool_math::round_up_to_divisibility(to_deduct_amount0, self.divisibility0).min(bucket0.amount());
+            let to_deduct_amount1 =
+                pool_math::round_up_to_divisibility(to_deduct_amount1, self.divisibility1).min(bucket1.amount());
+            self.vault0.put(bucket0.take(to_deduct_amount0));
+            self.vault1.put(bucket1.take(to_deduct_amount1));
+
+            debug!("### Liquidity added.");
+            debug!("### Bucket0={:?}", bucket0.amount());
+            debug!("### Bucket1={:?}", bucket1.amount());
+
+            (bucket0, bucket1)
+        }
+
+        /**
+         * Add the accumulated fees on the given position to the position liquidity.
+         */
+        pub fn add_accumulated_fees_to_liq(&mut self, auth: Proof) {
+            debug!("### Adding collected fees to liquidity...");
+            self.check_allowed(PausableOperation::Compound);
+            self.add_liq_internal(Decimal::zero(), Decimal::zero(), auth);
+            debug!("### Accumulated fees added to liquidity.");
+        }
+
+        /**
+         * Remove all the liquidity from the position identified by the provided proof.
+         *
+         * Return the amount0,1 corresponding to the liquidity removed. Amount0,1 contain also the fees already accumulated by the position.
+         */
+        pub fn remove_pos(&mut self, proof: Proof) -> (Bucket, Bucket) {
+            let checked_proof = self.check_proof(proof);
+            if self.emergency_mode {
+                return self.emergency_remove_pos(checked_proof);
+            }
+            let pos_nft: NonFungible<PositionNFTData> =
+                checked_proof.as_non_fungible().non_fungible();
+            self.remove_liq_internal(pos_nft.data().liq, checked_proof)
+        }
+
+        /**
+         * Remove the given liquidity from the position identified by the provided proof, the position staying open with the rest of its
+         * liquidity, e.g. for the partial withdrawals of a managed vault. Removing all the liquidity is the same as remove_pos.
+         *
+         * Return the amount0,1 corresponding to the liquidity removed. Amount0,1 contain also all the fees already accumulated by the
+         * position.
+         */
+        pub fn remove_liq(&mut self, liq: Decimal, proof: Proof) -> (Bucket, Bucket) {
+            //the emergency removal is only of the whole position, see remove_pos
+            assert!(!self.emergency_mode, "{}", PoolError::EmergencyMode);
+            self.remove_liq_internal(liq, self.check_proof(proof))
+        }
+
+        /**
+         * Collect the fees accumulated for the position identified by the NFT in the auth
+         */
+        pub fn collect_fees(&mut self, auth: Proof) -> (Bucket, Bucket) {
+            debug!("### Collecting fees...");
+            //the fee accounting isn't trusted in emergency mode, the positions are removed without their fees, see remove_pos
+            assert!(!self.emergency_mode, "{}", PoolError::EmergencyMode);
+            self.remove_liq_internal(Decimal::zero(), self.check_proof(auth))
+        }
+
+        /**
+         * Collect the fees accumulated for the position identified by the NFT in the auth as a single resource, the fees of the other
+         * resource being swapped in the pool. The output must be at least min_out.
+         *
+         * Returns the output_resource fees and the remainder of the other resource fees, if the liquidity runs out during the swap
+         */
+        pub fn collect_fees_as(&mut self, auth: Proof, output_resource: ResourceAddress, min_out: Decimal) -> (Bucket, Bucket) {
+            debug!("### Collecting fees as {:?}...", output_resource);
+            assert!(!self.emergency_mode, "{}", PoolError::EmergencyMode);
+
+            assert!(
+                output_resource == self.vault0.resource_address() || output_resource == self.vault1.resource_address(),
+                "Wrong output resource. Collect fees op aborted."
+            );
+
+            let (bucket0, bucket1) = self.remove_liq_internal(Decimal::zero(), self.check_proof(auth));
+            let (mut output, to_swap) = if output_resource == self.vault0.resource_address() {
+                (bucket0, bucket1)
+            } else {
+                (bucket1, bucket0)
+            };
+            let remainder = if to_swap.is_empty() {
+                to_swap
+            } else {
+                let (swapped, remainder) = self.swap(to_swap);
+                output.put(swapped);
+                remainder
+            };
+            assert!(
+                output.amount() >= min_out,
+                "Output amount lower than the minimum amount. Collect fees op aborted."
+            );
+
+            (output, remainder)
+        }
+
+        /**
+         * Migrates the position identified by the provided proof to the target pool, e.g. a pool of the same pair with another fee
+         * tier: all the liquidity and the accumulated fees are removed from this pool and added as a new position on the same range in
+         * the target pool. The range must be on the tick spacing of the target pool, and the target pool can't be gated by a member or
+         * LP whitelist badge, as it's called by this pool, not by the position owner.
+         *
+         * Returns the NFT of the new position in the target pool and the remainders amount0,1 that don't fit in the new position at the
+         * target pool price.
+         */
+        pub fn migrate_position(&mut self, proof: Proof, target_pool_addr: ComponentAddress) -> (Bucket, Bucket, Bucket) {
+            debug!("### Migrating position to pool {:?}...", target_pool_addr);
+            //the position would be migrated with its fees, the emergency removal leaves them in the pool
+            assert!(!self.emergency_mode, "{}", PoolError::EmergencyMode);
+
+            let mut target_pool: Global<Pool> = target_pool_addr.into();
+            assert!(
+                target_pool_addr != Runtime::global_address(),
+                "The target pool should be another pool. Migrate position op aborted."
+            );
+            //the range ticks are equivalent only if the target pool prices are of token0 vs token1 too
+            assert!(
+                target_pool.get_resource_addresses() == (self.vault0.resource_address(), self.vault1.resource_address()),
+                "The target pool should have the same resource0 and resource1. Migrate position op aborted."
+            );
+
+            let checked_proof = self.check_proof(proof);
+            let pos_nft: NonFungible<PositionNFTData> = checked_proof.as_non_fungible().non_fungible();
+            let pos_data = pos_nft.data();
+            let (bucket0, bucket1) = self.remove_liq_internal(pos_data.liq, checked_proof);
+            let (target_pos_nft, remainder0, remainder1) = target_pool.add_pos(bucket0, bucket1, pos_data.range);
+
+            debug!("### Position migrated.");
+
+            (target_pos_nft, remainder0, remainder1)
+        }
+
+        /**
+         * Moves the position identified by the provided proof to a new range of this pool, e.g. to follow the price: all the liquidity
+         * and the accumulated fees are removed and added as a new position on the given range. The emptied position NFT stays with its
+         * holder. Like add_pos, it's restricted to the LPs of the pools gated by a member or an LP whitelist badge.
+         *
+         * Returns the NFT of the new position and the remainders amount0,1 that don't fit in the new range at the current price.
+         */
+        pub fn rebalance_pos(&mut self, proof: Proof, range: TickRange) -> (Bucket, Bucket, Bucket) {
+            debug!("### Rebalancing position to range {:?}...", range);
+
+            let checked_proof = self.check_proof(proof);
+            let pos_nft: NonFungible<PositionNFTData> = checked_proof.as_non_fungible().non_fungible();
+            let (bucket0, bucket1) = self.remove_liq_internal(pos_nft.data().liq, checked_proof);
+            let (new_pos_nft, remainder0, remainder1) = self.add_pos(bucket0, bucket1, range);
+
+            debug!("### Position rebalanced.");
+
+            (new_pos_nft, remainder0, remainder1)
+        }
+
+        /**
+         * Swaps the provided amount0,1 for the opposite token. Internally the pool modifies the tokens price and live liquidity.
+         *
+         * Returns the swapped amount1,0 and the remainder of the provided amount0,1
+         */
+        pub fn swap(&mut self, bucket: Bucket) -> (Bucket, Bucket) {
+            self.swap_for(bucket, None, None)
+        }
+
+        /**
+         * Swaps the provided amount0,1 like swap, paying the referral fee share of the swap fees to the given referrer, through the
+         * referral registry of the pool, see ParameterChange::SetReferralRegistry. The swap aborts if the referrer isn't registered.
+         *
+         * Returns the swapped amount1,0 and the remainder of the provided amount0,1
+         */
+        pub fn swap_with_referrer(&mut self, bucket: Bucket, referrer_id: u64) -> (Bucket, Bucket) {
+            assert!(self.referral_registry.is_some(), "No referral registry set. Swap op aborted.");
+            self.swap_for(bucket, None, Some(referrer_id))
+        }
+
+        /**
+         * Swaps the provided amount0,1 like swap, on behalf of the trader identified by the provided proof of a non fungible badge.
+         * The swap gets the fee discount of the volume tier the trader reached with its trailing volume, the volume swapped in the
+         * last VOLUME_WINDOW_EPOCHS epochs, measured in resource1, see ParameterChange::SetVolumeTiers.
+         *
+         * Returns the swapped amount1,0 and the remainder of the provided amount0,1
+         */
+        pub fn swap_as_trader(&mut self, bucket: Bucket, trader_badge: Proof) -> (Bucket, Bucket) {
+            //any non fungible badge identifies a trader, the volume being accrued by each badge with its own swaps
+            let checked_badge = trader_badge.skip_checking();
+            let trader_id = NonFungibleGlobalId::new(
+                checked_badge.resource_address(),
+                checked_badge.as_non_fungible().non_fungible_local_id(),
+            );
+            self.swap_for(bucket, Some(trader_id), None)
+        }
+
+        fn swap_for(
+            &mut self,
+            bucket: Bucket,
+            trader_id: Option<NonFungibleGlobalId>,
+            referrer_id: Option<u64>,
+        ) -> (Bucket, Bucket) {
+            debug!("### Swapping...");
+            self.check_allowed(PausableOperation::Swap);
+            self.open_trading();
+            self.apply_fee_ramp();
+
+            //validate the resource to swap
+            assert!(
+                (bucket.resource_address() == self.vault0.resource_address()
+                    || bucket.resource_address() == self.vault1.resource_address()),
+                "Wrong resource type sent. Swap op aborted."
+            );
+
+            //the oracle observes the tick before the swap moves it
+            self.write_observation();
+
+            //the hooks can abort the swap before it happens
+            let (resource_in_addr, initial_amount) = (bucket.resource_address(), bucket.amount());
+            self.check_swap_input(resource_in_addr, initial_amount);
+            let fee_discount = match &trader_id {
+                Some(trader_id) => self.fee_discount(self.trader_volume(trader_id)),
+                None => Decimal::zero(),
+            };
+            let fee = self.swap_fee(resource_in_addr, initial_amount) * (Decimal::one() - fee_discount);
+            let swap_context = SwapContext {
+                resource_in_addr,
+                amount: initial_amount,
+                sqrt_price: SqrtPrice(self.sqrt_price),
+                fee,
+            };
+            self.call_hooks("before_swap", scrypto_args!(swap_context));
+
+            //depending on the resource type sent swap resource0 or resource1
+            let initial_sqrt_price = self.sqrt_price;
+            let referral_fee_share = if referrer_id.is_some() {
+                self.referral_fee_share
+            } else {
+                Decimal::zero()
+            };
+            let (output_bucket, mut remainder_bucket, referral_fee, fee_amount) =
+                if bucket.resource_address() == self.vault0.resource_address() {
+                    self.swap_internal(bucket, true, fee, referral_fee_share)
+                } else {
+                    self.swap_internal(bucket, false, fee, referral_fee_share)
+                };
+            self.check_price_impact(initial_sqrt_price);
+
+            //the registry aborts the swap if the referrer isn't registered
+            match referrer_id {
+                Some(referrer_id) => {
+                    let registry: Global<AnyComponent> = Global::from(self.referral_registry.unwrap());
+                    registry.call_raw::<()>("pay_referral_fee", scrypto_args!(referrer_id, referral_fee));
+                }
+                None => remainder_bucket.put(referral_fee),
+            }
+
+            if resource_in_addr == self.vault1.resource_address() {
+                self.check_buy_cap(&trader_id, output_bucket.amount());
+            }
+
+            //the trader volume is measured in resource1, the input or the output of the swap
+            if let Some(trader_id) = trader_id {
+                let volume = if resource_in_addr == self.vault1.resource_address() {
+                    initial_amount - remainder_bucket.amount()
+                } else {
+                    output_bucket.amount()
+                };
+                self.record_trader_volume(trader_id, volume);
+            }
+
+            let amount_in = initial_amount - remainder_bucket.amount();
+            let is_token0 = resource_in_addr == self.vault0.resource_address();
+            let swap_event = SwapEvent {
+                resource_in_addr,
+                amount_in,
+                resource_out_addr: output_bucket.resource_address(),
+                amount_out: output_bucket.amount(),
+                sqrt_price: SqrtPrice(self.sqrt_price),
+                effective_price: Pool::effective_price(is_token0, amount_in, output_bucket.amount()),
+                fee_amount,
+            };
+            self.call_hooks("after_swap", scrypto_args!(swap_event.clone()));
+            Runtime::emit_event(swap_event);
+
+            debug!("Swapping done.");
+
+            (output_bucket, remainder_bucket)
+        }
+
+        /**
+         * Computes the output of swapping the given amount of the given resource, with the same steps as the swap, without modifying
+         * the state of the pool.
+         *
+         * Returns the output amount and the remainder of the given amount, if the liquidity runs out.
+         */
+        pub fn quote_swap(&self, resource_addr: ResourceAddress, amount: Decimal) -> (Decimal, Decimal) {
+            assert!(
+                resource_addr == self.vault0.resource_address() || resource_addr == self.vault1.resource_address(),
+                "Wrong resource type sent. Quote op aborted."
+            );
+            assert!(amount >= Decimal::zero(), "Invalid amount, should be positive or zero.");
+
+            let is_token0 = resource_addr == self.vault0.resource_address();
+            let fee = self.swap_fee(resource_addr, amount);
+            let (mut live_liq, mut tick, mut sqrt_price) = (self.live_liq, self.tick, self.sqrt_price);
+            let mut available_amount = amount;
+            let mut total_swapped_amount = Decimal::zero();
+            while available_amount > Decimal::zero() && live_liq > Decimal::zero() {
+                let opt_tick_to_cross = if is_token0 {
+                    btree_set_ext::previous_or_equal_elem(&self.used_ticks, tick)
+                } else {
+                    btree_set_ext::next_elem(&self.used_ticks, tick)
+                };
+                let tick_to_cross = match opt_tick_to_cross {
+                    Some(tick_to_cross) => *tick_to_cross,
+                    None => break,
+                };
+                let sqrt_price_at_tick_to_cross = tick_math::sqrt_price_at_tick(Tick(tick_to_cross)).0;
+
+                let step = swap_math::compute_swap_step(live_liq, sqrt_price, sqrt_price_at_tick_to_cross, available_amount, fee);
+                available_amount = available_amount - step.amount_in - step.fee_amount;
+                total_swapped_amount += step.amount_out;
+                let previous_sqrt_price = sqrt_price;
+                sqrt_price = step.sqrt_price_next;
+
+                if step.reached_target {
+                    let liq_net = self.tick_states.get(&tick_to_cross).unwrap().liq_net;
+                    live_liq = if is_token0 { live_liq - liq_net } else { live_liq + liq_net };
+                    tick = if is_token0 { tick_to_cross - 1 } else { tick_to_cross };
+                } else if sqrt_price != previous_sqrt_price {
+                    tick = Self::tick_below_or_at(sqrt_price, is_token0, tick_to_cross);
+                }
+            }
+
+            //rounded like the swap, see swap_internal
+            let (divisibility_in, divisibility_out) = if is_token0 {
+                (self.divisibility0, self.divisibility1)
+            } else {
+                (self.divisibility1, self.divisibility0)
+            };
+            (
+                pool_math::round_down_to_divisibility(total_swapped_amount, divisibility_out),
+                pool_math::round_down_to_divisibility(available_amount, divisibility_in),
+            )
+        }
+
+        /**
+         * Computes the input amount needed to move the pool price from the current sqrt_price to the target_sqrt_price. The pool fee is
+         * included in the amount, so swapping it moves the price exactly to the target. The state of the pool is not modified.
+         *
+         * If the liquidity runs out before reaching the target, the returned amount is the one that moves the price as far as possible.
+         *
+         * Returns the resource that must be swapped (resource0 moves the price down, resource1 moves it up) and its amount.
+         */
+        pub fn amount_to_move_price(&self, target_sqrt_price: SqrtPrice) -> (ResourceAddress, Decimal) {
+            let target_sqrt_price = target_sqrt_price.0;
+            assert!(
+                target_sqrt_price > Decimal::zero(),
+                "Invalid target sqrt price, should be positive."
+            );
+            let fee = self.current_fee();
+            assert!(
+                fee < Decimal::one(),
+                "No amount can move the price of a pool with fee=1."
+            );
+
+            let is_token0 = target_sqrt_price < self.sqrt_price;
+            let (amount_in, _amount_out, _live_liq, _tick, _sqrt_price) =
+                self.move_price_virtually(self.live_liq, self.tick, self.sqrt_price, target_sqrt_price);
+            // the swap keeps the fee out of the swapped amount, so we gross the amount up with it
+            let amount = amount_in / (Decimal::one() - fee);
+
+            debug!("### Amount to move price to {:?} = {:?}", target_sqrt_price, amount);
+
+            let resource_addr = if is_token0 {
+                self.vault0.resource_address()
+            } else {
+                self.vault1.resource_address()
+            };
+            (resource_addr, amount)
+        }
+
+        /**
+         * Aggregates the liquidity around the current price in an order book like format. The ticks bellow and above the current one are
+         * grouped in the given number of levels, each level spanning tick_bucket_size ticks.
+         *
+         * For each level returns the sqrt_price where the level ends and the output amount available until that price:
+         * - bids: the amount1 available for sellers of token0, as the price goes down
+         * - asks: the amount0 available for sellers of token1, as the price goes up
+         */
+        pub fn get_depth(&self, levels: u32, tick_bucket_size: u32) -> Depth {
+            assert!(tick_bucket_size > 0, "Tick bucket size must be positive.");
+
+            let current_tick = tick_math::tick_at_sqrt_price(SqrtPrice(self.sqrt_price)).0;
+            let mut bids = Vec::new();
+            let mut asks = Vec::new();
+            for (is_token0, side) in [(true, &mut bids), (false, &mut asks)] {
+                let (mut live_liq, mut tick, mut sqrt_price) =
+                    (self.live_liq, self.tick, self.sqrt_price);
+                for level in 1..=(levels as i64) {
+                    let level_tick = if is_token0 {
+                        current_tick as i64 - level * tick_bucket_size as i64
+                    } else {
+                        current_tick as i64 + level * tick_bucket_size as i64
+                    };
+                    if level_tick < tick_math::MIN_TICK as i64 || level_tick > tick_math::MAX_TICK as i64 {
+                        break;
+                    }
+                    let level_sqrt_price = tick_math::sqrt_price_at_tick(Tick(level_tick as i32)).0;
+                    let (_amount_in, amount_out, new_live_liq, new_tick, new_sqrt_price) =
+                        self.move_price_virtually(live_liq, tick, sqrt_price, level_sqrt_price);
+                    (live_liq, tick, sqrt_price) = (new_live_liq, new_tick, new_sqrt_price);
+                    side.push(DepthLevel {
+                        sqrt_price: SqrtPrice(level_sqrt_price),
+                        amount: amount_out,
+                    });
+                }
+            }
+
+            Depth { bids, asks }
+        }
+
+        /**
+         * Returns the oracle observations of each of the given seconds_ago seconds before the current time, in the same order: the
+         * tick, the live liquidity and the seconds per live liquidity accumulated up to that time. The time weighted averages over a
+         * window are the differences between the observations at its ends divided by its seconds, e.g. with the observations of
+         * observe(vec![600, 0]), oracle::mean_tick(&observations[0], &observations[1]) for the last 10 minutes, see also
+         * oracle::mean_liquidity and oracle::seconds_in_range. The price at the average tick is the geometric mean of the prices in
+         * the window, which a price manipulated for a few blocks hardly moves, e.g. for the lending protocols.
+         *
+         * The observations are interpolated in between the recorded ones, or extrapolated with the current tick and live liquidity
+         * after the latest one. It aborts if any of the times is before the oldest observation the pool keeps.
+         */
+        pub fn observe(&self, seconds_ago: Vec<u64>) -> Vec<Observation> {
+            seconds_ago.into_iter().map(|seconds_ago| self.observe_at(seconds_ago)).collect()
+        }
+
+        //the observation of seconds_ago seconds before the current time, binary searched in the ring buffer, see Pool::observe
+        fn observe_at(&self, seconds_ago: u64) -> Observation {
+            let timestamp = Pool::current_timestamp() - seconds_ago as i64;
+            let latest = *self.observations.get(&self.observation_index).unwrap();
+            if timestamp >= latest.timestamp {
+                return latest.advance(timestamp, self.tick, self.live_liq);
+            }
+
+            //the observations in chronological order, starting with the oldest one, as the ring buffer may be not full yet
+            let (cardinality, count) = (self.observation_cardinality, self.observation_count);
+            let oldest_index = if count == cardinality { (self.observation_index + 1) % cardinality } else { 0 };
+            let observation_at = |position: u32| *self.observations.get(&((oldest_index + position) % cardinality)).unwrap();
+            assert!(
+                timestamp >= observation_at(0).timestamp,
+                "The time is before the oldest observation. Observe op aborted."
+            );
+
+            //binary search of the latest observation at or before the timestamp, the latest observation being after it
+            let (mut low, mut high) = (0, count - 1);
+            while high - low > 1 {
+                let middle = (low + high) / 2;
+                if observation_at(middle).timestamp <= timestamp {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            observation_at(low).interpolate(&observation_at(high), timestamp)
+        }
+
+        /**
+         * Returns the time weighted average tick over the last window seconds, rounded towards negative infinity, see Pool::observe
+         */
+        pub fn twap_tick(&self, window: u64) -> Tick {
+            assert!(window > 0, "Invalid window, should be positive.");
+            oracle::mean_tick(&self.observe_at(window), &self.observe_at(0))
+        }
+
+        /**
+         * Returns the price of token0 vs token1 at the time weighted average tick over the last window seconds, the geometric mean of
+         * the prices in the window
+         */
+        pub fn twap_price(&self, window: u64) -> Decimal {
+            let sqrt_price = tick_math::sqrt_price_at_tick(self.twap_tick(window)).0;
+            sqrt_price * sqrt_price
+        }
+
+        /**
+         * Grows the oracle ring buffer to keep up to the given number of observations, for longer time weighted average windows.
+         * Anyone can call it, the storage of the new observations being paid upfront by the transaction fee. The buffer grows once the
+         * observations currently kept are overwritten, so the window becomes longer in time.
+         */
+        pub fn increase_observation_cardinality(&mut self, cardinality: u32) {
+            assert!(
+                cardinality <= MAX_OBSERVATION_CARDINALITY,
+                "Invalid cardinality, should be at most {}.",
+                MAX_OBSERVATION_CARDINALITY
+            );
+            if cardinality <= self.observation_cardinality_next {
+                return;
+            }
+
+            //the new slots are written with placeholders, they are never read before being overwritten by real observations
+            for index in self.observation_cardinality_next..cardinality {
+                self.observations.insert(
+                    index,
+                    Observation {
+                        timestamp: 0,
+                        tick_cumulative: 0,
+                        liquidity_cumulative: Decimal::zero(),
+                        seconds_per_liquidity_cumulative: Decimal::zero(),
+                    },
+                );
+            }
+            self.observation_cardinality_next = cardinality;
+        }
+
+        /**
+         * Returns the number of observations the oracle keeps, currently and after the pending growth
+         */
+        pub fn get_observation_cardinality(&self) -> (u32, u32) {
+            (self.observation_cardinality, self.observation_cardinality_next)
+        }
+
+        /**
+         * Returns the addresses of resource0 and resource1
+         */
+        pub fn get_resource_addresses(&self) -> (ResourceAddress, ResourceAddress) {
+            (self.vault0.resource_address(), self.vault1.resource_address())
+        }
+
+        /**
+         * Returns the current price of token0 vs token1
+         */
+        pub fn get_price(&self) -> Decimal {
+            self.sqrt_price * self.sqrt_price
+        }
+
+        /**
+         * Returns the static pool fee, the one charged when the pool has no fee manager
+         */
+        pub fn get_fee(&self) -> Decimal {
+            self.current_fee()
+        }
+
+        /**
+         * Returns all the fee parameters of the pool in one call, so the routers can compute the quotes and the net outputs without
+         * hard-coding them, see FeeConfig
+         */
+        pub fn get_fee_config(&self) -> FeeConfig {
+            FeeConfig {
+                fee: self.current_fee(),
+                fee_ramp: self.fee_ramp.clone(),
+                fee_manager: self.fee_manager,
+                protocol_fee_enabled: self.protocol_fee_enabled,
+                protocol_fee_share: self.protocol_fee_share,
+                referral_fee_share: self.referral_fee_share,
+                volume_tiers: self.volume_tiers.clone(),
+            }
+        }
+
+        /**
+         * Returns the minimum active liquidity the swaps wait for, until the trading opens, see PoolConfig
+         */
+        pub fn get_min_liquidity(&self) -> Option<Decimal> {
+            self.min_liquidity
+        }
+
+        /**
+         * Returns the price band [low_tick, high_tick] bounding the position ranges, none for an unbounded pool, see PoolConfig and
+         * ParameterChange::WidenPriceBand
+         */
+        pub fn get_price_band(&self) -> Option<(i32, i32)> {
+            self.price_band
+        }
+
+        /**
+         * Returns the buy cap of a launch pool and its end epoch, while in force, see PoolConfig
+         */
+        pub fn get_buy_cap(&self) -> Option<(BuyCap, u64)> {
+            match &self.buy_cap {
+                Some(buy_cap) if Runtime::current_epoch().number() < self.buy_cap_end_epoch => {
+                    Some((buy_cap.clone(), self.buy_cap_end_epoch))
+                }
+                _ => None,
+            }
+        }
+
+        /**
+         * Returns the ongoing ramp of the static pool fee, if any, see ParameterChange::RampFee
+         */
+        pub fn get_fee_ramp(&self) -> Option<FeeRamp> {
+            self.fee_ramp.clone()
+        }
+
+        /**
+         * Returns the package version of the pool and the features it supports, so routers and strategies can detect the older
+         * deployments and degrade gracefully
+         */
+        pub fn get_version(&self) -> PoolVersion {
+            PoolVersion {
+                major: env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap(),
+                minor: env!("CARGO_PKG_VERSION_MINOR").parse().unwrap(),
+                patch: env!("CARGO_PKG_VERSION_PATCH").parse().unwrap(),
+                features: POOL_FEATURES.iter().map(|feature| feature.to_string()).collect(),
+            }
+        }
+
+        /**
+         * Returns a snapshot of the pool state
+         */
+        pub fn get_pool_state(&self) -> PoolState {
+            PoolState {
+                resource0_addr: self.vault0.resource_address(),
+                resource1_addr: self.vault1.resource_address(),
+                amount0: self.vault0.amount(),
+                amount1: self.vault1.amount(),
+                sqrt_price: SqrtPrice(self.sqrt_price),
+                current_tick: Tick(self.tick),
+                active_liquidity: self.live_liq,
+                fee: self.current_fee(),
+                tick_spacing: self.tick_spacing,
+                fee_growth_global0: self.fee_global0,
+                fee_growth_global1: self.fee_global1,
+                position_nft_addr: self.pos_nft_addr_resource_manager.address(),
+                protocol_fees: (self.protocol_fees0.amount(), self.protocol_fees1.amount()),
+            }
+        }
+
+        /**
+         * Returns the total value locked in the pool, split by the role the amounts play:
+         * - the amounts of the positions whose range contains the current tick, which are available for swaps
+         * - the amounts of the positions out of range, idle until the price moves into their range
+         * - the fees accumulated by the positions and not collected yet
+         *
+         * The vault balances might be slightly larger than the sum of these amounts, due to rounding.
+         */
+        pub fn get_tvl(&self) -> Tvl {
+            let mut tvl = Tvl {
+                active0: Decimal::zero(),
+                active1: Decimal::zero(),
+                out_of_range0: Decimal::zero(),
+                out_of_range1: Decimal::zero(),
+                uncollected_fees0: Decimal::zero(),
+                uncollected_fees1: Decimal::zero(),
+                total0: self.vault0.amount(),
+                total1: self.vault1.amount(),
+            };
+
+            for pos in self.positions.values() {
+                let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
+                    pos.liq,
+                    self.sqrt_price,
+                    tick_math::sqrt_price_at_tick(Tick(pos.low_tick)).0,
+                    tick_math::sqrt_price_at_tick(Tick(pos.high_tick)).0,
+                );
+                if self.tick >= pos.low_tick && self.tick < pos.high_tick {
+                    tvl.active0 += amount0;
+                    tvl.active1 += amount1;
+                } else {
+                    tvl.out_of_range0 += amount0;
+                    tvl.out_of_range1 += amount1;
+                }
+
+                let (pos_fee0, pos_fee1) = self.compute_uncollected_fees(pos);
+                tvl.uncollected_fees0 += pos_fee0;
+                tvl.uncollected_fees1 += pos_fee1;
+            }
+
+            tvl
+        }
+
+        /**
+         * Returns the swap statistics of the given epoch, if there were swaps in that epoch and it is one of the last
+         * EPOCH_STATS_CAPACITY epochs.
+         */
+        pub fn get_epoch_stats(&self, epoch: u64) -> Option<EpochStats> {
+            self.epoch_stats
+                .get(&(epoch % EPOCH_STATS_CAPACITY))
+                .map(|stats| stats.clone())
+                .filter(|stats| stats.epoch == epoch)
+        }
+
+        /**
+         * Returns the position with the given NFT id, if it is an active position of this pool
+         */
+        pub fn get_position(&self, pos_id: NonFungibleLocalId) -> Option<Position> {
+            self.positions.get(&pos_id).cloned()
+        }
+
+        /**
+         * Returns the amount0,1 the given position would redeem if the pool price were at the given sqrt_price, e.g. a TWAP sqrt price
+         * (see Pool::twap_tick), instead of the current one: the amounts of the position liquidity at that price and the uncollected
+         * fees. Lending protocols can value the position NFTs used as collateral with it, as the spot price can be manipulated.
+         */
+        pub fn get_position_amounts_at(&self, pos_id: NonFungibleLocalId, sqrt_price: SqrtPrice) -> (Decimal, Decimal) {
+            assert!(sqrt_price.0 > Decimal::zero(), "Invalid sqrt price, should be positive.");
+            self.validate_pos(&pos_id);
+
+            let pos = self.positions.get(&pos_id).unwrap();
+            let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
+                pos.liq,
+                sqrt_price.0,
+                tick_math::sqrt_price_at_tick(Tick(pos.low_tick)).0,
+                tick_math::sqrt_price_at_tick(Tick(pos.high_tick)).0,
+            );
+            let (pos_fee0, pos_fee1) = self.compute_uncollected_fees(pos);
+            (amount0 + pos_fee0, amount1 + pos_fee1)
+        }
+
+        /**
+         * Returns the portfolio of the positions identified by the NFTs in the proof, e.g. all the position NFTs of an account, so the
+         * wallet dashboards read it in a single call: the amounts of each position at the current price, its uncollected fees and if
+         * it is in range, and their totals, see Portfolio. The NFTs of the removed positions are skipped.
+         */
+        pub fn get_portfolio(&self, proof: Proof) -> Portfolio {
+            let checked_proof = self.check_proof(proof);
+            let mut portfolio = Portfolio {
+                positions: Vec::new(),
+                amount0: Decimal::zero(),
+                amount1: Decimal::zero(),
+                fees0: Decimal::zero(),
+                fees1: Decimal::zero(),
+                in_range_ratio: Decimal::zero(),
+            };
+            let (mut liq, mut in_range_liq) = (Decimal::zero(), Decimal::zero());
+
+            for pos_id in checked_proof.as_non_fungible().non_fungible_local_ids() {
+                let pos = match self.positions.get(&pos_id) {
+                    Some(pos) => pos,
+                    None => continue,
+                };
+                let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
+                    pos.liq,
+                    self.sqrt_price,
+                    tick_math::sqrt_price_at_tick(Tick(pos.low_tick)).0,
+                    tick_math::sqrt_price_at_tick(Tick(pos.high_tick)).0,
+                );
+                let (fees0, fees1) = self.compute_uncollected_fees(pos);
+                let in_range = self.tick >= pos.low_tick && self.tick < pos.high_tick;
+
+                portfolio.amount0 += amount0;
+                portfolio.amount1 += amount1;
+                portfolio.fees0 += fees0;
+                portfolio.fees1 += fees1;
+                liq += pos.liq;
+                if in_range {
+                    in_range_liq += pos.liq;
+                }
+                portfolio.positions.push(PortfolioPosition {
+                    pos_id,
+                    position: pos.clone(),
+                    amount0,
+                    amount1,
+                    fees0,
+                    fees1,
+                    in_range,
+                });
+            }
+            if liq > Decimal::zero() {
+                portfolio.in_range_ratio = in_range_liq / liq;
+            }
+
+            portfolio
+        }
+
+        /**
+         * Returns the NFT ids and the positions of all the active positions of this pool
+         */
+        pub fn get_positions(&self) -> Vec<(NonFungibleLocalId, Position)> {
+            self.positions
+                .iter()
+                .map(|(pos_id, pos)| (pos_id.clone(), pos.clone()))
+                .collect()
+        }
+
+        /**
+         * Returns the states of the used ticks, ordered by tick
+         */
+        pub fn get_tick_states(&self) -> Vec<TickState> {
+            self.used_ticks
+                .iter()
+                .map(|tick| self.tick_states.get(tick).unwrap().clone())
+                .collect()
+        }
+
+        /**
+         * Returns a page of at most limit used ticks, ordered by tick, after the cursor tick, or from the lowest one if there is no
+         * cursor, with their liquidity_net and liquidity_gross. The next page starts after the last tick of the page, the pages
+         * ending with an empty one, so the dashboards can rebuild the liquidity curve of the pools with many ticks.
+         */
+        pub fn list_initialized_ticks(&self, cursor: Option<i32>, limit: u32) -> Vec<(i32, Decimal, Decimal)> {
+            assert!(
+                limit > 0 && limit <= MAX_TICKS_PAGE_SIZE,
+                "Invalid limit, should be 0 < limit <= {}.",
+                MAX_TICKS_PAGE_SIZE
+            );
+            let ticks = match cursor {
+                Some(cursor) => self.used_ticks.range(cursor.saturating_add(1)..),
+                None => self.used_ticks.range(..),
+            };
+            ticks
+                .take(limit as usize)
+                .map(|tick| {
+                    let tick_state = self.tick_states.get(tick).unwrap();
+                    (*tick, tick_state.liq_net, tick_state.liq_gross)
+                })
+                .collect()
+        }
+
+        /**
+         * Computes the fees accumulated by the given position and not collected yet
+         */
+        fn compute_uncollected_fees(&self, pos: &Position) -> (Decimal, Decimal) {
+            let (range_fee0, range_fee1) = pool_math::compute_range_fees(
+                self.tick,
+                self.fee_global0,
+                self.fee_global1,
+                self.tick_states.get(&pos.low_tick).unwrap(),
+                self.tick_states.get(&pos.high_tick).unwrap(),
+            );
+            pool_math::compute_pos_fees(
+                pos.liq,
+                pos.range_fee0,
+                pos.range_fee1,
+                range_fee0,
+                range_fee1,
+            )
+        }
+
+        /**
+         * Moves a virtual price from sqrt_price towards target_sqrt_price, crossing the used ticks the same way the swap does, without
+         * modifying the pool state. It stops earlier if the liquidity runs out.
+         *
+         * Returns the input amount (fees excluded), the output amount and the live liquidity, tick and sqrt_price where the price stopped.
+         */
+        fn move_price_virtually(
+            &self,
+            mut live_liq: Decimal,
+            mut tick: i32,
+            mut sqrt_price: Decimal,
+            target_sqrt_price: Decimal,
+        ) -> (Decimal, Decimal, Decimal, i32, Decimal) {
+            let is_token0 = target_sqrt_price < sqrt_price;
+            let mut amount_in = Decimal::zero();
+            let mut amount_out = Decimal::zero();
+
+            while sqrt_price != target_sqrt_price && live_liq > Decimal::zero() {
+                let opt_tick_to_cross = if is_token0 {
+                    btree_set_ext::previous_or_equal_elem(&self.used_ticks, tick)
+                } else {
+                    btree_set_ext::next_elem(&self.used_ticks, tick)
+                };
+                let tick_to_cross = match opt_tick_to_cross {
+                    Some(tick_to_cross) => *tick_to_cross,
+                    None => break,
+                };
+                let sqrt_price_at_tick_to_cross = tick_math::sqrt_price_at_tick(Tick(tick_to_cross)).0;
+
+                // stop at the target if it is reached before the next used tick
+                let is_tick_cross_needed = if is_token0 {
+                    target_sqrt_price < sqrt_price_at_tick_to_cross
+                } else {
+                    target_sqrt_price > sqrt_price_at_tick_to_cross
+                };
+                let step_sqrt_price = if is_tick_cross_needed {
+                    sqrt_price_at_tick_to_cross
+                } else {
+                    target_sqrt_price
+                };
+
+                if is_token0 {
+                    amount_in += pool_math::compute_range_amount0_given_liq(live_liq, step_sqrt_price, sqrt_price);
+                    amount_out += pool_math::compute_range_amount1_given_liq(live_liq, step_sqrt_price, sqrt_price);
+                } else {
+                    amount_in += pool_math::compute_range_amount1_given_liq(live_liq, sqrt_price, step_sqrt_price);
+                    amount_out += pool_math::compute_range_amount0_given_liq(live_liq, sqrt_price, step_sqrt_price);
+                }
+                sqrt_price = step_sqrt_price;
+
+                if is_tick_cross_needed {
+                    let liq_net = self.tick_states.get(&tick_to_cross).unwrap().liq_net;
+                    live_liq = if is_token0 { live_liq - liq_net } else { live_liq + liq_net };
+                    tick = if is_token0 { tick_to_cross - 1 } else { tick_to_cross };
+                }
+            }
+
+            (amount_in, amount_out, live_liq, tick, sqrt_price)
+        }
+
+        /**
+         * Imports the state exported by a pool of the same pair, see ParameterChange::ExportState, together with its amount0,1 and
+         * position NFT minter badge. The pool must have no positions, e.g. its initial position was removed, and the exported positions
+         * must be on its tick spacing. The pool keeps its fee, while the positions of the exported pool and their NFTs move to it.
+         *
+         * Only the importer role can import, none initially: the admin assigns it to the exporting pool, i.e. the rule
+         * require(global_caller(<exporting pool address>)), before the export is executed.
+         */
+        pub fn import_state(&mut self, export: PoolExport, bucket0: Bucket, bucket1: Bucket, pos_nft_minter_badge: Bucket) {
+            assert!(
+                self.positions.is_empty() && self.imported_pos_nft_minter_badge.is_none(),
+                "The pool should have no positions. Import state op aborted."
+            );
+            assert!(
+                (export.resource0_addr, export.resource1_addr) == (self.vault0.resource_address(), self.vault1.resource_address()),
+                "The exported pool should have the same resource0 and resource1. Import state op aborted."
+            );
+            self.validate_resources(bucket0.resource_address(), bucket1.resource_address());
+            assert!(
+                export.used_ticks.iter().all(|tick| tick % self.tick_spacing == 0),
+                "The exported ticks should be on the pool tick spacing. Import state op aborted."
+            );
+
+            self.sqrt_price = export.sqrt_price.0;
+            self.tick = export.current_tick.0;
+            self.live_liq = export.active_liquidity;
+            self.fee_global0 = export.fee_growth_global0;
+            self.fee_global1 = export.fee_growth_global1;
+            self.pos_nft_addr_resource_manager = export.position_nft_addr.into();
+            self.positions = export.positions.into_iter().collect();
+            self.used_ticks = export.used_ticks.into_iter().collect();
+            self.tick_states = export.tick_states.into_iter().map(|tick_state| (tick_state.tick, tick_state)).collect();
+            self.vault0.put(bucket0);
+            self.vault1.put(bucket1);
+            self.imported_pos_nft_minter_badge = Some(Vault::with_bucket(pos_nft_minter_badge));
+
+            self.log_state("### Internal state after importing the state");
+        }
+
+        /**
+         * Schedules the given parameter change, executable with execute_change once the timelock delay passed, so the LPs can exit
+         * before an adverse change takes effect. Emits a ParameterChangeScheduledEvent. The change is validated when scheduled, so an
+         * invalid one doesn't wait the timelock delay to fail, and again when executed, as the pool may have changed in between. The
+         * fee_manager role schedules, executes and cancels the fee changes only: SetFee, RampFee, SetReferralFeeShare and
+         * SetVolumeTiers, the other changes being admin only.
+         *
+         * Returns the id of the scheduled change
+         */
+        pub fn schedule_change(&mut self, change: ParameterChange) -> u64 {
+            Pool::assert_change_role(&change);
+            self.validate_change(&change);
+            let change_id = self.change_count;
+            let execution_time = Pool::current_timestamp() + self.timelock_delay;
+            self.scheduled_changes.insert(
+                change_id,
+                ScheduledChange {
+                    change: change.clone(),
+                    execution_time,
+                },
+            );
+            self.change_count += 1;
+            Runtime::emit_event(ParameterChangeScheduledEvent {
+                change_id,
+                change,
+                execution_time,
+            });
+            change_id
+        }
+
+        /**
+         * Executes the given scheduled parameter change, once its execution time came
+         */
+        pub fn execute_change(&mut self, change_id: u64) {
+            let scheduled_change = self
+                .scheduled_changes
+                .remove(&change_id)
+                .expect("Parameter change not scheduled. Execute change op aborted.");
+            Pool::assert_change_role(&scheduled_change.change);
+            assert!(
+                Pool::current_timestamp() >= scheduled_change.execution_time,
+                "The timelock delay hasn't passed. Execute change op aborted."
+            );
+            self.validate_change(&scheduled_change.change);
+            match scheduled_change.change {
+                ParameterChange::RegisterHook(hook_addr) => self.hooks.push(hook_addr),
+                ParameterChange::UnregisterHook(hook_addr) => {
+                    self.hooks.retain(|registered_hook_addr| *registered_hook_addr != hook_addr)
+                }
+                ParameterChange::SetFeeManager(fee_manager_addr) => self.fee_manager = fee_manager_addr,
+                ParameterChange::SetTimelockDelay(timelock_delay) => self.timelock_delay = timelock_delay,
+                ParameterChange::SetFee(fee) => {
+                    self.fee = fee;
+                    self.fee_ramp = None;
+                    self.update_fee_metadata(fee);
+                }
+                ParameterChange::RampFee(target_fee, epochs) => self.fee_ramp = Some(self.new_fee_ramp(target_fee, epochs)),
+                ParameterChange::SetProtocolFeeShare(protocol_fee_share) => self.protocol_fee_share = protocol_fee_share,
+                ParameterChange::SetReferralRegistry(registry_addr) => self.referral_registry = registry_addr,
+                ParameterChange::SetReferralFeeShare(referral_fee_share) => self.referral_fee_share = referral_fee_share,
+                ParameterChange::SetVolumeTiers(volume_tiers) => self.volume_tiers = volume_tiers,
+                ParameterChange::SetMaxSingleSwapPriceImpact(max_price_impact) => {
+                    self.max_single_swap_price_impact = max_price_impact
+                }
+                ParameterChange::SetMaxSwapInputShare(max_input_share) => self.max_swap_input_share = max_input_share,
+                ParameterChange::SetSwapRoyalty(xrd_amount) => self.set_swap_royalty(xrd_amount),
+                ParameterChange::WidenPriceBand(low_tick, high_tick) => {
+                    self.price_band = Some((low_tick, high_tick));
+                    Runtime::emit_event(PriceBandWidenedEvent { low_tick, high_tick });
+                }
+                ParameterChange::ExportState(target_pool_addr) => self.export_state(target_pool_addr),
+            }
+        }
+
+        /**
+         * Returns the effects the given parameter change would have if it was executed now, without applying it, so the governance
+         * voters and the admins can inspect a proposed change, see ParameterChangePreview. The preview of an invalid change aborts
+         * with the same error as its execution.
+         */
+        pub fn preview_parameter_change(&self, change: ParameterChange) -> ParameterChangePreview {
+            self.validate_change(&change);
+            let mut preview = ParameterChangePreview {
+                change: change.clone(),
+                execution_time: Pool::current_timestamp() + self.timelock_delay,
+                tick_spacing: self.tick_spacing,
+                fee_config: self.get_fee_config(),
+                replaced_fee_ramp: None,
+                hooks: self.hooks.clone(),
+                timelock_delay: self.timelock_delay,
+                referral_registry: self.referral_registry,
+                max_single_swap_price_impact: self.max_single_swap_price_impact,
+                max_swap_input_share: self.max_swap_input_share,
+                swap_royalty: self.swap_royalty,
+                price_band: self.price_band,
+            };
+            match change {
+                ParameterChange::RegisterHook(hook_addr) => preview.hooks.push(hook_addr),
+                ParameterChange::UnregisterHook(hook_addr) => {
+                    preview.hooks.retain(|registered_hook_addr| *registered_hook_addr != hook_addr)
+                }
+                ParameterChange::SetFeeManager(fee_manager_addr) => preview.fee_config.fee_manager = fee_manager_addr,
+                ParameterChange::SetTimelockDelay(timelock_delay) => preview.timelock_delay = timelock_delay,
+                ParameterChange::SetFee(fee) => {
+                    preview.replaced_fee_ramp = preview.fee_config.fee_ramp.take();
+                    preview.fee_config.fee = fee;
+                }
+                ParameterChange::RampFee(target_fee, epochs) => {
+                    preview.replaced_fee_ramp = preview.fee_config.fee_ramp.take();
+                    preview.fee_config.fee_ramp = Some(self.new_fee_ramp(target_fee, epochs));
+                }
+                ParameterChange::SetProtocolFeeShare(protocol_fee_share) => {
+                    preview.fee_config.protocol_fee_share = protocol_fee_share
+                }
+                ParameterChange::SetReferralRegistry(registry_addr) => preview.referral_registry = registry_addr,
+                ParameterChange::SetReferralFeeShare(referral_fee_share) => {
+                    preview.fee_config.referral_fee_share = referral_fee_share
+                }
+                ParameterChange::SetVolumeTiers(volume_tiers) => preview.fee_config.volume_tiers = volume_tiers,
+                ParameterChange::SetMaxSingleSwapPriceImpact(max_price_impact) => {
+                    preview.max_single_swap_price_impact = max_price_impact
+                }
+                ParameterChange::SetMaxSwapInputShare(max_input_share) => preview.max_swap_input_share = max_input_share,
+                ParameterChange::SetSwapRoyalty(xrd_amount) => preview.swap_royalty = xrd_amount,
+                ParameterChange::WidenPriceBand(low_tick, high_tick) => preview.price_band = Some((low_tick, high_tick)),
+                ParameterChange::ExportState(_) => {}
+            }
+            preview
+        }
+
+        /**
+         * Cancels the given scheduled parameter change
+         */
+        pub fn cancel_change(&mut self, change_id: u64) {
+            let scheduled_change = self
+                .scheduled_changes
+                .remove(&change_id)
+                .expect("Parameter change not scheduled. Cancel change op aborted.");
+            Pool::assert_change_role(&scheduled_change.change);
+        }
+
+        /**
+         * Returns the scheduled parameter changes, by id
+         */
+        pub fn get_scheduled_changes(&self) -> Vec<(u64, ScheduledChange)> {
+            let mut scheduled_changes: Vec<(u64, ScheduledChange)> = self
+                .scheduled_changes
+                .iter()
+                .map(|(change_id, scheduled_change)| (*change_id, scheduled_change.clone()))
+                .collect();
+            scheduled_changes.sort_by_key(|(change_id, _)| *change_id);
+            scheduled_changes
+        }
+
+        /**
+         * Returns the seconds the parameter changes wait before they can be executed
+         */
+        pub fn get_timelock_delay(&self) -> i64 {
+            self.timelock_delay
+        }
+
+        /**
+         * Proposes a new admin badge, the first step of the admin handover: the current admin badge stays the admin until a holder of
+         * the new badge accepts it with accept_admin. A new proposal replaces the pending one.
+         */
+        pub fn propose_admin(&mut self, new_badge_addr: ResourceAddress) {
+            self.pending_admin_badge_addr = Some(new_badge_addr);
+            Runtime::global_component().set_role("pending_admin", rule!(require(new_badge_addr)));
+        }
+
+        /**
+         * Makes the proposed admin badge the admin of the pool, the second step of the admin handover. Only a holder of the proposed
+         * badge can accept it, so the pool can't be handed over to a wrong badge.
+         */
+        pub fn accept_admin(&mut self) {
+            let new_badge_addr = self
+                .pending_admin_badge_addr
+                .take()
+                .expect("No admin badge proposed. Accept admin op aborted.");
+            self.admin_badge_addr = Some(new_badge_addr);
+            let component = Runtime::global_component();
+            component.set_role("admin", rule!(require(new_badge_addr)));
+            component.set_role("pending_admin", rule!(deny_all));
+        }
+
+        /**
+         * Pauses or unpauses the given operation, e.g. during an incident. The paused operations fail with a PoolError::Paused error,
+         * while the withdrawals are always allowed.
+         */
+        pub fn set_paused(&mut self, operation: PausableOperation, paused: bool) {
+            self.paused_operations.retain(|paused_operation| *paused_operation != operation);
+            if paused {
+                self.paused_operations.push(operation);
+            }
+        }
+
+        /**
+         * Whether the given operation is paused
+         */
+        pub fn is_paused(&self, operation: PausableOperation) -> bool {
+            self.paused_operations.contains(&operation)
+        }
+
+        /**
+         * Switches the pool to emergency mode, permanently, e.g. if its fee accounting is found to be corrupted: the swaps, the
+         * deposits, the fee collections and the position migrations are disabled and the positions are removed with a bail-out path,
+         * see remove_pos, so the LPs can always retrieve their principal.
+         */
+        pub fn enable_emergency_mode(&mut self) {
+            assert!(!self.emergency_mode, "The pool is already in emergency mode.");
+            self.emergency_mode = true;
+        }
+
+        /**
+         * Whether the pool is in emergency mode
+         */
+        pub fn is_emergency_mode(&self) -> bool {
+            self.emergency_mode
+        }
+
+        /**
+         * Deprecates the pool, permanently, e.g. when its liquidity migrates to a new pool version: the swaps and the deposits are
+         * disabled, while the positions can still be removed and their fees collected. The factory flags the deprecated pools, see
+         * PoolFactory::flag_deprecated, so the routers stop quoting them.
+         */
+        pub fn deprecate(&mut self) {
+            assert!(!self.deprecated, "The pool is already deprecated.");
+            self.deprecated = true;
+        }
+
+        /**
+         * Whether the pool is deprecated
+         */
+        pub fn is_deprecated(&self) -> bool {
+            self.deprecated
+        }
+
+        /**
+         * Whether the pool accepts new positions and liquidity, i.e. it isn't in emergency mode or deprecated and the deposits aren't
+         * paused, e.g. so the integrations that remove and add back their positions know when to keep the amounts instead
+         */
+        pub fn deposits_allowed(&self) -> bool {
+            !self.emergency_mode && !self.deprecated && !self.is_paused(PausableOperation::Deposit)
+        }
+
+        /**
+         * Updates the metadata displaying the pool in the wallets and the explorers, only the given fields of the metadata being set,
+         * see PoolMetadata, e.g. the dApp definition account of the protocol, which should link back to the pool, so the wallets
+         * verify the pool belongs to the dApp. The pair set at creation is locked and the fee tier follows the static fee, see
+         * ParameterChange::SetFee.
+         */
+        pub fn update_metadata(&mut self, metadata: PoolMetadata) {
+            let component = Runtime::global_component();
+            if let Some(name) = metadata.name {
+                component.set_metadata("name", name);
+            }
+            if let Some(description) = metadata.description {
+                component.set_metadata("description", description);
+            }
+            if let Some(tags) = metadata.tags {
+                component.set_metadata("tags", tags);
+            }
+            if let Some(icon_url) = metadata.icon_url {
+                component.set_metadata("icon_url", UncheckedUrl::of(icon_url));
+            }
+            if let Some(info_url) = metadata.info_url {
+                component.set_metadata("info_url", UncheckedUrl::of(info_url));
+            }
+            if let Some(dapp_definition_addr) = metadata.dapp_definition_addr {
+                component.set_metadata("dapp_definition", GlobalAddress::from(dapp_definition_addr));
+            }
+        }
+
+        /**
+         * Sets the metadata displaying the position NFTs in the wallets and the explorers, initially a name and a description from
+         * the resource symbols and the fee, so the branding can evolve without redeploying the pool
+         */
+        pub fn set_position_nft_metadata(&mut self, name: String, description: String, icon_url: String, info_url: String) {
+            let resource_manager = self.pos_nft_addr_resource_manager;
+            self.pos_nft_minter_badge().as_fungible().authorize_with_amount(1, || {
+                resource_manager.set_metadata("name", name);
+                resource_manager.set_metadata("description", description);
+                resource_manager.set_metadata("icon_url", UncheckedUrl::of(icon_url));
+                resource_manager.set_metadata("info_url", UncheckedUrl::of(info_url));
+            });
+        }
+
+        /**
+         * Returns the XRD royalties paid by the swaps since the last claim
+         */
+        pub fn claim_royalties(&mut self) -> Bucket {
+            Runtime::global_component().claim_component_royalties()
+        }
+
+        /**
+         * Switches on or off the protocol fee, the share of the swap fees set with ParameterChange::SetProtocolFeeShare going to the
+         * protocol instead of the LPs. The switch is a single purpose action of the fee_switcher role, e.g. a governance badge, and
+         * each switch emits a ProtocolFeeSwitchEvent.
+         */
+        pub fn set_protocol_fee_switch(&mut self, enabled: bool) {
+            assert!(
+                self.protocol_fee_enabled != enabled,
+                "The protocol fee switch is already {}. Switch op aborted.",
+                if enabled { "on" } else { "off" }
+            );
+            self.protocol_fee_enabled = enabled;
+            Runtime::emit_event(ProtocolFeeSwitchEvent {
+                enabled,
+                protocol_fee_share: self.protocol_fee_share,
+            });
+        }
+
+        /**
+         * Whether the protocol fee is switched on
+         */
+        pub fn is_protocol_fee_enabled(&self) -> bool {
+            self.protocol_fee_enabled
+        }
+
+        /**
+         * Returns the share of the swap fees going to the protocol when the protocol fee is switched on
+         */
+        pub fn get_protocol_fee_share(&self) -> Decimal {
+            self.protocol_fee_share
+        }
+
+        /**
+         * Returns the volume tiers of the trader fee discounts, in ascending order
+         */
+        pub fn get_volume_tiers(&self) -> Vec<VolumeTier> {
+            self.volume_tiers.clone()
+        }
+
+        /**
+         * Returns the trailing volume of the given trader, the volume swapped with Pool::swap_as_trader in the last
+         * VOLUME_WINDOW_EPOCHS epochs, measured in resource1
+         */
+        pub fn get_trader_volume(&self, trader_id: NonFungibleGlobalId) -> Decimal {
+            self.trader_volume(&trader_id)
+        }
+
+        /**
+         * Returns the maximum relative change of the pool price a single swap can make, if set
+         */
+        pub fn get_max_single_swap_price_impact(&self) -> Option<Decimal> {
+            self.max_single_swap_price_impact
+        }
+
+        /**
+         * Returns the maximum input of a single swap, as a share of the active liquidity, if set, see
+         * ParameterChange::SetMaxSwapInputShare
+         */
+        pub fn get_max_swap_input_share(&self) -> Option<Decimal> {
+            self.max_swap_input_share
+        }
+
+        /**
+         * Returns the maximum number of used ticks a single swap can cross, see STABLE_MAX_TICKS_CROSSED, none for no limit
+         */
+        pub fn get_max_ticks_crossed(&self) -> Option<u32> {
+            self.max_ticks_crossed
+        }
+
+        /**
+         * Returns the referral registry of the pool, if set
+         */
+        pub fn get_referral_registry(&self) -> Option<ComponentAddress> {
+            self.referral_registry
+        }
+
+        /**
+         * Returns the share of the swap fees paid to the referrers of the referred swaps
+         */
+        pub fn get_referral_fee_share(&self) -> Decimal {
+            self.referral_fee_share
+        }
+
+        /**
+         * Collects the protocol fees accumulated by the pool, kept apart from the LP fees.
+         *
+         * Returns the protocol fees amount0,1
+         */
+        pub fn collect_protocol_fees(&mut self) -> (Bucket, Bucket) {
+            (self.protocol_fees0.take_all(), self.protocol_fees1.take_all())
+        }
+
+        /**
+         * Accepts the tokens sent to the pool other than its resources and position NFTs, e.g. airdrops or mistaken transfers, kept
+         * apart from the pool liquidity until the admin rescues them, see Pool::rescue
+         */
+        pub fn deposit(&mut self, bucket: Bucket) {
+            let resource_addr = bucket.resource_address();
+            self.assert_foreign_resource(resource_addr);
+            let has_vault = self.foreign_vaults.get(&resource_addr).is_some();
+            if has_vault {
+                self.foreign_vaults.get_mut(&resource_addr).unwrap().put(bucket);
+            } else {
+                self.foreign_vaults.insert(resource_addr, Vault::with_bucket(bucket));
             }
-            .instantiate()
-            .prepare_to_globalize(OwnerRole::None)
-            .roles(roles!(
-                admin => rule!(require(admin_badge_addr));
-            ))
-            .globalize();
+        }
+
+        /**
+         * Withdraws all the tokens of the given resource sent to the pool, see Pool::deposit. The pool resources and position NFTs
+         * can't be rescued, so the liquidity and the fees of the LPs stay in the pool.
+         *
+         * Returns the rescued tokens, an empty bucket if none were sent
+         */
+        pub fn rescue(&mut self, resource_addr: ResourceAddress) -> Bucket {
+            self.assert_foreign_resource(resource_addr);
+            match self.foreign_vaults.get_mut(&resource_addr) {
+                Some(mut vault) => vault.take_all(),
+                None => Bucket::new(resource_addr),
+            }
+        }
+
+        /**
+         * Returns the admin badge of the pool, none for a pool created with an admin rule, see PoolConfig, until the
+         * admin is handed over to a badge
+         */
+        pub fn get_admin_badge_addr(&self) -> Option<ResourceAddress> {
+            self.admin_badge_addr
+        }
+
+        /**
+         * Returns the proposed admin badge, if any, see propose_admin
+         */
+        pub fn get_pending_admin_badge_addr(&self) -> Option<ResourceAddress> {
+            self.pending_admin_badge_addr
+        }
+
+        /**
+         * Returns the registered hook components, in their call order
+         */
+        pub fn get_hooks(&self) -> Vec<ComponentAddress> {
+            self.hooks.clone()
+        }
+
+        /**
+         * Returns the fee manager component of the pool, if any
+         */
+        pub fn get_fee_manager(&self) -> Option<ComponentAddress> {
+            self.fee_manager
+        }
+
+        /**
+         * Returns the badge required to swap and add positions if the pool is permissioned, see PoolConfig
+         */
+        pub fn get_member_badge_addr(&self) -> Option<ResourceAddress> {
+            self.member_badge_addr
+        }
 
-            let (pos_nft, remaining_bucket0, remaining_bucket1) =
-                component.add_pos(bucket0, bucket1, low_sqrt_price, high_sqrt_price);
+        /**
+         * Sets the whitelist badge the LPs must present to add positions, see PoolConfig, e.g. to rotate it, or
+         * opens the positions to anyone, besides the members of a permissioned pool, if none is given. The existing positions aren't
+         * affected.
+         */
+        pub fn set_lp_badge_addr(&mut self, lp_badge_addr: Option<ResourceAddress>) {
+            self.lp_badge_addr = lp_badge_addr;
+            Runtime::global_component()
+                .set_role("liquidity_provider", Pool::lp_rule(self.member_badge_addr, lp_badge_addr, None));
+        }
 
-            (component, pos_nft, remaining_bucket0, remaining_bucket1)
+        /**
+         * Returns the whitelist badge required to add positions, if any, see PoolConfig
+         */
+        pub fn get_lp_badge_addr(&self) -> Option<ResourceAddress> {
+            self.lp_badge_addr
         }
 
         /**
-         * Validates that the pool resource types are fungibles.
+         * Destroy the pool if no more positions
          */
-        fn validate_resource_type_is_fungible(resource_addr: ResourceAddress) {
-            let token_resource_type = ResourceManager::from(resource_addr).resource_type();
+        pub fn destroy(&self) {
+            //todo
+        }
+
+        //only the resources other than the pool resources and position NFTs are deposited apart and rescued
+        fn assert_foreign_resource(&self, resource_addr: ResourceAddress) {
             assert!(
-                matches!(token_resource_type, ResourceType::Fungible { .. }),
-                "Pool resource0,1 must be of fungible type."
+                resource_addr != self.vault0.resource_address()
+                    && resource_addr != self.vault1.resource_address()
+                    && resource_addr != self.pos_nft_addr_resource_manager.address(),
+                "The pool resources and position NFTs can't be deposited or rescued. Op aborted."
             );
         }
 
         /**
-         * Adds a new liquidity position in range [low_tick, high_tick] using the resource amounts in bucket0 and bucket1. Depending on the current price, amount0,1 might not be used entirely.
-         *
-         * Returns a NFT representing the position with the newly created liquidity and the remainders amount0,1.
+         * Validate the type and quantity of the provided proof match the type issued by the pool
          */
-        pub fn add_pos(
-            &mut self,
-            mut bucket0: Bucket,
-            mut bucket1: Bucket,
-            low_sqrt_price: Decimal,
-            high_sqrt_price: Decimal,
-        ) -> (Bucket, Bucket, Bucket) {
-            debug!("### Adding a new position...");
-            debug!("### Bucket0 resource={:?}", bucket0.resource_address());
-            debug!("### Bucket0={:?}", bucket0.amount());
-            debug!("### Bucket1 resource={:?}", bucket1.resource_address());
-            debug!("### Bucket1={:?}", bucket1.amount());
-            debug!("### Low sqrt price={:?}", low_sqrt_price);
-            debug!("### High sqrt price={:?}", high_sqrt_price);
+        fn check_proof(&self, proof: Proof) -> CheckedProof {
+            proof.check(self.pos_nft_addr_resource_manager.address())
+        }
 
-            let low_tick = tick_math::tick_at_sqrt_price(low_sqrt_price);
-            let high_tick = tick_math::tick_at_sqrt_price(high_sqrt_price);
-            debug!("### Low tick={:?}", low_tick);
-            debug!("### High tick={:?}", high_tick);
+        /**
+         * The fee of swapping the given amount of the given resource: the one returned by the fee manager, if set, or the static fee
+         */
+        fn swap_fee(&self, resource_addr: ResourceAddress, amount: Decimal) -> Decimal {
+            match self.fee_manager {
+                Some(fee_manager_addr) => {
+                    let context = SwapContext {
+                        resource_in_addr: resource_addr,
+                        amount,
+                        sqrt_price: SqrtPrice(self.sqrt_price),
+                        fee: self.current_fee(),
+                    };
+                    let fee_manager: Global<AnyComponent> = Global::from(fee_manager_addr);
+                    let fee = fee_manager.call_raw::<Decimal>("get_swap_fee", scrypto_args!(context));
+                    assert!(
+                        fee >= Decimal::zero() && fee < Decimal::one(),
+                        "Invalid fee from the fee manager, should be 0 <= fee < 1. Swap op aborted."
+                    );
+                    fee
+                }
+                None => self.current_fee(),
+            }
+        }
 
-            //validate ticks
-            assert!(
-                low_tick < high_tick,
-                "Lower tick must be less than upper tick. Add position op aborted."
+        //the fee_manager role schedules, executes and cancels the fee changes only, the other changes being left to the admin
+        fn assert_change_role(change: &ParameterChange) {
+            let fee_change = matches!(
+                change,
+                ParameterChange::SetFee(_)
+                    | ParameterChange::RampFee(_, _)
+                    | ParameterChange::SetReferralFeeShare(_)
+                    | ParameterChange::SetVolumeTiers(_)
             );
+            if !fee_change {
+                let admin_rule = Runtime::global_component()
+                    .get_role("admin")
+                    .expect("The admin role should be set.");
+                Runtime::assert_access_rule(admin_rule);
+            }
+        }
 
-            // validate the passed resources
-            self.validate_resources(bucket0.resource_address(), bucket1.resource_address());
+        //the checks of the parameter changes, on their execution and preview, as the state they depend on may change while scheduled
+        fn validate_change(&self, change: &ParameterChange) {
+            match change {
+                ParameterChange::RegisterHook(hook_addr) => {
+                    assert!(!self.hooks.contains(hook_addr), "Hook already registered.");
+                    assert!(self.hooks.len() < MAX_HOOKS, "Too many hooks, at most {} can be registered.", MAX_HOOKS);
+                }
+                ParameterChange::UnregisterHook(hook_addr) => {
+                    assert!(self.hooks.contains(hook_addr), "Hook not registered.")
+                }
+                ParameterChange::SetFeeManager(_) | ParameterChange::SetReferralRegistry(_) => {}
+                ParameterChange::SetTimelockDelay(timelock_delay) => {
+                    assert!(*timelock_delay >= 0, "Invalid timelock delay, should be positive or zero.")
+                }
+                ParameterChange::SetFee(fee) => assert!(
+                    *fee >= Decimal::zero() && *fee <= Decimal::from(MAX_FEE_BPS) / 10000,
+                    "Invalid fee, should be 0 <= fee <= {} bps.",
+                    MAX_FEE_BPS
+                ),
+                ParameterChange::RampFee(target_fee, epochs) => {
+                    assert!(
+                        *target_fee >= Decimal::zero() && *target_fee <= Decimal::from(MAX_FEE_BPS) / 10000,
+                        "Invalid target fee, should be 0 <= fee <= {} bps.",
+                        MAX_FEE_BPS
+                    );
+                    assert!(*epochs > 0, "Invalid ramp epochs, should be positive.");
+                }
+                ParameterChange::SetProtocolFeeShare(protocol_fee_share) => assert!(
+                    *protocol_fee_share >= Decimal::zero()
+                        && *protocol_fee_share <= Decimal::from(MAX_PROTOCOL_FEE_SHARE_BPS) / 10000,
+                    "Invalid protocol fee share, should be 0 <= share <= {} bps.",
+                    MAX_PROTOCOL_FEE_SHARE_BPS
+                ),
+                ParameterChange::SetReferralFeeShare(referral_fee_share) => assert!(
+                    *referral_fee_share >= Decimal::zero()
+                        && *referral_fee_share <= Decimal::from(MAX_REFERRAL_FEE_SHARE_BPS) / 10000,
+                    "Invalid referral fee share, should be 0 <= share <= {} bps.",
+                    MAX_REFERRAL_FEE_SHARE_BPS
+                ),
+                ParameterChange::SetVolumeTiers(volume_tiers) => Pool::validate_volume_tiers(volume_tiers),
+                ParameterChange::SetMaxSingleSwapPriceImpact(max_price_impact) => assert!(
+                    max_price_impact.map_or(true, |max_price_impact| max_price_impact > Decimal::zero()),
+                    "Invalid max price impact, should be positive."
+                ),
+                ParameterChange::SetMaxSwapInputShare(max_input_share) => assert!(
+                    max_input_share.map_or(true, |share| share > Decimal::zero() && share <= Decimal::one()),
+                    "Invalid max swap input share, should be 0 < share <= 1."
+                ),
+                ParameterChange::SetSwapRoyalty(xrd_amount) => {
+                    assert!(*xrd_amount >= Decimal::zero(), "Invalid swap royalty, should be positive or zero.")
+                }
+                ParameterChange::WidenPriceBand(low_tick, high_tick) => {
+                    let (band_low_tick, band_high_tick) = self.price_band.expect("The pool has no price band to widen.");
+                    if let Err(err) = TickRange::try_new(Tick(*low_tick), Tick(*high_tick), self.tick_spacing) {
+                        panic!("Invalid price band: {}", err);
+                    }
+                    assert!(
+                        *low_tick <= band_low_tick
+                            && *high_tick >= band_high_tick
+                            && (*low_tick, *high_tick) != (band_low_tick, band_high_tick),
+                        "Invalid price band, should widen the current band [{}, {}].",
+                        band_low_tick,
+                        band_high_tick
+                    );
+                }
+                ParameterChange::ExportState(target_pool_addr) => {
+                    let target_pool: Global<AnyComponent> = Global::from(*target_pool_addr);
+                    assert!(
+                        target_pool.blueprint_id() == Pool::blueprint_id()
+                            && *target_pool_addr != Runtime::global_component().address(),
+                        "The target pool should be another Pool of the same package."
+                    );
+                }
+            }
+        }
 
-            self.log_state("### Internal state before adding the new position");
+        //the same royalty is paid by all the swap methods, free when zero
+        fn set_swap_royalty(&mut self, xrd_amount: Decimal) {
+            let royalty = if xrd_amount == Decimal::zero() {
+                RoyaltyAmount::Free
+            } else {
+                RoyaltyAmount::Xrd(xrd_amount)
+            };
+            Runtime::global_component().set_royalty("swap", royalty.clone());
+            Runtime::global_component().set_royalty("swap_with_referrer", royalty.clone());
+            Runtime::global_component().set_royalty("swap_as_trader", royalty);
+            self.swap_royalty = xrd_amount;
+        }
 
-            let low_sqrt_price = tick_math::sqrt_price_at_tick(low_tick);
-            let high_sqrt_price = tick_math::sqrt_price_at_tick(high_tick);
-            //compute the liquidty and amount0,1 required for this liquidity, depending on the current tick
-            let (liq, required_amount0, required_amount1) =
-                pool_math::compute_range_liq_given_amounts(
-                    bucket0.amount(),
-                    bucket1.amount(),
-                    self.sqrt_price,
-                    low_sqrt_price,
-                    high_sqrt_price,
-                );
+        //the pool gives away all its liquidity, the vaults amounts and the position NFT minter badge, so the LPs keep their NFTs
+        fn export_state(&mut self, target_pool_addr: ComponentAddress) {
+            debug!("### Exporting state to pool {:?}...", target_pool_addr);
 
-            //update live liq
-            self.update_live_liq(liq, low_tick, high_tick);
+            let export = PoolExport {
+                resource0_addr: self.vault0.resource_address(),
+                resource1_addr: self.vault1.resource_address(),
+                sqrt_price: SqrtPrice(self.sqrt_price),
+                current_tick: Tick(self.tick),
+                active_liquidity: self.live_liq,
+                fee_growth_global0: self.fee_global0,
+                fee_growth_global1: self.fee_global1,
+                position_nft_addr: self.pos_nft_addr_resource_manager.address(),
+                positions: self.get_positions(),
+                used_ticks: self.used_ticks.iter().copied().collect(),
+                tick_states: self.tick_states.values().cloned().collect(),
+            };
 
-            //update tick states
-            self.tick_states
-                .entry(low_tick)
-                .or_insert(TickState::new(low_tick))
-                .modify_liq(liq, false, self.tick, self.fee_global0, self.fee_global1);
-            self.tick_states
-                .entry(high_tick)
-                .or_insert(TickState::new(high_tick))
-                .modify_liq(liq, true, self.tick, self.fee_global0, self.fee_global1);
+            self.live_liq = Decimal::zero();
+            self.positions.clear();
+            self.used_ticks.clear();
+            self.tick_states.clear();
+            let pos_nft_minter_badge = match self.imported_pos_nft_minter_badge.as_mut() {
+                Some(imported_pos_nft_minter_badge) => imported_pos_nft_minter_badge.take_all(),
+                None => self.pos_nft_minter_badge.take_all(),
+            };
 
-            //mark ticks as used
-            self.used_ticks.insert(low_tick);
-            self.used_ticks.insert(high_tick);
+            let mut target_pool: Global<Pool> = target_pool_addr.into();
+            target_pool.import_state(export, self.vault0.take_all(), self.vault1.take_all(), pos_nft_minter_badge);
 
-            //take the required amounts in the pool vaults
-            self.vault0.put(bucket0.take(required_amount0));
-            self.vault1.put(bucket1.take(required_amount1));
+            debug!("### State exported.");
+        }
 
-            //mint the nft corresponding to the position with the liqudity on it
-            let pos_nft = self
-                .pos_nft_minter_badge
-                .as_fungible()
-                .authorize_with_amount(1, || {
-                    self.pos_nft_addr_resource_manager
-                        .mint_ruid_non_fungible(PositionNFTData {
-                            liq,
-                            low_sqrt_price,
-                            high_sqrt_price,
-                        })
-                });
+        //the ramp starts from the current fee at the current epoch
+        fn new_fee_ramp(&self, target_fee: Decimal, epochs: u64) -> FeeRamp {
+            let start_epoch = Runtime::current_epoch().number();
+            FeeRamp {
+                start_fee: self.current_fee(),
+                target_fee,
+                start_epoch,
+                end_epoch: start_epoch + epochs,
+            }
+        }
 
-            //save the new position
-            self.positions.insert(
-                (pos_nft.as_non_fungible().non_fungible_local_id()).clone(),
-                Position::new(liq, low_tick, high_tick, Decimal::zero(), Decimal::zero()),
+        //the static fee, moved along the ongoing ramp
+        fn current_fee(&self) -> Decimal {
+            match &self.fee_ramp {
+                Some(fee_ramp) => fee_ramp.fee_at(Runtime::current_epoch().number()),
+                None => self.fee,
+            }
+        }
+
+        //the ramp is applied lazily, on the swaps, and dropped once it ended, the fee metadata following its target fee then
+        fn apply_fee_ramp(&mut self) {
+            if let Some(fee_ramp) = self.fee_ramp.clone() {
+                let epoch = Runtime::current_epoch().number();
+                self.fee = fee_ramp.fee_at(epoch);
+                if epoch >= fee_ramp.end_epoch {
+                    self.fee_ramp = None;
+                    self.update_fee_metadata(fee_ramp.target_fee);
+                }
+            }
+        }
+
+        fn validate_volume_tiers(volume_tiers: &[VolumeTier]) {
+            assert!(
+                volume_tiers.len() <= MAX_VOLUME_TIERS,
+                "Too many volume tiers, at most {} can be set.",
+                MAX_VOLUME_TIERS
             );
+            for (index, tier) in volume_tiers.iter().enumerate() {
+                assert!(
+                    tier.min_volume > Decimal::zero() && tier.fee_discount > Decimal::zero() && tier.fee_discount <= Decimal::one(),
+                    "Invalid volume tier, should be min_volume > 0 and 0 < fee_discount <= 1."
+                );
+                if index > 0 {
+                    let previous_tier = &volume_tiers[index - 1];
+                    assert!(
+                        tier.min_volume > previous_tier.min_volume && tier.fee_discount > previous_tier.fee_discount,
+                        "Invalid volume tiers, should be in ascending order of min_volume and fee_discount."
+                    );
+                }
+            }
+        }
 
-            self.log_state("### Internal state after adding the new position");
+        //the trading stays open after the min liquidity is reached, even if the liquidity is removed later
+        fn open_trading(&mut self) {
+            if let Some(min_liquidity) = self.min_liquidity {
+                assert!(
+                    self.live_liq > min_liquidity,
+                    "The pool liquidity is below the minimum of {}, the trading isn't open yet. Swap op aborted.",
+                    min_liquidity
+                );
+                self.min_liquidity = None;
+            }
+        }
 
-            debug!(
-                "### Position id={:?}",
-                pos_nft.as_non_fungible().non_fungible_local_id()
+        //the amounts bought while the cap is in force are counted for each trader badge
+        fn check_buy_cap(&mut self, trader_id: &Option<NonFungibleGlobalId>, output_amount: Decimal) {
+            let buy_cap = match self.get_buy_cap() {
+                Some((buy_cap, _)) => buy_cap,
+                None => return,
+            };
+            let trader_id = match trader_id {
+                Some(trader_id) if trader_id.resource_address() == buy_cap.trader_badge_addr => trader_id,
+                _ => panic!("Only the traders with a buy cap badge can buy during the launch. Swap op aborted."),
+            };
+            let bought_amount = self.bought_amounts.get(trader_id).map_or(Decimal::zero(), |amount| *amount) + output_amount;
+            assert!(
+                bought_amount <= buy_cap.max_output,
+                "Buy cap of {} exceeded. Swap op aborted.",
+                buy_cap.max_output
             );
-            debug!("### Position NFT liq={:?}", liq);
-            debug!("### Bucket0={:?}", bucket0.amount());
-            debug!("### Bucket1={:?}", bucket1.amount());
-            debug!("### Position added.");
+            self.bought_amounts.insert(trader_id.clone(), bought_amount);
+        }
 
-            (pos_nft, bucket0, bucket1)
+        //the active liquidity holds the virtual reserves L / sqrt(P) of resource0 and L * sqrt(P) of resource1 at the current price
+        fn check_swap_input(&self, resource_in_addr: ResourceAddress, amount: Decimal) {
+            if let Some(max_input_share) = self.max_swap_input_share {
+                let active_reserve = if resource_in_addr == self.vault0.resource_address() {
+                    self.live_liq / self.sqrt_price
+                } else {
+                    self.live_liq * self.sqrt_price
+                };
+                let max_input = active_reserve * max_input_share;
+                assert!(
+                    amount <= max_input,
+                    "Swap input {} over the maximum {}, the swap should be split. Swap op aborted.",
+                    amount,
+                    max_input
+                );
+            }
         }
 
-        /**
-         * Adds more liquidity to an already existing position. The new liquidity is computed from the provided amounts of tokens0,1 depending on the current price. Any fees already accumulated by the position are collected and put to work as new liquidity.
-         *
-         * The caller must present a proof issued from the NFT that identifies the already existing, active position.
-         *
-         * Returns the remainder of the amount0,1, if any.
-         */
-        pub fn add_liq(
-            &mut self,
-            mut bucket0: Bucket,
-            mut bucket1: Bucket,
-            auth: Proof,
-        ) -> (Bucket, Bucket) {
-            debug!("### Adding liquidity...");
-            //validate the resources sent in
-            self.validate_resources(bucket0.resource_address(), bucket1.resource_address());
+        fn check_price_impact(&self, initial_sqrt_price: Decimal) {
+            if let Some(max_price_impact) = self.max_single_swap_price_impact {
+                let price_ratio = (self.sqrt_price / initial_sqrt_price) * (self.sqrt_price / initial_sqrt_price);
+                let price_impact = if price_ratio > Decimal::one() {
+                    price_ratio - Decimal::one()
+                } else {
+                    Decimal::one() - price_ratio
+                };
+                assert!(
+                    price_impact <= max_price_impact,
+                    "Price impact {} over the maximum {}. Swap op aborted.",
+                    price_impact,
+                    max_price_impact
+                );
+            }
+        }
 
-            //add liq
-            let (to_deduct_amount0, to_deduct_amount1) =
-                self.add_liq_internal(bucket0.amount(), bucket1.amount(), auth);
+        //the discount of the highest tier reached by the given volume
+        fn fee_discount(&self, volume: Decimal) -> Decimal {
+            self.volume_tiers
+                .iter()
+                .rev()
+                .find(|tier| volume >= tier.min_volume)
+                .map_or(Decimal::zero(), |tier| tier.fee_discount)
+        }
 
-            // take the required amounts in the pool vaults
-            self.vault0.put(bucket0.take(to_deduct_amount0));
-            self.vault1.put(bucket1.take(to_deduct_amount1));
+        fn trader_volume(&self, trader_id: &NonFungibleGlobalId) -> Decimal {
+            let first_epoch = Pool::first_volume_window_epoch();
+            match self.trader_volumes.get(trader_id) {
+                Some(epoch_volumes) => epoch_volumes
+                    .iter()
+                    .filter(|(epoch, _)| *epoch >= first_epoch)
+                    .fold(Decimal::zero(), |total, (_, volume)| total + *volume),
+                None => Decimal::zero(),
+            }
+        }
 
-            debug!("### Liquidity added.");
-            debug!("### Bucket0={:?}", bucket0.amount());
-            debug!("### Bucket1={:?}", bucket1.amount());
+        //the volumes are kept per epoch, the ones out of the volume window being dropped
+        fn record_trader_volume(&mut self, trader_id: NonFungibleGlobalId, volume: Decimal) {
+            let epoch = Runtime::current_epoch().number();
+            let first_epoch = Pool::first_volume_window_epoch();
+            let mut epoch_volumes = match self.trader_volumes.get(&trader_id) {
+                Some(epoch_volumes) => epoch_volumes.clone(),
+                None => Vec::new(),
+            };
+            epoch_volumes.retain(|(volume_epoch, _)| *volume_epoch >= first_epoch);
+            match epoch_volumes.last_mut() {
+                Some((last_epoch, last_volume)) if *last_epoch == epoch => *last_volume += volume,
+                _ => epoch_volumes.push((epoch, volume)),
+            }
+            self.trader_volumes.insert(trader_id, epoch_volumes);
+        }
 
-            (bucket0, bucket1)
+        fn first_volume_window_epoch() -> u64 {
+            (Runtime::current_epoch().number() + 1).saturating_sub(VOLUME_WINDOW_EPOCHS)
         }
 
         /**
-         * Add the accumulated fees on the given position to the position liquidity.
+         * The default name of a pool, embedding its pair symbols and its fee, e.g. "MojitoSwap XRD/USDT 0.3%"
          */
-        pub fn add_accumulated_fees_to_liq(&mut self, auth: Proof) {
-            debug!("### Adding collected fees to liquidity...");
-            self.add_liq_internal(Decimal::zero(), Decimal::zero(), auth);
-            debug!("### Accumulated fees added to liquidity.");
+        fn default_name(symbol0: &str, symbol1: &str, fee: Decimal) -> String {
+            format!("MojitoSwap {}/{} {}%", symbol0, symbol1, fee * dec!("100"))
         }
 
         /**
-         * Remove all the liquidity from the position identified by the provided proof.
-         *
-         * Return the amount0,1 corresponding to the liquidity removed. Amount0,1 contain also the fees already accumulated by the position.
+         * The default description of a pool, embedding its pair symbols and its fee
          */
-        pub fn remove_pos(&mut self, proof: Proof) -> (Bucket, Bucket) {
-            let checked_proof = self.check_proof(proof);
-            let pos_nft: NonFungible<PositionNFTData> =
-                checked_proof.as_non_fungible().non_fungible();
-            self.remove_liq_internal(pos_nft.data().liq, checked_proof)
+        fn default_description(symbol0: &str, symbol1: &str, fee: Decimal) -> String {
+            format!(
+                "MojitoSwap concentrated liquidity pool of {} and {}, swapping with a {}% fee.",
+                symbol0,
+                symbol1,
+                fee * dec!("100")
+            )
+        }
+
+        //the fee tier follows the static fee, and so do the name and the description while they are the defaults of the previous
+        //fee tier, the ones set by the admin being kept
+        fn update_fee_metadata(&self, fee: Decimal) {
+            let component = Runtime::global_component();
+            let (symbol0, symbol1) =
+                (Pool::resource_symbol(self.vault0.resource_address()), Pool::resource_symbol(self.vault1.resource_address()));
+            let fee_tier = component.get_metadata::<&str, Decimal>("fee_tier").ok().flatten();
+            if let Some(fee_tier) = fee_tier {
+                let name = component.get_metadata::<&str, String>("name").ok().flatten();
+                if name == Some(Pool::default_name(&symbol0, &symbol1, fee_tier)) {
+                    component.set_metadata("name", Pool::default_name(&symbol0, &symbol1, fee));
+                }
+                let description = component.get_metadata::<&str, String>("description").ok().flatten();
+                if description == Some(Pool::default_description(&symbol0, &symbol1, fee_tier)) {
+                    component.set_metadata("description", Pool::default_description(&symbol0, &symbol1, fee));
+                }
+            }
+            component.set_metadata("fee_tier", fee);
         }
 
         /**
-         * Collect the fees accumulated for the position identified by the NFT in the auth
+         * The symbol of the given resource, from its metadata, or "?" if it has none
          */
-        pub fn collect_fees(&mut self, auth: Proof) -> (Bucket, Bucket) {
-            debug!("### Collecting fees...");
-            self.remove_liq_internal(Decimal::zero(), self.check_proof(auth))
+        fn resource_symbol(resource_addr: ResourceAddress) -> String {
+            ResourceManager::from(resource_addr)
+                .get_metadata::<&str, String>("symbol")
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| "?".to_owned())
         }
 
         /**
-         * Swaps the provided amount0,1 for the opposite token. Internally the pool modifies the tokens price and live liquidity.
-         *
-         * Returns the swapped amount1,0 and the remainder of the provided amount0,1
+         * Aborts the given operation if the pool is in emergency mode, deprecated or if the operation is paused
          */
-        pub fn swap(&mut self, bucket: Bucket) -> (Bucket, Bucket) {
-            debug!("### Swapping...");
-
-            //validate the resource to swap
-            assert!(
-                (bucket.resource_address() == self.vault0.resource_address()
-                    || bucket.resource_address() == self.vault1.resource_address()),
-                "Wrong resource type sent. Swap op aborted."
-            );
-
-            //depending on the resource type sent swap resource0 or resource1
-            let (output_bucket, remainder_bucket) =
-                if bucket.resource_address() == self.vault0.resource_address() {
-                    self.swap_internal(bucket, true)
-                } else {
-                    self.swap_internal(bucket, false)
-                };
-
-            debug!("Swapping done.");
-
-            (output_bucket, remainder_bucket)
+        fn check_allowed(&self, operation: PausableOperation) {
+            if self.emergency_mode {
+                panic!("{}", PoolError::EmergencyMode);
+            }
+            if self.deprecated {
+                panic!("{}", PoolError::Deprecated);
+            }
+            if self.is_paused(operation) {
+                panic!("{}", PoolError::Paused(operation));
+            }
         }
 
         /**
-         * Destroy the pool if no more positions
+         * Calls the given method of the registered hooks with the given arguments. A hook aborts the swap by panicking.
          */
-        pub fn destroy(&self) {
-            //todo
+        fn call_hooks(&self, method: &str, args: Vec<u8>) {
+            for hook_addr in &self.hooks {
+                let hook: Global<AnyComponent> = Global::from(*hook_addr);
+                hook.call_raw::<()>(method, args.clone());
+            }
         }
 
         /**
-         * Validate the type and quantity of the provided proof match the type issued by the pool
+         * The badge that mints and updates the position NFTs: the imported one, if the pool imported the state of another pool
          */
-        fn check_proof(&self, proof: Proof) -> CheckedProof {
-            proof.check(self.pos_nft_addr_resource_manager.address())
+        fn pos_nft_minter_badge(&self) -> &Vault {
+            self.imported_pos_nft_minter_badge.as_ref().unwrap_or(&self.pos_nft_minter_badge)
         }
 
         /**
@@ -421,8 +2801,8 @@ mod pool_blueprint {
                     amount0 + pos_fee0,
                     amount1 + pos_fee1,
                     self.sqrt_price,
-                    tick_math::sqrt_price_at_tick(low_tick),
-                    tick_math::sqrt_price_at_tick(high_tick),
+                    tick_math::sqrt_price_at_tick(Tick(low_tick)).0,
+                    tick_math::sqrt_price_at_tick(Tick(high_tick)).0,
                 );
 
             debug!("### Liq={:?}", liq);
@@ -434,20 +2814,22 @@ mod pool_blueprint {
                 //we used for sure all the fee
                 range_fee0
             } else {
-                // we used just a part of the fee, how much is reflected by the required amount; of course we compute it per unit of liq
-                pos.range_fee0 + required_amount0 / pos.liq
+                // we used just a part of the fee, the rest stays uncollected on the position; as the fees are computed per unit of
+                // liq, we spread the rest over the new position liq
+                range_fee0 - (pos_fee0 - required_amount0) / (pos.liq + liq)
             };
             let pos_range_fee1 = if required_amount1 >= pos_fee1 {
                 range_fee1
             } else {
-                pos.range_fee1 + required_amount1 / pos.liq
+                range_fee1 - (pos_fee1 - required_amount1) / (pos.liq + liq)
             };
             pos.update(liq, pos_range_fee0, pos_range_fee1);
 
             debug!("### Pos_range_fee0={:?}", pos_range_fee0);
             debug!("### Pos_range_fee1={:?}", pos_range_fee1);
 
-            // update pool liquidity
+            // update pool liquidity, the oracle observing the live liq before it changes
+            self.write_observation();
             self.update_ticks_liq(liq, low_tick, high_tick);
             self.update_live_liq(liq, low_tick, high_tick);
             self.update_pos_nft_liq(checked_proof, liq);
@@ -464,6 +2846,18 @@ mod pool_blueprint {
                 Decimal::zero()
             };
 
+            Runtime::emit_event(LiquidityAddedEvent {
+                pos_id: pos_id.clone(),
+                low_tick,
+                high_tick,
+                amount0: to_deduct_amount0,
+                amount1: to_deduct_amount1,
+                fees0: required_amount0 - to_deduct_amount0,
+                fees1: required_amount1 - to_deduct_amount1,
+                liq,
+                pos_liq: self.positions.get(pos_id).unwrap().liq,
+            });
+
             self.log_state("### Internal state after adding the liquidity");
 
             debug!("### Liquidity added internally.");
@@ -533,8 +2927,11 @@ mod pool_blueprint {
 
             // update the liquidty on the pool
             pos.update(-liq, range_fee0, range_fee1);
+            let pos_liq = pos.liq;
             self.remove_pos_if_empty(pos_id);
 
+            //the oracle observes the live liq before it changes
+            self.write_observation();
             self.update_live_liq(-liq, low_tick, high_tick);
 
             self.update_ticks_liq(-liq, low_tick, high_tick);
@@ -545,15 +2942,16 @@ mod pool_blueprint {
             let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
                 liq,
                 self.sqrt_price,
-                tick_math::sqrt_price_at_tick(low_tick),
-                tick_math::sqrt_price_at_tick(high_tick),
+                tick_math::sqrt_price_at_tick(Tick(low_tick)).0,
+                tick_math::sqrt_price_at_tick(Tick(high_tick)).0,
             );
 
             debug!("### Amount0={:?}", amount0);
             debug!("### Amount1={:?}", amount1);
 
-            // give back also the fees
-            let (total_amount0, total_amount1) = (amount0 + pos_fee0, amount1 + pos_fee1);
+            // give back also the fees, rounded down to the divisibility of the resources
+            let total_amount0 = pool_math::round_down_to_divisibility(amount0 + pos_fee0, self.divisibility0);
+            let total_amount1 = pool_math::round_down_to_divisibility(amount1 + pos_fee1, self.divisibility1);
 
             debug!("### Total_amount0={:?}", total_amount0);
             debug!("### Total_amount1={:?}", total_amount1);
@@ -571,6 +2969,30 @@ mod pool_blueprint {
                 self.vault1.take_all()
             };
 
+            //the fees are collected with the liquidity removed, a zero liquidity removal only collects them
+            if liq.is_zero() {
+                Runtime::emit_event(FeesCollectedEvent {
+                    pos_id: pos_id.clone(),
+                    low_tick,
+                    high_tick,
+                    fees0: bucket0.amount(),
+                    fees1: bucket1.amount(),
+                    pos_liq,
+                });
+            } else {
+                Runtime::emit_event(LiquidityRemovedEvent {
+                    pos_id: pos_id.clone(),
+                    low_tick,
+                    high_tick,
+                    amount0: bucket0.amount() - pos_fee0.min(bucket0.amount()),
+                    amount1: bucket1.amount() - pos_fee1.min(bucket1.amount()),
+                    fees0: pos_fee0.min(bucket0.amount()),
+                    fees1: pos_fee1.min(bucket1.amount()),
+                    liq,
+                    pos_liq,
+                });
+            }
+
             self.log_state("### Internal state after removing the liquidity");
 
             debug!("### Internal liquidity removed.");
@@ -580,6 +3002,51 @@ mod pool_blueprint {
             (bucket0, bucket1)
         }
 
+        /**
+         * The bail-out path of removing a position in emergency mode: the position gets back only its principal, the amounts of its
+         * liquidity at the current price rounded down, without its fees and without updating the ticks, as no more swaps happen, so
+         * the corrupted fee accounting can't block the removal.
+         */
+        fn emergency_remove_pos(&mut self, checked_proof: CheckedProof) -> (Bucket, Bucket) {
+            debug!("### Removing position in emergency mode...");
+
+            let pos_nft: NonFungible<PositionNFTData> = checked_proof.as_non_fungible().non_fungible();
+            let pos_id = pos_nft.local_id().clone();
+            let pos = self.positions.remove(&pos_id).expect("No position exists for given position id. Op aborted.");
+            self.update_pos_nft_liq(checked_proof, -pos.liq);
+            self.update_live_liq(-pos.liq, pos.low_tick, pos.high_tick);
+
+            let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
+                pos.liq,
+                self.sqrt_price,
+                tick_math::sqrt_price_at_tick(Tick(pos.low_tick)).0,
+                tick_math::sqrt_price_at_tick(Tick(pos.high_tick)).0,
+            );
+            let amount0 = amount0
+                .checked_round(EMERGENCY_AMOUNT_DECIMALS.min(self.divisibility0 as i32), RoundingMode::ToZero)
+                .unwrap();
+            let amount1 = amount1
+                .checked_round(EMERGENCY_AMOUNT_DECIMALS.min(self.divisibility1 as i32), RoundingMode::ToZero)
+                .unwrap();
+            let bucket0 = self.vault0.take(if amount0 < self.vault0.amount() { amount0 } else { self.vault0.amount() });
+            let bucket1 = self.vault1.take(if amount1 < self.vault1.amount() { amount1 } else { self.vault1.amount() });
+            Runtime::emit_event(LiquidityRemovedEvent {
+                pos_id,
+                low_tick: pos.low_tick,
+                high_tick: pos.high_tick,
+                amount0: bucket0.amount(),
+                amount1: bucket1.amount(),
+                fees0: Decimal::zero(),
+                fees1: Decimal::zero(),
+                liq: pos.liq,
+                pos_liq: Decimal::zero(),
+            });
+
+            debug!("### Position removed in emergency mode.");
+
+            (bucket0, bucket1)
+        }
+
         /**
          * Updates the liquidity on the position NFT coming with the proof.
          */
@@ -592,7 +3059,7 @@ mod pool_blueprint {
                 "Position NFT liq should be positive, op aborted."
             );
 
-            self.pos_nft_minter_badge
+            self.pos_nft_minter_badge()
                 .as_fungible()
                 .authorize_with_amount(1, || {
                     self.pos_nft_addr_resource_manager.update_non_fungible_data(
@@ -670,9 +3137,17 @@ mod pool_blueprint {
         }
 
         /**
-         * Implements the swap algorithm of the pool
+         * Implements the swap algorithm of the pool.
+         *
+         * Returns the output, the remainder, the referral part of the fee and the total fee paid, in the input resource
          */
-        fn swap_internal(&mut self, mut bucket: Bucket, is_token0: bool) -> (Bucket, Bucket) {
+        fn swap_internal(
+            &mut self,
+            mut bucket: Bucket,
+            is_token0: bool,
+            fee: Decimal,
+            referral_fee_share: Decimal,
+        ) -> (Bucket, Bucket, Bucket, Decimal) {
             let initial_bucket_amount = bucket.amount();
 
             debug!(
@@ -688,6 +3163,9 @@ mod pool_blueprint {
             //compute the amount to give back following the swap
             let mut total_swapped_amount = Decimal::zero();
             let mut total_fee_amount = Decimal::zero();
+            let mut total_protocol_fee_amount = Decimal::zero();
+            let mut total_referral_fee_amount = Decimal::zero();
+            let mut ticks_crossed = 0;
             while available_amount > Decimal::zero() && self.live_liq > Decimal::zero() {
                 self.log_state("### Internal state before swap step");
 
@@ -695,9 +3173,17 @@ mod pool_blueprint {
                 debug!("### Total_swapped_amount={:?}", total_swapped_amount);
                 debug!("### Total_fee_amount={:?}", total_fee_amount);
 
+                // the swap of a stable pool stops once it crossed the maximum number of ticks, the remaining amount being given back
+                if self.max_ticks_crossed == Some(ticks_crossed) {
+                    debug!("### Maximum number of ticks crossed {:?}", ticks_crossed);
+                    break;
+                }
+
                 // get next/previous used tick, to see if we have enough available amount to move the price to it.
+                // The price lies in [sqrt_price_at_tick(self.tick), sqrt_price_at_tick(self.tick + 1)), so moving down the current tick
+                // itself is the first one to cross.
                 let opt_tick_to_cross = if is_token0 {
-                    let prev_tick = btree_set_ext::previous_elem(&self.used_ticks, self.tick);
+                    let prev_tick = btree_set_ext::previous_or_equal_elem(&self.used_ticks, self.tick);
                     debug!("### Swapping towards the previous tick {:?}", prev_tick);
                     prev_tick
                 } else {
@@ -706,97 +3192,62 @@ mod pool_blueprint {
                     next_tick
                 };
 
-                if let Some(tick_to_cross) = opt_tick_to_cross {
-                    let sqrt_price_at_tick_to_cross = tick_math::sqrt_price_at_tick(*tick_to_cross);
+                if let Some(tick_to_cross) = opt_tick_to_cross.copied() {
+                    let sqrt_price_at_tick_to_cross = tick_math::sqrt_price_at_tick(Tick(tick_to_cross)).0;
 
                     debug!(
                         "### Sqrt_price_at_tick_to_cross={:?}",
                         sqrt_price_at_tick_to_cross
                     );
 
-                    // compute the amount needed to cross tick
-                    let needed_amount_to_cross_tick = if is_token0 {
-                        pool_math::compute_range_amount0_given_liq(
-                            self.live_liq,
-                            sqrt_price_at_tick_to_cross,
-                            self.sqrt_price,
-                        )
-                    } else {
-                        pool_math::compute_range_amount1_given_liq(
-                            self.live_liq,
-                            self.sqrt_price,
-                            sqrt_price_at_tick_to_cross,
-                        )
-                    };
-                    debug!(
-                        "### Needed_amount_to_cross_tick={:?}",
-                        needed_amount_to_cross_tick
-                    );
-
-                    let is_tick_cross_needed = needed_amount_to_cross_tick < available_amount;
-                    debug!("### Is_tick_cross_needed={:?}", is_tick_cross_needed);
-
-                    // we swap just the amount corresponding to the current tick, or all available if we don't need to cross the tick
-                    let mut amount_to_swap = if is_tick_cross_needed {
-                        needed_amount_to_cross_tick
-                    } else {
-                        available_amount
-                    };
-                    debug!(
-                        "### Before substracting fee, amount_to_swap={:?}",
-                        amount_to_swap
-                    );
-
-                    // compute fee
-                    let fee_amount = amount_to_swap * self.fee;
-                    debug!("### Fee_amount={:?}", fee_amount);
-
-                    // don't swap the fees
-                    amount_to_swap -= fee_amount;
-                    debug!(
-                        "### After substracting fee, amount_to_swap={:?}",
-                        amount_to_swap
+                    // move the price towards the tick to cross, crossing it if the available amount is enough
+                    let step = swap_math::compute_swap_step(
+                        self.live_liq,
+                        self.sqrt_price,
+                        sqrt_price_at_tick_to_cross,
+                        available_amount,
+                        fee,
                     );
+                    debug!("### Swap_step={:?}", step);
 
-                    // compute the new sqrt price and the amount we get by swapping the provided amount
-                    let (new_sqrt_price, swapped_amount) = if is_token0 {
-                        pool_math::compute_swap_amount0_price_and_amount1(
-                            self.live_liq,
-                            self.sqrt_price,
-                            amount_to_swap,
-                        )
-                    } else {
-                        pool_math::compute_swap_amount1_price_and_amount0(
-                            self.live_liq,
-                            self.sqrt_price,
-                            amount_to_swap,
-                        )
-                    };
-                    debug!("### New_sqrt_price={:?}", new_sqrt_price);
-                    debug!("### Swapped_amount={:?}", swapped_amount);
+                    let is_tick_cross_needed = step.reached_target;
+                    let fee_amount = step.fee_amount;
 
                     // update the global values
-                    available_amount = available_amount - amount_to_swap - fee_amount;
-                    total_swapped_amount += swapped_amount;
+                    available_amount = available_amount - step.amount_in - fee_amount;
+                    total_swapped_amount += step.amount_out;
                     total_fee_amount += fee_amount;
-                    self.sqrt_price = new_sqrt_price;
+                    let previous_sqrt_price = self.sqrt_price;
+                    self.sqrt_price = step.sqrt_price_next;
 
-                    // update global fees
-                    let liq_unit_fee = fee_amount / self.live_liq;
+                    // update global fees, without the protocol and the referral parts of the fee
+                    let protocol_fee_amount = if self.protocol_fee_enabled {
+                        fee_amount * self.protocol_fee_share
+                    } else {
+                        Decimal::zero()
+                    };
+                    let referral_fee_amount = fee_amount * referral_fee_share;
+                    total_protocol_fee_amount += protocol_fee_amount;
+                    total_referral_fee_amount += referral_fee_amount;
+                    let liq_unit_fee = (fee_amount - protocol_fee_amount - referral_fee_amount) / self.live_liq;
                     if is_token0 {
                         self.fee_global0 += liq_unit_fee;
                     } else {
                         self.fee_global1 += liq_unit_fee;
                     }
 
-                    // cross tick if needed
+                    // cross tick if needed, the price lands exactly on the crossed tick
                     if is_tick_cross_needed {
-                        self.cross_tick(*tick_to_cross);
-                    } else {
-                        //self.tick = tick_math::tick_at_sqrt_price(new_sqrt_price);
+                        ticks_crossed += 1;
+                        self.cross_tick(tick_to_cross);
+                    } else if self.sqrt_price != previous_sqrt_price {
+                        self.tick = Self::tick_below_or_at(self.sqrt_price, is_token0, tick_to_cross);
                     }
 
                     self.log_state("### Internal state after swap step");
+                } else {
+                    // no more liquidity in the swap direction, the remaining amount is given back
+                    break;
                 }
             }
 
@@ -804,19 +3255,34 @@ mod pool_blueprint {
             debug!("### Total_swapped_amount={:?}", total_swapped_amount);
             debug!("### Total_fee_amount={:?}", total_fee_amount);
 
-            // compute the amount that will be kept by the pool
-            let to_deduct_amount = initial_bucket_amount - available_amount;
+            // compute the amount that will be kept by the pool, the input being rounded up and the output, the protocol and the
+            // referral fees being rounded down to the divisibility of the resources, so the dust stays in the pool
+            let (divisibility_in, divisibility_out) = if is_token0 {
+                (self.divisibility0, self.divisibility1)
+            } else {
+                (self.divisibility1, self.divisibility0)
+            };
+            let to_deduct_amount = pool_math::round_up_to_divisibility(initial_bucket_amount - available_amount, divisibility_in);
+            let total_swapped_amount = pool_math::round_down_to_divisibility(total_swapped_amount, divisibility_out);
+            let total_protocol_fee_amount = pool_math::round_down_to_divisibility(total_protocol_fee_amount, divisibility_in);
+            let total_referral_fee_amount = pool_math::round_down_to_divisibility(total_referral_fee_amount, divisibility_in);
             debug!("### To_deduct_amount={:?}", to_deduct_amount);
 
             // update the pool fees and return the tokens
+            let referral_fee = bucket.take(total_referral_fee_amount);
+            let kept_amount = to_deduct_amount - total_protocol_fee_amount - total_referral_fee_amount;
             let swapped_bucket = if is_token0 {
-                self.vault0.put(bucket.take(to_deduct_amount));
+                self.protocol_fees0.put(bucket.take(total_protocol_fee_amount));
+                self.vault0.put(bucket.take(kept_amount));
                 self.vault1.take(total_swapped_amount)
             } else {
-                self.vault1.put(bucket.take(to_deduct_amount));
+                self.protocol_fees1.put(bucket.take(total_protocol_fee_amount));
+                self.vault1.put(bucket.take(kept_amount));
                 self.vault0.take(total_swapped_amount)
             };
 
+            self.record_epoch_stats(is_token0, to_deduct_amount, total_fee_amount);
+
             debug!("### Swapped_bucket={:?}", swapped_bucket.amount());
             debug!("### Remainder_bucket={:?}", bucket.amount());
 
@@ -827,26 +3293,52 @@ mod pool_blueprint {
                 bucket.resource_address()
             );
 
-            (swapped_bucket, bucket)
+            (swapped_bucket, bucket, referral_fee, total_fee_amount)
+        }
+
+        /**
+         * The price of token0 vs token1 a swap of amount_in for amount_out was executed at, the fee included, or zero if nothing was
+         * swapped
+         */
+        fn effective_price(is_token0: bool, amount_in: Decimal, amount_out: Decimal) -> Decimal {
+            if amount_in.is_zero() || amount_out.is_zero() {
+                Decimal::zero()
+            } else if is_token0 {
+                amount_out / amount_in
+            } else {
+                amount_in / amount_out
+            }
+        }
+
+        /**
+         * Computes the tick of a price reached by a swap step that didn't cross tick_to_cross: the greatest tick whose price is lower
+         * than or equal to sqrt_price, kept on the same side of tick_to_cross so that the used ticks already crossed stay consistent
+         * with the live liquidity, even if the price computation rounded.
+         */
+        fn tick_below_or_at(sqrt_price: Decimal, is_token0: bool, tick_to_cross: i32) -> i32 {
+            let mut tick = tick_math::tick_at_sqrt_price(SqrtPrice(sqrt_price)).0;
+            if tick_math::sqrt_price_at_tick(Tick(tick)).0 > sqrt_price {
+                tick -= 1;
+            }
+            if is_token0 {
+                tick.max(tick_to_cross)
+            } else {
+                tick.min(tick_to_cross - 1)
+            }
         }
 
         /**
-         * Updates the pool tick, live liquidty and tick states as we cross the provided tick.
+         * Updates the pool tick, live liquidty and tick state as we cross the provided tick.
+         *
+         * Crossing up the tick T the price enters [T, next tick), so the current tick becomes T. Crossing down the tick T the price
+         * leaves [T, next tick), so the current tick becomes T - 1.
          */
         fn cross_tick(&mut self, cross_to_tick: i32) {
             let cross_up = self.tick < cross_to_tick;
             debug!("### Cross_up to tick {:?}? {:?}", cross_to_tick, cross_up);
 
-            //update the current tick fees
-            self.tick_states
-                .entry(self.tick)
-                .and_modify(|state| state.cross_tick(self.fee_global0, self.fee_global1));
-
-            //update tick
-            self.tick = cross_to_tick;
-
-            // update the new current tick fees and the pool live liq
-            self.tick_states.entry(self.tick).and_modify(|state| {
+            // update the crossed tick fees and the pool live liq
+            self.tick_states.entry(cross_to_tick).and_modify(|state| {
                 self.live_liq = if cross_up {
                     self.live_liq + state.liq_net
                 } else {
@@ -854,6 +3346,71 @@ mod pool_blueprint {
                 };
                 state.cross_tick(self.fee_global0, self.fee_global1)
             });
+
+            //update tick
+            self.tick = if cross_up { cross_to_tick } else { cross_to_tick - 1 };
+
+            let liquidity_net = self.tick_states.get(&cross_to_tick).map_or(Decimal::zero(), |state| state.liq_net);
+            Runtime::emit_event(TickCrossedEvent {
+                tick: cross_to_tick,
+                liquidity_net,
+                direction: if cross_up { CrossDirection::Up } else { CrossDirection::Down },
+            });
+        }
+
+        /**
+         * Adds the swapped amount and the fee to the statistics of the current epoch. The statistics are kept in a ring buffer
+         * indexed by epoch, so the statistics of an epoch overwrite the ones of the epoch EPOCH_STATS_CAPACITY epochs before it.
+         */
+        fn record_epoch_stats(&mut self, is_token0: bool, amount: Decimal, fee_amount: Decimal) {
+            let epoch = Runtime::current_epoch().number();
+            let slot = epoch % EPOCH_STATS_CAPACITY;
+
+            let mut stats = match self.epoch_stats.get(&slot) {
+                Some(stats) if stats.epoch == epoch => stats.clone(),
+                _ => EpochStats::new(epoch),
+            };
+            if is_token0 {
+                stats.volume0 += amount;
+                stats.fees0 += fee_amount;
+            } else {
+                stats.volume1 += amount;
+                stats.fees1 += fee_amount;
+            }
+            stats.sqrt_price = SqrtPrice(self.sqrt_price);
+            stats.swaps += 1;
+
+            debug!("### Epoch stats={:?}", stats);
+            self.epoch_stats.insert(slot, stats);
+        }
+
+        /**
+         * Records an oracle observation with the current tick and live liquidity, before they change, at most one per timestamp. The observations are kept in a ring buffer,
+         * a new one overwriting the oldest one when the buffer is full, unless the buffer can grow.
+         */
+        fn write_observation(&mut self) {
+            let timestamp = Pool::current_timestamp();
+            let latest = *self.observations.get(&self.observation_index).unwrap();
+            if latest.timestamp == timestamp {
+                return;
+            }
+
+            //the buffer grows when the latest slot is the last one, so the observations stay in chronological order
+            if self.observation_cardinality_next > self.observation_cardinality
+                && self.observation_index == self.observation_cardinality - 1
+            {
+                self.observation_cardinality = self.observation_cardinality_next;
+            }
+            self.observation_index = (self.observation_index + 1) % self.observation_cardinality;
+            self.observation_count = (self.observation_count + 1).min(self.observation_cardinality);
+            self.observations.insert(self.observation_index, latest.advance(timestamp, self.tick, self.live_liq));
+        }
+
+        /**
+         * The current time in seconds since the unix epoch, rounded down to minutes
+         */
+        fn current_timestamp() -> i64 {
+            Clock::current_time_rounded_to_minutes().seconds_since_unix_epoch
         }
 
         fn log_state(&self, ctx_msg: &str) {
@@ -881,7 +3438,7 @@ mod pool_blueprint {
  * - the fees generated when the price was outside this tick (bellow), this is needed to compute the fees generated by each range and
  * then each position.
  */
-#[derive(ScryptoSbor, Clone, Debug)]
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
 pub struct TickState {
     pub tick: i32,
     pub liq_net: Decimal,
@@ -940,13 +3497,13 @@ impl TickState {
 /**
  * Keeps the liquidity associated with each position and the fees that were already collected
  */
-#[derive(ScryptoSbor, Debug)]
-struct Position {
-    liq: Decimal,
-    low_tick: i32,
-    high_tick: i32,
-    range_fee0: Decimal,
-    range_fee1: Decimal,
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub liq: Decimal,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub range_fee0: Decimal,
+    pub range_fee1: Decimal,
 }
 
 impl Position {
@@ -973,6 +3530,631 @@ impl Position {
     }
 }
 
+/**
+ * A snapshot of the pool state, where:
+ * - amount0,1 = the balances of the pool vaults, fees included
+ * - current_tick = the tick whose range [current_tick, current_tick + 1) contains the price
+ * - active_liquidity = the liquidity of the positions whose range contains the current tick
+ * - fee_growth_global0,1 = the fees accumulated by the pool per unit of liquidity
+ * - protocol_fees = the protocol fees (amount0, amount1) accumulated and not collected yet by the treasury
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    pub resource0_addr: ResourceAddress,
+    pub resource1_addr: ResourceAddress,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub sqrt_price: SqrtPrice,
+    pub current_tick: Tick,
+    pub active_liquidity: Decimal,
+    pub fee: Decimal,
+    pub tick_spacing: i32,
+    pub fee_growth_global0: Decimal,
+    pub fee_growth_global1: Decimal,
+    pub position_nft_addr: ResourceAddress,
+    pub protocol_fees: (Decimal, Decimal),
+}
+
+/**
+ * The semantic version of the package of a pool and the features supported by the pool, see Pool::get_version
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub features: Vec<String>,
+}
+
+impl PoolVersion {
+    /**
+     * Whether the pool supports the given feature, e.g. "quote_swap"
+     */
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|supported| supported == feature)
+    }
+
+    /**
+     * Whether the version is at least major.minor.patch
+     */
+    pub fn is_at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+}
+
+/**
+ * The context of a swap passed to the before_swap method of the pool hooks: the swapped resource and amount, the sqrt price before
+ * the swap and the fee of the swap. The after_swap method of the hooks receives the SwapEvent of the swap.
+ *
+ * A hook component implements:
+ * - before_swap(&mut self, context: SwapContext), e.g. for compliance checks, panicking to abort the swap
+ * - after_swap(&mut self, event: SwapEvent), e.g. for swap statistics or rewards
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct SwapContext {
+    pub resource_in_addr: ResourceAddress,
+    pub amount: Decimal,
+    pub sqrt_price: SqrtPrice,
+    pub fee: Decimal,
+}
+
+/**
+ * A change of the pool parameters by the admin, scheduled with Pool::schedule_change:
+ * - RegisterHook, UnregisterHook = registers or unregisters a hook component called on each swap, see SwapContext. At most MAX_HOOKS
+ *   hooks can be registered, called in their registration order.
+ * - SetFeeManager = points the pool to a fee manager component computing the fee of each swap, e.g. from the price volatility or the
+ *   inventory imbalance, see FeeManager. With no fee manager the pool charges its static fee. A fee manager component implements
+ *   get_swap_fee(&self, context: SwapContext) -> Decimal, the context fee being the static fee of the pool.
+ * - SetTimelockDelay = sets the seconds the next parameter changes wait before they can be executed
+ * - SetFee = sets the static fee of the pool, at most MAX_FEE_BPS. The pool stays listed by a factory under its creation fee tier,
+ *   while its fee_tier metadata, and its name and description if they are the defaults, follow the new fee.
+ * - RampFee = moves the static fee of the pool linearly from its current value to the target fee, at most MAX_FEE_BPS, across the
+ *   given number of epochs, so the fee change doesn't create a single switchover with arbitrage opportunities, see FeeRamp. A SetFee
+ *   or a new ramp replaces the ongoing ramp. The fee metadata of the pool keeps the fee before the ramp while it's ongoing, see
+ *   Pool::get_fee_ramp, and follows the target fee once the ramp completes, on the first swap after its end epoch.
+ * - SetProtocolFeeShare = sets the share of the swap fees going to the protocol, at most MAX_PROTOCOL_FEE_SHARE_BPS, charged only
+ *   while the protocol fee is switched on, see Pool::set_protocol_fee_switch
+ * - SetReferralRegistry = sets the ReferralRegistry validating and paying the referrers of Pool::swap_with_referrer, none disabling
+ *   the referred swaps
+ * - SetReferralFeeShare = sets the share of the swap fees paid to the referrers, at most MAX_REFERRAL_FEE_SHARE_BPS
+ * - SetVolumeTiers = sets the fee discounts of the traders by trailing volume, see Pool::swap_as_trader and VolumeTier. At most
+ *   MAX_VOLUME_TIERS tiers, in ascending order of their minimum volume and fee discount, no tiers disabling the discounts.
+ * - SetMaxSingleSwapPriceImpact = sets the maximum relative change of the pool price a single swap can make, e.g. 0.02 for 2%, the
+ *   larger swaps being aborted, none for no limit. It protects the thin pools used as price references by other protocols.
+ * - SetMaxSwapInputShare = sets the maximum input of a single swap as a share of the reserve of the input resource held by the
+ *   active liquidity at the current price, e.g. 0.05 for 5%, the larger swaps being aborted, none for no limit. It makes the whales
+ *   split their orders over time in the small or launch pools, where a single swap could cross the whole liquidity band.
+ * - SetSwapRoyalty = sets the royalty paid in XRD by each swap, referred or not, going to the protocol without touching the LP fees,
+ *   zero for no royalty. There is no royalty when the pool is created, the other methods being free, see Pool::claim_royalties.
+ * - WidenPriceBand = widens the price band [low_tick, high_tick] bounding the position ranges of a pool created with one, see
+ *   PoolConfig, e.g. to open up a pool launched conservatively. The band can only include the current one, so it never narrows
+ *   below the existing positions, and each widening emits a PriceBandWidenedEvent.
+ * - ExportState = exports the state of the pool to the target pool, another Pool of the same package and pair, see Pool::import_state.
+ *   The pool gives away all its liquidity: the vaults amounts and the badge that mints and updates the position NFTs, so the LPs keep
+ *   their NFTs in the target pool. Afterwards the pool has no positions and the swaps give back the whole amount.
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub enum ParameterChange {
+    RegisterHook(ComponentAddress),
+    UnregisterHook(ComponentAddress),
+    SetFeeManager(Option<ComponentAddress>),
+    SetTimelockDelay(i64),
+    SetFee(Decimal),
+    RampFee(Decimal, u64),
+    SetProtocolFeeShare(Decimal),
+    SetReferralRegistry(Option<ComponentAddress>),
+    SetReferralFeeShare(Decimal),
+    SetVolumeTiers(Vec<VolumeTier>),
+    SetMaxSingleSwapPriceImpact(Option<Decimal>),
+    SetMaxSwapInputShare(Option<Decimal>),
+    SetSwapRoyalty(Decimal),
+    WidenPriceBand(i32, i32),
+    ExportState(ComponentAddress),
+}
+
+/**
+ * The effects of a parameter change if it was executed now, see Pool::preview_parameter_change, where:
+ * - execution_time = the earliest execution time of the change if it was scheduled now, with the current timelock delay
+ * - tick_spacing = the tick spacing of the pool, fixed at its creation, so the positions and their ranges aren't affected by any change
+ * - fee_config = the fee parameters after the change, the fee being the static fee at the current epoch. The fees accrued by the
+ *   positions are checkpointed in the fee growth on each swap, so a fee change applies to the next swaps only.
+ * - replaced_fee_ramp = the ongoing fee ramp dropped by a SetFee or replaced by a new RampFee, if any
+ * - hooks, timelock_delay, referral_registry, max_single_swap_price_impact, max_swap_input_share = the hooks and the guards of the
+ *   pool after the change
+ * - swap_royalty = the royalty paid in XRD by each swap after the change
+ * - price_band = the price band bounding the position ranges after the change, none for an unbounded pool
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ParameterChangePreview {
+    pub change: ParameterChange,
+    pub execution_time: i64,
+    pub tick_spacing: i32,
+    pub fee_config: FeeConfig,
+    pub replaced_fee_ramp: Option<FeeRamp>,
+    pub hooks: Vec<ComponentAddress>,
+    pub timelock_delay: i64,
+    pub referral_registry: Option<ComponentAddress>,
+    pub max_single_swap_price_impact: Option<Decimal>,
+    pub max_swap_input_share: Option<Decimal>,
+    pub swap_royalty: Decimal,
+    pub price_band: Option<(i32, i32)>,
+}
+
+/**
+ * The metadata displaying a pool in the wallets and the explorers, see PoolConfig and Pool::update_metadata, the fields
+ * not given getting their defaults at creation, or being left unchanged on update:
+ * - name, description, tags = by default a name and a description from the resource symbols and the fee, and the dex tags
+ * - icon_url, info_url = the pool icon and info page, none by default
+ * - dapp_definition_addr = the dApp definition account of the protocol, which should link back to the pool, none by default
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub icon_url: Option<String>,
+    pub info_url: Option<String>,
+    pub dapp_definition_addr: Option<ComponentAddress>,
+}
+
+/**
+ * The anti-snipe buy cap of a launch pool, see PoolConfig: during its first epochs the pool sells resource0 only to the holders of a
+ * trader badge of the given resource, each up to max_output
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct BuyCap {
+    pub trader_badge_addr: ResourceAddress,
+    pub max_output: Decimal,
+    pub epochs: u64,
+}
+
+/**
+ * The initial positions of a pool for the fair launch of a project token, resource0, seeded with the project tokens only, see
+ * PoolConfig: the tokens are split evenly in a ladder of steps single sided positions, on consecutive ranges of equal width from
+ * low_tick to high_tick. The pool price starts at low_tick, so the buyers swapping resource1 in move the price up the ladder,
+ * discovering it. The ladder width must be a multiple of the steps times the tick spacing.
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct LaunchLadder {
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub steps: u32,
+}
+
+impl LaunchLadder {
+    /**
+     * Validates the ladder of a pool with the given starting sqrt price and tick spacing
+     */
+    pub fn validate(&self, sqrt_price: SqrtPrice, tick_spacing: i32) {
+        assert!(self.steps > 0, "Invalid launch steps, should be positive.");
+        assert!(self.high_tick > self.low_tick, "Invalid launch range, should be low_tick < high_tick.");
+        let step_width = (self.high_tick - self.low_tick) / self.steps as i32;
+        assert!(
+            step_width > 0 && step_width * self.steps as i32 == self.high_tick - self.low_tick && step_width % tick_spacing == 0,
+            "Invalid launch range, its width should be a multiple of the steps times the tick spacing."
+        );
+        assert!(
+            sqrt_price == tick_math::sqrt_price_at_tick(Tick(self.low_tick)),
+            "Invalid sqrt price, a launch pool should start at the low tick of its ladder."
+        );
+    }
+}
+
+/**
+ * The configuration of a new pool, see Pool::new, where:
+ * - fee = the static pool fee, a percentage of the swapped amount, 0 <= fee <= 1
+ * - sqrt_price = the square root of the price of token0 vs token1 when the pool is created
+ * - tick_spacing = the positions can use only the ticks that are multiples of it. A larger spacing makes the swaps cheaper, as fewer
+ *   ticks are crossed, at the cost of a coarser choice of the position ranges. 1 by default, all the ticks being usable.
+ * - admin_badge_addr, admin_rule = the admin of the pool, either a badge or an access rule, so multisig setups work natively, e.g.
+ *   rule!(require_n_of(2, vec![badge_a, badge_b, badge_c])) for 2 of 3 badges. A pool administered by a rule has no admin badge, see
+ *   Pool::get_admin_badge_addr, until the admin hands it over to a badge with propose_admin.
+ * - initial_position = the range [low_sqrt_price, high_sqrt_price) of the initial position, none by default, the first LP adding the
+ *   first position with Pool::add_pos, on any range. Until then the pool has no liquidity and the swaps give back their whole input.
+ *   The full tick range, from MIN_TICK to MAX_TICK, is in range at any price, like the liquidity of a constant product pool.
+ * - launch_ladder = the initial positions of a launch pool instead of the initial position, see LaunchLadder, none by default
+ * - price_band = the range [low_tick, high_tick] all the positions must be inside of, none by default, the positions using any
+ *   tick. As the price moves only through the liquidity of the positions, the band bounds the pool price too. A pool launched
+ *   conservatively can open up later, see ParameterChange::WidenPriceBand.
+ * - member_badge_addr = the badge of a permissioned pool, none by default: only its holders can swap and add positions, presenting a
+ *   proof of it in their auth zone, e.g. a KYC badge of an institution running a gated pool, and collect their fees as a single
+ *   resource, see Pool::collect_fees_as. The other operations of the existing positions, such as removing them or collecting their
+ *   fees, stay open to their owners. It can't be changed later.
+ * - lp_badge_addr = the badge of an LP whitelist, none by default: only its holders can add positions, presenting a proof of it in
+ *   their auth zone, while the swaps stay open to anyone. The admin manages it, see Pool::set_lp_badge_addr.
+ * - min_liquidity = the active liquidity, at the pool price, the swaps wait for, none by default, so the first trades don't execute
+ *   against a near empty pool at absurd prices. Once the liquidity exceeds it, the first swap opens the trading for good.
+ * - buy_cap = the anti-snipe buy cap of a launch pool, see BuyCap, none by default. It's disabled automatically after its epochs.
+ * - stable = whether the pool is tuned for the stable pairs, e.g. USDT/xUSDC, false by default: the fee is at most
+ *   MAX_STABLE_FEE_BPS, the tick spacing is 1, the swaps cross up to STABLE_MAX_TICKS_CROSSED ticks, while the other pools have no
+ *   such limit, as the liquidity is spread on many narrow ranges around the peg, and the single swap price impact is capped at
+ *   STABLE_MAX_PRICE_IMPACT_BPS, a tighter band than the volatile pairs need. The cap is a timelocked parameter change afterwards,
+ *   like for the other pools.
+ * - metadata = the metadata displaying the pool in the wallets and the explorers, e.g. the dApp definition account of the protocol
+ *   and an icon, the fields not given getting their defaults, see PoolMetadata. The pools are always created with their pair symbols
+ *   and fee tier metadata.
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub fee: Decimal,
+    pub sqrt_price: SqrtPrice,
+    pub tick_spacing: i32,
+    pub admin_badge_addr: Option<ResourceAddress>,
+    pub admin_rule: Option<AccessRule>,
+    pub initial_position: Option<(SqrtPrice, SqrtPrice)>,
+    pub launch_ladder: Option<LaunchLadder>,
+    pub price_band: Option<(i32, i32)>,
+    pub member_badge_addr: Option<ResourceAddress>,
+    pub lp_badge_addr: Option<ResourceAddress>,
+    pub min_liquidity: Option<Decimal>,
+    pub buy_cap: Option<BuyCap>,
+    pub stable: bool,
+    pub metadata: PoolMetadata,
+}
+
+impl PoolConfig {
+    /**
+     * The configuration of a plain pool with the given fee, starting sqrt price and admin badge, the other fields getting their
+     * defaults, e.g. PoolConfig { initial_position: Some((low_sqrt_price, high_sqrt_price)), ..PoolConfig::new(..) }
+     */
+    pub fn new(fee: Decimal, sqrt_price: SqrtPrice, admin_badge_addr: ResourceAddress) -> Self {
+        Self {
+            fee,
+            sqrt_price,
+            tick_spacing: 1,
+            admin_badge_addr: Some(admin_badge_addr),
+            admin_rule: None,
+            initial_position: None,
+            launch_ladder: None,
+            price_band: None,
+            member_badge_addr: None,
+            lp_badge_addr: None,
+            min_liquidity: None,
+            buy_cap: None,
+            stable: false,
+            metadata: PoolMetadata::default(),
+        }
+    }
+}
+
+/**
+ * A linear ramp of the static pool fee, from start_fee at start_epoch to target_fee at end_epoch, see ParameterChange::RampFee
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct FeeRamp {
+    pub start_fee: Decimal,
+    pub target_fee: Decimal,
+    pub start_epoch: u64,
+    pub end_epoch: u64,
+}
+
+impl FeeRamp {
+    /**
+     * Returns the fee of the ramp at the given epoch, the target fee once the ramp ended
+     */
+    pub fn fee_at(&self, epoch: u64) -> Decimal {
+        if epoch >= self.end_epoch {
+            return self.target_fee;
+        }
+        let elapsed = Decimal::from(epoch.saturating_sub(self.start_epoch));
+        let duration = Decimal::from(self.end_epoch - self.start_epoch);
+        self.start_fee + (self.target_fee - self.start_fee) * elapsed / duration
+    }
+}
+
+/**
+ * The fee parameters of a pool, see Pool::get_fee_config, where:
+ * - fee = the static pool fee at the current epoch, moved along the fee_ramp if any
+ * - fee_manager = the component returning the dynamic fee of each swap instead of the static fee, if any
+ * - protocol_fee_share = the share of the swap fees going to the protocol while protocol_fee_enabled, the rest going to the LPs
+ * - referral_fee_share = the share of the swap fees paid to the referrer of Pool::swap_with_referrer
+ * - volume_tiers = the fee discounts of the traders swapping with Pool::swap_as_trader
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct FeeConfig {
+    pub fee: Decimal,
+    pub fee_ramp: Option<FeeRamp>,
+    pub fee_manager: Option<ComponentAddress>,
+    pub protocol_fee_enabled: bool,
+    pub protocol_fee_share: Decimal,
+    pub referral_fee_share: Decimal,
+    pub volume_tiers: Vec<VolumeTier>,
+}
+
+/**
+ * A fee discount tier of the traders swapping with Pool::swap_as_trader: the traders whose trailing volume, measured in resource1,
+ * is at least min_volume pay the pool fee reduced by fee_discount, e.g. 0.25 for a 25% lower fee
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct VolumeTier {
+    pub min_volume: Decimal,
+    pub fee_discount: Decimal,
+}
+
+/**
+ * The pool operations that can be paused, see Pool::set_paused: Swap, Deposit (add_pos and add_liq) and Compound
+ * (add_accumulated_fees_to_liq)
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PausableOperation {
+    Swap,
+    Deposit,
+    Compound,
+}
+
+/**
+ * The typed errors the pool methods fail with, the message of the failure starting with the error variant, e.g. "Paused(Swap)"
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolError {
+    Paused(PausableOperation),
+    EmergencyMode,
+    Deprecated,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Paused(operation) => write!(f, "{:?}: the {:?} operation is paused. Op aborted.", self, operation),
+            PoolError::EmergencyMode => {
+                write!(f, "{:?}: the pool only allows the position removals, without their fees. Op aborted.", self)
+            }
+            PoolError::Deprecated => write!(
+                f,
+                "{:?}: the pool only allows the position removals and the fee collections. Op aborted.",
+                self
+            ),
+        }
+    }
+}
+
+/**
+ * A parameter change scheduled to be executed from the given time on
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledChange {
+    pub change: ParameterChange,
+    pub execution_time: i64,
+}
+
+/**
+ * Emitted by Pool::schedule_change
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct ParameterChangeScheduledEvent {
+    pub change_id: u64,
+    pub change: ParameterChange,
+    pub execution_time: i64,
+}
+
+/**
+ * Emitted by Pool::set_protocol_fee_switch, with the protocol fee share in force
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolFeeSwitchEvent {
+    pub enabled: bool,
+    pub protocol_fee_share: Decimal,
+}
+
+/**
+ * Emitted when a ParameterChange::WidenPriceBand executes, with the new price band
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct PriceBandWidenedEvent {
+    pub low_tick: i32,
+    pub high_tick: i32,
+}
+
+/**
+ * Emitted by Pool::swap: the swapped resource and amount, the remainder excluded, the output resource and amount, the sqrt price
+ * after the swap, the effective price of token0 vs token1 the swap was executed at, the fee included, and the fee paid, in the
+ * swapped resource
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SwapEvent {
+    pub resource_in_addr: ResourceAddress,
+    pub amount_in: Decimal,
+    pub resource_out_addr: ResourceAddress,
+    pub amount_out: Decimal,
+    pub sqrt_price: SqrtPrice,
+    pub effective_price: Decimal,
+    pub fee_amount: Decimal,
+}
+
+/**
+ * The direction a swap crosses a tick in: Up, the price increasing, or Down
+ */
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossDirection {
+    Up,
+    Down,
+}
+
+/**
+ * Emitted by the swaps for each tick crossed, in the crossing order: the tick, its net liquidity, added to the live liquidity when
+ * crossed up and subtracted when crossed down, and the direction
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct TickCrossedEvent {
+    pub tick: i32,
+    pub liquidity_net: Decimal,
+    pub direction: CrossDirection,
+}
+
+/**
+ * Emitted by Pool::add_pos: the NFT id and the range of the new position, the amounts deposited and its liquidity
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct PositionAddedEvent {
+    pub pos_id: NonFungibleLocalId,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub liq: Decimal,
+}
+
+/**
+ * Emitted by Pool::add_liq and Pool::add_accumulated_fees_to_liq: the NFT id and the range of the position, the amounts deposited,
+ * the position fees added to the liquidity, the liquidity added and the resulting position liquidity
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct LiquidityAddedEvent {
+    pub pos_id: NonFungibleLocalId,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub liq: Decimal,
+    pub pos_liq: Decimal,
+}
+
+/**
+ * Emitted by Pool::remove_pos and by the methods moving the liquidity out of a position, e.g. Pool::migrate_position: the NFT id and
+ * the range of the position, the amounts of the liquidity removed, the position fees paid with them, the liquidity removed and the
+ * resulting position liquidity
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct LiquidityRemovedEvent {
+    pub pos_id: NonFungibleLocalId,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub liq: Decimal,
+    pub pos_liq: Decimal,
+}
+
+/**
+ * Emitted by Pool::collect_fees and Pool::collect_fees_as: the NFT id and the range of the position, the fees collected and the
+ * position liquidity, unchanged
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct FeesCollectedEvent {
+    pub pos_id: NonFungibleLocalId,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub pos_liq: Decimal,
+}
+
+/**
+ * The state of a pool exported to another pool, see ParameterChange::ExportState and Pool::import_state. Besides the pool
+ * state fields, it contains the positions by their NFT id and the states of the ticks used by the positions.
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolExport {
+    pub resource0_addr: ResourceAddress,
+    pub resource1_addr: ResourceAddress,
+    pub sqrt_price: SqrtPrice,
+    pub current_tick: Tick,
+    pub active_liquidity: Decimal,
+    pub fee_growth_global0: Decimal,
+    pub fee_growth_global1: Decimal,
+    pub position_nft_addr: ResourceAddress,
+    pub positions: Vec<(NonFungibleLocalId, Position)>,
+    pub used_ticks: Vec<i32>,
+    pub tick_states: Vec<TickState>,
+}
+
+/**
+ * A position of a portfolio, see Pool::get_portfolio: its amount0,1 at the current price, its uncollected fees0,1 and if the current
+ * tick is in its range, so it earns fees
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PortfolioPosition {
+    pub pos_id: NonFungibleLocalId,
+    pub position: Position,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub in_range: bool,
+}
+
+/**
+ * The positions of a portfolio, see Pool::get_portfolio, with their total amount0,1 and uncollected fees0,1, and the share of their
+ * liquidity that is in range
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Portfolio {
+    pub positions: Vec<PortfolioPosition>,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub in_range_ratio: Decimal,
+}
+
+/**
+ * The total value locked in the pool, see Pool::get_tvl, where total0,1 are the vault balances
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Tvl {
+    pub active0: Decimal,
+    pub active1: Decimal,
+    pub out_of_range0: Decimal,
+    pub out_of_range1: Decimal,
+    pub uncollected_fees0: Decimal,
+    pub uncollected_fees1: Decimal,
+    pub total0: Decimal,
+    pub total1: Decimal,
+}
+
+/**
+ * The swap statistics of an epoch, where:
+ * - volume0,1 = the amounts of resource0,1 swapped in, fees included
+ * - fees0,1 = the fees paid in resource0,1
+ * - sqrt_price = the pool sqrt_price after the last swap of the epoch
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct EpochStats {
+    pub epoch: u64,
+    pub volume0: Decimal,
+    pub volume1: Decimal,
+    pub fees0: Decimal,
+    pub fees1: Decimal,
+    pub sqrt_price: SqrtPrice,
+    pub swaps: u64,
+}
+
+impl EpochStats {
+    pub fn new(epoch: u64) -> EpochStats {
+        Self {
+            epoch,
+            volume0: Decimal::zero(),
+            volume1: Decimal::zero(),
+            fees0: Decimal::zero(),
+            fees1: Decimal::zero(),
+            sqrt_price: SqrtPrice(Decimal::zero()),
+            swaps: 0,
+        }
+    }
+}
+
+/**
+ * A price level of the pool depth: the output amount available until the price reaches sqrt_price
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct DepthLevel {
+    pub sqrt_price: SqrtPrice,
+    pub amount: Decimal,
+}
+
+/**
+ * The pool liquidity aggregated in price levels, bellow (bids) and above (asks) the current price
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Depth {
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+}
+
 /**
  * The NFT that the LP holds for each range it provided liquidty too
  */
@@ -980,6 +4162,5 @@ impl Position {
 pub struct PositionNFTData {
     #[mutable]
     pub liq: Decimal,
-    pub low_sqrt_price: Decimal,
-    pub high_sqrt_price: Decimal,
+    pub range: TickRange,
 }
@@ -1,5 +1,6 @@
+#[cfg(feature = "blueprint")]
 use crate::pool::TickState;
-use scrypto::prelude::*;
+use radix_engine_common::prelude::*;
 
 /**
  * Computes the amount0, amount1 for a given position described by tbe liquidity and range[sqrt_price_low, sqrt_price_high] at a given sqrt_price
@@ -129,6 +130,7 @@ fn compute_range_liq_given_amount1(amount1: Decimal, sqrt_price_low: Decimal, sq
  *    fee_outside_tick = 0, if current_tick < tick or
  *    fee_outside_tick = fee_global, if current_tick >= tick
  */
+#[cfg(feature = "blueprint")]
 pub fn compute_range_fees(
     current_tick: i32,
     fee_global0: Decimal,
@@ -136,28 +138,46 @@ pub fn compute_range_fees(
     low_tick_state: &TickState,
     high_tick_state: &TickState,
 ) -> (Decimal, Decimal) {
-    let fee_bellow_low_tick0 = if current_tick >= low_tick_state.tick {
-        low_tick_state.fee_outside0
+    compute_range_fees_given_outside(
+        current_tick,
+        (fee_global0, fee_global1),
+        (low_tick_state.tick, low_tick_state.fee_outside0, low_tick_state.fee_outside1),
+        (high_tick_state.tick, high_tick_state.fee_outside0, high_tick_state.fee_outside1),
+    )
+}
+
+/**
+ * Same as compute_range_fees, given the global fees and the (tick, fee_outside0, fee_outside1) of the range ticks, e.g. for the
+ * off-ledger simulations that don't build the pool blueprint
+ */
+pub fn compute_range_fees_given_outside(
+    current_tick: i32,
+    (fee_global0, fee_global1): (Decimal, Decimal),
+    (low_tick, low_fee_outside0, low_fee_outside1): (i32, Decimal, Decimal),
+    (high_tick, high_fee_outside0, high_fee_outside1): (i32, Decimal, Decimal),
+) -> (Decimal, Decimal) {
+    let fee_bellow_low_tick0 = if current_tick >= low_tick {
+        low_fee_outside0
     } else {
-        fee_global0 - low_tick_state.fee_outside0
+        fee_global0 - low_fee_outside0
     };
 
-    let fee_above_high_tick0 = if current_tick >= high_tick_state.tick {
-        fee_global0 - high_tick_state.fee_outside0
+    let fee_above_high_tick0 = if current_tick >= high_tick {
+        fee_global0 - high_fee_outside0
     } else {
-        high_tick_state.fee_outside0
+        high_fee_outside0
     };
 
-    let fee_bellow_low_tick1 = if current_tick >= low_tick_state.tick {
-        low_tick_state.fee_outside1
+    let fee_bellow_low_tick1 = if current_tick >= low_tick {
+        low_fee_outside1
     } else {
-        fee_global1 - low_tick_state.fee_outside1
+        fee_global1 - low_fee_outside1
     };
 
-    let fee_above_high_tick1 = if current_tick >= high_tick_state.tick {
-        fee_global1 - high_tick_state.fee_outside1
+    let fee_above_high_tick1 = if current_tick >= high_tick {
+        fee_global1 - high_fee_outside1
     } else {
-        high_tick_state.fee_outside1
+        high_fee_outside1
     };
 
     (
@@ -220,10 +240,49 @@ pub fn compute_pos_fees(
     ((range_fee0 - pos_range_fee0) * liq, (range_fee1 - pos_range_fee1) * liq)
 }
 
+/**
+ * Rounds the given amount down to the granularity of a resource with the given divisibility, e.g. 6 decimals for a divisibility of
+ * 6. The amounts paid out by the pool are rounded down, the dust staying in the pool.
+ */
+pub fn round_down_to_divisibility(amount: Decimal, divisibility: u8) -> Decimal {
+    amount.checked_round(divisibility as i32, RoundingMode::ToZero).unwrap()
+}
+
+/**
+ * Rounds the given amount up to the granularity of a resource with the given divisibility. The amounts paid in to the pool are
+ * rounded up, the dust staying in the pool.
+ */
+pub fn round_up_to_divisibility(amount: Decimal, divisibility: u8) -> Decimal {
+    amount.checked_round(divisibility as i32, RoundingMode::AwayFromZero).unwrap()
+}
+
+/**
+ * Computes the square root of the given value, truncated to 18 decimals, using the Newton (Babylonian) method: x = (x + value / x) / 2.
+ *
+ * Starting from value / 2 + 1, which is greater than the square root, x decreases towards the square root, so we stop when it
+ * doesn't decrease anymore. This takes at most ~70 iterations for the largest Decimal values and much less for usual prices.
+ */
+pub fn sqrt(value: Decimal) -> Decimal {
+    assert!(value >= Decimal::zero(), "Can't compute the square root of a negative value.");
+    if value == Decimal::zero() {
+        return Decimal::zero();
+    }
+
+    let two: Decimal = 2.into();
+    let mut x = value / two + Decimal::one();
+    loop {
+        let next_x = (x + value / x) / two;
+        if next_x >= x {
+            return x;
+        }
+        x = next_x;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tick_math;
+    use crate::tick_math::{self, Tick};
 
     #[test]
     fn range_liq_given_amount0() {
@@ -433,12 +492,12 @@ mod tests {
 
     #[test]
     fn swap_amount0_price_and_amount1() {
-        let live_liq = dec!("205051.662681070198680358");
+        let live_liq = dec!("205051.662681066435546857");
         let sqrt_price = Decimal::one();
-        let amount0 = dec!("9999.999999999999969789");
+        let amount0 = dec!("9999.999999999816448552");
         let amount1 = dec!("10000");
-        let sqrt_price_low = tick_math::sqrt_price_at_tick(-1000);
-        let sqrt_price_high = tick_math::sqrt_price_at_tick(1000);
+        let sqrt_price_low = tick_math::sqrt_price_at_tick(Tick(-1000)).0;
+        let sqrt_price_high = tick_math::sqrt_price_at_tick(Tick(1000)).0;
 
         let amount0_to_swap = dec!("1000");
         
@@ -460,12 +519,12 @@ mod tests {
 
     #[test]
     fn swap_amount1_price_and_amount0() {
-        let live_liq = dec!("205051.662681070198680358");
+        let live_liq = dec!("205051.662681070199299805");
         let sqrt_price = Decimal::one();
         let amount0 = dec!("10000");
-        let amount1 = dec!("9999.999999999999969789");
-        let sqrt_price_low = tick_math::sqrt_price_at_tick(-1000);
-        let sqrt_price_high = tick_math::sqrt_price_at_tick(1000);
+        let amount1 = dec!("10000.000000000183551447");
+        let sqrt_price_low = tick_math::sqrt_price_at_tick(Tick(-1000)).0;
+        let sqrt_price_high = tick_math::sqrt_price_at_tick(Tick(1000)).0;
 
         let amount1_to_swap = dec!("1000");
 
@@ -506,4 +565,26 @@ mod tests {
         assert!(-epsilon < diff);
         assert!(diff < epsilon);
     }
+
+    #[test]
+    fn sqrt_of_values() {
+        assert_eq!(sqrt(Decimal::zero()), Decimal::zero());
+        assert_eq!(sqrt(dec!("0.000000000000000001")), dec!("0.000000001"));
+        assert_eq!(sqrt(dec!("0.25")), dec!("0.5"));
+        assert_eq!(sqrt(Decimal::one()), Decimal::one());
+        assert_eq!(sqrt(dec!("1.0001")), dec!("1.000049998750062496"));
+        assert_eq!(sqrt(dec!("2")), dec!("1.414213562373095048"));
+        assert_eq!(sqrt(dec!("10000")), dec!("100"));
+        assert_eq!(sqrt(Decimal::MAX), dec!("56022770974786139918.731938227458171762"));
+    }
+
+    #[test]
+    fn round_to_divisibility() {
+        assert_eq!(round_down_to_divisibility(dec!("1.23456789"), 6), dec!("1.234567"));
+        assert_eq!(round_up_to_divisibility(dec!("1.23456789"), 6), dec!("1.234568"));
+        assert_eq!(round_down_to_divisibility(dec!("1.5"), 0), Decimal::one());
+        assert_eq!(round_up_to_divisibility(dec!("1.5"), 0), dec!("2"));
+        assert_eq!(round_up_to_divisibility(dec!("1.234"), 6), dec!("1.234"));
+        assert_eq!(round_down_to_divisibility(dec!("0.000000000000000001"), 18), dec!("0.000000000000000001"));
+    }
 }
@@ -0,0 +1,150 @@
+use radix_engine_common::prelude::*;
+use crate::tick_math::Tick;
+
+//the tick math of tick_math, using PreciseDecimal (36 decimals) instead of Decimal (18 decimals). With 36 decimals the tick range is
+//symmetric: the smallest sqrt price, at tick -931709, is 0.000000000000000000005877703180867893, while with Decimal the smallest tick
+//is -631042. This allows pools of tokens with extreme prices, e.g. a token worth 10^-30 XRD, at the cost of the wider arithmetic.
+pub const MIN_TICK: i32 = -931709;
+pub const MAX_TICK: i32 = 931709;
+
+//0.000000000000000000005877703180867893
+pub const MIN_PRICE: PreciseDecimal = PreciseDecimal(bnum_integer::I256::from_digits([5877703180867893, 0, 0, 0]));
+//the pre-computed sqrt prices, as in tick_math, truncated to 36 decimals, generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/precise_price_constants.rs"));
+//170134484377338262665.919079467083017686717678558036193137
+pub const MAX_PRICE: PreciseDecimal = PreciseDecimal(bnum_integer::I256::from_digits([2289183540216164209, 8337524062387863286, 499980313164059642, 0]));
+
+/**
+ * Same as tick_math::sqrt_price_at_tick, but in the symmetric tick range [MIN_TICK, MAX_TICK].
+ */
+pub fn sqrt_price_at_tick(tick: Tick) -> PreciseDecimal {
+    let tick = tick.0;
+    assert!(tick >= MIN_TICK && tick <= MAX_TICK, "Tick out of bounds.");
+
+    let abs_tick = if tick >= 0 { tick } else { -tick };
+    let prices = if tick >= 0 { &PRICES } else { &INV_PRICES };
+    let mut sqrt_price = PreciseDecimal::one();
+
+    for (power, price) in prices.iter().enumerate() {
+        if abs_tick & (1 << power) != 0 {
+            sqrt_price = sqrt_price * *price;
+        }
+    }
+    sqrt_price
+}
+
+/**
+ * Same as tick_math::tick_at_sqrt_price, but in the symmetric tick range [MIN_TICK, MAX_TICK].
+ */
+pub fn tick_at_sqrt_price(sqrt_price_in: PreciseDecimal) -> Tick {
+    assert!(sqrt_price_in >= MIN_PRICE && sqrt_price_in <= MAX_PRICE, "Sqrt price out of bounds.");
+
+    let is_negative_tick = sqrt_price_in < PreciseDecimal::one();
+    let mut sqrt_price = if is_negative_tick { PreciseDecimal::one() / sqrt_price_in } else { sqrt_price_in };
+    let mut tick = 0;
+    for power in (1..PRICES.len()).rev() {
+        if sqrt_price >= PRICES[power] {
+            sqrt_price = sqrt_price / PRICES[power];
+            tick += 1 << power;
+        }
+    }
+    if sqrt_price >= PRICES[0] {
+        tick += 1;
+    }
+
+    let tick_candidate = if is_negative_tick { -tick } else { tick };
+    let sqrt_price_tick_candidate = sqrt_price_at_tick(Tick(tick_candidate));
+    if sqrt_price_tick_candidate == sqrt_price_in { Tick(tick_candidate) }
+    else if sqrt_price_tick_candidate > sqrt_price_in { Tick(tick_candidate - 1) }
+    else { Tick(tick_candidate + 1) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_price_at_tick() {
+        assert_eq!(MIN_PRICE, sqrt_price_at_tick(Tick(MIN_TICK)));
+        assert_eq!(pdec!("0.000000000000019850990293793866121254"), sqrt_price_at_tick(Tick(-631042)));
+        assert_eq!(pdec!("0.999850018747812746066682619803011895"), sqrt_price_at_tick(Tick(-3)));
+        assert_eq!(PreciseDecimal::one(), sqrt_price_at_tick(Tick(0)));
+        assert_eq!(pdec!("1.000150003749937502343632819335498076"), sqrt_price_at_tick(Tick(3)));
+        assert_eq!(pdec!("3611718901.097549657198717522481203553571604763"), sqrt_price_at_tick(Tick(440171)));
+        assert_eq!(MAX_PRICE, sqrt_price_at_tick(Tick(MAX_TICK)));
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_price() {
+        assert_eq!(0, tick_at_sqrt_price(PreciseDecimal::one()).0);
+        assert_eq!(-3, tick_at_sqrt_price(pdec!("0.999850018747812746066682619803011895")).0);
+        assert_eq!(-631042, tick_at_sqrt_price(pdec!("0.000000000000019850990293793866121254")).0);
+        assert_eq!(MIN_TICK, tick_at_sqrt_price(MIN_PRICE).0);
+        assert_eq!(MAX_TICK, tick_at_sqrt_price(MAX_PRICE).0);
+    }
+
+    /**
+     * The constants generated by build.rs are the ones previously maintained by hand in this file.
+     */
+    #[test]
+    fn generated_constants_match_the_previous_table() {
+        let prices: [PreciseDecimal; 20] = [
+            pdec!("1.000049998750062496094023416993798697"),
+            pdec!("1.0001"),
+            pdec!("1.00020001"),
+            pdec!("1.0004000600040001"),
+            pdec!("1.00080028005600700056002800080001"),
+            pdec!("1.00160120056018204368800914412871144"),
+            pdec!("1.003204964963598014666528690811055252"),
+            pdec!("1.006420201727613920156533908409419272"),
+            pdec!("1.01288162244545109707809563193500557"),
+            pdec!("1.025929181087729343658708608578965861"),
+            pdec!("1.052530684607338948386589370372923836"),
+            pdec!("1.107820842039993613899215811078813988"),
+            pdec!("1.227267018058200482050503815090808829"),
+            pdec!("1.50618433361346738810795598119915172"),
+            pdec!("2.26859124682264482692560985934360724"),
+            pdec!("5.146506245160322222537991751503863982"),
+            pdec!("26.486526531474198664033811812785769604"),
+            pdec!("701.536087702486644953017488493794435252"),
+            pdec!("492152.882348911033633683861778354995017201"),
+            pdec!("242214459604.341065650571799093539783052207785644"),
+        ];
+        let inv_prices: [PreciseDecimal; 20] = [
+            pdec!("0.999950003749687527341289288064992197"),
+            pdec!("0.999900009999000099990000999900009999"),
+            pdec!("0.99980002999600049994000699920008999"),
+            pdec!("0.99960009998000349944008398800164978"),
+            pdec!("0.999200359880032992081715656864338561"),
+            pdec!("0.998401359184387445014246950501832822"),
+            pdec!("0.996805274021232232403522589364153187"),
+            pdec!("0.993620754316543878477700834205485601"),
+            pdec!("0.987282203408577650514394977141072622"),
+            pdec!("0.974726149167296094959767608987808722"),
+            pdec!("0.950091065870505957793733166276036749"),
+            pdec!("0.902673033446954090815966611100428427"),
+            pdec!("0.814818605312325898736980010634637721"),
+            pdec!("0.663929359563123931326022095378263637"),
+            pdec!("0.440802194489899902842800301640729327"),
+            pdec!("0.194306574667111540266988334939575749"),
+            pdec!("0.03775504495886579217532090447754052"),
+            pdec!("0.001425443419845977266771800329344378"),
+            pdec!("0.000002031888943182195016802617844386"),
+            pdec!("0.000000000004128572677426057329255071"),
+        ];
+        assert_eq!(prices, PRICES);
+        assert_eq!(inv_prices, INV_PRICES);
+    }
+
+    /**
+     * Tests that we are consistent with all the ticks between MIN_TICK and MAX_TICK. This test takes even longer than its tick_math
+     * counterpart, so by default is disabled.
+     */
+    #[test]
+    #[ignore]
+    fn tick_at_sqrt_price_equals_original_tick() {
+        for tick in (MIN_TICK..MAX_TICK + 1).rev() {
+            assert_eq!(Tick(tick), tick_at_sqrt_price(sqrt_price_at_tick(Tick(tick))));
+        }
+    }
+}
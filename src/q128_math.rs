@@ -0,0 +1,206 @@
+use core::ops::{Add, Div, Mul, Sub};
+use radix_engine_common::prelude::*;
+use crate::tick_math::Tick;
+
+//the sqrt price and liquidity math with Q128.128 fixed point numbers: 128 integer bits and 128 fractional bits in a 256 bits
+//unsigned integer. A fractional bit is worth 2^-128 ≈ 2.9 * 10^-39, so the truncation errors of the intermediate results stay far
+//under the 18 decimals of the vault amounts: the values are converted from Decimal on the way in and back to Decimal on the way out.
+type U256 = bnum::BUint<4>;
+type U512 = bnum::BUint<8>;
+
+//the tick range is symmetric, as in precise_tick_math, the smallest sqrt price at tick -931709 being ≈ 5.9 * 10^-21
+pub const MIN_TICK: i32 = -931709;
+pub const MAX_TICK: i32 = 931709;
+
+//10^18, the scale of the Decimal raw value
+const DECIMAL_SCALE: U512 = U512::from_digits([1000000000000000000, 0, 0, 0, 0, 0, 0, 0]);
+
+/**
+ * An unsigned Q128.128 fixed point number, value * 2^128
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Q128(pub U256);
+
+impl Q128 {
+    pub const ZERO: Q128 = Q128(U256::from_digits([0, 0, 0, 0]));
+    pub const ONE: Q128 = Q128(U256::from_digits([0, 0, 1, 0]));
+
+    /**
+     * Converts a non negative Decimal, rounding up, so that to_decimal(from_decimal(value)) == value
+     */
+    pub fn from_decimal(value: Decimal) -> Q128 {
+        assert!(value >= Decimal::zero(), "Can't convert a negative decimal.");
+        let raw = value.0 .0.to_bits();
+        let mut digits = [0; 8];
+        digits[..3].copy_from_slice(raw.digits());
+        let scaled = U512::from_digits(digits) << 128;
+        let (quotient, remainder) = (scaled / DECIMAL_SCALE, scaled % DECIMAL_SCALE);
+        let rounded = if remainder == U512::ZERO { quotient } else { quotient + U512::ONE };
+        Q128(narrow(rounded))
+    }
+
+    /**
+     * Converts to a Decimal, truncated to 18 decimals
+     */
+    pub fn to_decimal(self) -> Decimal {
+        let raw = (widen(self.0) * DECIMAL_SCALE) >> 128;
+        let digits = raw.digits();
+        assert!(digits[3..].iter().all(|digit| *digit == 0) && digits[2] >> 63 == 0, "Value doesn't fit in a decimal.");
+        Decimal(bnum_integer::I192::from_digits([digits[0], digits[1], digits[2]]))
+    }
+}
+
+fn widen(value: U256) -> U512 {
+    let mut digits = [0; 8];
+    digits[..4].copy_from_slice(value.digits());
+    U512::from_digits(digits)
+}
+
+fn narrow(value: U512) -> U256 {
+    let digits = value.digits();
+    assert!(digits[4..].iter().all(|digit| *digit == 0), "Q128 overflow.");
+    U256::from_digits([digits[0], digits[1], digits[2], digits[3]])
+}
+
+impl Add for Q128 {
+    type Output = Q128;
+
+    fn add(self, other: Q128) -> Q128 {
+        Q128(self.0.checked_add(other.0).expect("Q128 overflow."))
+    }
+}
+
+impl Sub for Q128 {
+    type Output = Q128;
+
+    fn sub(self, other: Q128) -> Q128 {
+        Q128(self.0.checked_sub(other.0).expect("Q128 underflow."))
+    }
+}
+
+/**
+ * The product truncated to 128 fractional bits, computed on 512 bits so the intermediate result doesn't overflow
+ */
+impl Mul for Q128 {
+    type Output = Q128;
+
+    fn mul(self, other: Q128) -> Q128 {
+        Q128(narrow((widen(self.0) * widen(other.0)) >> 128))
+    }
+}
+
+/**
+ * The quotient truncated to 128 fractional bits, computed on 512 bits so the intermediate result doesn't overflow
+ */
+impl Div for Q128 {
+    type Output = Q128;
+
+    fn div(self, other: Q128) -> Q128 {
+        assert!(other.0 != U256::ZERO, "Division by zero.");
+        Q128(narrow((widen(self.0) << 128) / widen(other.0)))
+    }
+}
+
+//the pre-computed sqrt prices, as in tick_math, truncated to 128 fractional bits, generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/q128_price_constants.rs"));
+
+/**
+ * Same as tick_math::sqrt_price_at_tick, but in the symmetric tick range [MIN_TICK, MAX_TICK].
+ */
+pub fn sqrt_price_at_tick(tick: Tick) -> Q128 {
+    let tick = tick.0;
+    assert!(tick >= MIN_TICK && tick <= MAX_TICK, "Tick out of bounds.");
+
+    let abs_tick = if tick >= 0 { tick } else { -tick };
+    let prices = if tick >= 0 { &PRICES } else { &INV_PRICES };
+    let mut sqrt_price = Q128::ONE;
+
+    for (power, price) in prices.iter().enumerate() {
+        if abs_tick & (1 << power) != 0 {
+            sqrt_price = sqrt_price * *price;
+        }
+    }
+    sqrt_price
+}
+
+/**
+ * Same as pool_math::compute_range_amount0_given_liq: Δx=L*Δ(1/√𝑃)
+ */
+pub fn compute_range_amount0_given_liq(liq: Q128, sqrt_price_low: Q128, sqrt_price_high: Q128) -> Q128 {
+    liq * (sqrt_price_high - sqrt_price_low) / (sqrt_price_high * sqrt_price_low)
+}
+
+/**
+ * Same as pool_math::compute_range_amount1_given_liq: Δ𝑦=L*Δ√𝑃
+ */
+pub fn compute_range_amount1_given_liq(liq: Q128, sqrt_price_low: Q128, sqrt_price_high: Q128) -> Q128 {
+    liq * (sqrt_price_high - sqrt_price_low)
+}
+
+/**
+ * Same as pool_math::compute_range_liq_given_amount0: L=Δx/Δ(1/√𝑃)
+ */
+pub fn compute_range_liq_given_amount0(amount0: Q128, sqrt_price_low: Q128, sqrt_price_high: Q128) -> Q128 {
+    (amount0 * sqrt_price_low * sqrt_price_high) / (sqrt_price_high - sqrt_price_low)
+}
+
+/**
+ * Same as pool_math::compute_range_liq_given_amount1: L=Δ𝑦/Δ√𝑃
+ */
+pub fn compute_range_liq_given_amount1(amount1: Q128, sqrt_price_low: Q128, sqrt_price_high: Q128) -> Q128 {
+    amount1 / (sqrt_price_high - sqrt_price_low)
+}
+
+/**
+ * Same as pool_math::compute_swap_amount0_price_and_amount1: √P1=1/(1/√P0+ΔX/L) and ΔY=(√P0-√P1)*L
+ *
+ * Returns the new sqrt_price and the corresponding amount1.
+ */
+pub fn compute_swap_amount0_price_and_amount1(liq: Q128, sqrt_price: Q128, amount0: Q128) -> (Q128, Q128) {
+    let new_sqrt_price = Q128::ONE / (Q128::ONE / sqrt_price + amount0 / liq);
+    (new_sqrt_price, (sqrt_price - new_sqrt_price) * liq)
+}
+
+/**
+ * Same as pool_math::compute_swap_amount1_price_and_amount0: √P1=√P0+ΔY/L and ΔX=(1/√P0-1/√P1)*L
+ *
+ * Returns the new sqrt_price and the corresponding amount0.
+ */
+pub fn compute_swap_amount1_price_and_amount0(liq: Q128, sqrt_price: Q128, amount1: Q128) -> (Q128, Q128) {
+    let new_sqrt_price = sqrt_price + amount1 / liq;
+    (new_sqrt_price, (Q128::ONE / sqrt_price - Q128::ONE / new_sqrt_price) * liq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        for value in [dec!("0"), dec!("0.000000000000000001"), dec!("0.999850018747812746"), dec!("1"), dec!("123456789.123456789123456789")] {
+            assert_eq!(value, Q128::from_decimal(value).to_decimal());
+        }
+        assert_eq!(Q128::ONE, Q128::from_decimal(Decimal::one()));
+    }
+
+    #[test]
+    fn test_sqrt_price_at_tick() {
+        assert_eq!(Q128::ONE, sqrt_price_at_tick(Tick(0)));
+        //the truncation of the exact values, while the Decimal tick math is off by a few units in the last decimal, e.g. 0.999850018747812744
+        assert_eq!(dec!("0.999850018747812746"), sqrt_price_at_tick(Tick(-3)).to_decimal());
+        assert_eq!(dec!("1.000150003749937502"), sqrt_price_at_tick(Tick(3)).to_decimal());
+        assert_eq!(dec!("0.006739631584094859"), sqrt_price_at_tick(Tick(-100000)).to_decimal());
+        assert_eq!(dec!("148.376062923074618982"), sqrt_price_at_tick(Tick(100000)).to_decimal());
+        assert_eq!(dec!("3611718901.097549657198717522"), sqrt_price_at_tick(Tick(440171)).to_decimal());
+        assert_eq!(dec!("0.000000000000019850"), sqrt_price_at_tick(Tick(-631042)).to_decimal());
+    }
+
+    #[test]
+    fn test_range_amounts_given_liq() {
+        let liq = Q128::from_decimal(dec!("1000"));
+        let sqrt_price_low = sqrt_price_at_tick(Tick(-3));
+        let sqrt_price_high = sqrt_price_at_tick(Tick(3));
+        assert_eq!(dec!("0.299985002124756276"), compute_range_amount0_given_liq(liq, sqrt_price_low, sqrt_price_high).to_decimal());
+        assert_eq!(dec!("0.299985002124756276"), compute_range_amount1_given_liq(liq, sqrt_price_low, sqrt_price_high).to_decimal());
+    }
+}
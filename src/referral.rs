@@ -0,0 +1,92 @@
+use scrypto::prelude::*;
+
+/**
+ * An integrator registered in a ReferralRegistry: the badge controlling the registration and the account the referral fees are paid
+ * to
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Referrer {
+    pub badge_addr: ResourceAddress,
+    pub payout_account: ComponentAddress,
+}
+
+/**
+ * The registry of the integrators, e.g. wallets or frontends, earning a share of the swap fees of the pools for the swaps they refer,
+ * see Pool::swap_with_referrer. The pools pay the referral fees through the registry, which aborts the swaps with referrer ids that
+ * aren't registered, so a spoofed id can't siphon the fees.
+ */
+#[blueprint]
+mod referral_registry_blueprint {
+    struct ReferralRegistry {
+        referrers: KeyValueStore<u64, Referrer>,
+        referrer_count: u64,
+    }
+
+    impl ReferralRegistry {
+        /**
+         * Creates a new, empty, referral registry
+         */
+        pub fn new() -> Global<ReferralRegistry> {
+            Self {
+                referrers: KeyValueStore::new(),
+                referrer_count: 0,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /**
+         * Registers an integrator, controlled by the badge of the provided proof, with the account the referral fees are paid to.
+         *
+         * Returns the referrer id, passed to Pool::swap_with_referrer
+         */
+        pub fn register(&mut self, badge: Proof, payout_account: ComponentAddress) -> u64 {
+            //any badge can control a registration, the proof only shows that the integrator holds it
+            let checked_badge = badge.skip_checking();
+            assert!(checked_badge.amount() > Decimal::zero(), "Empty badge proof. Register op aborted.");
+
+            let referrer_id = self.referrer_count;
+            self.referrers.insert(
+                referrer_id,
+                Referrer {
+                    badge_addr: checked_badge.resource_address(),
+                    payout_account,
+                },
+            );
+            self.referrer_count += 1;
+            referrer_id
+        }
+
+        /**
+         * Sets the account the referral fees of the given referrer are paid to, with a proof of its badge
+         */
+        pub fn set_payout_account(&mut self, badge: Proof, referrer_id: u64, payout_account: ComponentAddress) {
+            let mut referrer = self
+                .referrers
+                .get_mut(&referrer_id)
+                .expect("Referrer not registered. Set payout account op aborted.");
+            badge.check(referrer.badge_addr);
+            referrer.payout_account = payout_account;
+        }
+
+        /**
+         * Pays the provided referral fee to the account of the given referrer, aborting the swap if the referrer isn't registered
+         */
+        pub fn pay_referral_fee(&mut self, referrer_id: u64, fee: Bucket) {
+            let referrer = self
+                .referrers
+                .get(&referrer_id)
+                .expect("Referrer not registered. Swap op aborted.");
+            let mut payout_account: Global<Account> = referrer.payout_account.into();
+            payout_account.try_deposit_or_abort(fee, None);
+        }
+
+        /**
+         * Returns the given referrer, if registered
+         */
+        pub fn get_referrer(&self, referrer_id: u64) -> Option<Referrer> {
+            self.referrers.get(&referrer_id).map(|referrer| referrer.clone())
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use scrypto::prelude::*;
+use crate::pool::{
+    CrossDirection, FeesCollectedEvent, LiquidityAddedEvent, LiquidityRemovedEvent, PoolState, Position, PositionAddedEvent,
+    ProtocolFeeSwitchEvent, SwapEvent, TickCrossedEvent, TickState,
+};
+use crate::tick_math::{self, SqrtPrice, Tick};
+
+/**
+ * An event emitted by a pool, in the order of the ledger, see PoolReplay
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub enum PoolEvent {
+    Swap(SwapEvent),
+    PositionAdded(PositionAddedEvent),
+    LiquidityAdded(LiquidityAddedEvent),
+    LiquidityRemoved(LiquidityRemovedEvent),
+    FeesCollected(FeesCollectedEvent),
+    TickCrossed(TickCrossedEvent),
+    ProtocolFeeSwitch(ProtocolFeeSwitchEvent),
+}
+
+impl PoolEvent {
+    /**
+     * Decodes the SBOR payload of the pool event with the given name, e.g. from a transaction receipt. Returns None for the events
+     * not affecting the replayed state.
+     */
+    pub fn decode(event_name: &str, data: &[u8]) -> Option<PoolEvent> {
+        match event_name {
+            "SwapEvent" => scrypto_decode(data).ok().map(PoolEvent::Swap),
+            "PositionAddedEvent" => scrypto_decode(data).ok().map(PoolEvent::PositionAdded),
+            "LiquidityAddedEvent" => scrypto_decode(data).ok().map(PoolEvent::LiquidityAdded),
+            "LiquidityRemovedEvent" => scrypto_decode(data).ok().map(PoolEvent::LiquidityRemoved),
+            "FeesCollectedEvent" => scrypto_decode(data).ok().map(PoolEvent::FeesCollected),
+            "TickCrossedEvent" => scrypto_decode(data).ok().map(PoolEvent::TickCrossed),
+            "ProtocolFeeSwitchEvent" => scrypto_decode(data).ok().map(PoolEvent::ProtocolFeeSwitch),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * A replayed position: its range and liquidity
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayedPosition {
+    pub liq: Decimal,
+    pub low_tick: i32,
+    pub high_tick: i32,
+}
+
+/**
+ * A used tick of the replayed pool: its net and gross liquidity
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayedTick {
+    pub liq_net: Decimal,
+    pub liq_gross: Decimal,
+}
+
+/**
+ * A field of the replayed state differing from the on-ledger snapshot, with both values
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayMismatch {
+    pub field: String,
+    pub replayed: String,
+    pub on_ledger: String,
+}
+
+/**
+ * Reconstructs the state of a pool from the ordered stream of its events and verifies it against an on-ledger snapshot, e.g. for
+ * audits or for validating that the events are complete. The replay starts either from a new pool, see PoolReplay::new, or from a
+ * snapshot, see PoolReplay::from_snapshot, and applies the events in the ledger order, see PoolReplay::apply.
+ *
+ * The replayed state is the one the events determine: the price, the tick, the live liquidity, the vault amounts, the used ticks and
+ * the positions. It doesn't cover:
+ * - the fee growth, global, outside the ticks and of the positions, as the swap events carry the fee of the whole swap and not the
+ *   fee of each step between the crossed ticks
+ * - the referral part of the swap fees, paid out of the pool without a pool event, and the vaults moved by ParameterChange::ExportState
+ * - the removals in emergency mode, which don't update the ticks
+ * The protocol part of the swap fees is kept out of the vault amounts with the share of the last ProtocolFeeSwitchEvent, up to the
+ * rounding of its split between the swap steps on ledger.
+ */
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolReplay {
+    pub resource0_addr: ResourceAddress,
+    pub resource1_addr: ResourceAddress,
+    pub sqrt_price: Decimal,
+    pub tick: i32,
+    pub live_liq: Decimal,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub protocol_fee_share: Decimal,
+    pub ticks: BTreeMap<i32, ReplayedTick>,
+    pub positions: BTreeMap<NonFungibleLocalId, ReplayedPosition>,
+}
+
+impl PoolReplay {
+    /**
+     * Starts the replay of a new pool of the given resources and initial sqrt price, the events of its creation included
+     */
+    pub fn new(resource0_addr: ResourceAddress, resource1_addr: ResourceAddress, sqrt_price: SqrtPrice) -> PoolReplay {
+        PoolReplay {
+            resource0_addr,
+            resource1_addr,
+            sqrt_price: sqrt_price.0,
+            tick: tick_math::tick_at_sqrt_price(sqrt_price).0,
+            live_liq: Decimal::zero(),
+            amount0: Decimal::zero(),
+            amount1: Decimal::zero(),
+            protocol_fee_share: Decimal::zero(),
+            ticks: BTreeMap::new(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    /**
+     * Starts the replay from an on-ledger snapshot: Pool::get_pool_state, Pool::get_tick_states, Pool::get_positions and
+     * Pool::get_protocol_fee_share, if the protocol fee switch is on, zero otherwise
+     */
+    pub fn from_snapshot(
+        state: &PoolState,
+        tick_states: &[TickState],
+        positions: &[(NonFungibleLocalId, Position)],
+        protocol_fee_share: Decimal,
+    ) -> PoolReplay {
+        PoolReplay {
+            resource0_addr: state.resource0_addr,
+            resource1_addr: state.resource1_addr,
+            sqrt_price: state.sqrt_price.0,
+            tick: state.current_tick.0,
+            live_liq: state.active_liquidity,
+            amount0: state.amount0,
+            amount1: state.amount1,
+            protocol_fee_share,
+            ticks: tick_states
+                .iter()
+                .map(|tick_state| {
+                    (tick_state.tick, ReplayedTick { liq_net: tick_state.liq_net, liq_gross: tick_state.liq_gross })
+                })
+                .collect(),
+            positions: positions
+                .iter()
+                .map(|(pos_id, pos)| {
+                    (pos_id.clone(), ReplayedPosition { liq: pos.liq, low_tick: pos.low_tick, high_tick: pos.high_tick })
+                })
+                .collect(),
+        }
+    }
+
+    /**
+     * Replays the given events, in order
+     */
+    pub fn replay(&mut self, events: &[PoolEvent]) {
+        for event in events {
+            self.apply(event);
+        }
+    }
+
+    /**
+     * Applies an event to the replayed state, as the pool method that emitted it
+     */
+    pub fn apply(&mut self, event: &PoolEvent) {
+        match event {
+            PoolEvent::Swap(swap) => {
+                let is_token0 = swap.resource_in_addr == self.resource0_addr;
+                let kept_amount = swap.amount_in - swap.fee_amount * self.protocol_fee_share;
+                if is_token0 {
+                    self.amount0 += kept_amount;
+                    self.amount1 -= swap.amount_out;
+                } else {
+                    self.amount1 += kept_amount;
+                    self.amount0 -= swap.amount_out;
+                }
+                self.sqrt_price = swap.sqrt_price.0;
+                //the crossed ticks already moved the tick, it's recomputed only if the price left its range
+                if !self.tick_contains_price() {
+                    let mut tick = tick_math::tick_at_sqrt_price(swap.sqrt_price).0;
+                    if tick_math::sqrt_price_at_tick(Tick(tick)).0 > self.sqrt_price {
+                        tick -= 1;
+                    }
+                    self.tick = tick;
+                }
+            }
+            PoolEvent::PositionAdded(added) => {
+                self.amount0 += added.amount0;
+                self.amount1 += added.amount1;
+                self.update_liq(added.liq, added.low_tick, added.high_tick);
+                self.positions.insert(
+                    added.pos_id.clone(),
+                    ReplayedPosition { liq: added.liq, low_tick: added.low_tick, high_tick: added.high_tick },
+                );
+            }
+            PoolEvent::LiquidityAdded(added) => {
+                self.amount0 += added.amount0;
+                self.amount1 += added.amount1;
+                self.update_liq(added.liq, added.low_tick, added.high_tick);
+                self.update_pos_liq(&added.pos_id, added.pos_liq);
+            }
+            PoolEvent::LiquidityRemoved(removed) => {
+                self.amount0 -= removed.amount0 + removed.fees0;
+                self.amount1 -= removed.amount1 + removed.fees1;
+                self.update_liq(-removed.liq, removed.low_tick, removed.high_tick);
+                self.update_pos_liq(&removed.pos_id, removed.pos_liq);
+            }
+            PoolEvent::FeesCollected(collected) => {
+                self.amount0 -= collected.fees0;
+                self.amount1 -= collected.fees1;
+            }
+            PoolEvent::TickCrossed(crossed) => {
+                let liq_net = self.ticks.get(&crossed.tick).map_or(Decimal::zero(), |tick| tick.liq_net);
+                match crossed.direction {
+                    CrossDirection::Up => {
+                        self.live_liq += liq_net;
+                        self.tick = crossed.tick;
+                    }
+                    CrossDirection::Down => {
+                        self.live_liq -= liq_net;
+                        self.tick = crossed.tick - 1;
+                    }
+                }
+            }
+            PoolEvent::ProtocolFeeSwitch(switch) => {
+                self.protocol_fee_share = if switch.enabled { switch.protocol_fee_share } else { Decimal::zero() };
+            }
+        }
+    }
+
+    /**
+     * Compares the replayed state with the on-ledger snapshot, see PoolReplay::from_snapshot, and returns the differing fields, none
+     * if the events account for the whole state
+     */
+    pub fn verify(
+        &self,
+        state: &PoolState,
+        tick_states: &[TickState],
+        positions: &[(NonFungibleLocalId, Position)],
+    ) -> Vec<ReplayMismatch> {
+        let mut mismatches = vec![];
+        let mut check = |field: String, replayed: String, on_ledger: String| {
+            if replayed != on_ledger {
+                mismatches.push(ReplayMismatch { field, replayed, on_ledger });
+            }
+        };
+        check("sqrt_price".to_string(), self.sqrt_price.to_string(), state.sqrt_price.0.to_string());
+        check("current_tick".to_string(), self.tick.to_string(), state.current_tick.0.to_string());
+        check("active_liquidity".to_string(), self.live_liq.to_string(), state.active_liquidity.to_string());
+        check("amount0".to_string(), self.amount0.to_string(), state.amount0.to_string());
+        check("amount1".to_string(), self.amount1.to_string(), state.amount1.to_string());
+
+        let on_ledger_ticks: BTreeMap<i32, ReplayedTick> = tick_states
+            .iter()
+            .map(|tick_state| (tick_state.tick, ReplayedTick { liq_net: tick_state.liq_net, liq_gross: tick_state.liq_gross }))
+            .collect();
+        for tick in self.ticks.keys().chain(on_ledger_ticks.keys()).collect::<BTreeSet<_>>() {
+            check(format!("ticks[{}]", tick), format!("{:?}", self.ticks.get(tick)), format!("{:?}", on_ledger_ticks.get(tick)));
+        }
+
+        let on_ledger_positions: BTreeMap<NonFungibleLocalId, ReplayedPosition> = positions
+            .iter()
+            .map(|(pos_id, pos)| (pos_id.clone(), ReplayedPosition { liq: pos.liq, low_tick: pos.low_tick, high_tick: pos.high_tick }))
+            .collect();
+        for pos_id in self.positions.keys().chain(on_ledger_positions.keys()).collect::<BTreeSet<_>>() {
+            check(
+                format!("positions[{}]", pos_id),
+                format!("{:?}", self.positions.get(pos_id)),
+                format!("{:?}", on_ledger_positions.get(pos_id)),
+            );
+        }
+        mismatches
+    }
+
+    /**
+     * Whether the price is in the range of the current tick, [tick, tick + 1), or at its upper end, where a swap crossing down the
+     * tick + 1 leaves it
+     */
+    fn tick_contains_price(&self) -> bool {
+        tick_math::sqrt_price_at_tick(Tick(self.tick)).0 <= self.sqrt_price
+            && self.sqrt_price <= tick_math::sqrt_price_at_tick(Tick(self.tick + 1)).0
+    }
+
+    //as Pool::update_ticks_liq and Pool::update_live_liq, the ticks no position uses being removed
+    fn update_liq(&mut self, liq: Decimal, low_tick: i32, high_tick: i32) {
+        for (tick, liq_net) in [(low_tick, liq), (high_tick, -liq)] {
+            let replayed_tick =
+                self.ticks.entry(tick).or_insert(ReplayedTick { liq_net: Decimal::zero(), liq_gross: Decimal::zero() });
+            replayed_tick.liq_net += liq_net;
+            replayed_tick.liq_gross += liq;
+            if replayed_tick.liq_gross == Decimal::zero() {
+                self.ticks.remove(&tick);
+            }
+        }
+        if self.tick >= low_tick && self.tick < high_tick {
+            self.live_liq += liq;
+        }
+    }
+
+    //the positions without liquidity are removed, as by Pool::remove_pos_if_empty
+    fn update_pos_liq(&mut self, pos_id: &NonFungibleLocalId, pos_liq: Decimal) {
+        if pos_liq == Decimal::zero() {
+            self.positions.remove(pos_id);
+        } else if let Some(pos) = self.positions.get_mut(pos_id) {
+            pos.liq = pos_liq;
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use scrypto::prelude::*;
+
+use crate::pool::pool_blueprint::Pool;
+
+/**
+ * Routes the swaps across the pools of the same pair of resources, e.g. the factory pools of the pair with different fee tiers, so
+ * that large orders are split and have a lower price impact than in a single pool.
+ */
+#[blueprint]
+mod router_blueprint {
+    struct Router {}
+
+    impl Router {
+        /**
+         * Swaps the provided amount across the given pools, each pool receiving a part of the amount proportional to its weight. The
+         * last pool receives what's left after the other parts were taken, so no dust stays behind due to rounding.
+         *
+         * Returns the swapped amount and the remainder of the provided amount, if the pools run out of liquidity.
+         */
+        pub fn swap_with_weights(mut bucket: Bucket, pool_addrs: Vec<ComponentAddress>, weights: Vec<Decimal>) -> (Bucket, Bucket) {
+            assert!(
+                !pool_addrs.is_empty() && pool_addrs.len() == weights.len(),
+                "Invalid pools or weights, there should be a weight for each pool. Swap op aborted."
+            );
+            assert!(
+                weights.iter().all(|weight| *weight >= Decimal::zero()),
+                "Invalid weights, should be positive or zero. Swap op aborted."
+            );
+            let total_weight = weights.iter().fold(Decimal::zero(), |total, weight| total + *weight);
+            assert!(total_weight > Decimal::zero(), "Invalid weights, at least one should be positive. Swap op aborted.");
+
+            let pools = Router::pools_of_pair(&pool_addrs, bucket.resource_address());
+            let total_amount = bucket.amount();
+            let mut output: Option<Bucket> = None;
+            let mut remainder = Bucket::new(bucket.resource_address());
+            for (index, (pool, weight)) in pools.iter().zip(weights).enumerate() {
+                let part = if index == pools.len() - 1 {
+                    bucket.take(bucket.amount())
+                } else {
+                    bucket.take(total_amount * weight / total_weight)
+                };
+                if part.is_empty() {
+                    remainder.put(part);
+                    continue;
+                }
+                let (swapped, part_remainder) = pool.swap(part);
+                Router::put_output(&mut output, swapped);
+                remainder.put(part_remainder);
+            }
+            remainder.put(bucket);
+
+            (output.expect("No amount was swapped. Swap op aborted."), remainder)
+        }
+
+        /**
+         * Swaps the provided amount across the given pools in chunks of equal size, each chunk being swapped in the pool with the best
+         * price at that moment, fee included. As the pools prices move with each swapped chunk, the amount is allocated greedily to
+         * the pools until their prices are close, so more chunks give a better allocation at a higher cost.
+         *
+         * Returns the swapped amount and the remainder of the provided amount, if the pools run out of liquidity.
+         */
+        pub fn swap_greedy(mut bucket: Bucket, pool_addrs: Vec<ComponentAddress>, chunks: u32) -> (Bucket, Bucket) {
+            assert!(!pool_addrs.is_empty(), "No pools provided. Swap op aborted.");
+            assert!(chunks > 0, "Invalid chunks, should be positive. Swap op aborted.");
+
+            let pools = Router::pools_of_pair(&pool_addrs, bucket.resource_address());
+            let chunk_amount = bucket.amount() / Decimal::from(chunks);
+            let mut output: Option<Bucket> = None;
+            let mut remainder = Bucket::new(bucket.resource_address());
+            for chunk_index in 0..chunks {
+                let chunk = if chunk_index == chunks - 1 {
+                    bucket.take(bucket.amount())
+                } else {
+                    bucket.take(chunk_amount)
+                };
+                if chunk.is_empty() {
+                    remainder.put(chunk);
+                    continue;
+                }
+                let pool = Router::best_price_pool(&pools, chunk.resource_address());
+                let (swapped, chunk_remainder) = pool.swap(chunk);
+                Router::put_output(&mut output, swapped);
+                remainder.put(chunk_remainder);
+            }
+            remainder.put(bucket);
+
+            (output.expect("No amount was swapped. Swap op aborted."), remainder)
+        }
+
+        /**
+         * Returns the pools, validating that they are pools of the same pair of resources, containing the resource to swap
+         */
+        fn pools_of_pair(pool_addrs: &[ComponentAddress], resource_addr: ResourceAddress) -> Vec<Global<Pool>> {
+            let pools: Vec<Global<Pool>> = pool_addrs.iter().map(|pool_addr| Global::<Pool>::from(*pool_addr)).collect();
+            let first_state = pools[0].get_pool_state();
+            let pair = (first_state.resource0_addr, first_state.resource1_addr);
+            assert!(
+                resource_addr == pair.0 || resource_addr == pair.1,
+                "Wrong resource type sent. Swap op aborted."
+            );
+            for pool in &pools[1..] {
+                let state = pool.get_pool_state();
+                assert!(
+                    (state.resource0_addr, state.resource1_addr) == pair || (state.resource1_addr, state.resource0_addr) == pair,
+                    "The pools should have the same pair of resources. Swap op aborted."
+                );
+            }
+            pools
+        }
+
+        /**
+         * Returns the pool with the best marginal price for swapping the given resource, the fee included: the price of the swapped
+         * resource vs the other resource, multiplied by 1 - fee. The pools without active liquidity are skipped.
+         */
+        fn best_price_pool(pools: &[Global<Pool>], resource_addr: ResourceAddress) -> Global<Pool> {
+            let mut best: Option<(Decimal, Global<Pool>)> = None;
+            for pool in pools {
+                let state = pool.get_pool_state();
+                if state.active_liquidity == Decimal::zero() {
+                    continue;
+                }
+                // the pool price is the price of resource0 vs resource1
+                let price = state.sqrt_price.0 * state.sqrt_price.0;
+                let price = if resource_addr == state.resource0_addr { price } else { Decimal::one() / price };
+                let net_price = price * (Decimal::one() - state.fee);
+                if best.as_ref().map_or(true, |(best_price, _)| net_price > *best_price) {
+                    best = Some((net_price, *pool));
+                }
+            }
+            match best {
+                Some((_, pool)) => pool,
+                None => pools[0],
+            }
+        }
+
+        fn put_output(output: &mut Option<Bucket>, swapped: Bucket) {
+            match output {
+                Some(output) => output.put(swapped),
+                None => *output = Some(swapped),
+            }
+        }
+    }
+}
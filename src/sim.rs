@@ -0,0 +1,417 @@
+use radix_engine_common::prelude::*;
+use crate::pool_math;
+use crate::swap_math;
+use crate::tick_math::{self, SqrtPrice, Tick, TickRange};
+
+/**
+ * A used tick of a PoolSim, as the TickState of the pool: the liquidity added to the live liquidity when the tick is crossed up (and
+ * subtracted when crossed down), the liquidity of the positions using it and the fees accumulated outside of it
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimTick {
+    pub liq_net: Decimal,
+    pub liq_gross: Decimal,
+    pub fee_outside0: Decimal,
+    pub fee_outside1: Decimal,
+}
+
+/**
+ * A position of a PoolSim, as the Position of the pool: its liquidity, range and the range fees already collected
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimPosition {
+    pub liq: Decimal,
+    pub low_tick: i32,
+    pub high_tick: i32,
+    pub range_fee0: Decimal,
+    pub range_fee1: Decimal,
+}
+
+/**
+ * An off-ledger simulation of a pool, with the same add position, remove position and swap math as the pool blueprint, e.g. for the
+ * bots and the route finders evaluating many hypothetical trades without a transaction each. The swaps of a pool can be quoted on a
+ * clone of the simulation, see PoolSim::quote_swap.
+ *
+ * The fields mirror the on-ledger state, so a simulation of a live pool is built from Pool::get_pool_state, for the price, the live
+ * liquidity, the global fees and the vault amounts, and from Pool::get_tick_states, for the used ticks. The protocol fee share is
+ * the share of the swap fees kept by the protocol, zero if the pool protocol fee switch is off. The divisibilities are the ones of the
+ * pool resources, 18 by default, the amounts paid in being rounded up and the amounts paid out rounded down to them, as on ledger.
+ * The positions are identified by sequential ids, the ones of the pool NFTs being known only on ledger.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolSim {
+    pub sqrt_price: Decimal,
+    pub tick: i32,
+    pub live_liq: Decimal,
+    pub fee: Decimal,
+    pub protocol_fee_share: Decimal,
+    pub tick_spacing: i32,
+    pub divisibility0: u8,
+    pub divisibility1: u8,
+    pub fee_global0: Decimal,
+    pub fee_global1: Decimal,
+    pub amount0: Decimal,
+    pub amount1: Decimal,
+    pub ticks: BTreeMap<i32, SimTick>,
+    pub positions: BTreeMap<u64, SimPosition>,
+    pub next_pos_id: u64,
+}
+
+impl PoolSim {
+    /**
+     * Creates the simulation of an empty pool with the given fee, tick spacing and sqrt price, for resources of 18 decimals, e.g.
+     * PoolSim { divisibility1: 6, ..PoolSim::new(..) } for a 6 decimals resource1
+     */
+    pub fn new(fee: Decimal, tick_spacing: i32, sqrt_price: SqrtPrice) -> PoolSim {
+        assert!(fee >= Decimal::zero() && fee <= Decimal::one(), "Invalid fee, should be 0 <= fee <= 1");
+        assert!(tick_spacing > 0, "Invalid tick spacing, should be positive.");
+        let tick = match tick_math::try_tick_at_sqrt_price(sqrt_price) {
+            Ok(tick) => tick.0,
+            Err(err) => panic!("Invalid sqrt price: {}", err),
+        };
+        PoolSim {
+            sqrt_price: sqrt_price.0,
+            tick,
+            live_liq: Decimal::zero(),
+            fee,
+            protocol_fee_share: Decimal::zero(),
+            tick_spacing,
+            divisibility0: Decimal::SCALE as u8,
+            divisibility1: Decimal::SCALE as u8,
+            fee_global0: Decimal::zero(),
+            fee_global1: Decimal::zero(),
+            amount0: Decimal::zero(),
+            amount1: Decimal::zero(),
+            ticks: BTreeMap::new(),
+            positions: BTreeMap::new(),
+            next_pos_id: 0,
+        }
+    }
+
+    /**
+     * Adds a position on [low_tick, high_tick) with at most the given amounts, as Pool::add_pos.
+     *
+     * Returns the position id and the amount0,1 used, the rest being left to the caller
+     */
+    pub fn add_pos(&mut self, amount0: Decimal, amount1: Decimal, low_tick: i32, high_tick: i32) -> (u64, Decimal, Decimal) {
+        let range = TickRange::new(Tick(low_tick), Tick(high_tick), self.tick_spacing);
+        let (liq, required_amount0, required_amount1) = pool_math::compute_range_liq_given_amounts(
+            amount0,
+            amount1,
+            self.sqrt_price,
+            tick_math::sqrt_price_at_tick(range.low()).0,
+            tick_math::sqrt_price_at_tick(range.high()).0,
+        );
+        let required_amount0 = pool_math::round_up_to_divisibility(required_amount0, self.divisibility0).min(amount0);
+        let required_amount1 = pool_math::round_up_to_divisibility(required_amount1, self.divisibility1).min(amount1);
+
+        self.update_live_liq(liq, low_tick, high_tick);
+        self.update_tick(low_tick, liq, false);
+        self.update_tick(high_tick, liq, true);
+        let (range_fee0, range_fee1) = self.range_fees(low_tick, high_tick);
+
+        self.amount0 += required_amount0;
+        self.amount1 += required_amount1;
+        let pos_id = self.next_pos_id;
+        self.positions.insert(
+            pos_id,
+            SimPosition {
+                liq,
+                low_tick,
+                high_tick,
+                range_fee0,
+                range_fee1,
+            },
+        );
+        self.next_pos_id += 1;
+
+        (pos_id, required_amount0, required_amount1)
+    }
+
+    /**
+     * Removes the given position, as Pool::remove_pos.
+     *
+     * Returns the amount0,1 of its liquidity, its fees included
+     */
+    pub fn remove_pos(&mut self, pos_id: u64) -> (Decimal, Decimal) {
+        let liq = self.position(pos_id).liq;
+        self.remove_liq(pos_id, liq)
+    }
+
+    /**
+     * Collects the fees of the given position, as Pool::collect_fees
+     */
+    pub fn collect_fees(&mut self, pos_id: u64) -> (Decimal, Decimal) {
+        self.remove_liq(pos_id, Decimal::zero())
+    }
+
+    /**
+     * Returns the uncollected fees of the given position
+     */
+    pub fn pos_fees(&self, pos_id: u64) -> (Decimal, Decimal) {
+        let pos = self.position(pos_id);
+        let (range_fee0, range_fee1) = self.range_fees(pos.low_tick, pos.high_tick);
+        pool_math::compute_pos_fees(pos.liq, pos.range_fee0, pos.range_fee1, range_fee0, range_fee1)
+    }
+
+    /**
+     * Swaps the given amount of token0, if is_token0, or token1 for the other token, as Pool::swap with the static pool fee.
+     *
+     * Returns the output amount and the remainder of the given amount, if the liquidity runs out
+     */
+    pub fn swap(&mut self, amount: Decimal, is_token0: bool) -> (Decimal, Decimal) {
+        assert!(amount >= Decimal::zero(), "Invalid amount, should be positive or zero.");
+        let mut available_amount = amount;
+        let mut total_swapped_amount = Decimal::zero();
+        let mut total_protocol_fee_amount = Decimal::zero();
+        while available_amount > Decimal::zero() && self.live_liq > Decimal::zero() {
+            //the price lies in [sqrt_price_at_tick(self.tick), sqrt_price_at_tick(self.tick + 1)), so moving down the current tick
+            //itself is the first one to cross
+            let opt_tick_to_cross = if is_token0 {
+                self.ticks.range(..=self.tick).next_back().map(|(tick, _)| *tick)
+            } else {
+                self.ticks.range(self.tick + 1..).next().map(|(tick, _)| *tick)
+            };
+            let tick_to_cross = match opt_tick_to_cross {
+                Some(tick_to_cross) => tick_to_cross,
+                None => break,
+            };
+
+            let step = swap_math::compute_swap_step(
+                self.live_liq,
+                self.sqrt_price,
+                tick_math::sqrt_price_at_tick(Tick(tick_to_cross)).0,
+                available_amount,
+                self.fee,
+            );
+            available_amount = available_amount - step.amount_in - step.fee_amount;
+            total_swapped_amount += step.amount_out;
+            let previous_sqrt_price = self.sqrt_price;
+            self.sqrt_price = step.sqrt_price_next;
+
+            let protocol_fee_amount = step.fee_amount * self.protocol_fee_share;
+            total_protocol_fee_amount += protocol_fee_amount;
+            let liq_unit_fee = (step.fee_amount - protocol_fee_amount) / self.live_liq;
+            if is_token0 {
+                self.fee_global0 += liq_unit_fee;
+            } else {
+                self.fee_global1 += liq_unit_fee;
+            }
+
+            if step.reached_target {
+                self.cross_tick(tick_to_cross);
+            } else if self.sqrt_price != previous_sqrt_price {
+                self.tick = PoolSim::tick_below_or_at(self.sqrt_price, is_token0, tick_to_cross);
+            }
+        }
+
+        //as the pool, the input is rounded up and the output and the protocol fees rounded down, the dust staying in the pool
+        let (divisibility_in, divisibility_out) = if is_token0 {
+            (self.divisibility0, self.divisibility1)
+        } else {
+            (self.divisibility1, self.divisibility0)
+        };
+        let to_deduct_amount = pool_math::round_up_to_divisibility(amount - available_amount, divisibility_in);
+        let total_swapped_amount = pool_math::round_down_to_divisibility(total_swapped_amount, divisibility_out);
+        let total_protocol_fee_amount = pool_math::round_down_to_divisibility(total_protocol_fee_amount, divisibility_in);
+        let kept_amount = to_deduct_amount - total_protocol_fee_amount;
+        if is_token0 {
+            self.amount0 += kept_amount;
+            self.amount1 -= total_swapped_amount;
+        } else {
+            self.amount1 += kept_amount;
+            self.amount0 -= total_swapped_amount;
+        }
+
+        (total_swapped_amount, amount - to_deduct_amount)
+    }
+
+    /**
+     * Quotes the swap of the given amount on a clone of the simulation, without modifying it, see PoolSim::swap
+     */
+    pub fn quote_swap(&self, amount: Decimal, is_token0: bool) -> (Decimal, Decimal) {
+        self.clone().swap(amount, is_token0)
+    }
+
+    fn position(&self, pos_id: u64) -> &SimPosition {
+        self.positions.get(&pos_id).expect("No position exists for given position id.")
+    }
+
+    fn remove_liq(&mut self, pos_id: u64, liq: Decimal) -> (Decimal, Decimal) {
+        let (low_tick, high_tick) = {
+            let pos = self.position(pos_id);
+            (pos.low_tick, pos.high_tick)
+        };
+        let (range_fee0, range_fee1) = self.range_fees(low_tick, high_tick);
+        let (pos_fee0, pos_fee1) = self.pos_fees(pos_id);
+
+        let pos = self.positions.get_mut(&pos_id).unwrap();
+        pos.liq -= liq;
+        pos.range_fee0 = range_fee0;
+        pos.range_fee1 = range_fee1;
+        if pos.liq == Decimal::zero() {
+            self.positions.remove(&pos_id);
+        }
+
+        self.update_live_liq(-liq, low_tick, high_tick);
+        self.update_tick(low_tick, -liq, false);
+        self.update_tick(high_tick, -liq, true);
+
+        let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
+            liq,
+            self.sqrt_price,
+            tick_math::sqrt_price_at_tick(Tick(low_tick)).0,
+            tick_math::sqrt_price_at_tick(Tick(high_tick)).0,
+        );
+        //as the pool, rounded down to the divisibilities and never more than the vault amounts, which the rounding errors could exceed
+        let total_amount0 = pool_math::round_down_to_divisibility(amount0 + pos_fee0, self.divisibility0).min(self.amount0);
+        let total_amount1 = pool_math::round_down_to_divisibility(amount1 + pos_fee1, self.divisibility1).min(self.amount1);
+        self.amount0 -= total_amount0;
+        self.amount1 -= total_amount1;
+
+        (total_amount0, total_amount1)
+    }
+
+    fn range_fees(&self, low_tick: i32, high_tick: i32) -> (Decimal, Decimal) {
+        let low = self.ticks.get(&low_tick).unwrap();
+        let high = self.ticks.get(&high_tick).unwrap();
+        pool_math::compute_range_fees_given_outside(
+            self.tick,
+            (self.fee_global0, self.fee_global1),
+            (low_tick, low.fee_outside0, low.fee_outside1),
+            (high_tick, high.fee_outside0, high.fee_outside1),
+        )
+    }
+
+    fn update_live_liq(&mut self, liq: Decimal, low_tick: i32, high_tick: i32) {
+        if self.tick >= low_tick && self.tick < high_tick {
+            self.live_liq += liq;
+        }
+    }
+
+    //as TickState::modify_liq, the fees of a new tick below the current tick are all outside of it, and the unused ticks are removed
+    fn update_tick(&mut self, tick: i32, liq: Decimal, is_high_tick: bool) {
+        let (current_tick, fee_global0, fee_global1) = (self.tick, self.fee_global0, self.fee_global1);
+        let state = self.ticks.entry(tick).or_insert_with(|| {
+            let (fee_outside0, fee_outside1) = if tick <= current_tick {
+                (fee_global0, fee_global1)
+            } else {
+                (Decimal::zero(), Decimal::zero())
+            };
+            SimTick {
+                liq_net: Decimal::zero(),
+                liq_gross: Decimal::zero(),
+                fee_outside0,
+                fee_outside1,
+            }
+        });
+        state.liq_net += if is_high_tick { -liq } else { liq };
+        state.liq_gross += liq;
+        if liq < Decimal::zero() && state.liq_gross == Decimal::zero() {
+            self.ticks.remove(&tick);
+        }
+    }
+
+    fn cross_tick(&mut self, tick: i32) {
+        let cross_up = self.tick < tick;
+        let state = self.ticks.get_mut(&tick).unwrap();
+        self.live_liq = if cross_up {
+            self.live_liq + state.liq_net
+        } else {
+            self.live_liq - state.liq_net
+        };
+        state.fee_outside0 = self.fee_global0 - state.fee_outside0;
+        state.fee_outside1 = self.fee_global1 - state.fee_outside1;
+        self.tick = if cross_up { tick } else { tick - 1 };
+    }
+
+    //as Pool::tick_below_or_at, the tick of a price reached without crossing tick_to_cross stays on the same side of it
+    fn tick_below_or_at(sqrt_price: Decimal, is_token0: bool, tick_to_cross: i32) -> i32 {
+        let mut tick = tick_math::tick_at_sqrt_price(SqrtPrice(sqrt_price)).0;
+        if tick_math::sqrt_price_at_tick(Tick(tick)).0 > sqrt_price {
+            tick -= 1;
+        }
+        if is_token0 {
+            tick.max(tick_to_cross)
+        } else {
+            tick.min(tick_to_cross - 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_sim() -> PoolSim {
+        let mut sim = PoolSim::new(dec!("0.003"), 1, SqrtPrice(Decimal::one()));
+        sim.add_pos(dec!("10000"), dec!("10000"), -1000, 1000);
+        sim
+    }
+
+    #[test]
+    fn test_swap_round_trip() {
+        let mut sim = new_sim();
+        let (amount1, remainder) = sim.swap(dec!("100"), true);
+        assert_eq!(Decimal::zero(), remainder);
+        assert!(amount1 < dec!("100") && amount1 > dec!("99"));
+        assert!(sim.sqrt_price < Decimal::one());
+
+        //the fees are paid on both swaps, so the price doesn't get back to 1
+        let (amount0, _) = sim.swap(amount1, false);
+        assert!(amount0 < dec!("100"));
+        assert!(sim.sqrt_price < Decimal::one());
+    }
+
+    #[test]
+    fn test_quote_swap() {
+        let sim = new_sim();
+        let quote = sim.quote_swap(dec!("100"), false);
+        let mut swapped_sim = sim.clone();
+        assert_eq!(quote, swapped_sim.swap(dec!("100"), false));
+        assert_eq!(new_sim(), sim);
+    }
+
+    #[test]
+    fn test_swap_crosses_ticks_and_runs_out() {
+        let mut sim = new_sim();
+        let (pos_id, _, _) = sim.add_pos(dec!("100"), dec!("100"), -20, 20);
+        let live_liq = sim.live_liq;
+
+        sim.swap(dec!("500"), true);
+        assert!(sim.tick < -20);
+        assert_eq!(live_liq - sim.positions[&pos_id].liq, sim.live_liq);
+
+        let (_, remainder) = sim.swap(dec!("100000"), true);
+        assert!(remainder > Decimal::zero());
+        assert_eq!(Decimal::zero(), sim.live_liq);
+    }
+
+    #[test]
+    fn test_divisibility_rounding() {
+        let mut sim = PoolSim { divisibility1: 6, ..PoolSim::new(dec!("0.003"), 1, SqrtPrice(Decimal::one())) };
+        let (_, _, amount1) = sim.add_pos(dec!("10000"), dec!("10000"), -1000, 1000);
+        assert_eq!(amount1, pool_math::round_down_to_divisibility(amount1, 6));
+
+        let (amount1, remainder) = sim.swap(dec!("100"), true);
+        assert_eq!(Decimal::zero(), remainder);
+        assert_eq!(amount1, pool_math::round_down_to_divisibility(amount1, 6));
+        assert_eq!(sim.amount1, pool_math::round_down_to_divisibility(sim.amount1, 6));
+    }
+
+    #[test]
+    fn test_fees_and_removal() {
+        let mut sim = new_sim();
+        let (pos_id, amount0, amount1) = sim.add_pos(dec!("100"), dec!("100"), -1000, 1000);
+        sim.swap(dec!("1000"), true);
+
+        let (fee0, fee1) = sim.collect_fees(pos_id);
+        assert!(fee0 > Decimal::zero() && fee0 < dec!("3"));
+        assert_eq!(Decimal::zero(), fee1);
+        assert_eq!((Decimal::zero(), Decimal::zero()), sim.pos_fees(pos_id));
+
+        let (removed0, removed1) = sim.remove_pos(pos_id);
+        assert!(removed0 > amount0 && removed1 < amount1);
+        assert!(!sim.positions.contains_key(&pos_id));
+    }
+}
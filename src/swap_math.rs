@@ -0,0 +1,146 @@
+use radix_engine_common::prelude::*;
+use crate::pool_math;
+
+/**
+ * The result of a swap step: the sqrt price where the step stopped, the input amount that moved the price (fee excluded), the output
+ * amount, the fee charged on top of the input amount and whether the target sqrt price was reached.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next: Decimal,
+    pub amount_in: Decimal,
+    pub amount_out: Decimal,
+    pub fee_amount: Decimal,
+    pub reached_target: bool,
+}
+
+/**
+ * Computes a single swap step, moving the price from sqrt_price towards target_sqrt_price with the given liquidity, which is constant
+ * in between, e.g. up to the next used tick. The swap direction is given by the target: token0 is swapped for token1 if
+ * target_sqrt_price <= sqrt_price (the price goes down), token1 for token0 otherwise (the price goes up).
+ *
+ * The fee is charged on top of the amount that moves the price, so the amount needed to reach the target is
+ * amount_to_target / (1 - fee). If amount_remaining is greater than it, the step stops exactly on the target, otherwise the whole
+ * amount_remaining is swapped and the step stops before the target. With a fee of 1 the whole amount_remaining is a fee and the
+ * price doesn't move.
+ */
+pub fn compute_swap_step(
+    liq: Decimal,
+    sqrt_price: Decimal,
+    target_sqrt_price: Decimal,
+    amount_remaining: Decimal,
+    fee: Decimal,
+) -> SwapStep {
+    let is_token0 = target_sqrt_price <= sqrt_price;
+
+    let amount_to_target = if is_token0 {
+        pool_math::compute_range_amount0_given_liq(liq, target_sqrt_price, sqrt_price)
+    } else {
+        pool_math::compute_range_amount1_given_liq(liq, sqrt_price, target_sqrt_price)
+    };
+    let amount_to_target = if fee < Decimal::one() {
+        amount_to_target / (Decimal::one() - fee)
+    } else {
+        amount_to_target
+    };
+
+    let reached_target = fee < Decimal::one() && amount_to_target < amount_remaining;
+    let amount = if reached_target { amount_to_target } else { amount_remaining };
+    let fee_amount = amount * fee;
+    let amount_in = amount - fee_amount;
+
+    let (new_sqrt_price, amount_out) = if is_token0 {
+        pool_math::compute_swap_amount0_price_and_amount1(liq, sqrt_price, amount_in)
+    } else {
+        pool_math::compute_swap_amount1_price_and_amount0(liq, sqrt_price, amount_in)
+    };
+
+    SwapStep {
+        //the price lands exactly on the target, even if the amounts computation rounded
+        sqrt_price_next: if reached_target { target_sqrt_price } else { new_sqrt_price },
+        amount_in,
+        amount_out,
+        fee_amount,
+        reached_target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick_math::{self, Tick};
+
+    #[test]
+    fn swap_step_doesnt_reach_target() {
+        let step = compute_swap_step(dec!("1000"), Decimal::one(), tick_math::sqrt_price_at_tick(Tick(-100)).0, dec!("1"), dec!("0.003"));
+        assert_eq!(
+            SwapStep {
+                sqrt_price_next: dec!("0.999003993018960096"),
+                amount_in: dec!("0.997"),
+                amount_out: dec!("0.996006981039904"),
+                fee_amount: dec!("0.003"),
+                reached_target: false,
+            },
+            step
+        );
+
+        let step = compute_swap_step(dec!("1000"), Decimal::one(), tick_math::sqrt_price_at_tick(Tick(100)).0, dec!("1"), dec!("0.003"));
+        assert_eq!(
+            SwapStep {
+                sqrt_price_next: dec!("1.000997"),
+                amount_in: dec!("0.997"),
+                amount_out: dec!("0.996006981039904"),
+                fee_amount: dec!("0.003"),
+                reached_target: false,
+            },
+            step
+        );
+    }
+
+    #[test]
+    fn swap_step_reaches_target() {
+        let target_sqrt_price = tick_math::sqrt_price_at_tick(Tick(-100)).0;
+        let step = compute_swap_step(dec!("1000"), Decimal::one(), target_sqrt_price, dec!("10"), dec!("0.003"));
+        assert_eq!(
+            SwapStep {
+                sqrt_price_next: target_sqrt_price,
+                amount_in: dec!("5.012269623051206396"),
+                amount_out: dec!("4.987272070749099"),
+                fee_amount: dec!("0.015082055034256388"),
+                reached_target: true,
+            },
+            step
+        );
+
+        let target_sqrt_price = tick_math::sqrt_price_at_tick(Tick(100)).0;
+        let step = compute_swap_step(dec!("1000"), Decimal::one(), target_sqrt_price, dec!("10"), dec!("0.003"));
+        assert_eq!(
+            SwapStep {
+                sqrt_price_next: target_sqrt_price,
+                amount_in: dec!("5.012269623051113"),
+                amount_out: dec!("4.987272070749007"),
+                fee_amount: dec!("0.015082055034256107"),
+                reached_target: true,
+            },
+            step
+        );
+    }
+
+    #[test]
+    fn swap_step_with_the_price_on_the_target() {
+        let step = compute_swap_step(dec!("1000"), Decimal::one(), Decimal::one(), dec!("1"), dec!("0.003"));
+        assert_eq!(Decimal::one(), step.sqrt_price_next);
+        assert_eq!(Decimal::zero(), step.amount_in);
+        assert_eq!(Decimal::zero(), step.amount_out);
+        assert!(step.reached_target);
+    }
+
+    #[test]
+    fn swap_step_with_fee_one_doesnt_move_the_price() {
+        let step = compute_swap_step(dec!("1000"), Decimal::one(), tick_math::sqrt_price_at_tick(Tick(-100)).0, dec!("1"), Decimal::one());
+        assert_eq!(Decimal::one(), step.sqrt_price_next);
+        assert_eq!(Decimal::zero(), step.amount_in);
+        assert_eq!(Decimal::one(), step.fee_amount);
+        assert!(!step.reached_target);
+    }
+}
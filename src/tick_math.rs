@@ -1,135 +1,154 @@
-use scrypto::prelude::*;
+use radix_engine_common::prelude::*;
 
 //smallest decimal: 0.000000000000000001 (10 ^ -18) -> smallest tick: -828972, but effectively for us smallest price is 0.00000000000001985 with min tick -631042
 //as we can't have enough precision under this values: e.g. for tick -631043 the price would be 0.000000000000019849 as we don't have enough decimal places to represent it
 //we stop for now at decimal: 170141183460469231731.687303715884105727 (2^127 - 1) * 10 ^ -18 -> largest tick: 931709 -> real max 170134484377190040957.155711420855095752, but this limit can be increased
-const MIN_TICK: i32 = -631042;
-const MAX_TICK: i32 = 931709;
-
-//0.00000000000001985
-const MIN_PRICE: Decimal = Decimal(bnum_integer::I192::from_digits([19850, 0, 0]));
-//1.000049998750062496 = √1.0001^(2^0)
-const PRICE_0X1: Decimal = Decimal(bnum_integer::I192::from_digits([1000049998750062496, 0, 0]));
-//1.0001 = √1.0001^(2^1)
-const PRICE_0X2: Decimal = Decimal(bnum_integer::I192::from_digits([1000100000000000000, 0, 0]));
-//1.00020001 = √1.0001^(2^2)
-const PRICE_0X4: Decimal = Decimal(bnum_integer::I192::from_digits([1000200010000000000, 0, 0]));
-//1.000400060004000093 = √1.0001^(2^3)
-const PRICE_0X8: Decimal = Decimal(bnum_integer::I192::from_digits([1000400060004000093, 0, 0]));
-//1.000800280056006986 = √1.0001^(2^4)
-const PRICE_0X10: Decimal = Decimal(bnum_integer::I192::from_digits([1000800280056006986, 0, 0]));
-//1.001601200560182014 = √1.0001^(2^5)
-const PRICE_0X20: Decimal = Decimal(bnum_integer::I192::from_digits([1001601200560182014, 0, 0]));
-//1.003204964963597955 = √1.0001^(2^6)
-const PRICE_0X40: Decimal = Decimal(bnum_integer::I192::from_digits([1003204964963597955, 0, 0]));
-//1.006420201727613800 = √1.0001^(2^7)
-const PRICE_0X80: Decimal = Decimal(bnum_integer::I192::from_digits([1006420201727613800, 0, 0]));
-//1.012881622445450855 = √1.0001^(2^8)
-const PRICE_0X100: Decimal = Decimal(bnum_integer::I192::from_digits([1012881622445450855, 0, 0]));
-//1.025929181087728853 = √1.0001^(2^9)
-const PRICE_0X200: Decimal = Decimal(bnum_integer::I192::from_digits([1025929181087728853, 0, 0]));
-//1.052530684607337941 = √1.0001^(2^10)
-const PRICE_0X400: Decimal = Decimal(bnum_integer::I192::from_digits([1052530684607337941, 0, 0]));
-//1.107820842039991493 = √1.0001^(2^11)
-const PRICE_0X800: Decimal = Decimal(bnum_integer::I192::from_digits([1107820842039991493, 0, 0]));
-//1.227267018058195782 = √1.0001^(2^12)
-const PRICE_0X1000: Decimal = Decimal(bnum_integer::I192::from_digits([1227267018058195782, 0, 0]));
-//1.506184333613455851 = √1.0001^(2^13)
-const PRICE_0X2000: Decimal = Decimal(bnum_integer::I192::from_digits([1506184333613455851, 0, 0]));
-//2.268591246822610072 = √1.0001^(2^14)
-const PRICE_0X4000: Decimal = Decimal(bnum_integer::I192::from_digits([2268591246822610072, 0, 0]));
-//5.146506245160164533 = √1.0001^(2^15)
-const PRICE_0X8000: Decimal = Decimal(bnum_integer::I192::from_digits([5146506245160164533, 0, 0]));
-//26.486526531472575563 = √1.0001^(2^16)
-const PRICE_0X10000: Decimal = Decimal(bnum_integer::I192::from_digits([8039782457763023947, 1, 0]));
-//701.536087702400664335 = √1.0001^(2^17)
-const PRICE_0X20000: Decimal = Decimal(bnum_integer::I192::from_digits([559812901437702927, 38, 0]));
-//492152.882348790396620919 = √1.0001^(2^18)
-const PRICE_0X40000: Decimal = Decimal(bnum_integer::I192::from_digits([12197206293269057655, 26679, 0]));
-//242214459604.222321943471435452 = √1.0001^(2^19)
-const PRICE_0X80000: Decimal = Decimal(bnum_integer::I192::from_digits([9500346154952666812, 13130472165, 0]));
-//170134484377190040957.155711420855095752
-const MAX_PRICE: Decimal = Decimal(bnum_integer::I192::from_digits([4809668506064654792, 9223008878822527810, 0]));
+pub const MIN_TICK: i32 = -631042;
+pub const MAX_TICK: i32 = 931709;
+//the price (sqrt_price ^ 2) has a narrower tick range: under tick -322517 the prices of consecutive ticks are equal when truncated
+//to 18 decimals, e.g. 0.000000000000009861 for both -322517 and -322518, while above tick 909491 the price doesn't fit in a Decimal
+pub(crate) const MIN_PRICE_TICK: i32 = -322517;
+pub(crate) const MAX_PRICE_TICK: i32 = 909491;
 
-/** 
- * By definition, sqrt_price = sqrt(1.0001) ^ tick, but tick is always a sum of powers of 2, e.g. 7 = 2^0 + 2^1 + 2^2,
- * So, sqrt_price = sqrt(1.0001) ^ (2 ^ a + 2 ^ b + ...) = sqrt(1.0001) ^ (2 ^ a) * sqrt(1.0001) ^ (2 ^ b) * ...
- * Where  a,b,... are uniques values in interval [0, 19], given the max tick value of 931709.
- * 
- * So, the algorithm bellow decompose the given tick in a power of 2 sum, and for each power of 2, it multiplies 
- * the sqrt_price with the corresponding pre-computed sqrt_price from the constants above. This is the sqrt_price we are looking for.
- */ 
-pub fn sqrt_price_at_tick(tick: i32) -> Decimal {
-    assert!(tick >= MIN_TICK && tick <= MAX_TICK, "Tick out of bounds.");
+/**
+ * A tick, the price at tick i being 1.0001 ^ i
+ */
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[sbor(transparent)]
+pub struct Tick(pub i32);
 
-    let abs_tick = if tick >= 0 { tick } else { -tick };
-    let mut sqrt_price = Decimal::one();
+/**
+ * The square root of the price of token0 vs token1
+ */
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq, PartialOrd)]
+#[sbor(transparent)]
+pub struct SqrtPrice(pub Decimal);
 
-    if abs_tick & 0x1 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X1;
-    }
-    if abs_tick & 0x2 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X2;
-    }
-    if abs_tick & 0x4 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X4;
-    }
-    if abs_tick & 0x8 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X8;
-    }
-    if abs_tick & 0x10 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X10;
-    }
-    if abs_tick & 0x20 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X20;
-    }
-    if abs_tick & 0x40 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X40;
-    }
-    if abs_tick & 0x80 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X80;
-    }
-    if abs_tick & 0x100 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X100;
-    }
-    if abs_tick & 0x200 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X200;
-    }
-    if abs_tick & 0x400 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X400;
-    }
-    if abs_tick & 0x800 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X800;
+/**
+ * The errors returned by the try_ variants of the tick math functions
+ */
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickMathError {
+    TickOutOfBounds(Tick),
+    SqrtPriceOutOfBounds(SqrtPrice),
+    InvalidTickRange(Tick, Tick),
+    TickNotOnSpacing(Tick, i32),
+}
+
+impl core::fmt::Display for TickMathError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TickMathError::TickOutOfBounds(_) => write!(f, "Tick out of bounds."),
+            TickMathError::SqrtPriceOutOfBounds(_) => write!(f, "Sqrt price out of bounds."),
+            TickMathError::InvalidTickRange(_, _) => write!(f, "Lower tick must be less than upper tick."),
+            TickMathError::TickNotOnSpacing(_, _) => write!(f, "Tick must be a multiple of the tick spacing."),
+        }
     }
-    if abs_tick & 0x1000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X1000;
+}
+
+/**
+ * A range of ticks [low, high), validated at construction: MIN_TICK <= low < high <= MAX_TICK and both ticks multiples of the tick
+ * spacing. The ticks are private, so a TickRange built with the constructors is always valid, but a TickRange decoded from a
+ * transaction argument must be validated again with validate.
+ */
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TickRange {
+    low: Tick,
+    high: Tick,
+}
+
+impl TickRange {
+    pub fn new(low: Tick, high: Tick, spacing: i32) -> TickRange {
+        TickRange::try_new(low, high, spacing).unwrap_or_else(|err| panic!("{}", err))
     }
-    if abs_tick & 0x2000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X2000;
+
+    pub fn try_new(low: Tick, high: Tick, spacing: i32) -> Result<TickRange, TickMathError> {
+        TickRange { low, high }.validate(spacing)
     }
-    if abs_tick & 0x4000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X4000;
+
+    /**
+     * The range between the ticks of the given sqrt prices, the greatest ticks whose sqrt prices are smaller or equal to them.
+     */
+    pub fn try_from_sqrt_prices(low: SqrtPrice, high: SqrtPrice, spacing: i32) -> Result<TickRange, TickMathError> {
+        TickRange::try_new(try_tick_at_sqrt_price(low)?, try_tick_at_sqrt_price(high)?, spacing)
     }
-    if abs_tick & 0x8000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X8000;
+
+    /**
+     * Returns the range if it is valid for the given tick spacing, the first failed check otherwise.
+     */
+    pub fn validate(self, spacing: i32) -> Result<TickRange, TickMathError> {
+        assert!(spacing > 0, "Tick spacing must be positive.");
+        for tick in [self.low, self.high] {
+            if tick.0 < MIN_TICK || tick.0 > MAX_TICK {
+                return Err(TickMathError::TickOutOfBounds(tick));
+            }
+            if tick.0 % spacing != 0 {
+                return Err(TickMathError::TickNotOnSpacing(tick, spacing));
+            }
+        }
+        if self.low >= self.high {
+            return Err(TickMathError::InvalidTickRange(self.low, self.high));
+        }
+        Ok(self)
     }
-    if abs_tick & 0x10000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X10000;
+
+    pub fn low(&self) -> Tick {
+        self.low
     }
-    if abs_tick & 0x20000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X20000;
+
+    pub fn high(&self) -> Tick {
+        self.high
     }
-    if abs_tick & 0x40000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X40000;
+
+    pub fn contains(&self, tick: Tick) -> bool {
+        tick >= self.low && tick < self.high
     }
-    if abs_tick & 0x80000 != 0 {
-        sqrt_price = sqrt_price * PRICE_0X80000;
+}
+
+//0.00000000000001985, the smallest sqrt price, at MIN_TICK
+pub const MIN_PRICE: Decimal = Decimal(bnum_integer::I192::from_digits([19850, 0, 0]));
+//the pre-computed sqrt prices: PRICE_0X<2^k> = √1.0001^(2^k) and INV_PRICE_0X<2^k> = 1 / √1.0001^(2^k) for k in [0, 19], and the
+//PRICES, INV_PRICES arrays indexed by k, generated by build.rs. The inverse prices are used to compute the sqrt_price of the negative
+//ticks directly, without dividing by the sqrt_price of the opposite positive tick, which would carry its rounding error to the result
+include!(concat!(env!("OUT_DIR"), "/price_constants.rs"));
+
+//170134484377190040957.155711420855095752, the largest sqrt price, at MAX_TICK
+pub const MAX_PRICE: Decimal = Decimal(bnum_integer::I192::from_digits([4809668506064654792, 9223008878822527810, 0]));
+
+/** 
+ * By definition, sqrt_price = sqrt(1.0001) ^ tick, but tick is always a sum of powers of 2, e.g. 7 = 2^0 + 2^1 + 2^2,
+ * So, sqrt_price = sqrt(1.0001) ^ (2 ^ a + 2 ^ b + ...) = sqrt(1.0001) ^ (2 ^ a) * sqrt(1.0001) ^ (2 ^ b) * ...
+ * Where  a,b,... are uniques values in interval [0, 19], given the max tick value of 931709.
+ * 
+ * So, the algorithm bellow decompose the given tick in a power of 2 sum, and for each power of 2, it multiplies 
+ * the sqrt_price with the corresponding pre-computed sqrt_price from the constants above. This is the sqrt_price we are looking for.
+ *
+ * For negative ticks, sqrt_price = 1 / sqrt(1.0001) ^ (2 ^ a) * 1 / sqrt(1.0001) ^ (2 ^ b) * ..., so we multiply the inverse constants.
+ * The factors close to 1 are multiplied first, so the truncation errors are scaled down by the smaller factors that follow.
+ */ 
+pub fn sqrt_price_at_tick(tick: Tick) -> SqrtPrice {
+    try_sqrt_price_at_tick(tick).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/**
+ * Same as sqrt_price_at_tick, but returns an error instead of panicking if the tick is not in [MIN_TICK, MAX_TICK].
+ */
+pub fn try_sqrt_price_at_tick(tick: Tick) -> Result<SqrtPrice, TickMathError> {
+    if tick.0 < MIN_TICK || tick.0 > MAX_TICK {
+        return Err(TickMathError::TickOutOfBounds(tick));
     }
+    let tick = tick.0;
 
-    if tick < 0 {
-        sqrt_price = Decimal::one() / sqrt_price;
+    let abs_tick = if tick >= 0 { tick } else { -tick };
+    let prices = if tick >= 0 { &PRICES } else { &INV_PRICES };
+    let mut sqrt_price = Decimal::one();
+
+    for (power, price) in prices.iter().enumerate() {
+        if abs_tick & (1 << power) != 0 {
+            sqrt_price = sqrt_price * *price;
+        }
     }
-    sqrt_price
+    Ok(SqrtPrice(sqrt_price))
 }
 
 /**
@@ -138,8 +157,18 @@ pub fn sqrt_price_at_tick(tick: i32) -> Decimal {
  * 
  * In the end we want to make sure the sqrt_price_at_tick and tick_at_sqrt_price return consistent values, avoiding rounding errors.
  */
-pub fn tick_at_sqrt_price(sqrt_price_in: Decimal) -> i32 {
-    assert!(sqrt_price_in >= MIN_PRICE && sqrt_price_in <= MAX_PRICE, "Sqrt price out of bounds.");
+pub fn tick_at_sqrt_price(sqrt_price: SqrtPrice) -> Tick {
+    try_tick_at_sqrt_price(sqrt_price).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/**
+ * Same as tick_at_sqrt_price, but returns an error instead of panicking if the sqrt price is not in [MIN_PRICE, MAX_PRICE].
+ */
+pub fn try_tick_at_sqrt_price(sqrt_price_in: SqrtPrice) -> Result<Tick, TickMathError> {
+    if sqrt_price_in.0 < MIN_PRICE || sqrt_price_in.0 > MAX_PRICE {
+        return Err(TickMathError::SqrtPriceOutOfBounds(sqrt_price_in));
+    }
+    let sqrt_price_in = sqrt_price_in.0;
 
     let is_negative_tick = sqrt_price_in < Decimal::one();
     let mut sqrt_price = if is_negative_tick { Decimal::one() / sqrt_price_in } else {sqrt_price_in};
@@ -225,10 +254,111 @@ pub fn tick_at_sqrt_price(sqrt_price_in: Decimal) -> i32 {
     }
 
     let tick_candidate = if is_negative_tick { -tick } else { tick };
-    let sqrt_price_tick_candidate = sqrt_price_at_tick(tick_candidate);
-    if sqrt_price_tick_candidate == sqrt_price_in { tick_candidate }
-    else if sqrt_price_tick_candidate > sqrt_price_in { tick_candidate - 1 }
-    else { tick_candidate + 1 }
+    let sqrt_price_tick_candidate = sqrt_price_at_tick(Tick(tick_candidate)).0;
+    if sqrt_price_tick_candidate == sqrt_price_in { Ok(Tick(tick_candidate)) }
+    else if sqrt_price_tick_candidate > sqrt_price_in { Ok(Tick(tick_candidate - 1)) }
+    else { Ok(Tick(tick_candidate + 1)) }
+}
+
+/**
+ * The price at the given tick, 1.0001 ^ tick. It is computed as the square of sqrt_price_at_tick, so it is consistent with the
+ * sqrt_price the pool works with, e.g. the price at tick 1 is 1.000099999999999999 and not 1.0001.
+ */
+pub fn price_at_tick(tick: Tick) -> Decimal {
+    assert!(tick.0 >= MIN_PRICE_TICK && tick.0 <= MAX_PRICE_TICK, "Tick out of bounds.");
+
+    let sqrt_price = sqrt_price_at_tick(tick).0;
+    sqrt_price * sqrt_price
+}
+
+/**
+ * The largest tick whose price_at_tick is smaller or equal to the given price, so the price doesn't need to be exactly the price at
+ * a tick. Prices above the price at MAX_PRICE_TICK return MAX_PRICE_TICK.
+ *
+ * As price_at_tick is increasing in [MIN_PRICE_TICK, MAX_PRICE_TICK], we binary search the tick in this range. This takes ~20
+ * price_at_tick computations, so it is meant for frontends and manifests and not for the swap computations.
+ */
+pub fn tick_at_price(price: Decimal) -> Tick {
+    assert!(price >= price_at_tick(Tick(MIN_PRICE_TICK)), "Price out of bounds.");
+
+    //invariant: price_at_tick(low_tick) <= price < price_at_tick(high_tick)
+    let mut low_tick = MIN_PRICE_TICK;
+    let mut high_tick = MAX_PRICE_TICK + 1;
+    while high_tick - low_tick > 1 {
+        let mid_tick = low_tick + (high_tick - low_tick) / 2;
+        if price_at_tick(Tick(mid_tick)) <= price {
+            low_tick = mid_tick;
+        } else {
+            high_tick = mid_tick;
+        }
+    }
+    Tick(low_tick)
+}
+
+/**
+ * The price at the given tick as shown to the users, when the tick prices are in the smallest units of the tokens, 10^-divisibility0
+ * of token0 and 10^-divisibility1 of token1, e.g. for prices imported from chains counting the amounts in integer units: a token1
+ * smallest unit is worth 10^-divisibility1 token1, so the price in whole tokens is price_at_tick(tick) * 10^(divisibility0 - divisibility1).
+ */
+pub fn tick_to_display_price(tick: Tick, divisibility0: u8, divisibility1: u8) -> Decimal {
+    let price = price_at_tick(tick);
+    if divisibility0 >= divisibility1 {
+        price * pow10(divisibility0 - divisibility1)
+    } else {
+        price / pow10(divisibility1 - divisibility0)
+    }
+}
+
+/**
+ * The inverse of tick_to_display_price: the largest tick whose display price is smaller or equal to the given display price.
+ */
+pub fn display_price_to_tick(display_price: Decimal, divisibility0: u8, divisibility1: u8) -> Tick {
+    let price = if divisibility0 >= divisibility1 {
+        display_price / pow10(divisibility0 - divisibility1)
+    } else {
+        display_price * pow10(divisibility1 - divisibility0)
+    };
+    tick_at_price(price)
+}
+
+fn pow10(exp: u8) -> Decimal {
+    let ten: Decimal = 10.into();
+    (0..exp).fold(Decimal::one(), |value, _| value * ten)
+}
+
+/**
+ * The largest multiple of spacing smaller or equal to the given tick, e.g. -15 with spacing 10 is rounded down to -20.
+ */
+pub fn round_down_to_spacing(tick: Tick, spacing: i32) -> Tick {
+    assert!(spacing > 0, "Tick spacing must be positive.");
+
+    Tick(tick.0.div_euclid(spacing) * spacing)
+}
+
+/**
+ * The smallest multiple of spacing greater or equal to the given tick, e.g. -15 with spacing 10 is rounded up to -10.
+ */
+pub fn round_up_to_spacing(tick: Tick, spacing: i32) -> Tick {
+    let rounded_down = round_down_to_spacing(tick, spacing);
+    if rounded_down == tick { tick } else { Tick(rounded_down.0 + spacing) }
+}
+
+/**
+ * The multiple of spacing closest to the given tick, the ties being rounded up, e.g. -15 with spacing 10 is rounded to -10. The result
+ * is kept in [MIN_TICK, MAX_TICK], so the ticks close to the bounds are rounded to the first multiple of spacing inside the range.
+ */
+pub fn nearest_usable_tick(tick: Tick, spacing: i32) -> Tick {
+    assert!(tick.0 >= MIN_TICK && tick.0 <= MAX_TICK, "Tick out of bounds.");
+
+    let rounded_down = round_down_to_spacing(tick, spacing);
+    let nearest = if 2 * (tick.0 - rounded_down.0) >= spacing { rounded_down.0 + spacing } else { rounded_down.0 };
+    if nearest < MIN_TICK {
+        Tick(nearest + spacing)
+    } else if nearest > MAX_TICK {
+        Tick(nearest - spacing)
+    } else {
+        Tick(nearest)
+    }
 }
 
 #[cfg(test)]
@@ -237,32 +367,211 @@ mod tests {
 
     #[test]
     fn test_sqrt_price_at_tick() {
-        assert_eq!(MIN_PRICE, sqrt_price_at_tick(MIN_TICK));
-        assert_eq!(dec!("0.000000000276890319"), sqrt_price_at_tick(-440170));
-        assert_eq!(dec!("0.999850018747812746"), sqrt_price_at_tick(-3));
-        assert_eq!(dec!("0.999950003749687527"), sqrt_price_at_tick(-1));
-        assert_eq!(Decimal::one(), sqrt_price_at_tick(0));
-        assert_eq!(dec!("1.000150003749937502"), sqrt_price_at_tick(3));
-        assert_eq!(dec!("1.000250018750312495"), sqrt_price_at_tick(5));
-        assert_eq!(dec!("1.451912069310684182"), sqrt_price_at_tick(7458));
-        assert_eq!(dec!("13.043260825728760908"), sqrt_price_at_tick(51368));
-        assert_eq!(dec!("3611718901.096063128233128884"), sqrt_price_at_tick(440171));
-        assert_eq!(MAX_PRICE, sqrt_price_at_tick(MAX_TICK));
+        assert_eq!(MIN_PRICE, sqrt_price_at_tick(Tick(MIN_TICK)).0);
+        assert_eq!(dec!("0.000000000276890319"), sqrt_price_at_tick(Tick(-440170)).0);
+        assert_eq!(dec!("0.999850018747812744"), sqrt_price_at_tick(Tick(-3)).0);
+        assert_eq!(dec!("0.999950003749687527"), sqrt_price_at_tick(Tick(-1)).0);
+        assert_eq!(Decimal::one(), sqrt_price_at_tick(Tick(0)).0);
+        assert_eq!(dec!("1.000150003749937502"), sqrt_price_at_tick(Tick(3)).0);
+        assert_eq!(dec!("1.000250018750312495"), sqrt_price_at_tick(Tick(5)).0);
+        assert_eq!(dec!("1.451912069310684182"), sqrt_price_at_tick(Tick(7458)).0);
+        assert_eq!(dec!("13.043260825728760908"), sqrt_price_at_tick(Tick(51368)).0);
+        assert_eq!(dec!("3611718901.096063128233128884"), sqrt_price_at_tick(Tick(440171)).0);
+        assert_eq!(MAX_PRICE, sqrt_price_at_tick(Tick(MAX_TICK)).0);
+    }
+
+    /**
+     * The sqrt_price of the negative ticks is within a few units of the 18th decimal from the exact value, truncated to 18 decimals.
+     * Dividing by the sqrt_price of the opposite positive tick was off by up to thousands of units, e.g. 0.951231802418722000 for -1000.
+     */
+    #[test]
+    fn sqrt_price_at_negative_tick_precision() {
+        let max_error = dec!("0.00000000000000001");
+        for (tick, exact_sqrt_price) in [
+            (-1000, dec!("0.951231802418721111")),
+            (-10000, dec!("0.606545822157834757")),
+            (-100000, dec!("0.006739631584094859")),
+            (-525268, dec!("0.000000000003931158")),
+        ] {
+            let sqrt_price = sqrt_price_at_tick(Tick(tick)).0;
+            assert!(sqrt_price <= exact_sqrt_price && exact_sqrt_price - sqrt_price < max_error);
+        }
+    }
+
+    /**
+     * The constants generated by build.rs are the ones previously maintained by hand in this file, including the truncation errors
+     * of the squared PRICE_0X8 and above, which the tick and price tests rely on.
+     */
+    #[test]
+    fn generated_constants_match_the_previous_table() {
+        let prices: [Decimal; 20] = [
+            dec!("1.000049998750062496"),
+            dec!("1.0001"),
+            dec!("1.00020001"),
+            dec!("1.000400060004000093"),
+            dec!("1.000800280056006986"),
+            dec!("1.001601200560182014"),
+            dec!("1.003204964963597955"),
+            dec!("1.0064202017276138"),
+            dec!("1.012881622445450855"),
+            dec!("1.025929181087728853"),
+            dec!("1.052530684607337941"),
+            dec!("1.107820842039991493"),
+            dec!("1.227267018058195782"),
+            dec!("1.506184333613455851"),
+            dec!("2.268591246822610072"),
+            dec!("5.146506245160164533"),
+            dec!("26.486526531472575563"),
+            dec!("701.536087702400664335"),
+            dec!("492152.882348790396620919"),
+            dec!("242214459604.222321943471435452"),
+        ];
+        let inv_prices: [Decimal; 20] = [
+            dec!("0.999950003749687527"),
+            dec!("0.999900009999000099"),
+            dec!("0.999800029996000499"),
+            dec!("0.999600099980003499"),
+            dec!("0.999200359880032992"),
+            dec!("0.998401359184387445"),
+            dec!("0.996805274021232232"),
+            dec!("0.993620754316543878"),
+            dec!("0.98728220340857765"),
+            dec!("0.974726149167296094"),
+            dec!("0.950091065870505957"),
+            dec!("0.90267303344695409"),
+            dec!("0.814818605312325898"),
+            dec!("0.663929359563123931"),
+            dec!("0.440802194489899902"),
+            dec!("0.19430657466711154"),
+            dec!("0.037755044958865792"),
+            dec!("0.001425443419845977"),
+            dec!("0.000002031888943182"),
+            dec!("0.000000000004128572"),
+        ];
+        assert_eq!(prices, PRICES);
+        assert_eq!(inv_prices, INV_PRICES);
     }
 
     #[test]
     fn test_tick_at_sqrt_price() {
-        assert_eq!(0, tick_at_sqrt_price(Decimal::one()));
-        assert_eq!(1, tick_at_sqrt_price(dec!("1.0000499987500624")));
-        assert_eq!(2, tick_at_sqrt_price( dec!("1.0001")));
-        assert_eq!(3, tick_at_sqrt_price(dec!("1.000150003749937406")));
-        assert_eq!(-3, tick_at_sqrt_price(dec!("0.999850018747812842")));
-        assert_eq!(-440170, tick_at_sqrt_price(dec!("0.000000000276890319")));
-        assert_eq!(-440170, tick_at_sqrt_price(dec!("0.00000000027689032")));
-        assert_eq!(440171, tick_at_sqrt_price(dec!("3611718901.08879675118568791")));
-        assert_eq!(440171, tick_at_sqrt_price(dec!("3611718901.08879675118568792")));
-        assert_eq!(MAX_TICK, tick_at_sqrt_price(MAX_PRICE));
-        assert_eq!(MIN_TICK, tick_at_sqrt_price(MIN_PRICE));
+        assert_eq!(0, tick_at_sqrt_price(SqrtPrice(Decimal::one())).0);
+        assert_eq!(1, tick_at_sqrt_price(SqrtPrice(dec!("1.0000499987500624"))).0);
+        assert_eq!(2, tick_at_sqrt_price(SqrtPrice(dec!("1.0001"))).0);
+        assert_eq!(3, tick_at_sqrt_price(SqrtPrice(dec!("1.000150003749937406"))).0);
+        assert_eq!(-3, tick_at_sqrt_price(SqrtPrice(dec!("0.999850018747812842"))).0);
+        assert_eq!(-440170, tick_at_sqrt_price(SqrtPrice(dec!("0.000000000276890319"))).0);
+        assert_eq!(-440170, tick_at_sqrt_price(SqrtPrice(dec!("0.00000000027689032"))).0);
+        assert_eq!(440171, tick_at_sqrt_price(SqrtPrice(dec!("3611718901.08879675118568791"))).0);
+        assert_eq!(440171, tick_at_sqrt_price(SqrtPrice(dec!("3611718901.08879675118568792"))).0);
+        assert_eq!(MAX_TICK, tick_at_sqrt_price(SqrtPrice(MAX_PRICE)).0);
+        assert_eq!(MIN_TICK, tick_at_sqrt_price(SqrtPrice(MIN_PRICE)).0);
+    }
+
+    #[test]
+    fn try_variants_return_out_of_bounds_errors() {
+        assert_eq!(Ok(SqrtPrice(Decimal::one())), try_sqrt_price_at_tick(Tick(0)));
+        assert_eq!(Err(TickMathError::TickOutOfBounds(Tick(MIN_TICK - 1))), try_sqrt_price_at_tick(Tick(MIN_TICK - 1)));
+        assert_eq!(Err(TickMathError::TickOutOfBounds(Tick(MAX_TICK + 1))), try_sqrt_price_at_tick(Tick(MAX_TICK + 1)));
+
+        assert_eq!(Ok(Tick(MAX_TICK)), try_tick_at_sqrt_price(SqrtPrice(MAX_PRICE)));
+        assert_eq!(
+            Err(TickMathError::SqrtPriceOutOfBounds(SqrtPrice(Decimal::zero()))),
+            try_tick_at_sqrt_price(SqrtPrice(Decimal::zero()))
+        );
+        assert_eq!(
+            Err(TickMathError::SqrtPriceOutOfBounds(SqrtPrice(Decimal::MAX))),
+            try_tick_at_sqrt_price(SqrtPrice(Decimal::MAX))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Tick out of bounds.")]
+    fn sqrt_price_at_tick_panics_out_of_bounds() {
+        sqrt_price_at_tick(Tick(MAX_TICK + 1));
+    }
+
+    #[test]
+    fn test_tick_range() {
+        let range = TickRange::new(Tick(-1000), Tick(1000), 10);
+        assert_eq!((Tick(-1000), Tick(1000)), (range.low(), range.high()));
+        assert!(range.contains(Tick(-1000)) && range.contains(Tick(999)) && !range.contains(Tick(1000)));
+
+        assert_eq!(Err(TickMathError::InvalidTickRange(Tick(10), Tick(10))), TickRange::try_new(Tick(10), Tick(10), 1));
+        assert_eq!(Err(TickMathError::InvalidTickRange(Tick(10), Tick(-10))), TickRange::try_new(Tick(10), Tick(-10), 1));
+        assert_eq!(Err(TickMathError::TickOutOfBounds(Tick(MIN_TICK - 1))), TickRange::try_new(Tick(MIN_TICK - 1), Tick(0), 1));
+        assert_eq!(Err(TickMathError::TickOutOfBounds(Tick(MAX_TICK + 1))), TickRange::try_new(Tick(0), Tick(MAX_TICK + 1), 1));
+        assert_eq!(Err(TickMathError::TickNotOnSpacing(Tick(15), 10)), TickRange::try_new(Tick(-10), Tick(15), 10));
+
+        assert_eq!(
+            Ok(TickRange::new(Tick(-1000), Tick(1000), 1)),
+            TickRange::try_from_sqrt_prices(sqrt_price_at_tick(Tick(-1000)), sqrt_price_at_tick(Tick(1000)), 1)
+        );
+    }
+
+    #[test]
+    fn test_price_at_tick() {
+        assert_eq!(dec!("0.000000000000009861"), price_at_tick(Tick(MIN_PRICE_TICK)));
+        assert_eq!(dec!("0.904841941932768866"), price_at_tick(Tick(-1000)));
+        assert_eq!(Decimal::one(), price_at_tick(Tick(0)));
+        assert_eq!(dec!("1.000099999999999999"), price_at_tick(Tick(1)));
+        assert_eq!(dec!("1.105165392603230631"), price_at_tick(Tick(1000)));
+        assert_eq!(
+            dec!("3138466238069139522157459333574140808308.960955872804852848"),
+            price_at_tick(Tick(MAX_PRICE_TICK))
+        );
+    }
+
+    #[test]
+    fn test_tick_at_price() {
+        assert_eq!(MIN_PRICE_TICK, tick_at_price(dec!("0.000000000000009861")).0);
+        assert_eq!(-1000, tick_at_price(dec!("0.904841941932768866")).0);
+        assert_eq!(-1001, tick_at_price(dec!("0.904841941932768865")).0);
+        assert_eq!(0, tick_at_price(Decimal::one()).0);
+        assert_eq!(0, tick_at_price(dec!("1.000099999999999998")).0);
+        assert_eq!(1, tick_at_price(dec!("1.0001")).0);
+        assert_eq!(1000, tick_at_price(dec!("1.105165392603230631")).0);
+        assert_eq!(MAX_PRICE_TICK, tick_at_price(price_at_tick(Tick(MAX_PRICE_TICK))).0);
+        assert_eq!(MAX_PRICE_TICK, tick_at_price(Decimal::MAX).0);
+    }
+
+    #[test]
+    fn test_display_price() {
+        assert_eq!(dec!("1000000000000"), tick_to_display_price(Tick(0), 18, 6));
+        assert_eq!(dec!("0.000000000001105165"), tick_to_display_price(Tick(1000), 6, 18));
+        assert_eq!(dec!("1.105165392603230631"), tick_to_display_price(Tick(1000), 8, 8));
+        assert_eq!(0, display_price_to_tick(dec!("1000000000000"), 18, 6).0);
+        assert_eq!(1000, display_price_to_tick(tick_to_display_price(Tick(1000), 18, 6), 18, 6).0);
+        assert_eq!(1000, display_price_to_tick(dec!("1.105165392603230631"), 8, 8).0);
+        assert_eq!(0, display_price_to_tick(dec!("0.000000000001"), 6, 18).0);
+    }
+
+    #[test]
+    fn tick_at_price_equals_original_tick() {
+        for tick in (MIN_PRICE_TICK..MAX_PRICE_TICK + 1).step_by(997) {
+            assert_eq!(tick, tick_at_price(price_at_tick(Tick(tick))).0);
+        }
+    }
+
+    #[test]
+    fn test_round_to_spacing() {
+        assert_eq!(Tick(-20), round_down_to_spacing(Tick(-15), 10));
+        assert_eq!(Tick(-10), round_up_to_spacing(Tick(-15), 10));
+        assert_eq!(Tick(10), round_down_to_spacing(Tick(15), 10));
+        assert_eq!(Tick(20), round_up_to_spacing(Tick(15), 10));
+        assert_eq!(Tick(-60), round_down_to_spacing(Tick(-60), 60));
+        assert_eq!(Tick(-60), round_up_to_spacing(Tick(-60), 60));
+        assert_eq!(Tick(7), round_up_to_spacing(Tick(7), 1));
+    }
+
+    #[test]
+    fn test_nearest_usable_tick() {
+        assert_eq!(Tick(0), nearest_usable_tick(Tick(4), 10));
+        assert_eq!(Tick(10), nearest_usable_tick(Tick(5), 10));
+        assert_eq!(Tick(-10), nearest_usable_tick(Tick(-15), 10));
+        assert_eq!(Tick(-20), nearest_usable_tick(Tick(-16), 10));
+        assert_eq!(Tick(-631020), nearest_usable_tick(Tick(MIN_TICK), 30));
+        assert_eq!(Tick(931700), nearest_usable_tick(Tick(MAX_TICK), 10));
+        assert_eq!(Tick(MAX_TICK), nearest_usable_tick(Tick(MAX_TICK), 1));
     }
 
     /**
@@ -272,7 +581,7 @@ mod tests {
     #[ignore]
     fn tick_at_sqrt_price_equals_original_tick() {
         for tick in (MIN_TICK..MAX_TICK + 1).rev() {
-            assert_eq!(tick, tick_at_sqrt_price(sqrt_price_at_tick(tick)));
+            assert_eq!(Tick(tick), tick_at_sqrt_price(sqrt_price_at_tick(Tick(tick))));
         }
     }
 
@@ -0,0 +1,145 @@
+use scrypto::prelude::*;
+
+use crate::pool::pool_blueprint::Pool;
+
+/**
+ * Emitted by Treasury::deposit and Treasury::sweep for each deposit, the source being the swept pool, none for a direct deposit
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct TreasuryDepositEvent {
+    pub source_addr: Option<ComponentAddress>,
+    pub resource_addr: ResourceAddress,
+    pub amount: Decimal,
+}
+
+/**
+ * Emitted by Treasury::withdraw
+ */
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct TreasuryWithdrawalEvent {
+    pub resource_addr: ResourceAddress,
+    pub amount: Decimal,
+}
+
+/**
+ * The protocol treasury, pooling the protocol fees of many pools, see Pool::collect_protocol_fees, so they aren't swept one pool at
+ * a time by the admin. The pools assign their treasury role to the treasury component, i.e. the rule
+ * require(global_caller(<treasury address>)), and anyone can then sweep their protocol fees into the treasury, e.g. a keeper bot,
+ * each deposit emitting an event.
+ *
+ * Only the admin and the withdrawer role, e.g. a governance badge or a multisig, none initially, can withdraw from the treasury.
+ */
+#[blueprint]
+#[events(TreasuryDepositEvent, TreasuryWithdrawalEvent)]
+mod treasury_blueprint {
+
+    enable_method_auth! {
+        roles {
+            admin => updatable_by: [admin];
+            withdrawer => updatable_by: [admin];
+        },
+        methods {
+            deposit => PUBLIC;
+            sweep => PUBLIC;
+            withdraw => restrict_to: [withdrawer, admin];
+            get_balance => PUBLIC;
+            get_balances => PUBLIC;
+        }
+    }
+
+    struct Treasury {
+        vaults: KeyValueStore<ResourceAddress, Vault>,
+        resource_addrs: Vec<ResourceAddress>,
+    }
+
+    impl Treasury {
+        /**
+         * Creates a new, empty, treasury administered by the given admin badge
+         */
+        pub fn new(admin_badge_addr: ResourceAddress) -> Global<Treasury> {
+            Self {
+                vaults: KeyValueStore::new(),
+                resource_addrs: Vec::new(),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                admin => rule!(require(admin_badge_addr));
+                withdrawer => rule!(deny_all);
+            ))
+            .globalize()
+        }
+
+        /**
+         * Deposits the given bucket into the treasury, e.g. the protocol fees collected by other means than a sweep
+         */
+        pub fn deposit(&mut self, bucket: Bucket) {
+            self.deposit_from(None, bucket);
+        }
+
+        /**
+         * Collects the protocol fees of the given pools into the treasury, the pools having assigned their treasury role to the
+         * treasury component. It aborts if any of the pools didn't.
+         */
+        pub fn sweep(&mut self, pool_addrs: Vec<ComponentAddress>) {
+            for pool_addr in pool_addrs {
+                let mut pool: Global<Pool> = pool_addr.into();
+                let (protocol_fees0, protocol_fees1) = pool.collect_protocol_fees();
+                self.deposit_from(Some(pool_addr), protocol_fees0);
+                self.deposit_from(Some(pool_addr), protocol_fees1);
+            }
+        }
+
+        /**
+         * Withdraws the given amount of the resource from the treasury
+         */
+        pub fn withdraw(&mut self, resource_addr: ResourceAddress, amount: Decimal) -> Bucket {
+            assert!(amount > Decimal::zero(), "Invalid amount, should be positive. Withdraw op aborted.");
+            let mut vault = self
+                .vaults
+                .get_mut(&resource_addr)
+                .expect("No treasury balance of the resource. Withdraw op aborted.");
+            assert!(amount <= vault.amount(), "Not enough treasury balance. Withdraw op aborted.");
+            let bucket = vault.take(amount);
+            Runtime::emit_event(TreasuryWithdrawalEvent { resource_addr, amount });
+            bucket
+        }
+
+        /**
+         * Returns the treasury balance of the given resource
+         */
+        pub fn get_balance(&self, resource_addr: ResourceAddress) -> Decimal {
+            self.vaults.get(&resource_addr).map_or(Decimal::zero(), |vault| vault.amount())
+        }
+
+        /**
+         * Returns the treasury balances, by resource, in the order the resources were first deposited
+         */
+        pub fn get_balances(&self) -> Vec<(ResourceAddress, Decimal)> {
+            self.resource_addrs
+                .iter()
+                .map(|resource_addr| (*resource_addr, self.get_balance(*resource_addr)))
+                .collect()
+        }
+
+        //the empty buckets, e.g. of the pools without protocol fees since the last sweep, emit no event
+        fn deposit_from(&mut self, source_addr: Option<ComponentAddress>, bucket: Bucket) {
+            let resource_addr = bucket.resource_address();
+            let amount = bucket.amount();
+            let has_vault = self.vaults.get(&resource_addr).is_some();
+            if has_vault {
+                self.vaults.get_mut(&resource_addr).unwrap().put(bucket);
+            } else {
+                self.vaults.insert(resource_addr, Vault::with_bucket(bucket));
+                self.resource_addrs.push(resource_addr);
+            }
+            if amount > Decimal::zero() {
+                Runtime::emit_event(TreasuryDepositEvent {
+                    source_addr,
+                    resource_addr,
+                    amount,
+                });
+            }
+        }
+    }
+}
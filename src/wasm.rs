@@ -0,0 +1,185 @@
+use core::str::FromStr;
+use radix_engine_common::prelude::*;
+use wasm_bindgen::prelude::*;
+use crate::pool_math;
+use crate::sim::{PoolSim, SimTick};
+use crate::tick_math::{self, SqrtPrice, Tick, TickRange};
+
+//the JS bindings of the quoting math for the frontends, so the previews match the on-ledger results exactly. The decimals are passed
+//as strings both ways, as the JS numbers can't hold them without rounding
+
+fn parse_decimal(name: &str, value: &str) -> Result<Decimal, JsError> {
+    Decimal::from_str(value).map_err(|_| JsError::new(&format!("Invalid {}: {}", name, value)))
+}
+
+/**
+ * The sqrt price at the given tick
+ */
+#[wasm_bindgen(js_name = sqrtPriceAtTick)]
+pub fn sqrt_price_at_tick(tick: i32) -> Result<String, JsError> {
+    tick_math::try_sqrt_price_at_tick(Tick(tick))
+        .map(|sqrt_price| sqrt_price.0.to_string())
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/**
+ * The greatest tick whose sqrt price is smaller or equal to the given sqrt price
+ */
+#[wasm_bindgen(js_name = tickAtSqrtPrice)]
+pub fn tick_at_sqrt_price(sqrt_price: &str) -> Result<i32, JsError> {
+    let sqrt_price = parse_decimal("sqrt price", sqrt_price)?;
+    tick_math::try_tick_at_sqrt_price(SqrtPrice(sqrt_price))
+        .map(|tick| tick.0)
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/**
+ * The price of token0 vs token1 at the given tick
+ */
+#[wasm_bindgen(js_name = priceAtTick)]
+pub fn price_at_tick(tick: i32) -> String {
+    tick_math::price_at_tick(Tick(tick)).to_string()
+}
+
+/**
+ * The tick of the given price of token0 vs token1
+ */
+#[wasm_bindgen(js_name = tickAtPrice)]
+pub fn tick_at_price(price: &str) -> Result<i32, JsError> {
+    Ok(tick_math::tick_at_price(parse_decimal("price", price)?).0)
+}
+
+/**
+ * The liquidity of a position and the amount0,1 it takes
+ */
+#[wasm_bindgen(getter_with_clone)]
+pub struct LiquidityForAmounts {
+    pub liquidity: String,
+    pub amount0: String,
+    pub amount1: String,
+}
+
+/**
+ * The liquidity of a position on [low_tick, high_tick) funded with at most the given amounts at the given sqrt price, as
+ * Pool::add_pos computes it
+ */
+#[wasm_bindgen(js_name = liquidityForAmounts)]
+pub fn liquidity_for_amounts(
+    amount0: &str,
+    amount1: &str,
+    sqrt_price: &str,
+    low_tick: i32,
+    high_tick: i32,
+    tick_spacing: i32,
+) -> Result<LiquidityForAmounts, JsError> {
+    let range = TickRange::try_new(Tick(low_tick), Tick(high_tick), tick_spacing).map_err(|err| JsError::new(&err.to_string()))?;
+    let (liquidity, amount0, amount1) = pool_math::compute_range_liq_given_amounts(
+        parse_decimal("amount0", amount0)?,
+        parse_decimal("amount1", amount1)?,
+        parse_decimal("sqrt price", sqrt_price)?,
+        tick_math::sqrt_price_at_tick(range.low()).0,
+        tick_math::sqrt_price_at_tick(range.high()).0,
+    );
+    Ok(LiquidityForAmounts {
+        liquidity: liquidity.to_string(),
+        amount0: amount0.to_string(),
+        amount1: amount1.to_string(),
+    })
+}
+
+/**
+ * The output amount of a quoted swap and the remainder of the input amount, if the liquidity runs out
+ */
+#[wasm_bindgen(getter_with_clone)]
+pub struct SwapQuote {
+    pub amount_out: String,
+    pub remainder: String,
+}
+
+/**
+ * A PoolSim built by the frontend from the pool state, Pool::get_pool_state, and the used ticks, Pool::get_tick_states, quoting the
+ * swaps as the pool executes them
+ */
+#[wasm_bindgen]
+pub struct QuotingPool {
+    sim: PoolSim,
+}
+
+#[wasm_bindgen]
+impl QuotingPool {
+    /**
+     * Creates the quoting pool from the pool fee, tick spacing, sqrt price, current tick and active liquidity
+     */
+    #[wasm_bindgen(constructor)]
+    pub fn new(fee: &str, tick_spacing: i32, sqrt_price: &str, tick: i32, live_liquidity: &str) -> Result<QuotingPool, JsError> {
+        let mut sim = PoolSim::new(parse_decimal("fee", fee)?, tick_spacing, SqrtPrice(parse_decimal("sqrt price", sqrt_price)?));
+        sim.tick = tick;
+        sim.live_liq = parse_decimal("live liquidity", live_liquidity)?;
+        Ok(QuotingPool { sim })
+    }
+
+    /**
+     * Sets the divisibilities of the pool resources, 18 by default, so the quotes are rounded as the pool rounds the swaps
+     */
+    #[wasm_bindgen(js_name = setDivisibilities)]
+    pub fn set_divisibilities(&mut self, divisibility0: u8, divisibility1: u8) {
+        self.sim.divisibility0 = divisibility0;
+        self.sim.divisibility1 = divisibility1;
+    }
+
+    /**
+     * Sets a used tick, with its net and gross liquidity; the tick fees don't change the quotes
+     */
+    #[wasm_bindgen(js_name = setTick)]
+    pub fn set_tick(&mut self, tick: i32, liq_net: &str, liq_gross: &str) -> Result<(), JsError> {
+        self.sim.ticks.insert(
+            tick,
+            SimTick {
+                liq_net: parse_decimal("net liquidity", liq_net)?,
+                liq_gross: parse_decimal("gross liquidity", liq_gross)?,
+                fee_outside0: Decimal::zero(),
+                fee_outside1: Decimal::zero(),
+            },
+        );
+        Ok(())
+    }
+
+    /**
+     * Quotes the swap of the given amount of token0, if is_token0, or token1, see PoolSim::quote_swap
+     */
+    #[wasm_bindgen(js_name = quoteSwap)]
+    pub fn quote_swap(&self, amount: &str, is_token0: bool) -> Result<SwapQuote, JsError> {
+        let (amount_out, remainder) = self.sim.quote_swap(parse_decimal("amount", amount)?, is_token0);
+        Ok(SwapQuote {
+            amount_out: amount_out.to_string(),
+            remainder: remainder.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick_conversions() {
+        assert_eq!("1", sqrt_price_at_tick(0).unwrap());
+        assert_eq!(0, tick_at_sqrt_price("1").unwrap());
+        assert_eq!(tick_math::price_at_tick(Tick(100)).to_string(), price_at_tick(100));
+        assert_eq!(100, tick_at_price(&price_at_tick(100)).unwrap());
+    }
+
+    #[test]
+    fn test_quote_swap_matches_the_sim() {
+        let mut sim = PoolSim::new(dec!("0.003"), 1, SqrtPrice(Decimal::one()));
+        sim.add_pos(dec!("10000"), dec!("10000"), -1000, 1000);
+        let mut pool = QuotingPool::new("0.003", 1, "1", sim.tick, &sim.live_liq.to_string()).unwrap();
+        for (tick, state) in &sim.ticks {
+            pool.set_tick(*tick, &state.liq_net.to_string(), &state.liq_gross.to_string()).unwrap();
+        }
+
+        let (amount_out, remainder) = sim.quote_swap(dec!("100"), true);
+        let quote = pool.quote_swap("100", true).unwrap();
+        assert_eq!((amount_out.to_string(), remainder.to_string()), (quote.amount_out, quote.remainder));
+    }
+}
@@ -0,0 +1,107 @@
+use mojitoswap_pool::tick_math::{self, sqrt_price_at_tick, SqrtPrice, Tick, MAX_PRICE, MAX_TICK, MIN_PRICE, MIN_TICK};
+use scrypto::prelude::*;
+
+//1 / log2(√1.0001) = 13863.636746827590710391..., as a fixed point number with 32 fractional bits
+const INV_LOG2_SQRT_PRICE_Q32: i128 = 59543866431248;
+//the fractional bits of log2(sqrt_price) computed by tick_at_sqrt_price_log, a bit is worth 13863.63 / 2^16 = 0.21 ticks
+const LOG2_FRACTION_BITS: u32 = 16;
+
+/**
+ * Same as tick_at_sqrt_price, but the tick is estimated from the binary logarithm of the sqrt price, instead of the 20 divisions:
+ * tick = log2(sqrt_price) / log2(√1.0001). Returns the largest tick whose sqrt_price_at_tick is smaller or equal to the given sqrt price.
+ *
+ * The sqrt price is written as mantissa * 2^exponent, mantissa in [1, 2), the exponent being given by the most significant bit of
+ * the Decimal raw value. The fractional bits of log2(mantissa) are computed by squaring the mantissa: if mantissa^2 >= 2 the next bit
+ * is 1 and the mantissa is halved. With LOG2_FRACTION_BITS bits the estimation is at most a tick away from the result, so we correct
+ * it comparing with the sqrt_price_at_tick of the estimated tick and of the next one.
+ *
+ * Only used to compare the execution cost with tick_at_sqrt_price, see tests/tick_math_cost.rs of the pool package.
+ */
+pub fn tick_at_sqrt_price_log(sqrt_price: SqrtPrice) -> Tick {
+    assert!(sqrt_price.0 >= MIN_PRICE && sqrt_price.0 <= MAX_PRICE, "Sqrt price out of bounds.");
+
+    let two: Decimal = 2.into();
+    //the raw value is sqrt_price * 10^18 and 2^59 < 10^18 < 2^60, so the mantissa is raw / 2^(most significant bit - 60) / 10^18 or
+    //half of it
+    let raw = sqrt_price.0 .0 .0;
+    let mut exponent = (191 - raw.leading_zeros()) as i32 - 60;
+    let mut mantissa = if exponent >= 0 {
+        Decimal(bnum_integer::I192(raw >> exponent as u32))
+    } else {
+        Decimal(bnum_integer::I192(raw << (-exponent) as u32))
+    };
+    if mantissa >= two {
+        mantissa = mantissa / two;
+        exponent += 1;
+    }
+
+    //log2(sqrt_price) as a fixed point number with LOG2_FRACTION_BITS fractional bits
+    let mut log2 = exponent as i64;
+    for _ in 0..LOG2_FRACTION_BITS {
+        mantissa = mantissa * mantissa;
+        log2 <<= 1;
+        if mantissa >= two {
+            mantissa = mantissa / two;
+            log2 |= 1;
+        }
+    }
+
+    let estimated_tick = ((log2 as i128 * INV_LOG2_SQRT_PRICE_Q32) >> (32 + LOG2_FRACTION_BITS)) as i32;
+    let mut tick = estimated_tick.clamp(MIN_TICK, MAX_TICK);
+    while tick < MAX_TICK && sqrt_price_at_tick(Tick(tick + 1)) <= sqrt_price {
+        tick += 1;
+    }
+    while sqrt_price_at_tick(Tick(tick)) > sqrt_price {
+        tick -= 1;
+    }
+    Tick(tick)
+}
+
+/**
+ * Runs the implementations of tick_at_sqrt_price on ledger, so that their execution cost units can be compared, see
+ * tests/tick_math_cost.rs of the pool package. It is a separate test package, so it doesn't ship with the pool blueprint
+ */
+#[blueprint]
+mod tick_math_bench_blueprint {
+    struct TickMathBench {}
+
+    impl TickMathBench {
+        /**
+         * Computes the ticks of the given sqrt prices with tick_math::tick_at_sqrt_price
+         */
+        pub fn tick_at_sqrt_price(sqrt_prices: Vec<SqrtPrice>) -> Vec<Tick> {
+            sqrt_prices.into_iter().map(tick_math::tick_at_sqrt_price).collect()
+        }
+
+        /**
+         * Computes the ticks of the given sqrt prices with tick_at_sqrt_price_log
+         */
+        pub fn tick_at_sqrt_price_log(sqrt_prices: Vec<SqrtPrice>) -> Vec<Tick> {
+            sqrt_prices.into_iter().map(super::tick_at_sqrt_price_log).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mojitoswap_pool::tick_math::tick_at_sqrt_price;
+
+    #[test]
+    fn tick_at_sqrt_price_log_equals_tick_at_sqrt_price() {
+        for tick in (MIN_TICK..MAX_TICK + 1).step_by(997).chain([MAX_TICK]) {
+            let sqrt_price = sqrt_price_at_tick(Tick(tick));
+            assert_eq!(tick_at_sqrt_price(sqrt_price), tick_at_sqrt_price_log(sqrt_price));
+        }
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_price_log() {
+        assert_eq!(0, tick_at_sqrt_price_log(SqrtPrice(Decimal::one())).0);
+        assert_eq!(1, tick_at_sqrt_price_log(SqrtPrice(dec!("1.00005"))).0);
+        assert_eq!(-1, tick_at_sqrt_price_log(SqrtPrice(dec!("0.99999"))).0);
+        assert_eq!(-440170, tick_at_sqrt_price_log(SqrtPrice(dec!("0.00000000027689032"))).0);
+        assert_eq!(MIN_TICK, tick_at_sqrt_price_log(SqrtPrice(MIN_PRICE)).0);
+        assert_eq!(MAX_TICK, tick_at_sqrt_price_log(SqrtPrice(MAX_PRICE)).0);
+    }
+}
@@ -0,0 +1,2620 @@
+/*!
+ * Test utilities for the MojitoSwap pool: a test ledger context that publishes the pool package, creates the pool, funds accounts and
+ * builds the transaction manifests for the pool operations. Components integrating with the pool can use it in their own tests.
+ */
+
+use mojitoswap_pool::dca::DcaOrder;
+use mojitoswap_pool::factory::{PoolInfo, SwapQuote};
+use mojitoswap_pool::manifests;
+use mojitoswap_pool::oracle::Observation;
+use mojitoswap_pool::pool_math;
+use mojitoswap_pool::replay::PoolEvent;
+use mojitoswap_pool::pool::{
+    BuyCap, Depth, EpochStats, LaunchLadder, ParameterChange, PoolConfig, PoolMetadata, PoolState, Portfolio, Position, SwapEvent,
+    TickState, Tvl,
+};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use radix_engine::transaction::TransactionReceipt;
+use radix_engine_stores::memory_db::InMemorySubstateDatabase;
+use scrypto::prelude::*;
+use scrypto_unit::*;
+use std::path::Path;
+use transaction::{
+    builder::{ManifestBuilder, ResolvableArguments},
+    model::TransactionManifestV1,
+};
+
+/**
+ * An account used for testing purposes
+ */
+#[derive(Clone)]
+pub struct Account {
+    pub addr: ComponentAddress,
+    pub pub_key: Secp256k1PublicKey,
+}
+
+/**
+ * A test scenario context for the pool component, we keep some state here needed for testing
+ */
+pub struct Context {
+    pub runner: DefaultTestRunner,
+    pub package_addr: PackageAddress,
+    pub admin: Account,
+    pub admin_badge_addr: ResourceAddress,
+    pub moj_addr: ResourceAddress,
+    pub usdt_addr: ResourceAddress,
+    pub pool_addr: ComponentAddress,
+    pub position_nft_addr: ResourceAddress,
+}
+
+/**
+ * A snapshot of the ledger state of a test context, the pool component state included. It is taken after a scenario and restored
+ * in other tests, so that long setup sequences (many positions, many swaps) are executed only once.
+ */
+#[derive(Clone)]
+pub struct Snapshot {
+    substate_db: InMemorySubstateDatabase,
+    next_transaction_nonce: u32,
+    package_addr: PackageAddress,
+    admin: Account,
+    admin_badge_addr: ResourceAddress,
+    moj_addr: ResourceAddress,
+    usdt_addr: ResourceAddress,
+    pool_addr: ComponentAddress,
+    position_nft_addr: ResourceAddress,
+}
+
+impl Context {
+    /**
+     * Creates a context containing:
+     * - two fungible resources: MOJ and USDT
+     * - an admin account that owns these resources (it acts also as faucet, giving tokens to other accounts created later
+     * - a pool with the given fee and sqrt_price, the above account acts also the admin pool
+     */
+    pub fn new(
+        fee: Decimal,
+        sqrt_price: SqrtPrice,
+        low_sqrt_price: SqrtPrice,
+        high_sqrt_price: SqrtPrice,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> Self {
+        Self::new_with_usdt_divisibility(
+            DIVISIBILITY_MAXIMUM,
+            fee,
+            sqrt_price,
+            low_sqrt_price,
+            high_sqrt_price,
+            moj_amount,
+            usdt_amount,
+        )
+    }
+
+    /**
+     * Same as Context::new, but USDT has the given divisibility, e.g. 6 decimals, instead of 18
+     */
+    pub fn new_with_usdt_divisibility(
+        usdt_divisibility: u8,
+        fee: Decimal,
+        sqrt_price: SqrtPrice,
+        low_sqrt_price: SqrtPrice,
+        high_sqrt_price: SqrtPrice,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> Self {
+        Self::with_pool_created_by(
+            |admin_badge_addr| PoolConfig {
+                initial_position: Some((low_sqrt_price, high_sqrt_price)),
+                ..PoolConfig::new(fee, sqrt_price, admin_badge_addr)
+            },
+            usdt_divisibility,
+            moj_amount,
+            usdt_amount,
+        )
+    }
+
+    /**
+     * Same as Context::new, but from plain prices instead of sqrt prices, the sqrt prices being computed with pool_math::sqrt
+     */
+    pub fn new_with_price(
+        fee: Decimal,
+        price: Decimal,
+        low_price: Decimal,
+        high_price: Decimal,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> Self {
+        let sqrt_price = |price: Decimal| SqrtPrice(pool_math::sqrt(price));
+        Self::new(fee, sqrt_price(price), sqrt_price(low_price), sqrt_price(high_price), moj_amount, usdt_amount)
+    }
+
+    /**
+     * Same as Context::new, but the pool is created as a stable pool, with the guards of the stable pairs, see PoolConfig
+     */
+    pub fn new_stable(
+        fee: Decimal,
+        sqrt_price: SqrtPrice,
+        low_sqrt_price: SqrtPrice,
+        high_sqrt_price: SqrtPrice,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> Self {
+        Self::with_pool_created_by(
+            |admin_badge_addr| PoolConfig {
+                initial_position: Some((low_sqrt_price, high_sqrt_price)),
+                stable: true,
+                ..PoolConfig::new(fee, sqrt_price, admin_badge_addr)
+            },
+            DIVISIBILITY_MAXIMUM,
+            moj_amount,
+            usdt_amount,
+        )
+    }
+
+    /**
+     * Same as Context::new, but the initial position is on the full tick range
+     */
+    pub fn new_unbounded(fee: Decimal, sqrt_price: SqrtPrice, moj_amount: Decimal, usdt_amount: Decimal) -> Self {
+        Self::new(
+            fee,
+            sqrt_price,
+            tick_math::sqrt_price_at_tick(Tick(tick_math::MIN_TICK)),
+            tick_math::sqrt_price_at_tick(Tick(tick_math::MAX_TICK)),
+            moj_amount,
+            usdt_amount,
+        )
+    }
+
+    /**
+     * Creates the context, the pool being created with the PoolConfig built from the admin badge of the context
+     */
+    fn with_pool_created_by(
+        config: impl FnOnce(ResourceAddress) -> PoolConfig,
+        usdt_divisibility: u8,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> Self {
+        let mut runner = TestRunnerBuilder::new().build();
+
+        // the pool package is the parent directory of this crate
+        let package_addr = runner.compile_and_publish(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."));
+
+        let (pub_key, _priv_key, addr) = runner.new_allocated_account();
+        let admin = Account { addr, pub_key };
+
+        let mut moj_token_info: BTreeMap<String, String> = BTreeMap::new();
+        moj_token_info.insert("name".to_string(), "Mojito finance".to_string());
+        moj_token_info.insert("symbol".to_string(), "MOJ".to_string());
+
+        let mut usdt_token_info: BTreeMap<String, String> = BTreeMap::new();
+        usdt_token_info.insert("name".to_string(), "Teather USD".to_string());
+        usdt_token_info.insert("symbol".to_string(), "USDT".to_string());
+
+        let admin_res_manif = ManifestBuilder::new()
+            .new_token_fixed(
+                OwnerRole::None,
+                metadata!(
+                    init {
+                        "name" => "Mojito finance".to_owned(), locked;
+                        "symbol" => "MOJ".to_owned(), locked;
+                    }
+                ),
+                dec!("10000000"),
+            )
+            .create_fungible_resource(
+                OwnerRole::None,
+                true,
+                usdt_divisibility,
+                FungibleResourceRoles::default(),
+                metadata!(
+                    init {
+                        "name" => "Teather USD".to_owned(), locked;
+                        "symbol" => "USDT".to_owned(), locked;
+                    }
+                ),
+                Some(dec!("10000000")),
+            )
+            .new_badge_fixed(
+                OwnerRole::None,
+                metadata!(
+                    init {
+                        "name" => "Admin badge".to_owned(), locked;
+                    }
+                ),
+                Decimal::one(),
+            )
+            .call_method(
+                admin.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+
+        let admin_res_receipt = execute_manif(&mut runner, admin_res_manif, vec![&pub_key]);
+
+        let result = admin_res_receipt.expect_commit_success();
+
+        let usdt_addr: ResourceAddress = result.new_resource_addresses()[0];
+        let moj_addr: ResourceAddress = result.new_resource_addresses()[1];
+        let admin_badge_addr: ResourceAddress = result.new_resource_addresses()[2];
+
+        let new_pool_manif =
+            new_pool_manif(package_addr, &admin, (moj_addr, moj_amount), (usdt_addr, usdt_amount), config(admin_badge_addr));
+        let new_pool_receipt = execute_manif(&mut runner, new_pool_manif, vec![&pub_key]);
+        let result = new_pool_receipt.expect_commit_success();
+
+        let pool_addr: ComponentAddress = result.new_component_addresses()[0];
+        let pos_nft_badge_addr: ResourceAddress = result.new_resource_addresses()[1];
+
+        Self {
+            runner,
+            package_addr,
+            admin,
+            usdt_addr,
+            moj_addr,
+            admin_badge_addr,
+            pool_addr,
+            position_nft_addr: pos_nft_badge_addr,
+        }
+    }
+
+    /**
+     * Takes a snapshot of the ledger state of this context
+     */
+    pub fn snapshot(&mut self) -> Snapshot {
+        Snapshot {
+            substate_db: self.runner.substate_db().clone(),
+            next_transaction_nonce: self.runner.next_transaction_nonce(),
+            package_addr: self.package_addr,
+            admin: self.admin.clone(),
+            admin_badge_addr: self.admin_badge_addr,
+            moj_addr: self.moj_addr,
+            usdt_addr: self.usdt_addr,
+            pool_addr: self.pool_addr,
+            position_nft_addr: self.position_nft_addr,
+        }
+    }
+
+    /**
+     * Creates a context having the ledger state of the given snapshot. The accounts created before the snapshot can be used with the
+     * new context, while changes made through the new context don't affect the snapshot or other contexts restored from it.
+     */
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let mut runner = TestRunnerBuilder::new().build();
+        *runner.substate_db_mut() = snapshot.substate_db.clone();
+
+        // the addresses of the new entities are derived from the transaction hash, so we skip the nonces already used to avoid
+        // allocating again the addresses already in the snapshot, up to the snapshot nonce included, a bounded number of nonces
+        let first_nonce = runner.next_transaction_nonce();
+        for _ in first_nonce..snapshot.next_transaction_nonce {
+            runner.next_transaction_nonce();
+        }
+
+        Self {
+            runner,
+            package_addr: snapshot.package_addr,
+            admin: snapshot.admin.clone(),
+            admin_badge_addr: snapshot.admin_badge_addr,
+            moj_addr: snapshot.moj_addr,
+            usdt_addr: snapshot.usdt_addr,
+            pool_addr: snapshot.pool_addr,
+            position_nft_addr: snapshot.position_nft_addr,
+        }
+    }
+
+    /**
+     * Creates a new account used for testing having the given amounts of MOJ and USDT
+     */
+    pub fn new_account_with_moj_and_usdt(
+        &mut self,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> Account {
+        let (account_pub_key, _account_priv_key, account_addr) =
+            self.runner.new_allocated_account();
+
+        let account_amount_manif = ManifestBuilder::new()
+            .withdraw_from_account(self.admin.addr, self.moj_addr, moj_amount)
+            .withdraw_from_account(self.admin.addr, self.usdt_addr, usdt_amount)
+            .call_method(
+                account_addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+
+        execute_manif(
+            &mut self.runner,
+            account_amount_manif,
+            vec![&self.admin.pub_key, &account_pub_key],
+        );
+
+        Account {
+            addr: account_addr,
+            pub_key: account_pub_key,
+        }
+    }
+
+    /**
+     * Adds a new position to the pool, position is owned by the given account
+     */
+    pub fn add_pos(
+        &mut self,
+        account: &Account,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+        low_tick: i32,
+        high_tick: i32,
+    ) -> TransactionReceipt {
+        let add_pos_receipt = self.try_add_pos(account, moj_amount, usdt_amount, low_tick, high_tick);
+        add_pos_receipt.expect_commit_success();
+        add_pos_receipt
+    }
+
+    /**
+     * Adds a new position to the pool, owned by the given account. The receipt isn't checked, so failed additions can be tested too.
+     */
+    pub fn try_add_pos(
+        &mut self,
+        account: &Account,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+        low_tick: i32,
+        high_tick: i32,
+    ) -> TransactionReceipt {
+        self.try_add_pos_with_badge(account, None, moj_amount, usdt_amount, low_tick, high_tick)
+    }
+
+    /**
+     * Same as Context::try_add_pos, but a proof of the given badge of the account, if any, is presented to the pool, e.g. the member
+     * badge of a permissioned pool
+     */
+    pub fn try_add_pos_with_badge(
+        &mut self,
+        account: &Account,
+        badge_addr: Option<ResourceAddress>,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+        low_tick: i32,
+        high_tick: i32,
+    ) -> TransactionReceipt {
+        let add_pos_manif = badge_proof_manif(account, badge_addr)
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .withdraw_from_account(account.addr, self.usdt_addr, usdt_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .take_from_worktop(self.usdt_addr, usdt_amount, "usdt_bucket")
+            .call_method_with_name_lookup(self.pool_addr, "add_pos", |lookup| {
+                (
+                    lookup.bucket("moj_bucket"),
+                    lookup.bucket("usdt_bucket"),
+                    (low_tick, high_tick),
+                )
+            })
+            .assert_worktop_contains(self.moj_addr, Decimal::zero())
+            .assert_worktop_contains(self.usdt_addr, Decimal::zero())
+            .assert_worktop_contains(self.position_nft_addr, Decimal::one())
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+
+        let add_pos_receipt = self.runner.execute_manifest_ignoring_fee(
+            add_pos_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", add_pos_receipt);
+        add_pos_receipt
+    }
+
+    /**
+     * Adds more liquidity to the position owned by the given account (for the moment this test utility allows only for a position per account)
+     * Unfortunatelly we can't check the liquidity on the returned position NFT at the worktop level
+     */
+    pub fn add_liq(
+        &mut self,
+        account: &Account,
+        usdt_amount: Decimal,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        let add_liq_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .withdraw_from_account(account.addr, self.usdt_addr, usdt_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .take_from_worktop(self.usdt_addr, usdt_amount, "usdt_bucket")
+            .create_proof_from_account_of_non_fungible(
+                account.addr,
+                self.pos_nft_badge_id(account.addr),
+            )
+            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(self.pool_addr, "add_liq", |lookup| {
+                (
+                    lookup.bucket("moj_bucket"),
+                    lookup.bucket("usdt_bucket"),
+                    lookup.proof("proof"),
+                )
+            })
+            .assert_worktop_contains(self.moj_addr, Decimal::zero())
+            .assert_worktop_contains(self.usdt_addr, Decimal::zero())
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+
+        let add_liq_receipt = self.runner.execute_manifest_ignoring_fee(
+            add_liq_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", add_liq_receipt);
+
+        add_liq_receipt.expect_commit_success();
+        add_liq_receipt
+    }
+
+    fn pos_nft_badge_id(&mut self, account_addr: ComponentAddress) -> NonFungibleGlobalId {
+        let vaults = self
+            .runner
+            .get_component_vaults(account_addr, self.position_nft_addr);
+        let nft_local_id = self
+            .runner
+            .inspect_non_fungible_vault(vaults[0])
+            .unwrap()
+            .1
+            .next()
+            .unwrap();
+        NonFungibleGlobalId::new(self.position_nft_addr, nft_local_id)
+    }
+
+    /**
+     * Moves the position owned by the given account to [low_tick, high_tick), see Pool::rebalance_pos, presenting the given badge,
+     * if any, e.g. the badge of an LP whitelist. The receipt isn't checked, so failed rebalances can be tested too.
+     */
+    pub fn try_rebalance_pos_with_badge(
+        &mut self,
+        account: &Account,
+        badge_addr: Option<ResourceAddress>,
+        low_tick: i32,
+        high_tick: i32,
+    ) -> TransactionReceipt {
+        let rebalance_manif = badge_proof_manif(account, badge_addr)
+            .create_proof_from_account_of_non_fungible(
+                account.addr,
+                self.pos_nft_badge_id(account.addr),
+            )
+            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(self.pool_addr, "rebalance_pos", |lookup| {
+                (lookup.proof("proof"), (low_tick, high_tick))
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+
+        let rebalance_receipt = self.runner.execute_manifest_ignoring_fee(
+            rebalance_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}
+", rebalance_receipt);
+        rebalance_receipt
+    }
+
+    /**
+     * Migrates the position owned by the given account to the target pool. The receipt isn't checked, so failed migrations can be
+     * tested too.
+     */
+    pub fn migrate_position(&mut self, account: &Account, target_pool_addr: ComponentAddress) -> TransactionReceipt {
+        let migrate_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungible(
+                account.addr,
+                self.pos_nft_badge_id(account.addr),
+            )
+            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(self.pool_addr, "migrate_position", |lookup| {
+                (lookup.proof("proof"), target_pool_addr)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let migrate_receipt = self.runner.execute_manifest_ignoring_fee(
+            migrate_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", migrate_receipt);
+        migrate_receipt
+    }
+
+    /**
+     * Creates a SwapLimitHook component, aborting the swaps of amounts larger than max_amount
+     */
+    pub fn new_swap_limit_hook(&mut self, max_amount: Decimal) -> ComponentAddress {
+        let new_hook_manif = ManifestBuilder::new()
+            .call_function(self.package_addr, "SwapLimitHook", "new", manifest_args!(max_amount))
+            .build();
+        let new_hook_receipt = execute_manif(&mut self.runner, new_hook_manif, vec![]);
+        new_hook_receipt.expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Calls the given admin gated method of the pool, e.g. schedule_change, execute_change or propose_admin, with the admin badge. The
+     * receipt isn't checked, so failed calls can be tested too.
+     */
+    pub fn call_as_admin(&mut self, method: &str, args: impl ResolvableArguments) -> TransactionReceipt {
+        let admin = self.admin.clone();
+        self.call_with_badge(&admin, self.admin_badge_addr, method, args)
+    }
+
+    /**
+     * Calls the given method of the pool presenting the given badge of the given account, e.g. the badge of a delegated role. The
+     * receipt isn't checked, so failed calls can be tested too.
+     */
+    pub fn call_with_badge(
+        &mut self,
+        account: &Account,
+        badge_addr: ResourceAddress,
+        method: &str,
+        args: impl ResolvableArguments,
+    ) -> TransactionReceipt {
+        self.call_with_badges(account, &[badge_addr], method, args)
+    }
+
+    /**
+     * Same as Context::call_with_badge, presenting all the given badges of the account, e.g. the badges of a multisig admin rule
+     */
+    pub fn call_with_badges(
+        &mut self,
+        account: &Account,
+        badge_addrs: &[ResourceAddress],
+        method: &str,
+        args: impl ResolvableArguments,
+    ) -> TransactionReceipt {
+        let mut call_manif_builder = ManifestBuilder::new();
+        for badge_addr in badge_addrs {
+            call_manif_builder =
+                call_manif_builder.create_proof_from_account_of_amount(account.addr, *badge_addr, Decimal::one());
+        }
+        let call_manif = call_manif_builder.call_method(self.pool_addr, method, args).build();
+        let call_receipt = self.runner.execute_manifest_ignoring_fee(
+            call_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", call_receipt);
+        call_receipt
+    }
+
+    /**
+     * Assigns the given role of the pool (fee_manager, pauser, treasury, fee_switcher or importer) to the holders of the given badge, with the
+     * admin badge. The receipt isn't checked, so failed assignments can be tested too.
+     */
+    pub fn set_role(&mut self, role: &str, badge_addr: ResourceAddress) -> TransactionReceipt {
+        self.set_component_role(self.pool_addr, role, rule!(require(badge_addr)))
+    }
+
+    /**
+     * Assigns the given role of the given component, e.g. the pool or a treasury, to the given access rule, with the admin badge.
+     * The receipt isn't checked, so failed assignments can be tested too.
+     */
+    pub fn set_component_role(&mut self, component_addr: ComponentAddress, role: &str, rule: AccessRule) -> TransactionReceipt {
+        let set_role_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .set_role(component_addr, ObjectModuleId::Main, role, rule)
+            .build();
+        let set_role_receipt = self.runner.execute_manifest_ignoring_fee(
+            set_role_manif,
+            vec![NonFungibleGlobalId::from_public_key(&self.admin.pub_key)],
+        );
+        println!("{:?}\n", set_role_receipt);
+        set_role_receipt
+    }
+
+    /**
+     * Claims the royalties paid by the swaps to the admin account, with the admin badge
+     */
+    pub fn claim_royalties(&mut self) -> TransactionReceipt {
+        let claim_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .call_method(self.pool_addr, "claim_royalties", manifest_args!())
+            .call_method(
+                self.admin.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        execute_manif(&mut self.runner, claim_manif, vec![&self.admin.pub_key])
+    }
+
+    /**
+     * Collects the protocol fees of the pool to the admin account, with the admin badge
+     */
+    pub fn collect_protocol_fees(&mut self) -> TransactionReceipt {
+        let collect_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .call_method(self.pool_addr, "collect_protocol_fees", manifest_args!())
+            .call_method(
+                self.admin.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        execute_manif(&mut self.runner, collect_manif, vec![&self.admin.pub_key])
+    }
+
+    /**
+     * Sends the given amount of a resource of the account to the pool, e.g. a foreign token airdropped to it, see Pool::deposit. The
+     * receipt isn't checked, so failed deposits can be tested too.
+     */
+    pub fn try_deposit_to_pool(&mut self, account: &Account, resource_addr: ResourceAddress, amount: Decimal) -> TransactionReceipt {
+        let deposit_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, resource_addr, amount)
+            .take_from_worktop(resource_addr, amount, "bucket")
+            .call_method_with_name_lookup(self.pool_addr, "deposit", |lookup| (lookup.bucket("bucket"),))
+            .build();
+        let deposit_receipt = self.runner.execute_manifest_ignoring_fee(
+            deposit_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", deposit_receipt);
+        deposit_receipt
+    }
+
+    /**
+     * Rescues the tokens of the given resource sent to the pool to the account, presenting the given badge of the account, if any,
+     * see Pool::rescue. The receipt isn't checked, so failed rescues can be tested too.
+     */
+    pub fn try_rescue_with_badge(
+        &mut self,
+        account: &Account,
+        badge_addr: Option<ResourceAddress>,
+        resource_addr: ResourceAddress,
+    ) -> TransactionReceipt {
+        let rescue_manif = badge_proof_manif(account, badge_addr)
+            .call_method(self.pool_addr, "rescue", manifest_args!(resource_addr))
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let rescue_receipt = self.runner.execute_manifest_ignoring_fee(
+            rescue_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", rescue_receipt);
+        rescue_receipt
+    }
+
+    /**
+     * Creates a new admin badge in the given account and returns its address
+     */
+    pub fn new_admin_badge(&mut self, account: &Account) -> ResourceAddress {
+        self.new_badge(account, "Admin badge")
+    }
+
+    /**
+     * Creates a new badge with the given name in the given account and returns its address
+     */
+    pub fn new_badge(&mut self, account: &Account, name: &str) -> ResourceAddress {
+        let new_badge_manif = ManifestBuilder::new()
+            .new_badge_fixed(
+                OwnerRole::None,
+                metadata!(
+                    init {
+                        "name" => name.to_owned(), locked;
+                    }
+                ),
+                Decimal::one(),
+            )
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let new_badge_receipt = execute_manif(&mut self.runner, new_badge_manif, vec![&account.pub_key]);
+        new_badge_receipt.expect_commit_success().new_resource_addresses()[0]
+    }
+
+    /**
+     * Creates a new non fungible badge in the given account, e.g. identifying a trader, and returns its global id
+     */
+    pub fn new_trader_badge(&mut self, account: &Account) -> NonFungibleGlobalId {
+        let badge_addr = self.runner.create_non_fungible_resource(account.addr);
+        NonFungibleGlobalId::new(badge_addr, NonFungibleLocalId::integer(1))
+    }
+
+    /**
+     * Schedules the given parameter change of the pool, waits for the timelock delay and executes it. The receipt of the execution,
+     * or of the scheduling if the change is rejected right away, isn't checked, so failed changes can be tested too.
+     */
+    pub fn apply_change(&mut self, change: ParameterChange) -> TransactionReceipt {
+        let schedule_receipt = self.call_as_admin("schedule_change", manifest_args!(change));
+        if !schedule_receipt.is_commit_success() {
+            return schedule_receipt;
+        }
+        let change_id: u64 = schedule_receipt.expect_commit_success().output(1);
+        let timelock_delay: i64 = self.query_pool("get_timelock_delay");
+        let current_time = self.get_current_time();
+        self.set_current_time(current_time + timelock_delay);
+        self.call_as_admin("execute_change", manifest_args!(change_id))
+    }
+
+    /**
+     * Previews the given parameter change of the pool, see Pool::preview_parameter_change. The receipt isn't checked, so the previews
+     * of invalid changes can be tested too.
+     */
+    pub fn try_preview_parameter_change(&mut self, change: ParameterChange) -> TransactionReceipt {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "preview_parameter_change", manifest_args!(change))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt
+    }
+
+    /**
+     * Queries the number of swaps counted by the given SwapLimitHook
+     */
+    pub fn get_hook_swaps(&mut self, hook_addr: ComponentAddress) -> u64 {
+        let query_manif = ManifestBuilder::new()
+            .call_method(hook_addr, "get_swaps", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Creates a new FeeManager of the pool and returns its address
+     */
+    pub fn new_fee_manager(
+        &mut self,
+        base_fee: Decimal,
+        max_fee: Decimal,
+        volatility_fee: Decimal,
+        half_life: i64,
+    ) -> ComponentAddress {
+        let new_fee_manager_manif = ManifestBuilder::new()
+            .call_function(
+                self.package_addr,
+                "FeeManager",
+                "new",
+                manifest_args!(self.pool_addr, base_fee, max_fee, volatility_fee, half_life),
+            )
+            .build();
+        let new_fee_manager_receipt = execute_manif(&mut self.runner, new_fee_manager_manif, vec![]);
+        new_fee_manager_receipt.expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Queries the volatility of the pool price tracked by the given FeeManager
+     */
+    pub fn get_volatility(&mut self, fee_manager_addr: ComponentAddress) -> Decimal {
+        let query_manif = ManifestBuilder::new()
+            .call_method(fee_manager_addr, "get_volatility", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Creates a new Governance with MOJ as voting resource, holding the admin badge, and returns its address and the address of its
+     * vote receipts
+     */
+    pub fn new_governance(
+        &mut self,
+        voting_period: i64,
+        quorum: Decimal,
+        proposal_threshold: Decimal,
+    ) -> (ComponentAddress, ResourceAddress) {
+        let new_governance_manif = ManifestBuilder::new()
+            .withdraw_from_account(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .take_from_worktop(self.admin_badge_addr, Decimal::one(), "admin_badge")
+            .call_function_with_name_lookup(self.package_addr, "Governance", "new", |lookup| {
+                (lookup.bucket("admin_badge"), self.moj_addr, voting_period, quorum, proposal_threshold)
+            })
+            .build();
+        let new_governance_receipt = execute_manif(&mut self.runner, new_governance_manif, vec![&self.admin.pub_key]);
+        let result = new_governance_receipt.expect_commit_success();
+        (result.new_component_addresses()[0], result.new_resource_addresses()[1])
+    }
+
+    /**
+     * Proposes to call the given method of the given component with the given arguments, encoded with scrypto_args!, presenting a
+     * proof of the given amount of MOJ of the account. The receipt isn't checked, so failed proposals can be tested too.
+     */
+    pub fn propose(
+        &mut self,
+        account: &Account,
+        governance_addr: ComponentAddress,
+        moj_amount: Decimal,
+        component_addr: ComponentAddress,
+        method: &str,
+        args: Vec<u8>,
+    ) -> TransactionReceipt {
+        let propose_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, self.moj_addr, moj_amount)
+            .create_proof_from_auth_zone_of_amount(self.moj_addr, moj_amount, "proof")
+            .call_method_with_name_lookup(governance_addr, "propose", |lookup| {
+                (lookup.proof("proof"), format!("Call {}", method), component_addr, method.to_owned(), args)
+            })
+            .build();
+        let propose_receipt = self.runner.execute_manifest_ignoring_fee(
+            propose_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", propose_receipt);
+        propose_receipt
+    }
+
+    /**
+     * Votes for or against the given proposal with the given amount of MOJ of the account, which receives the vote receipt. The
+     * receipt isn't checked, so failed votes can be tested too.
+     */
+    pub fn vote(
+        &mut self,
+        account: &Account,
+        governance_addr: ComponentAddress,
+        proposal_id: u64,
+        moj_amount: Decimal,
+        support: bool,
+    ) -> TransactionReceipt {
+        let vote_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "votes")
+            .call_method_with_name_lookup(governance_addr, "vote", |lookup| {
+                (proposal_id, lookup.bucket("votes"), support)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let vote_receipt = self.runner.execute_manifest_ignoring_fee(
+            vote_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", vote_receipt);
+        vote_receipt
+    }
+
+    /**
+     * Withdraws the MOJ voting with all the vote receipts of the account. The receipt isn't checked, so failed withdrawals can be
+     * tested too.
+     */
+    pub fn withdraw_votes(
+        &mut self,
+        account: &Account,
+        governance_addr: ComponentAddress,
+        vote_receipt_addr: ResourceAddress,
+    ) -> TransactionReceipt {
+        let receipts_amount = self.runner.get_component_balance(account.addr, vote_receipt_addr);
+        let withdraw_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, vote_receipt_addr, receipts_amount)
+            .take_all_from_worktop(vote_receipt_addr, "vote_receipts")
+            .call_method_with_name_lookup(governance_addr, "withdraw_votes", |lookup| {
+                (lookup.bucket("vote_receipts"),)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let withdraw_receipt = self.runner.execute_manifest_ignoring_fee(
+            withdraw_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", withdraw_receipt);
+        withdraw_receipt
+    }
+
+    /**
+     * Executes the given proposal. The receipt isn't checked, so failed executions can be tested too.
+     */
+    pub fn execute_proposal(&mut self, governance_addr: ComponentAddress, proposal_id: u64) -> TransactionReceipt {
+        let execute_manif = ManifestBuilder::new()
+            .call_method(governance_addr, "execute", manifest_args!(proposal_id))
+            .build();
+        let execute_receipt = self.runner.execute_manifest_ignoring_fee(execute_manif, vec![]);
+        println!("{:?}\n", execute_receipt);
+        execute_receipt
+    }
+
+    /**
+     * Creates a limit order manager of the pool and returns its address and the resource of its order receipts
+     */
+    pub fn new_limit_order_manager(&mut self) -> (ComponentAddress, ResourceAddress) {
+        let new_manager_manif = ManifestBuilder::new()
+            .call_function(self.package_addr, "LimitOrderManager", "new", manifest_args!(self.pool_addr))
+            .build();
+        let new_manager_receipt = execute_manif(&mut self.runner, new_manager_manif, vec![&self.admin.pub_key]);
+        let result = new_manager_receipt.expect_commit_success();
+        (result.new_component_addresses()[0], result.new_resource_addresses()[1])
+    }
+
+    /**
+     * Creates a new, empty, referral registry and returns its address
+     */
+    pub fn new_referral_registry(&mut self) -> ComponentAddress {
+        let new_registry_manif = ManifestBuilder::new()
+            .call_function(self.package_addr, "ReferralRegistry", "new", manifest_args!())
+            .build();
+        let new_registry_receipt = execute_manif(&mut self.runner, new_registry_manif, vec![]);
+        new_registry_receipt.expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a new, empty, treasury, the admin account badge being its admin badge, and returns its address
+     */
+    pub fn new_treasury(&mut self) -> ComponentAddress {
+        let new_treasury_manif = ManifestBuilder::new()
+            .call_function(self.package_addr, "Treasury", "new", manifest_args!(self.admin_badge_addr))
+            .build();
+        let new_treasury_receipt = execute_manif(&mut self.runner, new_treasury_manif, vec![]);
+        new_treasury_receipt.expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Sweeps the protocol fees of the given pools into the given treasury, signed by the given account. The receipt isn't checked, so
+     * failed sweeps can be tested too.
+     */
+    pub fn sweep_treasury(
+        &mut self,
+        account: &Account,
+        treasury_addr: ComponentAddress,
+        pool_addrs: Vec<ComponentAddress>,
+    ) -> TransactionReceipt {
+        let sweep_manif = ManifestBuilder::new()
+            .call_method(treasury_addr, "sweep", manifest_args!(pool_addrs))
+            .build();
+        let sweep_receipt = self.runner.execute_manifest_ignoring_fee(
+            sweep_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", sweep_receipt);
+        sweep_receipt
+    }
+
+    /**
+     * Withdraws the given amount of the resource from the given treasury to the account, presenting the given badge of the account.
+     * The receipt isn't checked, so failed withdrawals can be tested too.
+     */
+    pub fn withdraw_from_treasury(
+        &mut self,
+        account: &Account,
+        badge_addr: ResourceAddress,
+        treasury_addr: ComponentAddress,
+        resource_addr: ResourceAddress,
+        amount: Decimal,
+    ) -> TransactionReceipt {
+        let withdraw_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, badge_addr, Decimal::one())
+            .call_method(treasury_addr, "withdraw", manifest_args!(resource_addr, amount))
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let withdraw_receipt = self.runner.execute_manifest_ignoring_fee(
+            withdraw_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", withdraw_receipt);
+        withdraw_receipt
+    }
+
+    /**
+     * Queries the balances of the given treasury, by resource
+     */
+    pub fn get_treasury_balances(&mut self, treasury_addr: ComponentAddress) -> Vec<(ResourceAddress, Decimal)> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(treasury_addr, "get_balances", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Registers the account as a referrer of the given registry, controlled by the given badge of the account, with the given
+     * payout account. The receipt isn't checked, so failed registrations can be tested too.
+     */
+    pub fn register_referrer(
+        &mut self,
+        account: &Account,
+        registry_addr: ComponentAddress,
+        badge_addr: ResourceAddress,
+        payout_account_addr: ComponentAddress,
+    ) -> TransactionReceipt {
+        let register_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, badge_addr, Decimal::one())
+            .create_proof_from_auth_zone_of_amount(badge_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(registry_addr, "register", |lookup| {
+                (lookup.proof("proof"), payout_account_addr)
+            })
+            .build();
+        let register_receipt = self.runner.execute_manifest_ignoring_fee(
+            register_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", register_receipt);
+        register_receipt
+    }
+
+    /**
+     * Sets the payout account of the given referrer of the registry, presenting the given badge of the account. The receipt isn't
+     * checked, so failed calls can be tested too.
+     */
+    pub fn set_referrer_payout_account(
+        &mut self,
+        account: &Account,
+        registry_addr: ComponentAddress,
+        badge_addr: ResourceAddress,
+        referrer_id: u64,
+        payout_account_addr: ComponentAddress,
+    ) -> TransactionReceipt {
+        let set_payout_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, badge_addr, Decimal::one())
+            .create_proof_from_auth_zone_of_amount(badge_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(registry_addr, "set_payout_account", |lookup| {
+                (lookup.proof("proof"), referrer_id, payout_account_addr)
+            })
+            .build();
+        let set_payout_receipt = self.runner.execute_manifest_ignoring_fee(
+            set_payout_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", set_payout_receipt);
+        set_payout_receipt
+    }
+
+    /**
+     * Places a limit order selling the given amount of MOJ of the account on the range [low_tick, low_tick + tick spacing]. The
+     * account receives the order receipt. The receipt isn't checked, so failed orders can be tested too.
+     */
+    pub fn place_limit_order(
+        &mut self,
+        account: &Account,
+        manager_addr: ComponentAddress,
+        moj_amount: Decimal,
+        low_tick: i32,
+    ) -> TransactionReceipt {
+        let place_order_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "input")
+            .call_method_with_name_lookup(manager_addr, "place_order", |lookup| (lookup.bucket("input"), low_tick))
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let place_order_receipt = self.runner.execute_manifest_ignoring_fee(
+            place_order_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", place_order_receipt);
+        place_order_receipt
+    }
+
+    /**
+     * Claims the given filled limit order with the receipt of the account, which holds a single order receipt. The receipt isn't
+     * checked, so failed claims can be tested too.
+     */
+    pub fn claim_filled_order(
+        &mut self,
+        account: &Account,
+        manager_addr: ComponentAddress,
+        receipt_addr: ResourceAddress,
+        order_id: u64,
+    ) -> TransactionReceipt {
+        let claim_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, receipt_addr, Decimal::one())
+            .take_all_from_worktop(receipt_addr, "receipt")
+            .call_method_with_name_lookup(manager_addr, "claim_filled_order", |lookup| {
+                (order_id, lookup.bucket("receipt"))
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let claim_receipt = self.runner.execute_manifest_ignoring_fee(
+            claim_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", claim_receipt);
+        claim_receipt
+    }
+
+    /**
+     * Queries the ids of the filled limit orders not yet claimed
+     */
+    pub fn get_filled_orders(&mut self, manager_addr: ComponentAddress) -> Vec<u64> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(manager_addr, "get_filled_orders", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Creates a managed vault of the pool, rebalanced by the holders of the given strategy badge, with the given wide and narrow
+     * ranges, and returns its address and the resource of its shares
+     */
+    pub fn new_managed_vault(
+        &mut self,
+        strategy_badge_addr: ResourceAddress,
+        wide_range: (i32, i32),
+        narrow_range: (i32, i32),
+    ) -> (ComponentAddress, ResourceAddress) {
+        let new_vault_manif = ManifestBuilder::new()
+            .call_function(
+                self.package_addr,
+                "ManagedVault",
+                "new",
+                manifest_args!(self.pool_addr, strategy_badge_addr, wide_range, narrow_range),
+            )
+            .build();
+        let new_vault_receipt = execute_manif(&mut self.runner, new_vault_manif, vec![&self.admin.pub_key]);
+        let result = new_vault_receipt.expect_commit_success();
+        (result.new_component_addresses()[0], result.new_resource_addresses()[1])
+    }
+
+    /**
+     * Deposits the given amounts of MOJ and USDT of the account in the managed vault, the account receiving the shares and the
+     * remainders. The receipt isn't checked, so failed deposits can be tested too.
+     */
+    pub fn deposit_to_managed_vault(
+        &mut self,
+        account: &Account,
+        vault_addr: ComponentAddress,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        let deposit_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .withdraw_from_account(account.addr, self.usdt_addr, usdt_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .take_from_worktop(self.usdt_addr, usdt_amount, "usdt_bucket")
+            .call_method_with_name_lookup(vault_addr, "deposit", |lookup| {
+                (lookup.bucket("moj_bucket"), lookup.bucket("usdt_bucket"))
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let deposit_receipt = self.runner.execute_manifest_ignoring_fee(
+            deposit_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", deposit_receipt);
+        deposit_receipt
+    }
+
+    /**
+     * Withdraws all the managed vault shares of the account, which receives their MOJ and USDT
+     */
+    pub fn withdraw_from_managed_vault(
+        &mut self,
+        account: &Account,
+        vault_addr: ComponentAddress,
+        share_addr: ResourceAddress,
+    ) -> TransactionReceipt {
+        let shares = self.runner.get_component_balance(account.addr, share_addr);
+        let withdraw_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, share_addr, shares)
+            .take_all_from_worktop(share_addr, "shares")
+            .call_method_with_name_lookup(vault_addr, "withdraw", |lookup| (lookup.bucket("shares"),))
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        execute_manif(&mut self.runner, withdraw_manif, vec![&account.pub_key])
+    }
+
+    /**
+     * Rebalances the managed vault to the given wide and narrow ranges, presenting the given badge of the account. The receipt isn't
+     * checked, so failed rebalances can be tested too.
+     */
+    pub fn rebalance_managed_vault(
+        &mut self,
+        account: &Account,
+        badge_addr: ResourceAddress,
+        vault_addr: ComponentAddress,
+        wide_range: (i32, i32),
+        narrow_range: (i32, i32),
+    ) -> TransactionReceipt {
+        let rebalance_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, badge_addr, Decimal::one())
+            .call_method(vault_addr, "rebalance", manifest_args!(wide_range, narrow_range))
+            .build();
+        let rebalance_receipt = self.runner.execute_manifest_ignoring_fee(
+            rebalance_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", rebalance_receipt);
+        rebalance_receipt
+    }
+
+    /**
+     * Creates a permissioned MOJ/USDT pool, with the given member badge, fee=0, sqrt_price=1 and an initial admin position=[1000 MOJ
+     * + 1000 USDT, -1000, 1000], and returns its address. The pool isn't used by the context methods, see Context::use_pool.
+     */
+    pub fn new_permissioned_pool(&mut self, member_badge_addr: ResourceAddress) -> ComponentAddress {
+        let config = PoolConfig {
+            initial_position: Some((tick_math::sqrt_price_at_tick(Tick(-1000)), tick_math::sqrt_price_at_tick(Tick(1000)))),
+            member_badge_addr: Some(member_badge_addr),
+            ..PoolConfig::new(Decimal::zero(), SqrtPrice(Decimal::one()), self.admin_badge_addr)
+        };
+        self.new_pool_with_config(config, dec!("1000"), dec!("1000")).expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a MOJ/USDT pool with the given config, funded with the given amounts of the admin account, which receives the position
+     * NFTs and the remainders. The pool isn't used by the context methods, see Context::use_pool. The receipt isn't checked, so
+     * failed creations can be tested too.
+     */
+    pub fn new_pool_with_config(&mut self, config: PoolConfig, moj_amount: Decimal, usdt_amount: Decimal) -> TransactionReceipt {
+        let new_pool_manif =
+            new_pool_manif(self.package_addr, &self.admin, (self.moj_addr, moj_amount), (self.usdt_addr, usdt_amount), config);
+        let new_pool_receipt = self.runner.execute_manifest_ignoring_fee(
+            new_pool_manif,
+            vec![NonFungibleGlobalId::from_public_key(&self.admin.pub_key)],
+        );
+        println!("{:?}\n", new_pool_receipt);
+        new_pool_receipt
+    }
+
+    /**
+     * Same as Context::new_permissioned_pool, but the pool has an LP whitelist with the given badge instead of a member badge, see
+     * PoolConfig
+     */
+    pub fn new_lp_whitelist_pool(&mut self, lp_badge_addr: ResourceAddress) -> ComponentAddress {
+        let config = PoolConfig {
+            initial_position: Some((tick_math::sqrt_price_at_tick(Tick(-1000)), tick_math::sqrt_price_at_tick(Tick(1000)))),
+            lp_badge_addr: Some(lp_badge_addr),
+            ..PoolConfig::new(Decimal::zero(), SqrtPrice(Decimal::one()), self.admin_badge_addr)
+        };
+        self.new_pool_with_config(config, dec!("1000"), dec!("1000")).expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Same as Context::new_permissioned_pool, but the pool is created with the given display metadata instead of a member badge, see
+     * PoolConfig
+     */
+    pub fn new_pool_with_metadata(&mut self, metadata: PoolMetadata) -> ComponentAddress {
+        let config = PoolConfig {
+            initial_position: Some((tick_math::sqrt_price_at_tick(Tick(-1000)), tick_math::sqrt_price_at_tick(Tick(1000)))),
+            metadata,
+            ..PoolConfig::new(Decimal::zero(), SqrtPrice(Decimal::one()), self.admin_badge_addr)
+        };
+        self.new_pool_with_config(config, dec!("1000"), dec!("1000")).expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a MOJ/USDT launch pool, with fee=0.01 and tick spacing 10, seeded with the given amount of MOJ of the admin account in a
+     * ladder of the given steps from low_tick to high_tick and with the given buy cap, see LaunchLadder. The admin account receives
+     * the position NFTs. The pool isn't used by the context methods, see Context::use_pool. The receipt isn't checked, so failed
+     * creations can be tested too.
+     */
+    pub fn new_launch_pool(
+        &mut self,
+        low_tick: i32,
+        high_tick: i32,
+        steps: u32,
+        buy_cap: Option<BuyCap>,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        let config = PoolConfig {
+            tick_spacing: 10,
+            launch_ladder: Some(LaunchLadder { low_tick, high_tick, steps }),
+            buy_cap,
+            ..PoolConfig::new(dec!("0.01"), tick_math::sqrt_price_at_tick(Tick(low_tick)), self.admin_badge_addr)
+        };
+        self.new_pool_with_config(config, moj_amount, Decimal::zero())
+    }
+
+    /**
+     * Creates a MOJ/USDT pool administered by the given access rule, with fee=0, sqrt_price=1 and an initial admin position=[1000
+     * MOJ + 1000 USDT, -1000, 1000], and returns its address. The pool isn't used by the context methods, see Context::use_pool.
+     */
+    pub fn new_pool_with_admin_rule(&mut self, admin_rule: AccessRule) -> ComponentAddress {
+        let config = PoolConfig {
+            admin_badge_addr: None,
+            admin_rule: Some(admin_rule),
+            initial_position: Some((tick_math::sqrt_price_at_tick(Tick(-1000)), tick_math::sqrt_price_at_tick(Tick(1000)))),
+            ..PoolConfig::new(Decimal::zero(), SqrtPrice(Decimal::one()), self.admin_badge_addr)
+        };
+        self.new_pool_with_config(config, dec!("1000"), dec!("1000")).expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a MOJ/USDT pool with fee=0, sqrt_price=1 and an initial admin position=[1000 MOJ + 1000 USDT, -1000, 1000], swapping
+     * only once the active liquidity exceeds the given minimum, see PoolConfig, and returns its address. The pool isn't used by the
+     * context methods, see Context::use_pool.
+     */
+    pub fn new_pool_with_min_liquidity(&mut self, min_liquidity: Decimal) -> ComponentAddress {
+        let config = PoolConfig {
+            initial_position: Some((tick_math::sqrt_price_at_tick(Tick(-1000)), tick_math::sqrt_price_at_tick(Tick(1000)))),
+            min_liquidity: Some(min_liquidity),
+            ..PoolConfig::new(Decimal::zero(), SqrtPrice(Decimal::one()), self.admin_badge_addr)
+        };
+        self.new_pool_with_config(config, dec!("1000"), dec!("1000")).expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a DCA vault swapping through the pool and returns its address and the resource of its order receipts
+     */
+    pub fn new_dca_vault(&mut self) -> (ComponentAddress, ResourceAddress) {
+        let new_dca_vault_manif = ManifestBuilder::new()
+            .call_function(self.package_addr, "DcaVault", "new", manifest_args!(self.pool_addr))
+            .build();
+        let new_dca_vault_receipt = execute_manif(&mut self.runner, new_dca_vault_manif, vec![&self.admin.pub_key]);
+        let result = new_dca_vault_receipt.expect_commit_success();
+        (result.new_component_addresses()[0], result.new_resource_addresses()[1])
+    }
+
+    /**
+     * Creates a DCA order swapping the given amount of MOJ of the account to USDT, in parts of amount_per_swap every interval
+     * seconds. The account receives the order receipt. The receipt isn't checked, so failed orders can be tested too.
+     */
+    pub fn create_dca_order(
+        &mut self,
+        account: &Account,
+        dca_vault_addr: ComponentAddress,
+        moj_amount: Decimal,
+        amount_per_swap: Decimal,
+        interval: i64,
+        min_output_rate: Decimal,
+    ) -> TransactionReceipt {
+        let create_order_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "input")
+            .call_method_with_name_lookup(dca_vault_addr, "create_order", |lookup| {
+                (lookup.bucket("input"), amount_per_swap, interval, min_output_rate)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let create_order_receipt = self.runner.execute_manifest_ignoring_fee(
+            create_order_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", create_order_receipt);
+        create_order_receipt
+    }
+
+    /**
+     * Executes the given DCA order as a keeper, without any badge. The receipt isn't checked, so failed executions can be tested too.
+     */
+    pub fn execute_dca_order(&mut self, dca_vault_addr: ComponentAddress, order_id: u64) -> TransactionReceipt {
+        let execute_manif = ManifestBuilder::new()
+            .call_method(dca_vault_addr, "execute_order", manifest_args!(order_id))
+            .build();
+        let execute_receipt = self.runner.execute_manifest_ignoring_fee(execute_manif, vec![]);
+        println!("{:?}\n", execute_receipt);
+        execute_receipt
+    }
+
+    /**
+     * Queries the given DCA order
+     */
+    pub fn get_dca_order(&mut self, dca_vault_addr: ComponentAddress, order_id: u64) -> DcaOrder {
+        let query_manif = ManifestBuilder::new()
+            .call_method(dca_vault_addr, "get_order", manifest_args!(order_id))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Claims the output of the DCA order of the account, which holds a single order receipt
+     */
+    pub fn claim_dca_output(
+        &mut self,
+        account: &Account,
+        dca_vault_addr: ComponentAddress,
+        receipt_addr: ResourceAddress,
+    ) -> TransactionReceipt {
+        let claim_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, receipt_addr, Decimal::one())
+            .create_proof_from_auth_zone_of_amount(receipt_addr, Decimal::one(), "receipt")
+            .call_method_with_name_lookup(dca_vault_addr, "claim_output", |lookup| (lookup.proof("receipt"),))
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        execute_manif(&mut self.runner, claim_manif, vec![&account.pub_key])
+    }
+
+    /**
+     * Cancels the DCA order of the account, which holds a single order receipt, getting back the input left and the output
+     */
+    pub fn cancel_dca_order(
+        &mut self,
+        account: &Account,
+        dca_vault_addr: ComponentAddress,
+        receipt_addr: ResourceAddress,
+    ) -> TransactionReceipt {
+        let cancel_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, receipt_addr, Decimal::one())
+            .take_all_from_worktop(receipt_addr, "receipt")
+            .call_method_with_name_lookup(dca_vault_addr, "cancel_order", |lookup| (lookup.bucket("receipt"),))
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        execute_manif(&mut self.runner, cancel_manif, vec![&account.pub_key])
+    }
+
+    /**
+     * Makes the given pool, e.g. a pool created with a factory, the pool used by the context methods
+     */
+    pub fn use_pool(&mut self, pool_addr: ComponentAddress) {
+        self.pool_addr = pool_addr;
+        self.position_nft_addr = self.get_pool_state().position_nft_addr;
+    }
+
+    /**
+     * Adds the fees accumulated by the account's position to liquidity
+     */
+    pub fn add_accumulated_fees_to_liq(&mut self, account: &Account) -> TransactionReceipt {
+        let add_liq_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungible(
+                account.addr,
+                self.pos_nft_badge_id(account.addr),
+            )
+            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(self.pool_addr, "add_accumulated_fees_to_liq", |lookup| {
+                (lookup.proof("proof"),)
+            })
+            .build();
+
+        let add_liq_receipt = self.runner.execute_manifest_ignoring_fee(
+            add_liq_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", add_liq_receipt);
+
+        add_liq_receipt.expect_commit_success();
+        add_liq_receipt
+    }
+
+    pub fn remove_admin_pos(
+        &mut self,
+        expected_moj_amount: Decimal,
+        expected_usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        let remove_liq_manif = self.create_remove_liq_manif(
+            self.admin.addr,
+            expected_moj_amount,
+            expected_usdt_amount,
+        );
+        self.execute_remove_lig_manif(remove_liq_manif, &self.admin.pub_key.clone())
+    }
+
+    /**
+     * Removes the given account's position and checks that upon removal the account got the expected amounts
+     */
+    pub fn remove_pos(
+        &mut self,
+        account: &Account,
+        expected_moj_amount: Decimal,
+        expected_usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        let remove_liq_manif =
+            self.create_remove_liq_manif(account.addr, expected_moj_amount, expected_usdt_amount);
+        self.execute_remove_lig_manif(remove_liq_manif, &account.pub_key)
+    }
+
+    fn create_remove_liq_manif(
+        &mut self,
+        account_addr: ComponentAddress,
+        expected_moj_amount: Decimal,
+        expected_usdt_amount: Decimal,
+    ) -> TransactionManifestV1 {
+        let pos_nft_id = self.pos_nft_badge_id(account_addr);
+        manifests::build_remove_pos_manifest(
+            self.pool_addr,
+            account_addr,
+            pos_nft_id,
+            (self.moj_addr, expected_moj_amount),
+            (self.usdt_addr, expected_usdt_amount),
+        )
+    }
+
+    fn execute_remove_lig_manif(
+        &mut self,
+        remove_liq_manif: TransactionManifestV1,
+        account_pub_key: &Secp256k1PublicKey,
+    ) -> TransactionReceipt {
+        let remove_liq_receipt = self.runner.execute_manifest_ignoring_fee(
+            remove_liq_manif,
+            vec![NonFungibleGlobalId::from_public_key(account_pub_key)],
+        );
+        println!("{:?}\n", remove_liq_receipt);
+
+        remove_liq_receipt.expect_commit_success();
+        remove_liq_receipt
+    }
+
+    /**
+     * Collects the fees accumulated by the given account's position and checks the expected amounts of fees
+     */
+    pub fn collect_fees(
+        &mut self,
+        account: &Account,
+        expected_moj_amount: Decimal,
+        expected_usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        let collect_fees_receipt = self.try_collect_fees(account, expected_moj_amount, expected_usdt_amount);
+        collect_fees_receipt.expect_commit_success();
+        collect_fees_receipt
+    }
+
+    /**
+     * Collects the fees accumulated by the given account's position, at least the given amounts of fees. The receipt isn't checked,
+     * so failed collects can be tested too.
+     */
+    pub fn try_collect_fees(
+        &mut self,
+        account: &Account,
+        expected_moj_amount: Decimal,
+        expected_usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        let pos_nft_id = self.pos_nft_badge_id(account.addr);
+        let collect_fees_manif = manifests::build_collect_fees_manifest(
+            self.pool_addr,
+            account.addr,
+            pos_nft_id,
+            (self.moj_addr, expected_moj_amount),
+            (self.usdt_addr, expected_usdt_amount),
+        );
+
+        let remove_liq_receipt = self.runner.execute_manifest_ignoring_fee(
+            collect_fees_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", remove_liq_receipt);
+        remove_liq_receipt
+    }
+
+    /**
+     * Collects the fees accumulated by the given account's position as the given resource, with a minimum output amount. The receipt
+     * isn't checked, so failed collects can be tested too.
+     */
+    pub fn collect_fees_as(
+        &mut self,
+        account: &Account,
+        output_resource: ResourceAddress,
+        min_out: Decimal,
+    ) -> TransactionReceipt {
+        let collect_fees_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungible(
+                account.addr,
+                self.pos_nft_badge_id(account.addr),
+            )
+            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
+            .call_method_with_name_lookup(self.pool_addr, "collect_fees_as", |lookup| {
+                (lookup.proof("proof"), output_resource, min_out)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+
+        let collect_fees_receipt = self.runner.execute_manifest_ignoring_fee(
+            collect_fees_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", collect_fees_receipt);
+        collect_fees_receipt
+    }
+
+    /**
+     * Swaps the given amount of MOJ taken from the given account to USDT. Also check the expected amount of USDT.
+     */
+    pub fn swap_moj_for_usdt(
+        &mut self,
+        account: &Account,
+        moj_amount: Decimal,
+        exp_usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        self.swap(
+            account,
+            self.moj_addr,
+            moj_amount,
+            self.usdt_addr,
+            exp_usdt_amount,
+        )
+    }
+
+    /**
+     * Swaps the given amount of MOJ taken from the given account to USDT. The receipt isn't checked, so failed swaps can be tested
+     * too.
+     */
+    pub fn try_swap_moj_for_usdt(&mut self, account: &Account, moj_amount: Decimal) -> TransactionReceipt {
+        self.try_swap_moj_for_usdt_with_badge(account, None, moj_amount)
+    }
+
+    /**
+     * Same as Context::try_swap_moj_for_usdt, but a proof of the given badge of the account, if any, is presented to the pool, e.g.
+     * the member badge of a permissioned pool
+     */
+    pub fn try_swap_moj_for_usdt_with_badge(
+        &mut self,
+        account: &Account,
+        badge_addr: Option<ResourceAddress>,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        let swap_manif = badge_proof_manif(account, badge_addr)
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .call_method_with_name_lookup(self.pool_addr, "swap", |lookup| {
+                (lookup.bucket("moj_bucket"),)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt
+    }
+
+    /**
+     * Same as Context::try_swap_moj_for_usdt, but the swap is referred by the given referrer, see Pool::swap_with_referrer
+     */
+    pub fn try_swap_moj_for_usdt_with_referrer(
+        &mut self,
+        account: &Account,
+        moj_amount: Decimal,
+        referrer_id: u64,
+    ) -> TransactionReceipt {
+        let swap_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .call_method_with_name_lookup(self.pool_addr, "swap_with_referrer", |lookup| {
+                (lookup.bucket("moj_bucket"), referrer_id)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt
+    }
+
+    /**
+     * Same as Context::try_swap_moj_for_usdt, but the swap is done as the trader identified by the given non fungible badge of the
+     * account, see Pool::swap_as_trader
+     */
+    pub fn try_swap_moj_for_usdt_as_trader(
+        &mut self,
+        account: &Account,
+        trader_id: &NonFungibleGlobalId,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        self.try_swap_as_trader(account, trader_id, self.moj_addr, moj_amount)
+    }
+
+    /**
+     * Swaps the given amount of USDT taken from the given account to MOJ. The receipt isn't checked, so failed swaps can be tested
+     * too.
+     */
+    pub fn try_swap_usdt_for_moj(&mut self, account: &Account, usdt_amount: Decimal) -> TransactionReceipt {
+        let swap_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.usdt_addr, usdt_amount)
+            .take_from_worktop(self.usdt_addr, usdt_amount, "usdt_bucket")
+            .call_method_with_name_lookup(self.pool_addr, "swap", |lookup| {
+                (lookup.bucket("usdt_bucket"),)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt
+    }
+
+    /**
+     * Same as Context::try_swap_usdt_for_moj, but the swap is done as the trader identified by the given non fungible badge of the
+     * account, see Pool::swap_as_trader
+     */
+    pub fn try_swap_usdt_for_moj_as_trader(
+        &mut self,
+        account: &Account,
+        trader_id: &NonFungibleGlobalId,
+        usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        self.try_swap_as_trader(account, trader_id, self.usdt_addr, usdt_amount)
+    }
+
+    fn try_swap_as_trader(
+        &mut self,
+        account: &Account,
+        trader_id: &NonFungibleGlobalId,
+        token_addr: ResourceAddress,
+        token_amount: Decimal,
+    ) -> TransactionReceipt {
+        let swap_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungible(account.addr, trader_id.clone())
+            .create_proof_from_auth_zone_of_all(trader_id.resource_address(), "trader_badge")
+            .withdraw_from_account(account.addr, token_addr, token_amount)
+            .take_from_worktop(token_addr, token_amount, "token_bucket")
+            .call_method_with_name_lookup(self.pool_addr, "swap_as_trader", |lookup| {
+                (lookup.bucket("token_bucket"), lookup.proof("trader_badge"))
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt
+    }
+
+    /**
+     * Swaps the given amount of USDT taken from the given account to MOJ. Also check the expected amount of MOJ.
+     */
+    pub fn swap_usdt_for_moj(
+        &mut self,
+        account: &Account,
+        usdt_amount: Decimal,
+        exp_moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        self.swap(
+            account,
+            self.usdt_addr,
+            usdt_amount,
+            self.moj_addr,
+            exp_moj_amount,
+        )
+    }
+    /**
+     * Executes a swap for the given resource address and amount. It expects the given expected resurce address and amount.
+     */
+    fn swap(
+        &mut self,
+        account: &Account,
+        token_addr: ResourceAddress,
+        token_amount: Decimal,
+        expected_token_addr: ResourceAddress,
+        expected_token_amount: Decimal,
+    ) -> TransactionReceipt {
+        let swap_manif = manifests::build_swap_manifest(
+            self.pool_addr,
+            account.addr,
+            token_addr,
+            token_amount,
+            expected_token_addr,
+            expected_token_amount,
+        );
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt.expect_commit_success();
+        swap_receipt
+    }
+
+    /**
+     * Queries the pool oracle observations of each of the given seconds_ago seconds before the current time. The receipt isn't
+     * checked, so the observations too old can be tested too.
+     */
+    pub fn observe(&mut self, seconds_ago: Vec<u64>) -> TransactionReceipt {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "observe", manifest_args!(seconds_ago))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt
+    }
+
+    /**
+     * Queries the pool oracle observations of each of the given seconds_ago seconds before the current time and expects them to exist
+     */
+    pub fn get_observations(&mut self, seconds_ago: Vec<u64>) -> Vec<Observation> {
+        self.observe(seconds_ago).expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the pool oracle observation of seconds_ago seconds before the current time and expects it to exist
+     */
+    pub fn get_observation(&mut self, seconds_ago: u64) -> Observation {
+        self.get_observations(vec![seconds_ago])[0]
+    }
+
+    /**
+     * Queries the time weighted average tick and price of the pool over the last window seconds
+     */
+    pub fn get_twap(&mut self, window: u64) -> (Tick, Decimal) {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "twap_tick", manifest_args!(window))
+            .call_method(self.pool_addr, "twap_price", manifest_args!(window))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        let commit_result = query_receipt.expect_commit_success();
+        (commit_result.output(0), commit_result.output(1))
+    }
+
+    /**
+     * Grows the pool oracle ring buffer to the given number of observations, paid by the given account
+     */
+    pub fn increase_observation_cardinality(&mut self, account: &Account, cardinality: u32) -> TransactionReceipt {
+        let increase_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "increase_observation_cardinality", manifest_args!(cardinality))
+            .build();
+        let increase_receipt = self.runner.execute_manifest_ignoring_fee(
+            increase_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", increase_receipt);
+        increase_receipt
+    }
+
+    /**
+     * Queries the number of observations the pool oracle keeps, currently and after the pending growth
+     */
+    pub fn get_observation_cardinality(&mut self) -> (u32, u32) {
+        self.query_pool("get_observation_cardinality")
+    }
+
+    /**
+     * Returns the ledger time in seconds since the unix epoch, rounded to minutes as the pool timestamps
+     */
+    pub fn get_current_time(&mut self) -> i64 {
+        self.runner.get_current_time(TimePrecision::Minute).seconds_since_unix_epoch
+    }
+
+    /**
+     * Advances the ledger time to the given seconds since the unix epoch. The time can only move forward.
+     */
+    pub fn set_current_time(&mut self, seconds: i64) {
+        // a consensus round for each second keeps the rounds increasing with the time
+        self.runner.advance_to_round_at_timestamp(Round::of(seconds as u64), seconds * 1000);
+    }
+
+    /**
+     * Returns the swap events emitted in the transaction of the given receipt
+     */
+    pub fn get_swap_events(&self, receipt: &TransactionReceipt) -> Vec<SwapEvent> {
+        self.get_events(receipt, "SwapEvent")
+    }
+
+    /**
+     * Returns the events of the given name, e.g. ParameterChangeScheduledEvent, emitted in the transaction of the given receipt
+     */
+    pub fn get_events<T: ScryptoDecode>(&self, receipt: &TransactionReceipt, event_name: &str) -> Vec<T> {
+        receipt
+            .expect_commit_success()
+            .application_events
+            .iter()
+            .filter(|(event_type_id, _)| self.runner.event_name(event_type_id) == event_name)
+            .map(|(_, event_data)| scrypto_decode::<T>(event_data).unwrap())
+            .collect()
+    }
+
+    /**
+     * Returns the events of the pool replayed by PoolReplay, in the order they were emitted, see PoolEvent::decode
+     */
+    pub fn get_pool_events(&self, receipt: &TransactionReceipt) -> Vec<PoolEvent> {
+        receipt
+            .expect_commit_success()
+            .application_events
+            .iter()
+            .filter_map(|(event_type_id, event_data)| PoolEvent::decode(&self.runner.event_name(event_type_id), event_data))
+            .collect()
+    }
+
+    /**
+     * Returns the metadata of the pool component with the given key, e.g. name or dapp_definition
+     */
+    pub fn get_pool_metadata(&mut self, key: &str) -> Option<MetadataValue> {
+        self.runner.get_metadata(self.pool_addr.into(), key)
+    }
+
+    /**
+     * Returns the metadata of the position NFT resource with the given key, e.g. name or icon_url
+     */
+    pub fn get_position_nft_metadata(&mut self, key: &str) -> Option<MetadataValue> {
+        self.runner.get_metadata(self.position_nft_addr.into(), key)
+    }
+
+    /**
+     * Calls the given getter of the pool, without arguments, and returns its output, e.g. get_price or get_fee
+     */
+    pub fn query_pool<T: ScryptoDecode>(&mut self, getter: &str) -> T {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, getter, manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the trailing volume of the given trader, see Pool::get_trader_volume
+     */
+    pub fn get_trader_volume(&mut self, trader_id: &NonFungibleGlobalId) -> Decimal {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_trader_volume", manifest_args!(trader_id.clone()))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Returns the MOJ and USDT amounts held by the given account
+     */
+    pub fn get_balances(&mut self, account: &Account) -> (Decimal, Decimal) {
+        (
+            self.runner.get_component_balance(account.addr, self.moj_addr),
+            self.runner.get_component_balance(account.addr, self.usdt_addr),
+        )
+    }
+
+    /**
+     * Queries the resource and the amount needed to move the pool price to the given tick
+     */
+    pub fn amount_to_move_price(&mut self, tick: i32) -> (ResourceAddress, Decimal) {
+        let query_manif = ManifestBuilder::new()
+            .call_method(
+                self.pool_addr,
+                "amount_to_move_price",
+                manifest_args!(tick_math::sqrt_price_at_tick(Tick(tick)).0),
+            )
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries a snapshot of the pool state
+     */
+    pub fn get_pool_state(&mut self) -> PoolState {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_pool_state", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the total value locked in the pool
+     */
+    pub fn get_tvl(&mut self) -> Tvl {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_tvl", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the swap statistics of the given epoch
+     */
+    pub fn get_epoch_stats(&mut self, epoch: u64) -> Option<EpochStats> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_epoch_stats", manifest_args!(epoch))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the pool depth, aggregated in the given number of levels of tick_bucket_size ticks each
+     */
+    pub fn get_depth(&mut self, levels: u32, tick_bucket_size: u32) -> Depth {
+        let query_manif = ManifestBuilder::new()
+            .call_method(
+                self.pool_addr,
+                "get_depth",
+                manifest_args!(levels, tick_bucket_size),
+            )
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the pool position held by the given account
+     */
+    pub fn get_position(&mut self, account: &Account) -> Option<Position> {
+        let pos_id = self.pos_nft_badge_id(account.addr).local_id().clone();
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_position", manifest_args!(pos_id))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the amounts of MOJ and USDT the position of the given account would redeem at the given sqrt price
+     */
+    pub fn get_position_amounts_at(&mut self, account: &Account, sqrt_price: SqrtPrice) -> (Decimal, Decimal) {
+        let pos_id = self.pos_nft_badge_id(account.addr).local_id().clone();
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_position_amounts_at", manifest_args!(pos_id, sqrt_price.0))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the portfolio of all the positions of the given account, presenting a proof of all its position NFTs, see
+     * Pool::get_portfolio
+     */
+    pub fn get_portfolio(&mut self, account: &Account) -> Portfolio {
+        let vaults = self.runner.get_component_vaults(account.addr, self.position_nft_addr);
+        let pos_ids: Vec<NonFungibleLocalId> = self.runner.inspect_non_fungible_vault(vaults[0]).unwrap().1.collect();
+        let query_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungibles(account.addr, self.position_nft_addr, pos_ids)
+            .create_proof_from_auth_zone_of_all(self.position_nft_addr, "proof")
+            .call_method_with_name_lookup(self.pool_addr, "get_portfolio", |lookup| (lookup.proof("proof"),))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(
+            query_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(2)
+    }
+
+    /**
+     * Queries the NFT ids and the positions of all the active pool positions
+     */
+    pub fn get_positions(&mut self) -> Vec<(NonFungibleLocalId, Position)> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_positions", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the states of the pool used ticks, ordered by tick
+     */
+    pub fn get_tick_states(&mut self) -> Vec<TickState> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "get_tick_states", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries a page of the pool used ticks with their liquidity_net and liquidity_gross, see Pool::list_initialized_ticks. The
+     * receipt isn't checked, so invalid page limits can be tested too.
+     */
+    pub fn try_list_initialized_ticks(&mut self, cursor: Option<i32>, limit: u32) -> TransactionReceipt {
+        let query_manif = ManifestBuilder::new()
+            .call_method(self.pool_addr, "list_initialized_ticks", manifest_args!(cursor, limit))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt
+    }
+
+    /**
+     * Creates another MOJ/USDT pool without any position, the admin account badge being its admin badge. The context pool is left
+     * unchanged.
+     */
+    pub fn new_pool_without_position(&mut self, fee: Decimal, tick_spacing: i32, sqrt_price: SqrtPrice) -> ComponentAddress {
+        let config = PoolConfig { tick_spacing, ..PoolConfig::new(fee, sqrt_price, self.admin_badge_addr) };
+        self.new_pool_with_config(config, Decimal::zero(), Decimal::zero()).expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a pool factory, the admin account badge being the factory admin badge, with a creation fee paid in MOJ, in allowlist
+     * mode or not
+     */
+    pub fn new_factory(&mut self, creation_fee: Decimal, allowlist_mode: bool) -> ComponentAddress {
+        let new_factory_manif = ManifestBuilder::new()
+            .call_function(
+                self.package_addr,
+                "PoolFactory",
+                "new",
+                manifest_args!(self.admin_badge_addr, self.moj_addr, creation_fee, allowlist_mode),
+            )
+            .build();
+        let new_factory_receipt = execute_manif(&mut self.runner, new_factory_manif, vec![&self.admin.pub_key]);
+        new_factory_receipt.expect_commit_success().new_component_addresses()[0]
+    }
+
+    /**
+     * Creates a MOJ/USDT pool with the given factory, funded by the given account, which also pays the current creation fee. The
+     * receipt isn't checked, so failed pool creations can be tested too.
+     */
+    pub fn create_pool_with_factory(
+        &mut self,
+        factory_addr: ComponentAddress,
+        account: &Account,
+        fee: Decimal,
+        sqrt_price: SqrtPrice,
+        low_sqrt_price: SqrtPrice,
+        high_sqrt_price: SqrtPrice,
+        moj_amount: Decimal,
+        usdt_amount: Decimal,
+    ) -> TransactionReceipt {
+        let (moj_addr, usdt_addr) = (self.moj_addr, self.usdt_addr);
+        self.create_pair_pool_with_factory(
+            factory_addr,
+            account,
+            fee,
+            (sqrt_price, low_sqrt_price, high_sqrt_price),
+            (moj_addr, moj_amount),
+            (usdt_addr, usdt_amount),
+        )
+    }
+
+    /**
+     * Same as Context::create_pool_with_factory, but the pool is created with the resources in reverse order, USDT/MOJ, the sqrt
+     * prices being of USDT vs MOJ
+     */
+    pub fn create_reversed_pool_with_factory(
+        &mut self,
+        factory_addr: ComponentAddress,
+        account: &Account,
+        fee: Decimal,
+        sqrt_price: SqrtPrice,
+        low_sqrt_price: SqrtPrice,
+        high_sqrt_price: SqrtPrice,
+        usdt_amount: Decimal,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        let (moj_addr, usdt_addr) = (self.moj_addr, self.usdt_addr);
+        self.create_pair_pool_with_factory(
+            factory_addr,
+            account,
+            fee,
+            (sqrt_price, low_sqrt_price, high_sqrt_price),
+            (usdt_addr, usdt_amount),
+            (moj_addr, moj_amount),
+        )
+    }
+
+    fn create_pair_pool_with_factory(
+        &mut self,
+        factory_addr: ComponentAddress,
+        account: &Account,
+        fee: Decimal,
+        (sqrt_price, low_sqrt_price, high_sqrt_price): (SqrtPrice, SqrtPrice, SqrtPrice),
+        (resource0_addr, amount0): (ResourceAddress, Decimal),
+        (resource1_addr, amount1): (ResourceAddress, Decimal),
+    ) -> TransactionReceipt {
+        let (creation_fee_resource_addr, creation_fee) = self.get_creation_fee(factory_addr);
+        let create_pool_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, creation_fee_resource_addr, creation_fee)
+            .take_from_worktop(creation_fee_resource_addr, creation_fee, "creation_fee_bucket")
+            .withdraw_from_account(account.addr, resource0_addr, amount0)
+            .withdraw_from_account(account.addr, resource1_addr, amount1)
+            .take_from_worktop(resource0_addr, amount0, "bucket0")
+            .take_from_worktop(resource1_addr, amount1, "bucket1")
+            .call_method_with_name_lookup(factory_addr, "create_pool", |lookup| {
+                (
+                    resource0_addr,
+                    resource1_addr,
+                    fee,
+                    sqrt_price.0,
+                    low_sqrt_price.0,
+                    high_sqrt_price.0,
+                    lookup.bucket("bucket0"),
+                    lookup.bucket("bucket1"),
+                    lookup.bucket("creation_fee_bucket"),
+                )
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let create_pool_receipt = self.runner.execute_manifest_ignoring_fee(
+            create_pool_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", create_pool_receipt);
+        create_pool_receipt
+    }
+
+    /**
+     * Queries the resource and the amount paid to create a pool with the given factory
+     */
+    pub fn get_creation_fee(&mut self, factory_addr: ComponentAddress) -> (ResourceAddress, Decimal) {
+        let query_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "get_creation_fee", manifest_args!())
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Withdraws the MOJ creation fees of the given factory to the admin account
+     */
+    pub fn withdraw_creation_fees(&mut self, factory_addr: ComponentAddress) -> TransactionReceipt {
+        let withdraw_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .call_method(factory_addr, "withdraw_creation_fees", manifest_args!(self.moj_addr))
+            .call_method(
+                self.admin.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        execute_manif(&mut self.runner, withdraw_manif, vec![&self.admin.pub_key])
+    }
+
+    /**
+     * Calls an allowlist admin method of the given factory: add_to_allowlist, remove_from_allowlist with the given resource, or
+     * disable_allowlist without arguments
+     */
+    pub fn update_allowlist(&mut self, factory_addr: ComponentAddress, method: &str, resource_addr: Option<ResourceAddress>) {
+        let args = match resource_addr {
+            Some(resource_addr) => manifest_args!(resource_addr),
+            None => manifest_args!(),
+        };
+        let update_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .call_method(factory_addr, method, args)
+            .build();
+        execute_manif(&mut self.runner, update_manif, vec![&self.admin.pub_key]);
+    }
+
+    /**
+     * Marks a pool of the given factory as curated or not, signed by the given account. The receipt isn't checked.
+     */
+    pub fn set_curated(
+        &mut self,
+        factory_addr: ComponentAddress,
+        account: &Account,
+        pool_addr: ComponentAddress,
+        curated: bool,
+    ) -> TransactionReceipt {
+        let set_curated_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, self.admin_badge_addr, Decimal::one())
+            .call_method(factory_addr, "set_curated", manifest_args!(pool_addr, curated))
+            .build();
+        let set_curated_receipt = self.runner.execute_manifest_ignoring_fee(
+            set_curated_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", set_curated_receipt);
+        set_curated_receipt
+    }
+
+    /**
+     * Flags a deprecated pool of the given factory, see PoolFactory::flag_deprecated. The receipt isn't checked, so pools that aren't
+     * deprecated can be tested too.
+     */
+    pub fn flag_deprecated_pool(&mut self, factory_addr: ComponentAddress, pool_addr: ComponentAddress) -> TransactionReceipt {
+        let flag_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "flag_deprecated", manifest_args!(pool_addr))
+            .build();
+        let flag_receipt = self.runner.execute_manifest_ignoring_fee(flag_manif, vec![]);
+        println!("{:?}\n", flag_receipt);
+        flag_receipt
+    }
+
+    /**
+     * Enables a fee tier of the given factory, signed by the admin account. The receipt isn't checked.
+     */
+    pub fn enable_fee_tier(&mut self, factory_addr: ComponentAddress, fee: Decimal, tick_spacing: i32) -> TransactionReceipt {
+        let enable_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.admin.addr, self.admin_badge_addr, Decimal::one())
+            .call_method(factory_addr, "enable_fee_tier", manifest_args!(fee, tick_spacing))
+            .build();
+        let enable_receipt = self.runner.execute_manifest_ignoring_fee(
+            enable_manif,
+            vec![NonFungibleGlobalId::from_public_key(&self.admin.pub_key)],
+        );
+        println!("{:?}\n", enable_receipt);
+        enable_receipt
+    }
+
+    /**
+     * Calls the given method of the given factory presenting the given badge of the account, e.g. propose_admin or accept_admin.
+     * The receipt isn't checked, so failed calls can be tested too.
+     */
+    pub fn call_factory_with_badge(
+        &mut self,
+        factory_addr: ComponentAddress,
+        account: &Account,
+        badge_addr: ResourceAddress,
+        method: &str,
+        args: impl ResolvableArguments,
+    ) -> TransactionReceipt {
+        let call_manif = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account.addr, badge_addr, Decimal::one())
+            .call_method(factory_addr, method, args)
+            .build();
+        let call_receipt = self.runner.execute_manifest_ignoring_fee(
+            call_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", call_receipt);
+        call_receipt
+    }
+
+    /**
+     * Queries the tick spacing of the given fee tier of the factory, if the fee tier is enabled
+     */
+    pub fn get_tick_spacing(&mut self, factory_addr: ComponentAddress, fee: Decimal) -> Option<i32> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "get_tick_spacing", manifest_args!(fee))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries whether the given fee tier of the factory is a stable one
+     */
+    pub fn is_stable_fee_tier(&mut self, factory_addr: ComponentAddress, fee: Decimal) -> bool {
+        let query_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "is_stable_fee_tier", manifest_args!(fee))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries the address of the factory pool of the given resources and fee
+     */
+    pub fn get_factory_pool(
+        &mut self,
+        factory_addr: ComponentAddress,
+        resource_a_addr: ResourceAddress,
+        resource_b_addr: ResourceAddress,
+        fee: Decimal,
+    ) -> Option<ComponentAddress> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "get_pool", manifest_args!(resource_a_addr, resource_b_addr, fee))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Queries a page of the pools created by the given factory, see PoolFactory::list_pools
+     */
+    pub fn list_factory_pools(&mut self, factory_addr: ComponentAddress, cursor: u64, limit: u64) -> (Vec<PoolInfo>, Option<u64>) {
+        let query_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "list_pools", manifest_args!(cursor, limit))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Returns the best route of the given factory to swap the given amount of token_in for token_out
+     */
+    pub fn best_quote(
+        &mut self,
+        factory_addr: ComponentAddress,
+        token_in: ResourceAddress,
+        token_out: ResourceAddress,
+        amount: Decimal,
+    ) -> Option<SwapQuote> {
+        let query_manif = ManifestBuilder::new()
+            .call_method(factory_addr, "best_quote", manifest_args!(token_in, token_out, amount))
+            .build();
+        let query_receipt = self.runner.execute_manifest_ignoring_fee(query_manif, vec![]);
+        println!("{:?}\n", query_receipt);
+        query_receipt.expect_commit_success().output(0)
+    }
+
+    /**
+     * Swaps the given amount of MOJ taken from the given account to USDT with the Router, split across the given pools according to
+     * the given weights. The receipt isn't checked, so failed swaps can be tested too.
+     */
+    pub fn swap_moj_with_weights(
+        &mut self,
+        account: &Account,
+        pool_addrs: Vec<ComponentAddress>,
+        weights: Vec<Decimal>,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        let swap_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .call_function_with_name_lookup(self.package_addr, "Router", "swap_with_weights", |lookup| {
+                (lookup.bucket("moj_bucket"), pool_addrs, weights)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt
+    }
+
+    /**
+     * Swaps the given amount of MOJ taken from the given account to USDT with the Router, split greedily across the given pools in
+     * the given number of chunks. The receipt isn't checked, so failed swaps can be tested too.
+     */
+    pub fn swap_moj_greedy(
+        &mut self,
+        account: &Account,
+        pool_addrs: Vec<ComponentAddress>,
+        chunks: u32,
+        moj_amount: Decimal,
+    ) -> TransactionReceipt {
+        let swap_manif = ManifestBuilder::new()
+            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
+            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
+            .call_function_with_name_lookup(self.package_addr, "Router", "swap_greedy", |lookup| {
+                (lookup.bucket("moj_bucket"), pool_addrs, chunks)
+            })
+            .call_method(
+                account.addr,
+                "deposit_batch",
+                manifest_args!(ManifestExpression::EntireWorktop),
+            )
+            .build();
+        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
+            swap_manif,
+            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
+        );
+        println!("{:?}\n", swap_receipt);
+        swap_receipt
+    }
+
+    /**
+     * Publishes the TickMathBench test package (testkit/bench directory), returning its address for compute_ticks_on_ledger
+     */
+    pub fn publish_tick_math_bench(&mut self) -> PackageAddress {
+        self.runner.compile_and_publish(Path::new(env!("CARGO_MANIFEST_DIR")).join("bench"))
+    }
+
+    /**
+     * Computes on ledger the ticks of the given sqrt prices with the given TickMathBench function of the published bench package:
+     * tick_at_sqrt_price or tick_at_sqrt_price_log. Returns the ticks and the execution cost units of the transaction.
+     */
+    pub fn compute_ticks_on_ledger(
+        &mut self,
+        bench_package_addr: PackageAddress,
+        bench_function: &str,
+        sqrt_prices: &[SqrtPrice],
+    ) -> (Vec<Tick>, u32) {
+        let sqrt_prices: Vec<Decimal> = sqrt_prices.iter().map(|sqrt_price| sqrt_price.0).collect();
+        let bench_manif = ManifestBuilder::new()
+            .call_function(bench_package_addr, "TickMathBench", bench_function, manifest_args!(sqrt_prices))
+            .build();
+        let bench_receipt = self.runner.execute_manifest_ignoring_fee(bench_manif, vec![]);
+        let ticks = bench_receipt.expect_commit_success().output(0);
+        (ticks, bench_receipt.fee_summary.total_execution_cost_units_consumed)
+    }
+}
+
+/**
+ * Builds the manifest creating a MOJ/USDT pool with the given config, funded with the given amounts of the admin account, which
+ * receives the position NFTs and the remainders
+ */
+fn new_pool_manif(
+    package_addr: PackageAddress,
+    admin: &Account,
+    (moj_addr, moj_amount): (ResourceAddress, Decimal),
+    (usdt_addr, usdt_amount): (ResourceAddress, Decimal),
+    config: PoolConfig,
+) -> TransactionManifestV1 {
+    ManifestBuilder::new()
+        .withdraw_from_account(admin.addr, moj_addr, moj_amount)
+        .withdraw_from_account(admin.addr, usdt_addr, usdt_amount)
+        .take_from_worktop(moj_addr, moj_amount, "moj_bucket")
+        .take_from_worktop(usdt_addr, usdt_amount, "usdt_bucket")
+        .call_function_with_name_lookup(package_addr, "Pool", "new", |lookup| {
+            (moj_addr, usdt_addr, config.clone(), lookup.bucket("moj_bucket"), lookup.bucket("usdt_bucket"))
+        })
+        .call_method(
+            admin.addr,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build()
+}
+
+/**
+ * Starts a manifest presenting a proof of the given badge of the account, if any
+ */
+fn badge_proof_manif(account: &Account, badge_addr: Option<ResourceAddress>) -> ManifestBuilder {
+    match badge_addr {
+        Some(badge_addr) => {
+            ManifestBuilder::new().create_proof_from_account_of_amount(account.addr, badge_addr, Decimal::one())
+        }
+        None => ManifestBuilder::new(),
+    }
+}
+
+/**
+ * Executes a given manifest and expects to be successful
+ */
+pub fn execute_manif(
+    runner: &mut DefaultTestRunner,
+    manif: TransactionManifestV1,
+    pub_keys: Vec<&Secp256k1PublicKey>,
+) -> TransactionReceipt {
+    let receipt = runner.execute_manifest_ignoring_fee(
+        manif,
+        pub_keys
+            .iter()
+            .map(|pub_key| NonFungibleGlobalId::from_public_key(*pub_key))
+            .collect::<Vec<_>>(),
+    );
+    println!("{:?}\n", receipt);
+    receipt.expect_commit_success();
+    receipt
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::aggregator::{PoolVersion, SwapEvent};
+use mojitoswap_pool::pool::{FeeConfig, ParameterChange, VolumeTier};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Aggregator interface.
+ *
+ * We test the getters of the aggregator interface and that a swap emits a SwapEvent with the amounts swapped, the new price, the
+ * effective price of the swap and the fee paid.
+ */
+#[test]
+fn aggregator_interface() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let (moj_addr, usdt_addr) = (context.moj_addr, context.usdt_addr);
+    assert_eq!((moj_addr, usdt_addr), context.query_pool::<(ResourceAddress, ResourceAddress)>("get_resource_addresses"));
+    assert_eq!(Decimal::one(), context.query_pool::<Decimal>("get_price"));
+    assert_eq!(dec!("0.003"), context.query_pool::<Decimal>("get_fee"));
+
+    let account = context.new_account_with_moj_and_usdt(dec!("100"), dec!("0"));
+    let receipt = context.swap_moj_for_usdt(&account, dec!("100"), Decimal::zero());
+    let (_, usdt_balance) = context.get_balances(&account);
+    let pool_state = context.get_pool_state();
+    assert_eq!(
+        vec![SwapEvent {
+            resource_in_addr: moj_addr,
+            amount_in: dec!("100"),
+            resource_out_addr: usdt_addr,
+            amount_out: usdt_balance,
+            sqrt_price: pool_state.sqrt_price,
+            effective_price: usdt_balance / dec!("100"),
+            fee_amount: dec!("0.3"),
+        }],
+        context.get_swap_events(&receipt)
+    );
+    assert_eq!(pool_state.sqrt_price.0 * pool_state.sqrt_price.0, context.query_pool::<Decimal>("get_price"));
+}
+
+/**
+ * Version handshake.
+ *
+ * We test that the pool returns the package version and its supported features.
+ */
+#[test]
+fn get_version() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let version = context.query_pool::<PoolVersion>("get_version");
+    assert_eq!((0, 1, 0), (version.major, version.minor, version.patch));
+    assert!(version.is_at_least(0, 1, 0) && !version.is_at_least(0, 2, 0));
+    assert!(version.supports("quote_swap") && version.supports("export_state"));
+    assert!(!version.supports("unknown_feature"));
+}
+
+/**
+ * Fee configuration.
+ *
+ * We test that the pool returns its fee parameters in one call: the static fee, the protocol and referral fee shares and the
+ * volume tiers, as they are changed.
+ */
+#[test]
+fn get_fee_config() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let mut fee_config = FeeConfig {
+        fee: dec!("0.003"),
+        fee_ramp: None,
+        fee_manager: None,
+        protocol_fee_enabled: false,
+        protocol_fee_share: Decimal::zero(),
+        referral_fee_share: Decimal::zero(),
+        volume_tiers: vec![],
+    };
+    assert_eq!(fee_config, context.query_pool::<FeeConfig>("get_fee_config"));
+
+    let tiers = vec![VolumeTier { min_volume: dec!("50"), fee_discount: dec!("0.5") }];
+    context.apply_change(ParameterChange::SetProtocolFeeShare(dec!("0.25"))).expect_commit_success();
+    context.apply_change(ParameterChange::SetReferralFeeShare(dec!("0.2"))).expect_commit_success();
+    context.apply_change(ParameterChange::SetVolumeTiers(tiers.clone())).expect_commit_success();
+    fee_config.protocol_fee_share = dec!("0.25");
+    fee_config.referral_fee_share = dec!("0.2");
+    fee_config.volume_tiers = tiers;
+    assert_eq!(fee_config, context.query_pool::<FeeConfig>("get_fee_config"));
+}
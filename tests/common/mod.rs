@@ -0,0 +1,5 @@
+#![allow(dead_code)]
+
+pub use mojitoswap_testkit::*;
+
+pub mod model;
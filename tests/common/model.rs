@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, HashMap};
+
+/**
+ * The sqrt price at the given tick, computed with the closed formula sqrt(1.0001^tick)
+ */
+pub fn sqrt_price_at_tick(tick: i32) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
+}
+
+/**
+ * The greatest tick whose sqrt price is lower than or equal to the given sqrt price. The small epsilon keeps the prices computed
+ * by sqrt_price_at_tick on their own tick despite the f64 rounding.
+ */
+pub fn tick_at_sqrt_price(sqrt_price: f64) -> i32 {
+    (2.0 * sqrt_price.ln() / 1.0001f64.ln() + 1e-9).floor() as i32
+}
+
+fn amount0_given_liq(liq: f64, low_sqrt_price: f64, high_sqrt_price: f64) -> f64 {
+    liq * (high_sqrt_price - low_sqrt_price) / (high_sqrt_price * low_sqrt_price)
+}
+
+fn amount1_given_liq(liq: f64, low_sqrt_price: f64, high_sqrt_price: f64) -> f64 {
+    liq * (high_sqrt_price - low_sqrt_price)
+}
+
+fn liq_given_amount0(amount0: f64, low_sqrt_price: f64, high_sqrt_price: f64) -> f64 {
+    amount0 * low_sqrt_price * high_sqrt_price / (high_sqrt_price - low_sqrt_price)
+}
+
+fn liq_given_amount1(amount1: f64, low_sqrt_price: f64, high_sqrt_price: f64) -> f64 {
+    amount1 / (high_sqrt_price - low_sqrt_price)
+}
+
+/**
+ * Model of a used tick: the liquidity it adds or removes as it is crossed and the fees generated outside of it
+ */
+struct TickModel {
+    liq_net: f64,
+    liq_gross: f64,
+    fee_outside0: f64,
+    fee_outside1: f64,
+}
+
+/**
+ * Model of a position: its liquidity, range and the range fees already collected
+ */
+struct PositionModel {
+    liq: f64,
+    low_tick: i32,
+    high_tick: i32,
+    range_fee0: f64,
+    range_fee1: f64,
+}
+
+/**
+ * A floating point reference model of the pool component.
+ *
+ * It implements the same concentrated liquidity algorithm as the component, but with f64 values and the closed formula tick
+ * prices instead of the 18 decimals fixed point math. It is used by the differential tests, that expect the component results to
+ * agree with the model results within a documented tolerance.
+ */
+pub struct PoolModel {
+    pub fee: f64,
+    pub sqrt_price: f64,
+    pub tick: i32,
+    pub live_liq: f64,
+    pub fee_global0: f64,
+    pub fee_global1: f64,
+    pub amount0: f64,
+    pub amount1: f64,
+    ticks: BTreeMap<i32, TickModel>,
+    positions: HashMap<u64, PositionModel>,
+    next_pos_id: u64,
+}
+
+impl PoolModel {
+    /**
+     * Creates an empty pool model with the given fee and sqrt price
+     */
+    pub fn new(fee: f64, sqrt_price: f64) -> Self {
+        Self {
+            fee,
+            sqrt_price,
+            tick: tick_at_sqrt_price(sqrt_price),
+            live_liq: 0.0,
+            fee_global0: 0.0,
+            fee_global1: 0.0,
+            amount0: 0.0,
+            amount1: 0.0,
+            ticks: BTreeMap::new(),
+            positions: HashMap::new(),
+            next_pos_id: 0,
+        }
+    }
+
+    /**
+     * Adds a position in the [low_tick, high_tick] range. Returns the position id and the amounts given back.
+     */
+    pub fn add_pos(&mut self, amount0: f64, amount1: f64, low_tick: i32, high_tick: i32) -> (u64, f64, f64) {
+        let low_sqrt_price = sqrt_price_at_tick(low_tick);
+        let high_sqrt_price = sqrt_price_at_tick(high_tick);
+
+        let (liq, used0, used1) = if self.sqrt_price <= low_sqrt_price {
+            (liq_given_amount0(amount0, low_sqrt_price, high_sqrt_price), amount0, 0.0)
+        } else if self.sqrt_price < high_sqrt_price {
+            let liq0 = liq_given_amount0(amount0, self.sqrt_price, high_sqrt_price);
+            let liq1 = liq_given_amount1(amount1, low_sqrt_price, self.sqrt_price);
+            if liq0 < liq1 {
+                (liq0, amount0, amount1_given_liq(liq0, low_sqrt_price, self.sqrt_price))
+            } else {
+                (liq1, amount0_given_liq(liq1, self.sqrt_price, high_sqrt_price), amount1)
+            }
+        } else {
+            (liq_given_amount1(amount1, low_sqrt_price, high_sqrt_price), 0.0, amount1)
+        };
+
+        self.update_live_liq(liq, low_tick, high_tick);
+        self.update_tick(low_tick, liq, false);
+        self.update_tick(high_tick, liq, true);
+        self.amount0 += used0;
+        self.amount1 += used1;
+
+        let (range_fee0, range_fee1) = self.range_fees(low_tick, high_tick);
+        self.next_pos_id += 1;
+        self.positions.insert(
+            self.next_pos_id,
+            PositionModel {
+                liq,
+                low_tick,
+                high_tick,
+                range_fee0,
+                range_fee1,
+            },
+        );
+        (self.next_pos_id, amount0 - used0, amount1 - used1)
+    }
+
+    /**
+     * Removes the position. Returns the amounts given back, fees included.
+     */
+    pub fn remove_pos(&mut self, pos_id: u64) -> (f64, f64) {
+        let liq = self.positions[&pos_id].liq;
+        self.remove_liq(pos_id, liq)
+    }
+
+    /**
+     * Collects the position fees
+     */
+    pub fn collect_fees(&mut self, pos_id: u64) -> (f64, f64) {
+        self.remove_liq(pos_id, 0.0)
+    }
+
+    /**
+     * Swaps the given amount of token0 or token1. Returns the swapped amount and the amount given back.
+     */
+    pub fn swap(&mut self, amount: f64, is_token0: bool) -> (f64, f64) {
+        let mut available_amount = amount;
+        let mut total_swapped_amount = 0.0;
+
+        while available_amount > 0.0 && self.live_liq > 0.0 {
+            let opt_tick_to_cross = if is_token0 {
+                self.ticks.range(..=self.tick).next_back()
+            } else {
+                self.ticks.range(self.tick + 1..).next()
+            };
+            let tick_to_cross = match opt_tick_to_cross {
+                Some((tick, _)) => *tick,
+                None => break,
+            };
+            let sqrt_price_at_tick_to_cross = sqrt_price_at_tick(tick_to_cross);
+
+            let needed_amount_to_move_price = if is_token0 {
+                amount0_given_liq(self.live_liq, sqrt_price_at_tick_to_cross, self.sqrt_price)
+            } else {
+                amount1_given_liq(self.live_liq, self.sqrt_price, sqrt_price_at_tick_to_cross)
+            };
+            let needed_amount_to_cross_tick = needed_amount_to_move_price / (1.0 - self.fee);
+            let is_tick_cross_needed = needed_amount_to_cross_tick < available_amount;
+
+            let amount_to_swap = if is_tick_cross_needed {
+                needed_amount_to_cross_tick
+            } else {
+                available_amount
+            };
+            let fee_amount = amount_to_swap * self.fee;
+            let amount_to_swap = amount_to_swap - fee_amount;
+
+            let (new_sqrt_price, swapped_amount) = if is_token0 {
+                let new_sqrt_price = 1.0 / (1.0 / self.sqrt_price + amount_to_swap / self.live_liq);
+                (new_sqrt_price, (self.sqrt_price - new_sqrt_price) * self.live_liq)
+            } else {
+                let new_sqrt_price = self.sqrt_price + amount_to_swap / self.live_liq;
+                (new_sqrt_price, (1.0 / self.sqrt_price - 1.0 / new_sqrt_price) * self.live_liq)
+            };
+
+            available_amount -= amount_to_swap + fee_amount;
+            total_swapped_amount += swapped_amount;
+            if is_token0 {
+                self.fee_global0 += fee_amount / self.live_liq;
+            } else {
+                self.fee_global1 += fee_amount / self.live_liq;
+            }
+
+            if is_tick_cross_needed {
+                self.sqrt_price = sqrt_price_at_tick_to_cross;
+                self.cross_tick(tick_to_cross);
+            } else if new_sqrt_price != self.sqrt_price {
+                self.sqrt_price = new_sqrt_price;
+                let tick = tick_at_sqrt_price(new_sqrt_price);
+                self.tick = if is_token0 {
+                    tick.max(tick_to_cross)
+                } else {
+                    tick.min(tick_to_cross - 1)
+                };
+            }
+        }
+
+        if is_token0 {
+            self.amount0 += amount - available_amount;
+            self.amount1 -= total_swapped_amount;
+        } else {
+            self.amount1 += amount - available_amount;
+            self.amount0 -= total_swapped_amount;
+        }
+        (total_swapped_amount, available_amount)
+    }
+
+    fn cross_tick(&mut self, tick: i32) {
+        let cross_up = self.tick < tick;
+        let state = self.ticks.get_mut(&tick).unwrap();
+        state.fee_outside0 = self.fee_global0 - state.fee_outside0;
+        state.fee_outside1 = self.fee_global1 - state.fee_outside1;
+        if cross_up {
+            self.live_liq += state.liq_net;
+            self.tick = tick;
+        } else {
+            self.live_liq -= state.liq_net;
+            self.tick = tick - 1;
+        }
+        self.snap_live_liq();
+    }
+
+    fn remove_liq(&mut self, pos_id: u64, liq: f64) -> (f64, f64) {
+        let (low_tick, high_tick) = {
+            let pos = &self.positions[&pos_id];
+            (pos.low_tick, pos.high_tick)
+        };
+        let (range_fee0, range_fee1) = self.range_fees(low_tick, high_tick);
+
+        let pos = self.positions.get_mut(&pos_id).unwrap();
+        let fees0 = (range_fee0 - pos.range_fee0) * pos.liq;
+        let fees1 = (range_fee1 - pos.range_fee1) * pos.liq;
+        pos.liq -= liq;
+        pos.range_fee0 = range_fee0;
+        pos.range_fee1 = range_fee1;
+        if pos.liq == 0.0 {
+            self.positions.remove(&pos_id);
+        }
+
+        self.update_live_liq(-liq, low_tick, high_tick);
+        self.update_tick(low_tick, -liq, false);
+        self.update_tick(high_tick, -liq, true);
+
+        let low_sqrt_price = sqrt_price_at_tick(low_tick);
+        let high_sqrt_price = sqrt_price_at_tick(high_tick);
+        let (amount0, amount1) = if self.sqrt_price < low_sqrt_price {
+            (amount0_given_liq(liq, low_sqrt_price, high_sqrt_price), 0.0)
+        } else if self.sqrt_price < high_sqrt_price {
+            (
+                amount0_given_liq(liq, self.sqrt_price, high_sqrt_price),
+                amount1_given_liq(liq, low_sqrt_price, self.sqrt_price),
+            )
+        } else {
+            (0.0, amount1_given_liq(liq, low_sqrt_price, high_sqrt_price))
+        };
+
+        // like the component, never give back more than the vaults hold
+        let amount0 = (amount0 + fees0).min(self.amount0);
+        let amount1 = (amount1 + fees1).min(self.amount1);
+        self.amount0 -= amount0;
+        self.amount1 -= amount1;
+        (amount0, amount1)
+    }
+
+    fn range_fees(&self, low_tick: i32, high_tick: i32) -> (f64, f64) {
+        let low = &self.ticks[&low_tick];
+        let high = &self.ticks[&high_tick];
+        let (below0, below1) = if self.tick >= low_tick {
+            (low.fee_outside0, low.fee_outside1)
+        } else {
+            (self.fee_global0 - low.fee_outside0, self.fee_global1 - low.fee_outside1)
+        };
+        let (above0, above1) = if self.tick >= high_tick {
+            (self.fee_global0 - high.fee_outside0, self.fee_global1 - high.fee_outside1)
+        } else {
+            (high.fee_outside0, high.fee_outside1)
+        };
+        (
+            self.fee_global0 - below0 - above0,
+            self.fee_global1 - below1 - above1,
+        )
+    }
+
+    fn update_live_liq(&mut self, liq: f64, low_tick: i32, high_tick: i32) {
+        if self.tick >= low_tick && self.tick < high_tick {
+            self.live_liq += liq;
+            self.snap_live_liq();
+        }
+    }
+
+    fn update_tick(&mut self, tick: i32, liq: f64, is_high_tick: bool) {
+        let current_tick = self.tick;
+        let (fee_global0, fee_global1) = (self.fee_global0, self.fee_global1);
+        let state = self.ticks.entry(tick).or_insert_with(|| {
+            // by convention, on init, all fees were generated outside of the tick
+            let is_below = tick <= current_tick;
+            TickModel {
+                liq_net: 0.0,
+                liq_gross: 0.0,
+                fee_outside0: if is_below { fee_global0 } else { 0.0 },
+                fee_outside1: if is_below { fee_global1 } else { 0.0 },
+            }
+        });
+        state.liq_net += if is_high_tick { -liq } else { liq };
+        state.liq_gross += liq;
+        if state.liq_gross == 0.0 {
+            self.ticks.remove(&tick);
+        }
+    }
+
+    /**
+     * The f64 rounding leaves a tiny live liquidity when the last active position is removed, while the component gets to zero
+     */
+    fn snap_live_liq(&mut self) {
+        if self.live_liq.abs() < 1e-6 {
+            self.live_liq = 0.0;
+        }
+    }
+}
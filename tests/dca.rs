@@ -0,0 +1,52 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+// a time after the ledger genesis, rounded to minutes as the orders schedule
+const START_TIME: i64 = 1_700_000_040;
+
+/**
+ * DCA orders.
+ *
+ * We test that a DCA order is swapped by a keeper in parts, once per interval, until its input is swapped entirely, that the owner
+ * claims the output and that an order with a minimum output rate over the pool price isn't swapped and can be cancelled.
+ */
+#[test]
+fn dca_orders() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    let (dca_vault_addr, receipt_addr) = context.new_dca_vault();
+    let account = context.new_account_with_moj_and_usdt(dec!("300"), dec!("0"));
+    let account2 = context.new_account_with_moj_and_usdt(dec!("100"), dec!("0"));
+
+    context.create_dca_order(&account, dca_vault_addr, dec!("300"), Decimal::zero(), 3600, Decimal::zero()).expect_commit_failure();
+    context.create_dca_order(&account, dca_vault_addr, dec!("300"), dec!("100"), 3600, Decimal::zero()).expect_commit_success();
+    context.execute_dca_order(dca_vault_addr, 0).expect_commit_success();
+    context.execute_dca_order(dca_vault_addr, 0).expect_commit_failure();
+    context.set_current_time(START_TIME + 3600);
+    context.execute_dca_order(dca_vault_addr, 0).expect_commit_success();
+    context.set_current_time(START_TIME + 7200);
+    context.execute_dca_order(dca_vault_addr, 0).expect_commit_success();
+    context.set_current_time(START_TIME + 10800);
+    context.execute_dca_order(dca_vault_addr, 0).expect_commit_failure();
+
+    let order = context.get_dca_order(dca_vault_addr, 0);
+    assert_eq!(Decimal::zero(), order.input_amount);
+    assert!(order.output_amount > dec!("290") && order.output_amount < dec!("300"));
+    context.claim_dca_output(&account, dca_vault_addr, receipt_addr);
+    assert_eq!((Decimal::zero(), order.output_amount), context.get_balances(&account));
+
+    context.create_dca_order(&account2, dca_vault_addr, dec!("100"), dec!("10"), 3600, dec!("2")).expect_commit_success();
+    context.execute_dca_order(dca_vault_addr, 1).expect_commit_failure();
+    context.cancel_dca_order(&account2, dca_vault_addr, receipt_addr);
+    assert_eq!((dec!("100"), Decimal::zero()), context.get_balances(&account2));
+}
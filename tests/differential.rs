@@ -0,0 +1,278 @@
+mod common;
+
+use common::model::PoolModel;
+use common::*;
+use mojitoswap_pool::sim::PoolSim;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * The tolerance of the differential tests.
+ *
+ * The component uses 18 decimals fixed point math truncating after each operation, while the model uses f64 (15-16 significant
+ * digits) and the closed formula tick prices. Over the operation sequences below the two drift apart by less than 1e-10 relative
+ * error, so we allow a relative error of 1e-9, applied to at least 1 unit, so that values close to zero (e.g. the amount given
+ * back by a swap) are compared with an absolute error of 1e-9.
+ */
+const TOLERANCE: f64 = 1e-9;
+
+/**
+ * The number of random operations executed for each seed
+ */
+const OPS_PER_SEED: u32 = 30;
+
+/**
+ * A xorshift pseudo random generator, so that each operation sequence can be reproduced from its seed
+ */
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /**
+     * A random number in [low, high)
+     */
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next() % (high - low) as u64) as i64
+    }
+}
+
+fn to_f64(amount: Decimal) -> f64 {
+    amount.to_string().parse().unwrap()
+}
+
+fn assert_close(seed: u64, what: &str, actual: Decimal, expected: f64) {
+    let actual = to_f64(actual);
+    let error = (actual - expected).abs() / expected.abs().max(1.0);
+    assert!(
+        error <= TOLERANCE,
+        "Seed {}: {} differs, component={} model={} error={}",
+        seed,
+        what,
+        actual,
+        expected,
+        error
+    );
+}
+
+/**
+ * Executes OPS_PER_SEED random operations on both the component and the model and asserts that, after each operation, the
+ * amounts received by the accounts and the pool state agree.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000], the operations are:
+ * - add a position of 100..5000 MOJ + 100..5000 USDT with a range 10..490 ticks wide, somewhere in [-1200, 1200]
+ * - swap 10..3000 MOJ or USDT (twice as likely as the other operations)
+ * - collect the fees of a random position
+ * - remove a random position
+ */
+fn run_differential(seed: u64) {
+    let mut rng = Rng(seed);
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let mut model = PoolModel::new(0.01, 1.0);
+    model.add_pos(10000.0, 10000.0, -1000, 1000);
+
+    let trader = context.new_account_with_moj_and_usdt(dec!("1000000"), dec!("1000000"));
+    // each position is owned by its own account, (account, model position id)
+    let mut positions: Vec<(Account, u64)> = Vec::new();
+
+    for _ in 0..OPS_PER_SEED {
+        let op = rng.range(0, 5);
+        if op == 0 || positions.is_empty() {
+            let low_tick = rng.range(-120, 120) as i32 * 10;
+            let high_tick = low_tick + rng.range(1, 50) as i32 * 10;
+            let moj_amount = rng.range(100, 5000);
+            let usdt_amount = rng.range(100, 5000);
+
+            let account = context.new_account_with_moj_and_usdt(moj_amount.into(), usdt_amount.into());
+            context.add_pos(
+                &account,
+                moj_amount.into(),
+                usdt_amount.into(),
+                low_tick,
+                high_tick,
+            );
+            let (moj_back, usdt_back) = context.get_balances(&account);
+
+            let (pos_id, exp_moj_back, exp_usdt_back) =
+                model.add_pos(moj_amount as f64, usdt_amount as f64, low_tick, high_tick);
+            assert_close(seed, "add_pos MOJ given back", moj_back, exp_moj_back);
+            assert_close(seed, "add_pos USDT given back", usdt_back, exp_usdt_back);
+            positions.push((account, pos_id));
+        } else if op <= 2 {
+            let is_token0 = rng.range(0, 2) == 0;
+            let amount = rng.range(10, 3000);
+
+            let (moj_before, usdt_before) = context.get_balances(&trader);
+            if is_token0 {
+                context.swap_moj_for_usdt(&trader, amount.into(), Decimal::zero());
+            } else {
+                context.swap_usdt_for_moj(&trader, amount.into(), Decimal::zero());
+            }
+            let (moj_after, usdt_after) = context.get_balances(&trader);
+
+            let (exp_swapped, exp_given_back) = model.swap(amount as f64, is_token0);
+            let (swapped, given_back) = if is_token0 {
+                (usdt_after - usdt_before, moj_after - moj_before + Decimal::from(amount))
+            } else {
+                (moj_after - moj_before, usdt_after - usdt_before + Decimal::from(amount))
+            };
+            assert_close(seed, "swapped amount", swapped, exp_swapped);
+            assert_close(seed, "swap amount given back", given_back, exp_given_back);
+        } else if op == 3 {
+            let (account, pos_id) = &positions[rng.range(0, positions.len() as i64) as usize];
+
+            let (moj_before, usdt_before) = context.get_balances(account);
+            context.collect_fees(account, Decimal::zero(), Decimal::zero());
+            let (moj_after, usdt_after) = context.get_balances(account);
+
+            let (exp_moj, exp_usdt) = model.collect_fees(*pos_id);
+            assert_close(seed, "collected MOJ fees", moj_after - moj_before, exp_moj);
+            assert_close(seed, "collected USDT fees", usdt_after - usdt_before, exp_usdt);
+        } else {
+            let (account, pos_id) = positions.remove(rng.range(0, positions.len() as i64) as usize);
+
+            let (moj_before, usdt_before) = context.get_balances(&account);
+            context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+            let (moj_after, usdt_after) = context.get_balances(&account);
+
+            let (exp_moj, exp_usdt) = model.remove_pos(pos_id);
+            assert_close(seed, "remove_pos MOJ received", moj_after - moj_before, exp_moj);
+            assert_close(seed, "remove_pos USDT received", usdt_after - usdt_before, exp_usdt);
+        }
+
+        let pool_state = context.get_pool_state();
+        assert_close(seed, "pool MOJ amount", pool_state.amount0, model.amount0);
+        assert_close(seed, "pool USDT amount", pool_state.amount1, model.amount1);
+        assert_close(seed, "pool sqrt price", pool_state.sqrt_price.0, model.sqrt_price);
+        assert_close(seed, "pool live liquidity", pool_state.active_liquidity, model.live_liq);
+    }
+}
+
+#[test]
+fn differential_seed_1() {
+    run_differential(1);
+}
+
+#[test]
+fn differential_seed_2() {
+    run_differential(2);
+}
+
+#[test]
+fn differential_seed_3() {
+    run_differential(3);
+}
+
+#[test]
+fn differential_seed_4() {
+    run_differential(4);
+}
+
+#[test]
+fn differential_seed_5() {
+    run_differential(5);
+}
+
+/**
+ * Executes OPS_PER_SEED random operations, the ones of run_differential on the same pool, on both the component and a PoolSim, with a
+ * USDT of the given divisibility, and asserts that, after each operation, the amounts received by the accounts and the pool state
+ * are the same, as the sim rounds the amounts to the divisibility of the resources like the component.
+ */
+fn run_sim_differential(seed: u64, usdt_divisibility: u8) {
+    let mut rng = Rng(seed);
+    let mut context = Context::new_with_usdt_divisibility(
+        usdt_divisibility,
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let mut sim = PoolSim { divisibility1: usdt_divisibility, ..PoolSim::new(dec!("0.01"), 1, SqrtPrice(Decimal::one())) };
+    sim.add_pos(dec!("10000"), dec!("10000"), -1000, 1000);
+
+    let trader = context.new_account_with_moj_and_usdt(dec!("1000000"), dec!("1000000"));
+    // each position is owned by its own account, (account, sim position id)
+    let mut positions: Vec<(Account, u64)> = Vec::new();
+
+    for _ in 0..OPS_PER_SEED {
+        let op = rng.range(0, 5);
+        if op == 0 || positions.is_empty() {
+            let low_tick = rng.range(-120, 120) as i32 * 10;
+            let high_tick = low_tick + rng.range(1, 50) as i32 * 10;
+            let moj_amount = Decimal::from(rng.range(100, 5000));
+            let usdt_amount = Decimal::from(rng.range(100, 5000));
+
+            let account = context.new_account_with_moj_and_usdt(moj_amount, usdt_amount);
+            context.add_pos(&account, moj_amount, usdt_amount, low_tick, high_tick);
+            let (pos_id, used_moj, used_usdt) = sim.add_pos(moj_amount, usdt_amount, low_tick, high_tick);
+            assert_eq!((moj_amount - used_moj, usdt_amount - used_usdt), context.get_balances(&account), "Seed {}: add_pos", seed);
+            positions.push((account, pos_id));
+        } else if op <= 2 {
+            let is_token0 = rng.range(0, 2) == 0;
+            let amount = Decimal::from(rng.range(10, 3000));
+
+            let (moj_before, usdt_before) = context.get_balances(&trader);
+            if is_token0 {
+                context.swap_moj_for_usdt(&trader, amount, Decimal::zero());
+            } else {
+                context.swap_usdt_for_moj(&trader, amount, Decimal::zero());
+            }
+            let (moj_after, usdt_after) = context.get_balances(&trader);
+
+            let (swapped, given_back) = if is_token0 {
+                (usdt_after - usdt_before, moj_after - moj_before + amount)
+            } else {
+                (moj_after - moj_before, usdt_after - usdt_before + amount)
+            };
+            assert_eq!(sim.swap(amount, is_token0), (swapped, given_back), "Seed {}: swap", seed);
+        } else if op == 3 {
+            let (account, pos_id) = &positions[rng.range(0, positions.len() as i64) as usize];
+
+            let (moj_before, usdt_before) = context.get_balances(account);
+            context.collect_fees(account, Decimal::zero(), Decimal::zero());
+            let (moj_after, usdt_after) = context.get_balances(account);
+            assert_eq!(sim.collect_fees(*pos_id), (moj_after - moj_before, usdt_after - usdt_before), "Seed {}: collect_fees", seed);
+        } else {
+            let (account, pos_id) = positions.remove(rng.range(0, positions.len() as i64) as usize);
+
+            let (moj_before, usdt_before) = context.get_balances(&account);
+            context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+            let (moj_after, usdt_after) = context.get_balances(&account);
+            assert_eq!(sim.remove_pos(pos_id), (moj_after - moj_before, usdt_after - usdt_before), "Seed {}: remove_pos", seed);
+        }
+
+        let pool_state = context.get_pool_state();
+        assert_eq!((sim.amount0, sim.amount1), (pool_state.amount0, pool_state.amount1), "Seed {}: pool amounts", seed);
+        assert_eq!(SqrtPrice(sim.sqrt_price), pool_state.sqrt_price, "Seed {}: pool sqrt price", seed);
+        assert_eq!(sim.live_liq, pool_state.active_liquidity, "Seed {}: pool live liquidity", seed);
+    }
+}
+
+#[test]
+fn sim_differential_seed_1() {
+    run_sim_differential(1, 18);
+}
+
+#[test]
+fn sim_differential_6_decimals_seed_1() {
+    run_sim_differential(1, 6);
+}
+
+#[test]
+fn sim_differential_6_decimals_seed_2() {
+    run_sim_differential(2, 6);
+}
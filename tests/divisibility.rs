@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+fn has_decimals(amount: Decimal, decimals: i32) -> bool {
+    amount.checked_round(decimals, RoundingMode::ToZero).unwrap() == amount
+}
+
+/**
+ * Resources with less than 18 decimals.
+ *
+ * We test that a pool of MOJ, with 18 decimals, and USDT, with 6 decimals, takes the deposits and pays out the swaps and the
+ * position removals in amounts of USDT rounded to 6 decimals, the rounding dust staying in the pool.
+ */
+#[test]
+fn six_decimals_resource() {
+    let mut context = Context::new_with_usdt_divisibility(
+        6,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -500, 700);
+    let (moj_amount, usdt_amount) = context.get_balances(&account);
+    assert!(has_decimals(usdt_amount, 6));
+
+    context.try_swap_moj_for_usdt(&account, dec!("12.345678901234567891")).expect_commit_success();
+    let (_, swapped_usdt_amount) = context.get_balances(&account);
+    assert!(swapped_usdt_amount > usdt_amount && has_decimals(swapped_usdt_amount, 6));
+
+    context.try_swap_usdt_for_moj(&account, dec!("7.654321")).expect_commit_success();
+    let (swapped_moj_amount, _) = context.get_balances(&account);
+    assert!(swapped_moj_amount > moj_amount - dec!("12.345678901234567891"));
+
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    let (_, usdt_amount) = context.get_balances(&account);
+    assert!(has_decimals(usdt_amount, 6));
+
+    let pool_state = context.get_pool_state();
+    assert!(has_decimals(pool_state.amount1, 6));
+    context.remove_admin_pos(Decimal::zero(), Decimal::zero());
+}
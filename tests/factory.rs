@@ -0,0 +1,394 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::factory::{PoolCreatedEvent, PoolInfo};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+fn new_context() -> Context {
+    Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    )
+}
+
+/**
+ * Pool creation with the factory.
+ *
+ * We test that the factory creates and tracks pools for different fees, emitting a PoolCreatedEvent for each, and aborts the
+ * creation of a second pool with the same resources and fee.
+ */
+#[test]
+fn create_pools() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let (low_sqrt_price, high_sqrt_price) = (tick_math::sqrt_price_at_tick(Tick(-1200)), tick_math::sqrt_price_at_tick(Tick(1200)));
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        low_sqrt_price,
+        high_sqrt_price,
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+    let events: Vec<PoolCreatedEvent> = context.get_events(&receipt, "PoolCreatedEvent");
+    assert_eq!(1, events.len());
+    assert!(receipt.expect_commit_success().new_resource_addresses().contains(&events[0].position_nft_addr));
+    assert_eq!(
+        PoolCreatedEvent {
+            pool_addr,
+            resource0_addr: context.moj_addr,
+            resource1_addr: context.usdt_addr,
+            fee: dec!("0.003"),
+            tick_spacing: 60,
+            position_nft_addr: events[0].position_nft_addr,
+        },
+        events[0]
+    );
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        low_sqrt_price,
+        high_sqrt_price,
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let other_fee_pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        low_sqrt_price,
+        high_sqrt_price,
+        dec!("1000"),
+        dec!("1000"),
+    );
+    receipt.expect_commit_failure();
+
+    let (pool_infos, next_cursor) = context.list_factory_pools(factory_addr, 0, 10);
+    let pool_addrs: Vec<ComponentAddress> = pool_infos.iter().map(|pool_info| pool_info.pool_addr).collect();
+    assert_eq!(vec![pool_addr, other_fee_pool_addr], pool_addrs);
+    assert_eq!(None, next_cursor);
+}
+
+/**
+ * Pool enumeration.
+ *
+ * We test that the factory pools are listed in pages, with their resources, fee and creation epoch.
+ */
+#[test]
+fn list_pools() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+
+    let mut pool_addrs = Vec::new();
+    for fee in [dec!("0.0001"), dec!("0.0005"), dec!("0.003")] {
+        let receipt = context.create_pool_with_factory(
+            factory_addr,
+            &admin,
+            fee,
+            SqrtPrice(Decimal::one()),
+            tick_math::sqrt_price_at_tick(Tick(-1200)),
+            tick_math::sqrt_price_at_tick(Tick(1200)),
+            dec!("1000"),
+            dec!("1000"),
+        );
+        pool_addrs.push(receipt.expect_commit_success().new_component_addresses()[0]);
+    }
+
+    let (first_page, next_cursor) = context.list_factory_pools(factory_addr, 0, 2);
+    assert_eq!(
+        PoolInfo {
+            pool_addr: pool_addrs[0],
+            resource0_addr: context.moj_addr,
+            resource1_addr: context.usdt_addr,
+            fee: dec!("0.0001"),
+            creation_epoch: first_page[0].creation_epoch,
+            curated: false,
+            deprecated: false,
+        },
+        first_page[0]
+    );
+    assert_eq!(pool_addrs[1], first_page[1].pool_addr);
+    assert_eq!(Some(2), next_cursor);
+
+    let (second_page, next_cursor) = context.list_factory_pools(factory_addr, 2, 2);
+    assert_eq!(1, second_page.len());
+    assert_eq!((pool_addrs[2], dec!("0.003")), (second_page[0].pool_addr, second_page[0].fee));
+    assert_eq!(None, next_cursor);
+
+    assert_eq!((vec![], None), context.list_factory_pools(factory_addr, 3, 2));
+}
+
+/**
+ * Pool lookup.
+ *
+ * We test that the factory pools can be looked up by their resources, in any order, and fee.
+ */
+#[test]
+fn get_pool() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let (moj_addr, usdt_addr) = (context.moj_addr, context.usdt_addr);
+
+    assert_eq!(None, context.get_factory_pool(factory_addr, moj_addr, usdt_addr, dec!("0.003")));
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    assert_eq!(Some(pool_addr), context.get_factory_pool(factory_addr, moj_addr, usdt_addr, dec!("0.003")));
+    assert_eq!(Some(pool_addr), context.get_factory_pool(factory_addr, usdt_addr, moj_addr, dec!("0.003")));
+    assert_eq!(None, context.get_factory_pool(factory_addr, moj_addr, usdt_addr, dec!("0.01")));
+}
+
+/**
+ * Fee tiers.
+ *
+ * We test that the pools can be created only with the enabled fee tiers, that the admin can enable new fee tiers, of at most
+ * MAX_FEE_BPS, and that the ranges of the initial positions must use the tick spacing of the fee tier.
+ */
+#[test]
+fn fee_tiers() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+
+    assert_eq!(Some(1), context.get_tick_spacing(factory_addr, dec!("0.0001")));
+    assert_eq!(Some(10), context.get_tick_spacing(factory_addr, dec!("0.0005")));
+    assert_eq!(Some(60), context.get_tick_spacing(factory_addr, dec!("0.003")));
+    assert_eq!(Some(200), context.get_tick_spacing(factory_addr, dec!("0.01")));
+    assert_eq!(None, context.get_tick_spacing(factory_addr, dec!("0.02")));
+
+    let (low_sqrt_price, high_sqrt_price) = (tick_math::sqrt_price_at_tick(Tick(-1200)), tick_math::sqrt_price_at_tick(Tick(1200)));
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.02"),
+        SqrtPrice(Decimal::one()),
+        low_sqrt_price,
+        high_sqrt_price,
+        dec!("1000"),
+        dec!("1000"),
+    );
+    receipt.expect_commit_failure();
+
+    context.enable_fee_tier(factory_addr, dec!("0.2"), 500).expect_commit_failure();
+    context.enable_fee_tier(factory_addr, dec!("0.02"), 500).expect_commit_success();
+    context.enable_fee_tier(factory_addr, dec!("0.02"), 100).expect_commit_failure();
+    assert_eq!(Some(500), context.get_tick_spacing(factory_addr, dec!("0.02")));
+
+    // -1200 and 1200 aren't multiples of 500
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.02"),
+        SqrtPrice(Decimal::one()),
+        low_sqrt_price,
+        high_sqrt_price,
+        dec!("1000"),
+        dec!("1000"),
+    );
+    receipt.expect_commit_failure();
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.02"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1500)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    receipt.expect_commit_success();
+}
+
+/**
+ * Permissionless pool creation.
+ *
+ * We test that any account can create a pool by paying the creation fee, which goes to the factory treasury, and that only the admin
+ * can mark the pools as curated.
+ */
+#[test]
+fn permissionless_pool_creation() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let account = context.new_account_with_moj_and_usdt(dec!("1100"), dec!("1000"));
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &account,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    context.set_curated(factory_addr, &account, pool_addr, true).expect_commit_failure();
+    context.set_curated(factory_addr, &admin, pool_addr, true).expect_commit_success();
+    let (pool_infos, _) = context.list_factory_pools(factory_addr, 0, 1);
+    assert!(pool_infos[0].curated);
+
+    let (admin_moj_balance, _) = context.get_balances(&admin);
+    context.withdraw_creation_fees(factory_addr);
+    assert_eq!(admin_moj_balance + dec!("100"), context.get_balances(&admin).0);
+}
+
+/**
+ * Allowlist mode.
+ *
+ * We test that in allowlist mode only the allowlisted resources can be used in new pools, until the allowlist is disabled.
+ */
+#[test]
+fn allowlist_mode() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), true);
+    let admin = context.admin.clone();
+    let (moj_addr, usdt_addr) = (context.moj_addr, context.usdt_addr);
+    let create_pool = |context: &mut Context, fee: Decimal| {
+        context.create_pool_with_factory(
+            factory_addr,
+            &admin,
+            fee,
+            SqrtPrice(Decimal::one()),
+            tick_math::sqrt_price_at_tick(Tick(-1200)),
+            tick_math::sqrt_price_at_tick(Tick(1200)),
+            dec!("1000"),
+            dec!("1000"),
+        )
+    };
+
+    context.update_allowlist(factory_addr, "add_to_allowlist", Some(moj_addr));
+    create_pool(&mut context, dec!("0.003")).expect_commit_failure();
+
+    context.update_allowlist(factory_addr, "add_to_allowlist", Some(usdt_addr));
+    create_pool(&mut context, dec!("0.003")).expect_commit_success();
+
+    context.update_allowlist(factory_addr, "remove_from_allowlist", Some(usdt_addr));
+    create_pool(&mut context, dec!("0.01")).expect_commit_failure();
+
+    context.update_allowlist(factory_addr, "disable_allowlist", None);
+    create_pool(&mut context, dec!("0.01")).expect_commit_success();
+}
+
+/**
+ * Factory admin badge rotation.
+ *
+ * We test that the factory admin badge is handed over in two steps, the proposed badge becoming the admin only once accepted by one
+ * of its holders, that the old badge loses the admin methods and that the pools created from then on get the new admin badge.
+ */
+#[test]
+fn admin_rotation() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let account = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let old_badge_addr = context.admin_badge_addr;
+    let new_badge_addr = context.new_admin_badge(&admin);
+
+    context
+        .call_factory_with_badge(factory_addr, &admin, new_badge_addr, "accept_admin", manifest_args!())
+        .expect_commit_failure();
+    context
+        .call_factory_with_badge(factory_addr, &admin, new_badge_addr, "propose_admin", manifest_args!(new_badge_addr))
+        .expect_commit_failure();
+    context
+        .call_factory_with_badge(factory_addr, &admin, old_badge_addr, "propose_admin", manifest_args!(new_badge_addr))
+        .expect_commit_success();
+    context
+        .call_factory_with_badge(factory_addr, &account, new_badge_addr, "accept_admin", manifest_args!())
+        .expect_commit_failure();
+    context
+        .call_factory_with_badge(factory_addr, &admin, new_badge_addr, "accept_admin", manifest_args!())
+        .expect_commit_success();
+
+    context.enable_fee_tier(factory_addr, dec!("0.02"), 400).expect_commit_failure();
+    context.admin_badge_addr = new_badge_addr;
+    context.enable_fee_tier(factory_addr, dec!("0.02"), 400).expect_commit_success();
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+    context.use_pool(pool_addr);
+    assert_eq!(Some(new_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_admin_badge_addr"));
+}
+
+/**
+ * Pool deprecation.
+ *
+ * We test that the admin deprecates a factory pool, which then rejects the swaps and the new positions while its positions are
+ * removed with their fees, and that once flagged by the factory the pool is no longer quoted. A pool that isn't deprecated can't be
+ * flagged.
+ */
+#[test]
+fn pool_deprecation() {
+    let mut context = new_context();
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let (moj_addr, usdt_addr) = (context.moj_addr, context.usdt_addr);
+    let account = context.new_account_with_moj_and_usdt(dec!("1200"), dec!("1100"));
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &account,
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+    context.use_pool(pool_addr);
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+
+    context.flag_deprecated_pool(factory_addr, pool_addr).expect_commit_failure();
+    context.call_with_badges(&account, &[], "deprecate", manifest_args!()).expect_commit_failure();
+    context.call_as_admin("deprecate", manifest_args!()).expect_commit_success();
+    assert!(context.query_pool::<bool>("is_deprecated"));
+    assert!(context.best_quote(factory_addr, moj_addr, usdt_addr, dec!("10")).is_some());
+    context.flag_deprecated_pool(factory_addr, pool_addr).expect_commit_success();
+    let (pool_infos, _) = context.list_factory_pools(factory_addr, 0, 1);
+    assert!(pool_infos[0].deprecated);
+    assert_eq!(None, context.best_quote(factory_addr, moj_addr, usdt_addr, dec!("10")));
+
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_failure();
+    context.try_add_pos(&account, dec!("100"), dec!("100"), -1200, 1200).expect_commit_failure();
+    context.collect_fees(&account, dec!("0.299"), Decimal::zero());
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, INITIAL_TIMELOCK_DELAY};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+// a time after the ledger genesis, rounded to minutes as the voting ends
+const START_TIME: i64 = 1_700_000_040;
+
+/**
+ * Governance of the pool parameters.
+ *
+ * We test that the MOJ holders vote on a proposal to enable a new fee tier of the factory and that the proposal is executed with the
+ * admin badge held by the governance only if it passed, once its voting ended, the voting MOJ being locked until then.
+ */
+#[test]
+fn governance() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let (governance_addr, vote_receipt_addr) = context.new_governance(600, dec!("100"), dec!("10"));
+    let account1 = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let account2 = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let args = scrypto_args!(dec!("0.02"), 400_i32);
+
+    context.propose(&account1, governance_addr, dec!("5"), factory_addr, "enable_fee_tier", args.clone()).expect_commit_failure();
+    let receipt = context.propose(&account1, governance_addr, dec!("10"), factory_addr, "enable_fee_tier", args.clone());
+    let proposal_id: u64 = receipt.expect_commit_success().output(2);
+    let receipt = context.propose(&account2, governance_addr, dec!("10"), factory_addr, "enable_fee_tier", args);
+    let rejected_proposal_id: u64 = receipt.expect_commit_success().output(2);
+
+    context.vote(&account1, governance_addr, proposal_id, dec!("200"), true).expect_commit_success();
+    context.vote(&account2, governance_addr, proposal_id, dec!("50"), false).expect_commit_success();
+    context.vote(&account2, governance_addr, rejected_proposal_id, dec!("150"), false).expect_commit_success();
+    assert_eq!((dec!("800"), Decimal::zero()), context.get_balances(&account1));
+    context.execute_proposal(governance_addr, proposal_id).expect_commit_failure();
+    context.withdraw_votes(&account1, governance_addr, vote_receipt_addr).expect_commit_failure();
+
+    context.set_current_time(START_TIME + 600);
+    context.vote(&account1, governance_addr, rejected_proposal_id, dec!("200"), true).expect_commit_failure();
+    context.execute_proposal(governance_addr, rejected_proposal_id).expect_commit_failure();
+    assert_eq!(None, context.get_tick_spacing(factory_addr, dec!("0.02")));
+    context.execute_proposal(governance_addr, proposal_id).expect_commit_success();
+    context.execute_proposal(governance_addr, proposal_id).expect_commit_failure();
+    assert_eq!(Some(400), context.get_tick_spacing(factory_addr, dec!("0.02")));
+
+    context.withdraw_votes(&account1, governance_addr, vote_receipt_addr).expect_commit_success();
+    context.withdraw_votes(&account2, governance_addr, vote_receipt_addr).expect_commit_success();
+    assert_eq!((dec!("1000"), Decimal::zero()), context.get_balances(&account1));
+    assert_eq!((dec!("1000"), Decimal::zero()), context.get_balances(&account2));
+}
+
+/**
+ * Governance of a pool parameter change.
+ *
+ * We test that the MOJ holders schedule a fee change of a pool, the pool returning the id of the scheduled change, and execute it
+ * once its timelock delay passed, with two proposals executed by the governance.
+ */
+#[test]
+fn governance_parameter_change() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    let pool_addr = context.pool_addr;
+    let (governance_addr, _) = context.new_governance(600, dec!("100"), dec!("10"));
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+
+    let args = scrypto_args!(ParameterChange::SetFee(dec!("0.005")));
+    let receipt = context.propose(&account, governance_addr, dec!("10"), pool_addr, "schedule_change", args);
+    let proposal_id: u64 = receipt.expect_commit_success().output(2);
+    context.vote(&account, governance_addr, proposal_id, dec!("100"), true).expect_commit_success();
+    context.set_current_time(START_TIME + 600);
+    context.execute_proposal(governance_addr, proposal_id).expect_commit_success();
+    assert_eq!(dec!("0.003"), context.query_pool::<Decimal>("get_fee"));
+
+    // the first change scheduled by the pool
+    let receipt = context.propose(&account, governance_addr, dec!("10"), pool_addr, "execute_change", scrypto_args!(0_u64));
+    let proposal_id: u64 = receipt.expect_commit_success().output(2);
+    context.vote(&account, governance_addr, proposal_id, dec!("100"), true).expect_commit_success();
+    context.set_current_time(START_TIME + 1200);
+    context.execute_proposal(governance_addr, proposal_id).expect_commit_failure();
+    context.set_current_time(START_TIME + 600 + INITIAL_TIMELOCK_DELAY);
+    context.execute_proposal(governance_addr, proposal_id).expect_commit_success();
+    assert_eq!(dec!("0.005"), context.query_pool::<Decimal>("get_fee"));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::ParameterChange;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Swap hooks.
+ *
+ * We test that the admin registers and unregisters the hooks of the pool and that the registered hooks are called on each swap,
+ * aborting it if needed.
+ */
+#[test]
+fn swap_hooks() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let hook_addr = context.new_swap_limit_hook(dec!("100"));
+
+    context.apply_change(ParameterChange::RegisterHook(hook_addr)).expect_commit_success();
+    context.apply_change(ParameterChange::RegisterHook(hook_addr)).expect_commit_failure();
+    assert_eq!(vec![hook_addr], context.query_pool::<Vec<ComponentAddress>>("get_hooks"));
+
+    context.try_swap_moj_for_usdt(&account, dec!("50")).expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("150")).expect_commit_failure();
+    assert_eq!(1, context.get_hook_swaps(hook_addr));
+
+    context.apply_change(ParameterChange::UnregisterHook(hook_addr)).expect_commit_success();
+    context.apply_change(ParameterChange::UnregisterHook(hook_addr)).expect_commit_failure();
+    context.try_swap_moj_for_usdt(&account, dec!("150")).expect_commit_success();
+    assert_eq!(1, context.get_hook_swaps(hook_addr));
+}
+
+// a time after the ledger genesis, rounded to minutes as the fee manager timestamps
+const START_TIME: i64 = 1_700_000_040;
+
+/**
+ * Swaps the given amount of MOJ and returns the fee charged, from the fee growth of the pool liquidity
+ */
+fn swap_fee(context: &mut Context, account: &Account, moj_amount: Decimal) -> Decimal {
+    let initial_state = context.get_pool_state();
+    context.swap_moj_for_usdt(account, moj_amount, Decimal::zero());
+    (context.get_pool_state().fee_growth_global0 - initial_state.fee_growth_global0) * initial_state.active_liquidity
+}
+
+fn assert_fee(expected_fee: Decimal, fee: Decimal) {
+    let tolerance = dec!("0.000000001");
+    assert!(fee > expected_fee - tolerance && fee < expected_fee + tolerance, "fee {} != {}", fee, expected_fee);
+}
+
+/**
+ * Dynamic fee manager.
+ *
+ * We test that the pool charges the fee returned by its fee manager, growing with the volatility reported by the swaps and decaying
+ * in time, and its static fee once the fee manager is unset.
+ */
+#[test]
+fn fee_manager() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    context.set_current_time(START_TIME);
+    let fee_manager_addr = context.new_fee_manager(dec!("0.001"), dec!("0.01"), dec!("0.1"), 600);
+    context.apply_change(ParameterChange::SetFeeManager(Some(fee_manager_addr))).expect_commit_success();
+    context.apply_change(ParameterChange::RegisterHook(fee_manager_addr)).expect_commit_success();
+    assert_eq!(Some(fee_manager_addr), context.query_pool::<Option<ComponentAddress>>("get_fee_manager"));
+
+    // no volatility before the first price move
+    assert_fee(dec!("0.01"), swap_fee(&mut context, &account, dec!("10")));
+    let sqrt_price = context.get_pool_state().sqrt_price.0;
+    assert_fee(dec!("0.5"), swap_fee(&mut context, &account, dec!("500")));
+    let volatility = context.get_volatility(fee_manager_addr);
+    assert_eq!(Decimal::one() - context.get_pool_state().sqrt_price.0 / sqrt_price, volatility);
+    assert_fee(dec!("10") * (dec!("0.001") + dec!("0.1") * volatility), swap_fee(&mut context, &account, dec!("10")));
+
+    // the volatility halves each 10 minutes
+    let volatility = context.get_volatility(fee_manager_addr);
+    let current_time = context.get_current_time();
+    context.set_current_time(current_time + 1200);
+    assert_eq!(volatility / dec!("4"), context.get_volatility(fee_manager_addr));
+
+    context.apply_change(ParameterChange::SetFeeManager(None)).expect_commit_success();
+    assert_fee(dec!("0.03"), swap_fee(&mut context, &account, dec!("10")));
+}
@@ -0,0 +1,222 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use scrypto::prelude::*;
+
+/**
+ * An operation executed on the pool by the invariant tests. The positions are picked by index among the open positions.
+ */
+#[derive(Clone, Debug)]
+enum Op {
+    AddPos {
+        moj_amount: u32,
+        usdt_amount: u32,
+        low_tick: i32,
+        high_tick: i32,
+    },
+    AddLiq {
+        pos: Index,
+        moj_amount: u32,
+        usdt_amount: u32,
+    },
+    Swap {
+        moj_for_usdt: bool,
+        amount: u32,
+    },
+    RoundTripSwap {
+        moj_for_usdt: bool,
+        amount: u32,
+    },
+    CollectFees {
+        pos: Index,
+    },
+    RemovePos {
+        pos: Index,
+    },
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        1 => (100u32..5000, 100u32..5000, -120i32..120, 1i32..50).prop_map(
+            |(moj_amount, usdt_amount, low, width)| Op::AddPos {
+                moj_amount,
+                usdt_amount,
+                low_tick: low * 10,
+                high_tick: (low + width) * 10,
+            }
+        ),
+        1 => (any::<Index>(), 10u32..1000, 10u32..1000).prop_map(
+            |(pos, moj_amount, usdt_amount)| Op::AddLiq {
+                pos,
+                moj_amount,
+                usdt_amount,
+            }
+        ),
+        2 => (any::<bool>(), 10u32..3000)
+            .prop_map(|(moj_for_usdt, amount)| Op::Swap { moj_for_usdt, amount }),
+        1 => (any::<bool>(), 10u32..3000)
+            .prop_map(|(moj_for_usdt, amount)| Op::RoundTripSwap { moj_for_usdt, amount }),
+        1 => any::<Index>().prop_map(|pos| Op::CollectFees { pos }),
+        1 => any::<Index>().prop_map(|pos| Op::RemovePos { pos }),
+    ]
+}
+
+/**
+ * Swaps the given amount and then swaps back everything received, returning the trader balances before and after.
+ */
+fn round_trip_swap(
+    context: &mut Context,
+    trader: &Account,
+    moj_for_usdt: bool,
+    amount: u32,
+) -> ((Decimal, Decimal), (Decimal, Decimal)) {
+    let before = context.get_balances(trader);
+    if moj_for_usdt {
+        context.swap_moj_for_usdt(trader, amount.into(), Decimal::zero());
+        let usdt_amount = context.get_balances(trader).1 - before.1;
+        if usdt_amount > Decimal::zero() {
+            context.swap_usdt_for_moj(trader, usdt_amount, Decimal::zero());
+        }
+    } else {
+        context.swap_usdt_for_moj(trader, amount.into(), Decimal::zero());
+        let moj_amount = context.get_balances(trader).0 - before.0;
+        if moj_amount > Decimal::zero() {
+            context.swap_moj_for_usdt(trader, moj_amount, Decimal::zero());
+        }
+    }
+    (before, context.get_balances(trader))
+}
+
+/**
+ * Executes the given operations on a pool with the given fee, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ * and checks after each operation that:
+ * - the pool balances and the active liquidity are not negative
+ * - the pool vaults cover the amounts and the uncollected fees of all positions
+ * - the fees accumulated per unit of liquidity never decrease
+ * - a round trip swap is never profitable
+ */
+fn check_invariants(fee: Decimal, ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let mut context = Context::new(
+        fee,
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let trader = context.new_account_with_moj_and_usdt(dec!("1000000"), dec!("1000000"));
+    // each position is owned by its own account, funded enough to add liquidity to the position later
+    let mut positions: Vec<Account> = Vec::new();
+    let mut prev_pool_state = context.get_pool_state();
+    // the rounding error allowed when checking that the pool vaults cover what the positions can withdraw
+    let solvency_tolerance = dec!("0.000000001");
+
+    for op in ops {
+        match op {
+            Op::AddPos {
+                moj_amount,
+                usdt_amount,
+                low_tick,
+                high_tick,
+            } => {
+                let account = context.new_account_with_moj_and_usdt(dec!("100000"), dec!("100000"));
+                context.add_pos(
+                    &account,
+                    moj_amount.into(),
+                    usdt_amount.into(),
+                    low_tick,
+                    high_tick,
+                );
+                positions.push(account);
+            }
+            Op::AddLiq {
+                pos,
+                moj_amount,
+                usdt_amount,
+            } => {
+                if positions.is_empty() {
+                    continue;
+                }
+                let account = &positions[pos.index(positions.len())];
+                context.add_liq(account, usdt_amount.into(), moj_amount.into());
+            }
+            Op::Swap {
+                moj_for_usdt,
+                amount,
+            } => {
+                if moj_for_usdt {
+                    context.swap_moj_for_usdt(&trader, amount.into(), Decimal::zero());
+                } else {
+                    context.swap_usdt_for_moj(&trader, amount.into(), Decimal::zero());
+                }
+            }
+            Op::RoundTripSwap {
+                moj_for_usdt,
+                amount,
+            } => {
+                let (before, after) = round_trip_swap(&mut context, &trader, moj_for_usdt, amount);
+                prop_assert!(
+                    after.0 <= before.0 && after.1 <= before.1,
+                    "Profitable round trip swap, balances before={:?} after={:?}",
+                    before,
+                    after
+                );
+            }
+            Op::CollectFees { pos } => {
+                if positions.is_empty() {
+                    continue;
+                }
+                let account = &positions[pos.index(positions.len())];
+                context.collect_fees(account, Decimal::zero(), Decimal::zero());
+            }
+            Op::RemovePos { pos } => {
+                if positions.is_empty() {
+                    continue;
+                }
+                let account = positions.remove(pos.index(positions.len()));
+                context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+            }
+        }
+
+        let pool_state = context.get_pool_state();
+        prop_assert!(pool_state.amount0 >= Decimal::zero());
+        prop_assert!(pool_state.amount1 >= Decimal::zero());
+        prop_assert!(pool_state.active_liquidity >= Decimal::zero());
+        prop_assert!(pool_state.fee_growth_global0 >= prev_pool_state.fee_growth_global0);
+        prop_assert!(pool_state.fee_growth_global1 >= prev_pool_state.fee_growth_global1);
+
+        let tvl = context.get_tvl();
+        prop_assert!(
+            tvl.total0 >= tvl.active0 + tvl.out_of_range0 + tvl.uncollected_fees0 - solvency_tolerance,
+            "Pool MOJ vault doesn't cover the positions: {:?}",
+            tvl
+        );
+        prop_assert!(
+            tvl.total1 >= tvl.active1 + tvl.out_of_range1 + tvl.uncollected_fees1 - solvency_tolerance,
+            "Pool USDT vault doesn't cover the positions: {:?}",
+            tvl
+        );
+
+        prev_pool_state = pool_state;
+    }
+    Ok(())
+}
+
+proptest! {
+    // each case publishes the package and executes tens of transactions, so we keep the number of cases low
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    /**
+     * Pool invariants hold for random operation sequences, on pools with fee 0, 0.3% and 1%
+     */
+    #[test]
+    fn pool_invariants(
+        fee_bps in prop::sample::select(vec![0u32, 30, 100]),
+        ops in prop::collection::vec(op_strategy(), 1..30),
+    ) {
+        check_invariants(Decimal::from(fee_bps) / Decimal::from(10000), ops)?;
+    }
+}
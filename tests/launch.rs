@@ -0,0 +1,84 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::BuyCap;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Launch pool.
+ *
+ * We test that a launch pool is seeded with the project token only, in a ladder of equal single sided positions from the low tick to
+ * the high tick, that the price starts at the low tick and that the buyers move it up the ladder. A ladder width that isn't a
+ * multiple of the steps times the tick spacing aborts the creation.
+ */
+#[test]
+fn launch_ladder() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.new_launch_pool(0, 1000, 3, None, dec!("1000")).expect_commit_failure();
+    let pool_addr = context.new_launch_pool(0, 1000, 5, None, dec!("1000")).expect_commit_success().new_component_addresses()[0];
+    context.use_pool(pool_addr);
+
+    let pool_state = context.get_pool_state();
+    assert_eq!(Tick(0), pool_state.current_tick);
+    assert_eq!(Decimal::zero(), pool_state.amount1);
+    let mut ranges: Vec<(i32, i32)> =
+        context.get_positions().iter().map(|(_, position)| (position.low_tick, position.high_tick)).collect();
+    ranges.sort();
+    assert_eq!(vec![(0, 200), (200, 400), (400, 600), (600, 800), (800, 1000)], ranges);
+
+    let buyer = context.new_account_with_moj_and_usdt(dec!("0"), dec!("500"));
+    context.swap_usdt_for_moj(&buyer, dec!("500"), Decimal::zero()).expect_commit_success();
+    let pool_state = context.get_pool_state();
+    assert!(pool_state.current_tick.0 > 200);
+    assert!(pool_state.amount0 < dec!("600"));
+}
+
+/**
+ * Launch buy cap.
+ *
+ * We test that during the first epochs of a launch pool with a buy cap only the traders with a buy cap badge buy MOJ, each badge up
+ * to the maximum output, and that the cap is disabled afterwards.
+ */
+#[test]
+fn launch_buy_cap() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let buyer = context.new_account_with_moj_and_usdt(dec!("0"), dec!("1000"));
+    let trader_id = context.new_trader_badge(&buyer);
+    let other_trader_id = NonFungibleGlobalId::new(trader_id.resource_address(), NonFungibleLocalId::integer(2));
+    let buy_cap = BuyCap {
+        trader_badge_addr: trader_id.resource_address(),
+        max_output: dec!("100"),
+        epochs: 10,
+    };
+    let epoch = context.runner.get_current_epoch().number();
+    let pool_addr = context
+        .new_launch_pool(0, 1000, 5, Some(buy_cap.clone()), dec!("1000"))
+        .expect_commit_success()
+        .new_component_addresses()[0];
+    context.use_pool(pool_addr);
+    assert_eq!(Some((buy_cap, epoch + 10)), context.query_pool::<Option<(BuyCap, u64)>>("get_buy_cap"));
+
+    context.try_swap_usdt_for_moj(&buyer, dec!("50")).expect_commit_failure();
+    context.try_swap_usdt_for_moj_as_trader(&buyer, &trader_id, dec!("60")).expect_commit_success();
+    context.try_swap_usdt_for_moj_as_trader(&buyer, &trader_id, dec!("60")).expect_commit_failure();
+    context.try_swap_usdt_for_moj_as_trader(&buyer, &other_trader_id, dec!("60")).expect_commit_success();
+
+    context.runner.set_current_epoch(Epoch::of(epoch + 10));
+    assert_eq!(None, context.query_pool::<Option<(BuyCap, u64)>>("get_buy_cap"));
+    context.try_swap_usdt_for_moj(&buyer, dec!("200")).expect_commit_success();
+}
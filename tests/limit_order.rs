@@ -0,0 +1,39 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Limit orders.
+ *
+ * Given a pool with fee=0, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ *
+ * We test that a limit order selling 1000 MOJ at tick 199 (a price of ~1.02) can't be claimed until the price moves past its range
+ * and that, once filled, its owner claims the same ~1020 USDT as the equivalent position of scenario_6.
+ */
+#[test]
+fn limit_order() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("20000"), dec!("20000"));
+    context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
+    let (manager_addr, receipt_addr) = context.new_limit_order_manager();
+
+    let account2 = context.new_account_with_moj_and_usdt(dec!("1000"), Decimal::zero());
+    context.place_limit_order(&account2, manager_addr, dec!("1000"), -10).expect_commit_failure();
+    context.place_limit_order(&account2, manager_addr, dec!("1000"), 199).expect_commit_success();
+    context.claim_filled_order(&account2, manager_addr, receipt_addr, 0).expect_commit_failure();
+    assert!(context.get_filled_orders(manager_addr).is_empty());
+
+    context.swap_usdt_for_moj(&account, dec!("8000"), dec!("7750.081465536550594191"));
+    assert_eq!(vec![0], context.get_filled_orders(manager_addr));
+    context.claim_filled_order(&account2, manager_addr, receipt_addr, 0).expect_commit_success();
+    assert_eq!((Decimal::zero(), dec!("1020.149313703371480602")), context.get_balances(&account2));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Managed vault.
+ *
+ * We test that the depositors of a managed vault get shares in proportion to their deposits, topping up the same two vault positions,
+ * that only the strategist rebalances the vault ranges and that the depositors withdraw their part of the vault amounts, with the fees
+ * earned by the vault positions.
+ */
+#[test]
+fn managed_vault() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let strategist = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let strategy_badge_addr = context.new_badge(&strategist, "Strategy badge");
+    let (vault_addr, share_addr) = context.new_managed_vault(strategy_badge_addr, (-2000, 2000), (-100, 100));
+    let account1 = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let account2 = context.new_account_with_moj_and_usdt(dec!("500"), dec!("500"));
+    let trader = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+
+    context.deposit_to_managed_vault(&account1, vault_addr, dec!("1000"), dec!("1000")).expect_commit_success();
+    assert_eq!(dec!("2000"), context.runner.get_component_balance(account1.addr, share_addr));
+    context.deposit_to_managed_vault(&account2, vault_addr, dec!("500"), dec!("500")).expect_commit_success();
+    let shares2 = context.runner.get_component_balance(account2.addr, share_addr);
+    assert!(shares2 > dec!("999.99") && shares2 < dec!("1000.01"));
+    assert_eq!(dec!("2"), context.runner.get_component_balance(vault_addr, context.position_nft_addr));
+
+    context.try_swap_moj_for_usdt(&trader, dec!("200")).expect_commit_success();
+    context.rebalance_managed_vault(&account1, strategy_badge_addr, vault_addr, (-3000, 3000), (-200, 0)).expect_commit_failure();
+    context.rebalance_managed_vault(&strategist, strategy_badge_addr, vault_addr, (-3000, 3000), (-200, 0)).expect_commit_success();
+
+    context.withdraw_from_managed_vault(&account2, vault_addr, share_addr);
+    let (moj_amount, usdt_amount) = context.get_balances(&account2);
+    assert!(moj_amount > dec!("500") && usdt_amount < dec!("500"));
+    assert!(moj_amount + usdt_amount > dec!("999"));
+    assert_eq!(Decimal::zero(), context.runner.get_component_balance(account2.addr, share_addr));
+}
+
+/**
+ * Managed vault withdrawal after the pool deprecation.
+ *
+ * We test that the depositors withdraw from a managed vault once its pool is deprecated, taking their share of the vault positions
+ * liquidity out, while the positions of the other depositors stay in the pool.
+ */
+#[test]
+fn withdraw_after_deprecation() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let strategist = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let strategy_badge_addr = context.new_badge(&strategist, "Strategy badge");
+    let (vault_addr, share_addr) = context.new_managed_vault(strategy_badge_addr, (-2000, 2000), (-100, 100));
+    let account1 = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let account2 = context.new_account_with_moj_and_usdt(dec!("500"), dec!("500"));
+    context.deposit_to_managed_vault(&account1, vault_addr, dec!("1000"), dec!("1000")).expect_commit_success();
+    context.deposit_to_managed_vault(&account2, vault_addr, dec!("500"), dec!("500")).expect_commit_success();
+
+    assert!(context.query_pool::<bool>("deposits_allowed"));
+    context.call_as_admin("deprecate", manifest_args!()).expect_commit_success();
+    assert!(!context.query_pool::<bool>("deposits_allowed"));
+
+    context.withdraw_from_managed_vault(&account2, vault_addr, share_addr).expect_commit_success();
+    let (moj_amount, usdt_amount) = context.get_balances(&account2);
+    assert!(moj_amount + usdt_amount > dec!("999.99"));
+    assert_eq!(dec!("2"), context.runner.get_component_balance(vault_addr, context.position_nft_addr));
+
+    context.withdraw_from_managed_vault(&account1, vault_addr, share_addr).expect_commit_success();
+    let (moj_amount, usdt_amount) = context.get_balances(&account1);
+    assert!(moj_amount + usdt_amount > dec!("1999.99"));
+    assert_eq!(Decimal::zero(), context.runner.get_component_balance(account1.addr, share_addr));
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, PoolMetadata};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Pool metadata.
+ *
+ * We test that the pool is created with a name, a description and tags for the wallets and the explorers and that the admin sets
+ * its display metadata and its dApp definition.
+ */
+#[test]
+fn pool_metadata() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    match context.get_pool_metadata("name") {
+        Some(MetadataValue::String(name)) => {
+            assert!(name.starts_with("MojitoSwap ") && name.contains("MOJ") && name.contains("USDT") && name.ends_with(" 0.3%"))
+        }
+        metadata => panic!("Unexpected pool name {:?}", metadata),
+    }
+    assert!(context.get_pool_metadata("description").is_some());
+    assert_eq!(
+        Some(MetadataValue::StringArray(vec![
+            "dex".to_owned(),
+            "liquidity-pool".to_owned(),
+            "mojitoswap".to_owned()
+        ])),
+        context.get_pool_metadata("tags")
+    );
+    assert_eq!(None, context.get_pool_metadata("dapp_definition"));
+
+    let dapp_definition = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let badge_addr = context.new_admin_badge(&dapp_definition);
+    let update = PoolMetadata { dapp_definition_addr: Some(dapp_definition.addr), ..Default::default() };
+    context
+        .call_with_badge(&dapp_definition, badge_addr, "update_metadata", manifest_args!(update.clone()))
+        .expect_commit_failure();
+    context.call_as_admin("update_metadata", manifest_args!(update)).expect_commit_success();
+    assert_eq!(
+        Some(MetadataValue::GlobalAddress(dapp_definition.addr.into())),
+        context.get_pool_metadata("dapp_definition")
+    );
+
+    let update = PoolMetadata {
+        name: Some("MOJ/USDT".to_owned()),
+        description: Some("The MOJ/USDT pool.".to_owned()),
+        tags: Some(vec!["dex".to_owned()]),
+        icon_url: Some("https://mojitoswap.finance/icon.png".to_owned()),
+        ..Default::default()
+    };
+    context.call_as_admin("update_metadata", manifest_args!(update)).expect_commit_success();
+    assert_eq!(Some(MetadataValue::String("MOJ/USDT".to_owned())), context.get_pool_metadata("name"));
+    assert_eq!(
+        Some(MetadataValue::Url(UncheckedUrl::of("https://mojitoswap.finance/icon.png"))),
+        context.get_pool_metadata("icon_url")
+    );
+}
+
+/**
+ * Position NFT metadata.
+ *
+ * We test that the position NFT resource is created with a name and a description and that only the admin updates its metadata.
+ */
+#[test]
+fn position_nft_metadata() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    match context.get_position_nft_metadata("name") {
+        Some(MetadataValue::String(name)) => assert!(name.starts_with("MojitoSwap ") && name.ends_with(" 0.3% position")),
+        metadata => panic!("Unexpected position NFT name {:?}", metadata),
+    }
+    assert!(context.get_position_nft_metadata("description").is_some());
+
+    let args = || {
+        manifest_args!(
+            "MOJ/USDT position".to_owned(),
+            "A MOJ/USDT liquidity position.".to_owned(),
+            "https://mojitoswap.finance/position.png".to_owned(),
+            "https://mojitoswap.finance".to_owned()
+        )
+    };
+    let account = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let badge_addr = context.new_admin_badge(&account);
+    context
+        .call_with_badge(&account, badge_addr, "set_position_nft_metadata", args())
+        .expect_commit_failure();
+    context.call_as_admin("set_position_nft_metadata", args()).expect_commit_success();
+    assert_eq!(
+        Some(MetadataValue::String("MOJ/USDT position".to_owned())),
+        context.get_position_nft_metadata("name")
+    );
+    assert_eq!(
+        Some(MetadataValue::Url(UncheckedUrl::of("https://mojitoswap.finance"))),
+        context.get_position_nft_metadata("info_url")
+    );
+}
+
+/**
+ * Pool metadata at creation and update.
+ *
+ * We test that the pools are created with their pair symbols and fee tier metadata, with the dApp definition and the icon if given,
+ * and that only the admin updates the given fields of the metadata, the others being left unchanged.
+ */
+#[test]
+fn update_pool_metadata() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    assert_eq!(Some(MetadataValue::String("MOJ/USDT".to_owned())), context.get_pool_metadata("pair"));
+    assert_eq!(Some(MetadataValue::Decimal(dec!("0.003"))), context.get_pool_metadata("fee_tier"));
+    assert_eq!(None, context.get_pool_metadata("icon_url"));
+
+    let dapp_definition = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let pool_addr = context.new_pool_with_metadata(PoolMetadata {
+        icon_url: Some("https://mojitoswap.finance/icon.png".to_owned()),
+        dapp_definition_addr: Some(dapp_definition.addr),
+        ..Default::default()
+    });
+    context.use_pool(pool_addr);
+    match context.get_pool_metadata("name") {
+        Some(MetadataValue::String(name)) => assert_eq!("MojitoSwap MOJ/USDT 0%", name),
+        metadata => panic!("Unexpected pool name {:?}", metadata),
+    }
+    assert_eq!(Some(MetadataValue::Decimal(Decimal::zero())), context.get_pool_metadata("fee_tier"));
+    assert_eq!(
+        Some(MetadataValue::Url(UncheckedUrl::of("https://mojitoswap.finance/icon.png"))),
+        context.get_pool_metadata("icon_url")
+    );
+    assert_eq!(
+        Some(MetadataValue::GlobalAddress(dapp_definition.addr.into())),
+        context.get_pool_metadata("dapp_definition")
+    );
+
+    let update = PoolMetadata {
+        description: Some("The MOJ/USDT pool.".to_owned()),
+        info_url: Some("https://mojitoswap.finance".to_owned()),
+        ..Default::default()
+    };
+    let badge_addr = context.new_admin_badge(&dapp_definition);
+    context
+        .call_with_badge(&dapp_definition, badge_addr, "update_metadata", manifest_args!(update.clone()))
+        .expect_commit_failure();
+    context.call_as_admin("update_metadata", manifest_args!(update)).expect_commit_success();
+    assert_eq!(Some(MetadataValue::String("The MOJ/USDT pool.".to_owned())), context.get_pool_metadata("description"));
+    assert_eq!(
+        Some(MetadataValue::Url(UncheckedUrl::of("https://mojitoswap.finance"))),
+        context.get_pool_metadata("info_url")
+    );
+    assert_eq!(Some(MetadataValue::String("MojitoSwap MOJ/USDT 0%".to_owned())), context.get_pool_metadata("name"));
+    assert!(context.get_pool_metadata("icon_url").is_some());
+}
+
+/**
+ * Pool fee metadata.
+ *
+ * We test that the fee tier of the pool follows the executed fee changes, the set fees at once and the fee ramps once they complete,
+ * with the default name and description, while the name set by the admin is kept.
+ */
+#[test]
+fn pool_fee_metadata() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let description = |context: &mut Context| match context.get_pool_metadata("description") {
+        Some(MetadataValue::String(description)) => description,
+        metadata => panic!("Unexpected pool description {:?}", metadata),
+    };
+
+    context.apply_change(ParameterChange::SetFee(dec!("0.005"))).expect_commit_success();
+    assert_eq!(Some(MetadataValue::Decimal(dec!("0.005"))), context.get_pool_metadata("fee_tier"));
+    match context.get_pool_metadata("name") {
+        Some(MetadataValue::String(name)) => assert!(name.starts_with("MojitoSwap ") && name.ends_with(" 0.5%")),
+        metadata => panic!("Unexpected pool name {:?}", metadata),
+    }
+    assert!(description(&mut context).ends_with(" a 0.5% fee."));
+
+    let update = PoolMetadata { name: Some("MOJ/USDT".to_owned()), ..Default::default() };
+    context.call_as_admin("update_metadata", manifest_args!(update)).expect_commit_success();
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    context.apply_change(ParameterChange::RampFee(dec!("0.01"), 10)).expect_commit_success();
+    let epoch = context.runner.get_current_epoch().number();
+    context.runner.set_current_epoch(Epoch::of(epoch + 5));
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    assert_eq!(Some(MetadataValue::Decimal(dec!("0.005"))), context.get_pool_metadata("fee_tier"));
+    assert!(description(&mut context).ends_with(" a 0.5% fee."));
+
+    context.runner.set_current_epoch(Epoch::of(epoch + 10));
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    assert_eq!(Some(MetadataValue::Decimal(dec!("0.01"))), context.get_pool_metadata("fee_tier"));
+    assert_eq!(Some(MetadataValue::String("MOJ/USDT".to_owned())), context.get_pool_metadata("name"));
+    assert!(description(&mut context).ends_with(" a 1% fee."));
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::ParameterChange;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Position migration.
+ *
+ * We test that a position, with its accumulated fees, is migrated to a pool of the same pair with another fee tier on the same range,
+ * and that the range must be on the tick spacing of the target pool.
+ */
+#[test]
+fn migrate_position() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let pool_addr = context.pool_addr;
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let target_pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    let off_spacing_account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.add_pos(&off_spacing_account, dec!("1000"), dec!("1000"), -100, 100);
+    context.migrate_position(&off_spacing_account, target_pool_addr).expect_commit_failure();
+    context.migrate_position(&off_spacing_account, pool_addr).expect_commit_failure();
+
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -600, 600);
+    let swapper = context.new_account_with_moj_and_usdt(dec!("100"), dec!("0"));
+    context.swap_moj_for_usdt(&swapper, dec!("100"), Decimal::zero());
+    let target_pool_tvl = {
+        context.use_pool(target_pool_addr);
+        let tvl = context.get_tvl();
+        context.use_pool(pool_addr);
+        tvl
+    };
+
+    context.migrate_position(&account, target_pool_addr).expect_commit_success();
+    assert_eq!(None, context.get_position(&account));
+
+    context.use_pool(target_pool_addr);
+    let position = context.get_position(&account).unwrap();
+    assert_eq!((-600, 600), (position.low_tick, position.high_tick));
+    let (moj_balance, usdt_balance) = context.get_balances(&account);
+    let tvl = context.get_tvl();
+    // the fees and the remainders that don't fit in the new position at the target pool price are given back
+    assert!(moj_balance > Decimal::zero() || usdt_balance > Decimal::zero());
+    assert!(tvl.total0 > target_pool_tvl.total0 && tvl.total1 > target_pool_tvl.total1);
+}
+
+/**
+ * Pool state export.
+ *
+ * We test that the state of a pool is exported with a timelocked parameter change to another pool of the same package with no
+ * positions, whose admin assigned the importer role to the pool, where the LPs keep using their position NFTs, and that the exporting
+ * pool is left with no positions and liquidity.
+ */
+#[test]
+fn export_state() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let pool_addr = context.pool_addr;
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let target_pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -600, 600);
+    let swapper = context.new_account_with_moj_and_usdt(dec!("100"), dec!("0"));
+    context.swap_moj_for_usdt(&swapper, dec!("100"), Decimal::zero());
+    let position = context.get_position(&account).unwrap();
+    let pool_state = context.get_pool_state();
+
+    // the target must be another pool of the same package
+    context.apply_change(ParameterChange::ExportState(factory_addr)).expect_commit_failure();
+    context.apply_change(ParameterChange::ExportState(pool_addr)).expect_commit_failure();
+    // the target pool must allow the import
+    context.apply_change(ParameterChange::ExportState(target_pool_addr)).expect_commit_failure();
+    context
+        .set_component_role(target_pool_addr, "importer", rule!(require(global_caller(pool_addr))))
+        .expect_commit_success();
+    // the target pool must have no positions
+    context.apply_change(ParameterChange::ExportState(target_pool_addr)).expect_commit_failure();
+    context.use_pool(target_pool_addr);
+    context.remove_admin_pos(Decimal::zero(), Decimal::zero());
+    context.use_pool(pool_addr);
+    context.apply_change(ParameterChange::ExportState(target_pool_addr)).expect_commit_success();
+
+    let exported_pool_state = context.get_pool_state();
+    assert_eq!(
+        (Decimal::zero(), Decimal::zero(), Decimal::zero()),
+        (exported_pool_state.amount0, exported_pool_state.amount1, exported_pool_state.active_liquidity)
+    );
+    assert!(context.get_positions().is_empty());
+
+    context.use_pool(target_pool_addr);
+    let target_pool_state = context.get_pool_state();
+    assert_eq!(pool_state.position_nft_addr, target_pool_state.position_nft_addr);
+    assert_eq!(pool_state.sqrt_price, target_pool_state.sqrt_price);
+    assert_eq!(pool_state.active_liquidity, target_pool_state.active_liquidity);
+    assert_eq!(Some(position), context.get_position(&account));
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    assert_eq!(None, context.get_position(&account));
+}
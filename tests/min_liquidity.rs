@@ -0,0 +1,38 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Minimum liquidity gate.
+ *
+ * We test that the swaps of a pool created with a minimum liquidity are aborted while the active liquidity is below the minimum,
+ * that they go through once more liquidity is added and that the trading stays open after the liquidity is removed.
+ */
+#[test]
+fn min_liquidity_gate() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let pool_addr = context.new_pool_with_min_liquidity(dec!("30000"));
+    context.use_pool(pool_addr);
+    assert_eq!(Some(dec!("30000")), context.query_pool::<Option<Decimal>>("get_min_liquidity"));
+    assert!(context.get_pool_state().active_liquidity < dec!("30000"));
+
+    let account = context.new_account_with_moj_and_usdt(dec!("2000"), dec!("2000"));
+    context.try_swap_moj_for_usdt(&account, dec!("10")).expect_commit_failure();
+
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -1000, 1000);
+    assert!(context.get_pool_state().active_liquidity > dec!("30000"));
+    context.try_swap_moj_for_usdt(&account, dec!("10")).expect_commit_success();
+    assert_eq!(None, context.query_pool::<Option<Decimal>>("get_min_liquidity"));
+
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    context.try_swap_moj_for_usdt(&account, dec!("10")).expect_commit_success();
+}
@@ -0,0 +1,208 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::oracle;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+// a time after the ledger genesis, rounded to minutes as the pool observations
+const START_TIME: i64 = 1_700_000_040;
+
+fn new_context() -> Context {
+    Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    )
+}
+
+/**
+ * TWAP oracle.
+ *
+ * We test that the swaps record the tick accumulated in time and that the observations are interpolated in between the recorded
+ * ones and extrapolated with the current tick after the latest one.
+ */
+#[test]
+fn observe() {
+    let mut context = new_context();
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+
+    context.set_current_time(START_TIME);
+    let start_tick_cumulative = context.get_observation(0).tick_cumulative;
+    context.swap_moj_for_usdt(&account, dec!("100"), Decimal::zero());
+    let tick1 = context.get_pool_state().current_tick.0 as i64;
+
+    context.set_current_time(START_TIME + 600);
+    context.swap_moj_for_usdt(&account, dec!("100"), Decimal::zero());
+    let tick2 = context.get_pool_state().current_tick.0 as i64;
+
+    context.set_current_time(START_TIME + 1200);
+    assert_eq!(start_tick_cumulative + tick1 * 600 + tick2 * 600, context.get_observation(0).tick_cumulative);
+    assert_eq!(start_tick_cumulative + tick1 * 600, context.get_observation(600).tick_cumulative);
+    assert_eq!(start_tick_cumulative + tick1 * 300, context.get_observation(900).tick_cumulative);
+    assert_eq!(START_TIME + 300, context.get_observation(900).timestamp);
+
+    // the live liquidity didn't change, the swaps staying in the range of the initial position
+    let live_liq = context.get_pool_state().active_liquidity;
+    assert_eq!(live_liq, oracle::mean_liquidity(&context.get_observation(1200), &context.get_observation(0)));
+
+    // the 10 minutes time weighted average tick
+    assert_eq!(Tick(tick2 as i32), oracle::mean_tick(&context.get_observation(600), &context.get_observation(0)));
+
+    let (twap_tick, twap_price) = context.get_twap(1200);
+    assert_eq!(Tick(((tick1 + tick2) * 600).div_euclid(1200) as i32), twap_tick);
+    let twap_sqrt_price = tick_math::sqrt_price_at_tick(twap_tick).0;
+    assert_eq!(twap_sqrt_price * twap_sqrt_price, twap_price);
+
+    // before the pool creation
+    context.observe(vec![(START_TIME + 1200 + 1) as u64]).expect_commit_failure();
+}
+
+/**
+ * TWAP oracle ring buffer.
+ *
+ * We test that the oracle keeps the latest observations, at most one per minute, the older ones being overwritten.
+ */
+#[test]
+fn observations_ring_buffer() {
+    let mut context = new_context();
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+
+    for minute in 0..15 {
+        context.set_current_time(START_TIME + minute * 60);
+        context.swap_moj_for_usdt(&account, dec!("10"), Decimal::zero());
+        context.swap_usdt_for_moj(&account, dec!("10"), Decimal::zero());
+    }
+
+    // the oldest of the 10 observations kept is the one of minute 5
+    context.set_current_time(START_TIME + 15 * 60);
+    context.get_observation(10 * 60);
+    context.observe(vec![10 * 60 + 1]).expect_commit_failure();
+}
+
+/**
+ * TWAP oracle growth.
+ *
+ * We test that anyone can grow the oracle ring buffer, up to the maximum cardinality, and that the buffer grows once the observations
+ * kept are overwritten, so the longer windows can be observed.
+ */
+#[test]
+fn increase_observation_cardinality() {
+    let mut context = new_context();
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    assert_eq!((10, 10), context.get_observation_cardinality());
+
+    context.increase_observation_cardinality(&account, 70000).expect_commit_failure();
+    context.increase_observation_cardinality(&account, 20).expect_commit_success();
+    context.increase_observation_cardinality(&account, 15).expect_commit_success();
+    assert_eq!((10, 20), context.get_observation_cardinality());
+
+    for minute in 0..25 {
+        context.set_current_time(START_TIME + minute * 60);
+        context.swap_moj_for_usdt(&account, dec!("10"), Decimal::zero());
+        context.swap_usdt_for_moj(&account, dec!("10"), Decimal::zero());
+    }
+    assert_eq!((20, 20), context.get_observation_cardinality());
+
+    // the oldest of the 20 observations kept is the one of minute 5
+    context.set_current_time(START_TIME + 25 * 60);
+    context.get_observation(20 * 60);
+    context.observe(vec![20 * 60 + 1]).expect_commit_failure();
+}
+
+/**
+ * Time weighted average liquidity.
+ *
+ * We test that the oracle accumulates the live liquidity in time, as the positions are added and removed.
+ */
+#[test]
+fn mean_liquidity() {
+    let mut context = new_context();
+    let account = context.new_account_with_moj_and_usdt(dec!("10000"), dec!("10000"));
+    let initial_live_liq = context.get_pool_state().active_liquidity;
+
+    context.set_current_time(START_TIME);
+    context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
+    let live_liq = context.get_pool_state().active_liquidity;
+
+    context.set_current_time(START_TIME + 600);
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+
+    context.set_current_time(START_TIME + 1200);
+    assert_eq!(live_liq, oracle::mean_liquidity(&context.get_observation(1200), &context.get_observation(600)));
+    assert_eq!(
+        (live_liq * 600 + initial_live_liq * 600) / 1200,
+        oracle::mean_liquidity(&context.get_observation(1200), &context.get_observation(0))
+    );
+}
+
+/**
+ * TWAP oracle batch observations.
+ *
+ * We test that the observations of many times are queried at once, in the given order, and that the seconds per live liquidity
+ * accumulated give the seconds a position was in range, the seconds without live liquidity counting as with a liquidity of 1.
+ */
+#[test]
+fn observe_batch() {
+    let mut context = new_context();
+    let account = context.new_account_with_moj_and_usdt(dec!("10000"), dec!("10000"));
+    let initial_live_liq = context.get_pool_state().active_liquidity;
+
+    context.set_current_time(START_TIME);
+    context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
+    let live_liq = context.get_pool_state().active_liquidity;
+
+    context.set_current_time(START_TIME + 600);
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+
+    context.set_current_time(START_TIME + 1200);
+    let observations = context.get_observations(vec![1200, 0, 600]);
+    assert_eq!(3, observations.len());
+    assert_eq!(context.get_observation(1200), observations[0]);
+    assert_eq!(context.get_observation(0), observations[1]);
+    assert_eq!(context.get_observation(600), observations[2]);
+    assert_eq!(START_TIME + 600, observations[2].timestamp);
+
+    // the initial position was in range all along, the removed one only for the first 10 minutes
+    let seconds_in_range = oracle::seconds_in_range(&observations[0], &observations[1], initial_live_liq);
+    let seconds_per_liq = Decimal::from(600) / live_liq + Decimal::from(600) / initial_live_liq;
+    assert_eq!(seconds_per_liq * initial_live_liq, seconds_in_range);
+    let seconds_in_range = oracle::seconds_in_range(&observations[0], &observations[2], live_liq - initial_live_liq);
+    assert_eq!(Decimal::from(600) / live_liq * (live_liq - initial_live_liq), seconds_in_range);
+
+    assert!(context.get_observations(vec![]).is_empty());
+    // any of the times before the pool creation
+    context.observe(vec![0, (START_TIME + 1200 + 1) as u64]).expect_commit_failure();
+}
+
+/**
+ * Position valuation at a given price.
+ *
+ * We test that a position is valued at any given sqrt price, e.g. a TWAP one, with its uncollected fees, the valuation at the current
+ * sqrt price being the amounts redeemed by removing the position.
+ */
+#[test]
+fn get_position_amounts_at() {
+    let mut context = new_context();
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -600, 600);
+    let swapper = context.new_account_with_moj_and_usdt(dec!("100"), dec!("0"));
+    context.swap_moj_for_usdt(&swapper, dec!("100"), Decimal::zero());
+
+    let (low_amount0, low_amount1) = context.get_position_amounts_at(&account, tick_math::sqrt_price_at_tick(Tick(-600)));
+    let (high_amount0, high_amount1) = context.get_position_amounts_at(&account, tick_math::sqrt_price_at_tick(Tick(600)));
+    // out of the range the position has a single token, besides the fees, only in MOJ as only MOJ was swapped
+    assert_eq!(Decimal::zero(), low_amount1);
+    assert!(low_amount0 > dec!("1000") && high_amount1 > dec!("1000"));
+    assert!(high_amount0 > Decimal::zero() && high_amount0 < dec!("1"));
+
+    let pool_state = context.get_pool_state();
+    let amounts = context.get_position_amounts_at(&account, pool_state.sqrt_price);
+    let (moj_balance, usdt_balance) = context.get_balances(&account);
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    let (new_moj_balance, new_usdt_balance) = context.get_balances(&account);
+    assert_eq!(amounts, (new_moj_balance - moj_balance, new_usdt_balance - usdt_balance));
+}
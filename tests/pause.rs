@@ -0,0 +1,110 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{PausableOperation, EMERGENCY_AMOUNT_DECIMALS};
+use mojitoswap_pool::pool_math;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Pausable pool.
+ *
+ * We test that the pauser pauses and unpauses the swaps and the deposits separately, the paused operations failing with a Paused
+ * error, while the withdrawals are always allowed.
+ */
+#[test]
+fn pause() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("2000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -1000, 1000);
+    let pauser = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let pauser_badge_addr = context.new_admin_badge(&pauser);
+
+    context
+        .call_with_badge(&pauser, pauser_badge_addr, "set_paused", manifest_args!(PausableOperation::Swap, true))
+        .expect_commit_failure();
+    context.set_role("pauser", pauser_badge_addr).expect_commit_success();
+    context
+        .call_with_badge(&pauser, pauser_badge_addr, "set_paused", manifest_args!(PausableOperation::Swap, true))
+        .expect_commit_success();
+    context
+        .try_swap_moj_for_usdt(&account, dec!("10"))
+        .expect_specific_failure(|error| format!("{:?}", error).contains("Paused(Swap)"));
+
+    context
+        .call_with_badge(&pauser, pauser_badge_addr, "set_paused", manifest_args!(PausableOperation::Deposit, true))
+        .expect_commit_success();
+    context
+        .try_add_pos(&account, dec!("100"), Decimal::zero(), 1000, 2000)
+        .expect_specific_failure(|error| format!("{:?}", error).contains("Paused(Deposit)"));
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+
+    context
+        .call_with_badge(&pauser, pauser_badge_addr, "set_paused", manifest_args!(PausableOperation::Swap, false))
+        .expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("10")).expect_commit_success();
+}
+
+/**
+ * Emergency mode.
+ *
+ * We test that in emergency mode the swaps, the deposits, the fee collections and the position migrations are disabled, while the
+ * positions are removed with their principal only, the fees staying in the pool.
+ */
+#[test]
+fn emergency_mode() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("2000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -1000, 1000);
+    context.swap_moj_for_usdt(&account, dec!("500"), Decimal::zero());
+    let pool_state = context.get_pool_state();
+    let position = context.get_position(&account).unwrap();
+    let (moj_balance, usdt_balance) = context.get_balances(&account);
+
+    context.call_as_admin("enable_emergency_mode", manifest_args!()).expect_commit_success();
+    context.call_as_admin("enable_emergency_mode", manifest_args!()).expect_commit_failure();
+    context
+        .try_swap_moj_for_usdt(&account, dec!("10"))
+        .expect_specific_failure(|error| format!("{:?}", error).contains("EmergencyMode"));
+    context
+        .try_add_pos(&account, dec!("100"), Decimal::zero(), 1000, 2000)
+        .expect_specific_failure(|error| format!("{:?}", error).contains("EmergencyMode"));
+    // the fee accounting isn't trusted, so the fees can't be collected or migrated with the positions
+    context
+        .try_collect_fees(&account, Decimal::zero(), Decimal::zero())
+        .expect_specific_failure(|error| format!("{:?}", error).contains("EmergencyMode"));
+    let moj_addr = context.moj_addr;
+    context
+        .collect_fees_as(&account, moj_addr, Decimal::zero())
+        .expect_specific_failure(|error| format!("{:?}", error).contains("EmergencyMode"));
+    let target_pool_addr = context.new_pool_without_position(dec!("0.003"), 1, SqrtPrice(Decimal::one()));
+    context
+        .migrate_position(&account, target_pool_addr)
+        .expect_specific_failure(|error| format!("{:?}", error).contains("EmergencyMode"));
+
+    context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    let (amount0, amount1) = pool_math::compute_range_amounts_given_liq(
+        position.liq,
+        pool_state.sqrt_price.0,
+        tick_math::sqrt_price_at_tick(Tick(-1000)).0,
+        tick_math::sqrt_price_at_tick(Tick(1000)).0,
+    );
+    let (new_moj_balance, new_usdt_balance) = context.get_balances(&account);
+    assert_eq!(amount0.checked_round(EMERGENCY_AMOUNT_DECIMALS, RoundingMode::ToZero).unwrap(), new_moj_balance - moj_balance);
+    assert_eq!(amount1.checked_round(EMERGENCY_AMOUNT_DECIMALS, RoundingMode::ToZero).unwrap(), new_usdt_balance - usdt_balance);
+    assert_eq!(pool_state.active_liquidity - position.liq, context.get_pool_state().active_liquidity);
+}
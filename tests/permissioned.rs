@@ -0,0 +1,116 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Permissioned pool.
+ *
+ * We test that only the holders of the member badge of a permissioned pool can swap and add positions, presenting a proof of it,
+ * while the pools created without a member badge stay open to anyone.
+ */
+#[test]
+fn permissioned_pool() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    assert_eq!(None, context.query_pool::<Option<ResourceAddress>>("get_member_badge_addr"));
+
+    let member = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let outsider = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let member_badge_addr = context.new_badge(&member, "Member badge");
+    let pool_addr = context.new_permissioned_pool(member_badge_addr);
+    context.use_pool(pool_addr);
+    assert_eq!(Some(member_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_member_badge_addr"));
+
+    context.try_swap_moj_for_usdt(&outsider, dec!("10")).expect_commit_failure();
+    context.try_swap_moj_for_usdt(&member, dec!("10")).expect_commit_failure();
+    context.try_swap_moj_for_usdt_with_badge(&member, Some(member_badge_addr), dec!("10")).expect_commit_success();
+
+    context.try_add_pos(&outsider, dec!("100"), dec!("100"), -100, 100).expect_commit_failure();
+    context.try_add_pos_with_badge(&member, Some(member_badge_addr), dec!("100"), dec!("100"), -100, 100).expect_commit_success();
+    assert!(context.get_position(&member).is_some());
+
+    // collecting the fees as a single resource swaps them, so it's gated too
+    context.collect_fees_as(&member, context.moj_addr, Decimal::zero()).expect_commit_failure();
+    context.collect_fees(&member, Decimal::zero(), Decimal::zero());
+}
+
+/**
+ * Permissioned pool migration.
+ *
+ * We test that the positions of an open pool can't be migrated to a permissioned pool of the same pair, as the permissioned pool
+ * would be entered without the member badge.
+ */
+#[test]
+fn migrate_to_permissioned_pool() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let member = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let member_badge_addr = context.new_badge(&member, "Member badge");
+    let permissioned_pool_addr = context.new_permissioned_pool(member_badge_addr);
+
+    context.add_pos(&member, dec!("100"), dec!("100"), -100, 100);
+    context.migrate_position(&member, permissioned_pool_addr).expect_commit_failure();
+    assert!(context.get_position(&member).is_some());
+}
+
+/**
+ * LP whitelist.
+ *
+ * We test that only the holders of the whitelist badge of a pool with an LP whitelist can add positions or move them to a new range,
+ * while anyone can swap, and that the admin can rotate the badge or open the positions to anyone.
+ */
+#[test]
+fn lp_whitelist() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    assert_eq!(None, context.query_pool::<Option<ResourceAddress>>("get_lp_badge_addr"));
+
+    let lp = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let outsider = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let lp_badge_addr = context.new_badge(&lp, "LP badge");
+    let pool_addr = context.new_lp_whitelist_pool(lp_badge_addr);
+    context.use_pool(pool_addr);
+    assert_eq!(Some(lp_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_lp_badge_addr"));
+
+    context.try_swap_moj_for_usdt(&outsider, dec!("10")).expect_commit_success();
+    context.try_add_pos(&outsider, dec!("100"), dec!("100"), -100, 100).expect_commit_failure();
+    context.try_add_pos(&lp, dec!("100"), dec!("100"), -100, 100).expect_commit_failure();
+    context.try_add_pos_with_badge(&lp, Some(lp_badge_addr), dec!("100"), dec!("100"), -100, 100).expect_commit_success();
+    context.try_rebalance_pos_with_badge(&lp, None, -200, 200).expect_commit_failure();
+
+    let new_lp_badge_addr = context.new_badge(&outsider, "New LP badge");
+    context
+        .call_with_badge(&outsider, new_lp_badge_addr, "set_lp_badge_addr", manifest_args!(None::<ResourceAddress>))
+        .expect_commit_failure();
+    context.call_as_admin("set_lp_badge_addr", manifest_args!(Some(new_lp_badge_addr))).expect_commit_success();
+    assert_eq!(Some(new_lp_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_lp_badge_addr"));
+    context.try_add_pos_with_badge(&lp, Some(lp_badge_addr), dec!("100"), dec!("100"), -100, 100).expect_commit_failure();
+    context.try_rebalance_pos_with_badge(&lp, Some(lp_badge_addr), -300, 300).expect_commit_failure();
+    context
+        .try_add_pos_with_badge(&outsider, Some(new_lp_badge_addr), dec!("100"), dec!("100"), -100, 100)
+        .expect_commit_success();
+
+    context.call_as_admin("set_lp_badge_addr", manifest_args!(None::<ResourceAddress>)).expect_commit_success();
+    context.try_rebalance_pos_with_badge(&lp, None, -200, 200).expect_commit_success();
+    context.try_add_pos(&lp, dec!("100"), dec!("100"), -100, 100).expect_commit_success();
+}
@@ -1,581 +1,61 @@
+mod common;
+
+use common::*;
 use lazy_static::lazy_static;
-use mojitoswap_pool::tick_math;
-use radix_engine::transaction::{TransactionReceipt, TransactionResult};
-use regex::Regex;
+use mojitoswap_pool::pool::{
+    DepthLevel, EpochStats, ParameterChange, PoolConfig, PoolState, Position, PriceBandWidenedEvent, Tvl, MAX_TICKS_PAGE_SIZE,
+};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
 use scrypto::prelude::*;
-use scrypto_unit::*;
-use std::collections::HashMap;
-use transaction::{builder::ManifestBuilder, model::TransactionManifestV1};
-
-/**
- * An account used for testing purposes
- */
-struct Account {
-    pub addr: ComponentAddress,
-    pub pub_key: Secp256k1PublicKey,
-}
-
-/**
- * A test scenario context for the pool component, we keep some state here needed for testing
- */
-struct Context {
-    runner: DefaultTestRunner,
-    admin: Account,
-    _admin_badge_addr: ResourceAddress,
-    moj_addr: ResourceAddress,
-    usdt_addr: ResourceAddress,
-    pool_addr: ComponentAddress,
-    position_nft_addr: ResourceAddress,
-}
-
-impl Context {
-    /**
-     * Creates a context containing:
-     * - two fungible resources: MOJ and USDT
-     * - an admin account that owns these resources (it acts also as faucet, giving tokens to other accounts created later
-     * - a pool with the given fee and sqrt_price, the above account acts also the admin pool
-     */
-    pub fn new(
-        fee: Decimal,
-        sqrt_price: Decimal,
-        low_sqrt_price: Decimal,
-        high_sqrt_price: Decimal,
-        moj_amount: Decimal,
-        usdt_amount: Decimal,
-    ) -> Self {
-        let mut runner = TestRunnerBuilder::new().build();
-
-        let package_addr = runner.compile_and_publish(this_package!());
-
-        let (pub_key, _priv_key, addr) = runner.new_allocated_account();
-        let admin = Account { addr, pub_key };
-
-        let mut moj_token_info: BTreeMap<String, String> = BTreeMap::new();
-        moj_token_info.insert("name".to_string(), "Mojito finance".to_string());
-        moj_token_info.insert("symbol".to_string(), "MOJ".to_string());
-
-        let mut usdt_token_info: BTreeMap<String, String> = BTreeMap::new();
-        usdt_token_info.insert("name".to_string(), "Teather USD".to_string());
-        usdt_token_info.insert("symbol".to_string(), "USDT".to_string());
-
-        let admin_res_manif = ManifestBuilder::new()
-            .new_token_fixed(
-                OwnerRole::None,
-                metadata!(
-                    init {
-                        "name" => "Mojito finance".to_owned(), locked;
-                        "symbol" => "MOJ".to_owned(), locked;
-                    }
-                ),
-                dec!("10000000"),
-            )
-            .new_token_fixed(
-                OwnerRole::None,
-                metadata!(
-                    init {
-                        "name" => "Teather USD".to_owned(), locked;
-                        "symbol" => "USDT".to_owned(), locked;
-                    }
-                ),
-                dec!("10000000"),
-            )
-            .new_badge_fixed(
-                OwnerRole::None,
-                metadata!(
-                    init {
-                        "name" => "Admin badge".to_owned(), locked;
-                    }
-                ),
-                Decimal::one(),
-            )
-            .call_method(
-                admin.addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-
-        let admin_res_receipt = execute_manif(&mut runner, admin_res_manif, vec![&pub_key]);
-
-        let result = admin_res_receipt.expect_commit_success();
-
-        let usdt_addr: ResourceAddress = result.new_resource_addresses()[0];
-        let moj_addr: ResourceAddress = result.new_resource_addresses()[1];
-        let admin_badge_addr: ResourceAddress = result.new_resource_addresses()[2];
-
-        let new_pool_manif = ManifestBuilder::new()
-            .withdraw_from_account(admin.addr, moj_addr, moj_amount)
-            .withdraw_from_account(admin.addr, usdt_addr, usdt_amount)
-            .take_from_worktop(moj_addr, moj_amount, "moj_bucket")
-            .take_from_worktop(usdt_addr, usdt_amount, "usdt_bucket")
-            .call_function_with_name_lookup(package_addr, "Pool", "new", |lookup| {
-                (
-                    moj_addr,
-                    usdt_addr,
-                    fee,
-                    sqrt_price,
-                    admin_badge_addr,
-                    low_sqrt_price,
-                    high_sqrt_price,
-                    lookup.bucket("moj_bucket"),
-                    lookup.bucket("usdt_bucket"),
-                )
-            })
-            .call_method(
-                admin.addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-        let new_pool_receipt = runner.execute_manifest_ignoring_fee(
-            new_pool_manif,
-            vec![NonFungibleGlobalId::from_public_key(&pub_key)],
-        );
-        println!("{:?}\n", new_pool_receipt);
-        let result = new_pool_receipt.expect_commit_success();
-
-        let pool_addr: ComponentAddress = result.new_component_addresses()[0];
-        let pos_nft_badge_addr: ResourceAddress = result.new_resource_addresses()[1];
-
-        Self {
-            runner,
-            admin,
-            usdt_addr,
-            moj_addr,
-            _admin_badge_addr: admin_badge_addr,
-            pool_addr,
-            position_nft_addr: pos_nft_badge_addr,
-        }
-    }
-
-    /**
-     * Creates a new account used for testing having the given amounts of MOJ and USDT
-     */
-    pub fn new_account_with_moj_and_usdt(
-        &mut self,
-        moj_amount: Decimal,
-        usdt_amount: Decimal,
-    ) -> Account {
-        let (account_pub_key, _account_priv_key, account_addr) =
-            self.runner.new_allocated_account();
-
-        let account_amount_manif = ManifestBuilder::new()
-            .withdraw_from_account(self.admin.addr, self.moj_addr, moj_amount)
-            .withdraw_from_account(self.admin.addr, self.usdt_addr, usdt_amount)
-            .call_method(
-                account_addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-
-        execute_manif(
-            &mut self.runner,
-            account_amount_manif,
-            vec![&self.admin.pub_key, &account_pub_key],
-        );
-
-        Account {
-            addr: account_addr,
-            pub_key: account_pub_key,
-        }
-    }
-
-    /**
-     * Adds a new position to the pool, position is owned by the given account
-     */
-    pub fn add_pos(
-        &mut self,
-        account: &Account,
-        moj_amount: Decimal,
-        usdt_amount: Decimal,
-        low_tick: i32,
-        high_tick: i32,
-    ) -> TransactionReceipt {
-        let add_pos_manif = ManifestBuilder::new()
-            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
-            .withdraw_from_account(account.addr, self.usdt_addr, usdt_amount)
-            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
-            .take_from_worktop(self.usdt_addr, usdt_amount, "usdt_bucket")
-            .call_method_with_name_lookup(self.pool_addr, "add_pos", |lookup| {
-                (
-                    lookup.bucket("moj_bucket"),
-                    lookup.bucket("usdt_bucket"),
-                    tick_math::sqrt_price_at_tick(low_tick),
-                    tick_math::sqrt_price_at_tick(high_tick),
-                )
-            })
-            .assert_worktop_contains(self.moj_addr, Decimal::zero())
-            .assert_worktop_contains(self.usdt_addr, Decimal::zero())
-            .assert_worktop_contains(self.position_nft_addr, Decimal::one())
-            .call_method(
-                account.addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-
-        let add_pos_receipt = self.runner.execute_manifest_ignoring_fee(
-            add_pos_manif,
-            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
-        );
-        println!("{:?}\n", add_pos_receipt);
-
-        add_pos_receipt.expect_commit_success();
-        add_pos_receipt
-    }
-
-    /**
-     * Adds more liquidity to the position owned by the given account (for the moment this test utility allows only for a position per account)
-     * Unfortunatelly we can't check the liquidity on the returned position NFT at the worktop level
-     */
-    pub fn add_liq(
-        &mut self,
-        account: &Account,
-        usdt_amount: Decimal,
-        moj_amount: Decimal,
-    ) -> TransactionReceipt {
-        let add_liq_manif = ManifestBuilder::new()
-            .withdraw_from_account(account.addr, self.moj_addr, moj_amount)
-            .withdraw_from_account(account.addr, self.usdt_addr, usdt_amount)
-            .take_from_worktop(self.moj_addr, moj_amount, "moj_bucket")
-            .take_from_worktop(self.usdt_addr, usdt_amount, "usdt_bucket")
-            .create_proof_from_account_of_non_fungible(
-                account.addr,
-                self.pos_nft_badge_id(account.addr),
-            )
-            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
-            .call_method_with_name_lookup(self.pool_addr, "add_liq", |lookup| {
-                (
-                    lookup.bucket("moj_bucket"),
-                    lookup.bucket("usdt_bucket"),
-                    lookup.proof("proof"),
-                )
-            })
-            .assert_worktop_contains(self.moj_addr, Decimal::zero())
-            .assert_worktop_contains(self.usdt_addr, Decimal::zero())
-            .call_method(
-                account.addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-
-        let add_liq_receipt = self.runner.execute_manifest_ignoring_fee(
-            add_liq_manif,
-            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
-        );
-        println!("{:?}\n", add_liq_receipt);
-
-        add_liq_receipt.expect_commit_success();
-        add_liq_receipt
-    }
-
-    fn pos_nft_badge_id(&mut self, account_addr: ComponentAddress) -> NonFungibleGlobalId {
-        let vaults = self
-            .runner
-            .get_component_vaults(account_addr, self.position_nft_addr);
-        let nft_local_id = self
-            .runner
-            .inspect_non_fungible_vault(vaults[0])
-            .unwrap()
-            .1
-            .next()
-            .unwrap();
-        NonFungibleGlobalId::new(self.position_nft_addr, nft_local_id)
-    }
-
-    /**
-     * Adds the fees accumulated by the account's position to liquidity
-     */
-    pub fn add_accumulated_fees_to_liq(&mut self, account: &Account) -> TransactionReceipt {
-        let add_liq_manif = ManifestBuilder::new()
-            .create_proof_from_account_of_non_fungible(
-                account.addr,
-                self.pos_nft_badge_id(account.addr),
-            )
-            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
-            .call_method_with_name_lookup(self.pool_addr, "add_accumulated_fees_to_liq", |lookup| {
-                (lookup.proof("proof"),)
-            })
-            .build();
-
-        let add_liq_receipt = self.runner.execute_manifest_ignoring_fee(
-            add_liq_manif,
-            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
-        );
-        println!("{:?}\n", add_liq_receipt);
-
-        add_liq_receipt.expect_commit_success();
-        add_liq_receipt
-    }
-
-    pub fn remove_admin_pos(
-        &mut self,
-        expected_moj_amount: Decimal,
-        expected_usdt_amount: Decimal,
-    ) -> TransactionReceipt {
-        let remove_liq_manif = self.create_remove_liq_manif(
-            self.admin.addr,
-            expected_moj_amount,
-            expected_usdt_amount,
-        );
-        self.execute_remove_lig_manif(remove_liq_manif, &self.admin.pub_key.clone())
-    }
-
-    /**
-     * Removes the given account's position and checks that upon removal the account got the expected amounts
-     */
-    pub fn remove_pos(
-        &mut self,
-        account: &Account,
-        expected_moj_amount: Decimal,
-        expected_usdt_amount: Decimal,
-    ) -> TransactionReceipt {
-        let remove_liq_manif =
-            self.create_remove_liq_manif(account.addr, expected_moj_amount, expected_usdt_amount);
-        self.execute_remove_lig_manif(remove_liq_manif, &account.pub_key)
-    }
-
-    fn create_remove_liq_manif(
-        &mut self,
-        account_addr: ComponentAddress,
-        expected_moj_amount: Decimal,
-        expected_usdt_amount: Decimal,
-    ) -> TransactionManifestV1 {
-        ManifestBuilder::new()
-            .create_proof_from_account_of_non_fungible(
-                account_addr,
-                self.pos_nft_badge_id(account_addr),
-            )
-            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
-            .call_method_with_name_lookup(self.pool_addr, "remove_pos", |lookup| {
-                (lookup.proof("proof"),)
-            })
-            .assert_worktop_contains(self.moj_addr, expected_moj_amount)
-            .assert_worktop_contains(self.usdt_addr, expected_usdt_amount)
-            .call_method(
-                account_addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build()
-    }
-
-    fn execute_remove_lig_manif(
-        &mut self,
-        remove_liq_manif: TransactionManifestV1,
-        account_pub_key: &Secp256k1PublicKey,
-    ) -> TransactionReceipt {
-        let remove_liq_receipt = self.runner.execute_manifest_ignoring_fee(
-            remove_liq_manif,
-            vec![NonFungibleGlobalId::from_public_key(account_pub_key)],
-        );
-        println!("{:?}\n", remove_liq_receipt);
-
-        remove_liq_receipt.expect_commit_success();
-        remove_liq_receipt
-    }
-
-    /**
-     * Collects the fees accumulated by the given account's position and checks the expected amounts of fees
-     */
-    pub fn collect_fees(
-        &mut self,
-        account: &Account,
-        expected_moj_amount: Decimal,
-        expected_usdt_amount: Decimal,
-    ) -> TransactionReceipt {
-        let collect_fees_manif = ManifestBuilder::new()
-            .create_proof_from_account_of_non_fungible(
-                account.addr,
-                self.pos_nft_badge_id(account.addr),
-            )
-            .create_proof_from_auth_zone_of_amount(self.position_nft_addr, Decimal::one(), "proof")
-            .call_method_with_name_lookup(self.pool_addr, "collect_fees", |lookup| {
-                (lookup.proof("proof"),)
-            })
-            .assert_worktop_contains(self.moj_addr, expected_moj_amount)
-            .assert_worktop_contains(self.usdt_addr, expected_usdt_amount)
-            .call_method(
-                account.addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-
-        let remove_liq_receipt = self.runner.execute_manifest_ignoring_fee(
-            collect_fees_manif,
-            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
-        );
-        println!("{:?}\n", remove_liq_receipt);
-
-        remove_liq_receipt.expect_commit_success();
-        remove_liq_receipt
-    }
-
-    /**
-     * Swaps the given amount of MOJ taken from the given account to USDT. Also check the expected amount of USDT.
-     */
-    pub fn swap_moj_for_usdt(
-        &mut self,
-        account: &Account,
-        moj_amount: Decimal,
-        exp_usdt_amount: Decimal,
-    ) -> TransactionReceipt {
-        self.swap(
-            account,
-            self.moj_addr,
-            moj_amount,
-            self.usdt_addr,
-            exp_usdt_amount,
-        )
-    }
-
-    /**
-     * Swaps the given amount of USDT taken from the given account to MOJ. Also check the expected amount of MOJ.
-     */
-    pub fn swap_usdt_for_moj(
-        &mut self,
-        account: &Account,
-        usdt_amount: Decimal,
-        exp_moj_amount: Decimal,
-    ) -> TransactionReceipt {
-        self.swap(
-            account,
-            self.usdt_addr,
-            usdt_amount,
-            self.moj_addr,
-            exp_moj_amount,
-        )
-    }
-    /**
-     * Executes a swap for the given resource address and amount. It expects the given expected resurce address and amount.
-     */
-    fn swap(
-        &mut self,
-        account: &Account,
-        token_addr: ResourceAddress,
-        token_amount: Decimal,
-        expected_token_addr: ResourceAddress,
-        expected_token_amount: Decimal,
-    ) -> TransactionReceipt {
-        let swap_manif = ManifestBuilder::new()
-            .withdraw_from_account(account.addr, token_addr, token_amount)
-            .take_from_worktop(token_addr, token_amount, "token_bucket")
-            .call_method_with_name_lookup(self.pool_addr, "swap", |lookup| {
-                (lookup.bucket("token_bucket"),)
-            })
-            .assert_worktop_contains(token_addr, Decimal::zero())
-            .assert_worktop_contains(expected_token_addr, expected_token_amount)
-            .call_method(
-                account.addr,
-                "deposit_batch",
-                manifest_args!(ManifestExpression::EntireWorktop),
-            )
-            .build();
-        let swap_receipt = self.runner.execute_manifest_ignoring_fee(
-            swap_manif,
-            vec![NonFungibleGlobalId::from_public_key(&account.pub_key)],
-        );
-        println!("{:?}\n", swap_receipt);
-        swap_receipt.expect_commit_success();
-        swap_receipt
-    }
-}
-
-/**
- * Executes a given manifest and expects to be successful
- */
-fn execute_manif(
-    runner: &mut DefaultTestRunner,
-    manif: TransactionManifestV1,
-    pub_keys: Vec<&Secp256k1PublicKey>,
-) -> TransactionReceipt {
-    let receipt = runner.execute_manifest_ignoring_fee(
-        manif,
-        pub_keys
-            .iter()
-            .map(|pub_key| NonFungibleGlobalId::from_public_key(*pub_key))
-            .collect::<Vec<_>>(),
-    );
-    println!("{:?}\n", receipt);
-    receipt.expect_commit_success();
-    receipt
-}
 
 /**
  * Add position.
  *
- * We test that upon adding a position the internal state of the pool is as expected. For this we read the logs from the transaction receipt.
+ * We test that upon adding a position the internal state of the pool is as expected. For this we query the pool state, the positions
+ * and the used ticks states.
  */
 #[test]
 fn add_pos() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     let account = context.new_account_with_moj_and_usdt(dec!("10000"), dec!("10000"));
-    let add_pos_receipt = context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
-
-    //todo refactor/extract somewhere the code bellow in a more generic way, so we can use this in every test if we want to check an expected internal state.
-    lazy_static! {
-        static ref RE_KEY_TO_LOG: Regex = Regex::new(r"### (.*)=(.*)").unwrap();
-        static ref RE_POSITION_IDS: Regex = Regex::new(r"\[(.*)\]").unwrap();
-        static ref RE_POS_ID: Regex = Regex::new(r"\{\S*\}").unwrap();
-    }
-
-    if let TransactionResult::Commit(result) = &add_pos_receipt.result {
-        let key_to_log: HashMap<String, String> = result
-            .application_logs
-            .iter()
-            .filter(|level_and_log| RE_KEY_TO_LOG.is_match(level_and_log.1.as_str()))
-            .map(|level_and_log| level_and_log.1.as_str())
-            .flat_map(|mess| RE_KEY_TO_LOG.captures(mess))
-            .map(|captures| (captures[1].to_string(), captures[2].to_string()))
-            .collect();
-
-        println!("{:?}", key_to_log);
-
-        assert_eq!(
-            key_to_log.get("Vault0"),
-            Some(&String::from("19999.999999999999939578"))
-        );
-        assert_eq!(key_to_log.get("Vault1"), Some(&String::from("20000")));
-        assert_eq!(
-            key_to_log.get("Life liq"),
-            Some(&String::from("410103.325362140397360716"))
-        );
-        assert_eq!(
-            key_to_log.get("Used ticks"),
-            Some(&String::from("{-1000, 1000}"))
-        );
-        assert_eq!(
-            key_to_log.get("Positions"),
-            Some(&String::from(
-                "[Position { liq: 205051.662681070198680358, low_tick: -1000, high_tick: 1000, range_fee0: 0, range_fee1: 0 }, Position { liq: 205051.662681070198680358, low_tick: -1000, high_tick: 1000, range_fee0: 0, range_fee1: 0 }]"
-            ))
-        );
-
-        assert_eq!(
-            key_to_log.get("Position NFT liq"),
-            Some(&String::from("205051.662681070198680358"))
-        );
+    context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
 
-        let ids = key_to_log
-            .get("Position ids")
-            .and_then(|raw_ids| RE_POSITION_IDS.captures(raw_ids).map(|c| c[1].to_string()))
-            .unwrap();
-        let id_set: HashSet<&str> = RE_POS_ID
-            .find_iter(ids.as_str())
-            .map(|id| id.as_str())
-            .collect();
-        assert!(id_set.contains(key_to_log.get("Position id").map(|l| l.as_str()).unwrap()));
-    }
+    let pool_state = context.get_pool_state();
+    assert_eq!(pool_state.amount0, dec!("19999.999999999632897104"));
+    assert_eq!(pool_state.amount1, dec!("20000"));
+    assert_eq!(pool_state.active_liquidity, dec!("410103.325362132871093714"));
+    assert_eq!(pool_state.sqrt_price, SqrtPrice(Decimal::one()));
+    assert_eq!(pool_state.current_tick, Tick(0));
+
+    let expected_pos = Position {
+        liq: dec!("205051.662681066435546857"),
+        low_tick: -1000,
+        high_tick: 1000,
+        range_fee0: Decimal::zero(),
+        range_fee1: Decimal::zero(),
+    };
+    assert_eq!(context.get_position(&account), Some(expected_pos.clone()));
+    let positions = context.get_positions();
+    assert_eq!(positions.len(), 2);
+    assert!(positions.iter().all(|(_pos_id, pos)| *pos == expected_pos));
+
+    let tick_states = context.get_tick_states();
+    assert_eq!(
+        tick_states.iter().map(|state| state.tick).collect::<Vec<_>>(),
+        vec![-1000, 1000]
+    );
+    assert_eq!(tick_states[0].liq_net, pool_state.active_liquidity);
+    assert_eq!(tick_states[1].liq_net, -pool_state.active_liquidity);
+    assert!(tick_states
+        .iter()
+        .all(|state| state.liq_gross == pool_state.active_liquidity && state.init));
 }
 
 /**
@@ -587,9 +67,9 @@ fn add_pos() {
 fn add_liq() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -608,9 +88,9 @@ fn add_liq() {
 fn scenario_1() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -618,10 +98,15 @@ fn scenario_1() {
     context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
     let _remove_pos_receipt = context.remove_pos(
         &account,
-        dec!("9999.99999999999985322"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
-    // to do check pool internal state to have the initial state
+
+    let pool_state = context.get_pool_state();
+    assert_eq!(pool_state.amount0, dec!("9999.999999999816448552"));
+    assert_eq!(pool_state.amount1, dec!("10000.000000000000000001"));
+    assert_eq!(pool_state.active_liquidity, dec!("205051.662681066435546857"));
+    assert_eq!(pool_state.sqrt_price, SqrtPrice(Decimal::one()));
 }
 
 /**
@@ -635,9 +120,9 @@ fn scenario_1() {
 fn scenario_2() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -659,9 +144,9 @@ fn scenario_2() {
 fn scenario_3() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -685,20 +170,20 @@ fn scenario_3() {
 fn scenario_4() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     let account = context.new_account_with_moj_and_usdt(dec!("20000"), dec!("10000"));
     context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
     let _swap1_receipt =
-        context.swap_moj_for_usdt(&account, dec!("10000"), dec!("9761.963321966572685348"));
+        context.swap_moj_for_usdt(&account, dec!("10000"), dec!("9761.963321966568236605"));
     let _swap2_receipt = context.swap_usdt_for_moj(
         &account,
-        dec!("9761.963321966572685348"),
-        dec!("9999.999999999999589896"),
+        dec!("9761.963321966568236605"),
+        dec!("9999.999999999999384844"),
     );
 }
 
@@ -713,9 +198,9 @@ fn scenario_4() {
 fn scenario_5() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -744,9 +229,9 @@ fn scenario_5() {
 fn scenario_6() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -771,9 +256,9 @@ fn scenario_6() {
 fn scenario_7() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -795,9 +280,9 @@ fn scenario_7() {
 fn scenario_8() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -810,6 +295,213 @@ fn scenario_8() {
     // to do check pool internal state to have the expected state
 }
 
+/**
+ * Fees of a crossed range.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1, a position=[10000 MOJ + 10000 USDT, -1000, 1000] and a position=[1000 MOJ, 10, 200]
+ *
+ * If there is a 5000 USDT swap, crossing the ticks 10 and 200
+ *
+ * Then the uncollected fees of the positions add up to the fees of the swap: crossing a tick flips only the fees outside of the
+ * crossed tick, so the fees of the range [10, 200] aren't flipped back as the price leaves it
+ */
+#[test]
+fn fees_of_crossed_range() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("6000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), 10, 200);
+    context.swap_usdt_for_moj(&account, dec!("5000"), Decimal::zero());
+
+    assert!(context.get_pool_state().sqrt_price > tick_math::sqrt_price_at_tick(Tick(200)));
+    let epoch = context.runner.get_current_epoch().number();
+    let epoch_stats = context.get_epoch_stats(epoch).unwrap();
+    let tvl = context.get_tvl();
+    assert!(tvl.uncollected_fees1 > epoch_stats.fees1 - dec!("0.000001"));
+    assert!(tvl.uncollected_fees1 < epoch_stats.fees1 + dec!("0.000001"));
+}
+
+/**
+ * Swap to the end of the liquidity.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ *
+ * If there is a 20000 MOJ swap, more than needed to move the price to the tick -1000
+ *
+ * Then the fee is charged on top of the amount needed to cross the tick -1000, so the price lands exactly on the tick and there is
+ * no active liquidity left
+ */
+#[test]
+fn swap_to_end_of_liquidity() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("20000"), Decimal::zero());
+    context.swap_moj_for_usdt(&account, dec!("20000"), Decimal::zero());
+
+    let pool_state = context.get_pool_state();
+    assert_eq!(pool_state.sqrt_price, tick_math::sqrt_price_at_tick(Tick(-1000)));
+    assert_eq!(pool_state.active_liquidity, Decimal::zero());
+}
+
+/**
+ * Swap without a tick to cross.
+ *
+ * Given a pool with fee=0, sqrt_price=1 and a position=[10000 MOJ, 0, 1000], so there is no used tick below the price
+ *
+ * If there is a 100 MOJ swap
+ *
+ * Then the swap stops instead of looping forever looking for a tick to cross, the MOJ is given back and the price doesn't move
+ */
+#[test]
+fn swap_without_tick_to_cross() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(0)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("100"), Decimal::zero());
+    context.swap_moj_for_usdt(&account, dec!("100"), Decimal::zero());
+
+    assert_eq!(context.get_pool_state().sqrt_price, SqrtPrice(Decimal::one()));
+}
+
+/**
+ * Tick after crossing down.
+ *
+ * Given a pool with fee=0, sqrt_price=1, a position=[10000 MOJ + 10000 USDT, -1000, 1000] and a position=[1000 MOJ + 1000 USDT,
+ * -500, 500]
+ *
+ * If there is a 8000 MOJ swap, crossing down the tick -500 but not the tick -1000
+ *
+ * Then the current tick is below the crossed tick -500, as the price left the range [-500, 500], and only the first position is
+ * active
+ */
+#[test]
+fn tick_after_crossing_down() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let liq = context.get_pool_state().active_liquidity;
+    let account = context.new_account_with_moj_and_usdt(dec!("9000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -500, 500);
+    context.swap_moj_for_usdt(&account, dec!("8000"), Decimal::zero());
+
+    let pool_state = context.get_pool_state();
+    assert!(pool_state.current_tick < Tick(-500) && pool_state.current_tick > Tick(-1000));
+    assert_eq!(pool_state.active_liquidity, liq);
+}
+
+/**
+ * Swap down from a used tick.
+ *
+ * Given a pool with fee=0, sqrt_price=1, a position=[10000 MOJ + 10000 USDT, -1000, 1000] and a position=[1000 MOJ, 0, 1000]
+ *
+ * If there is a 1000 MOJ swap
+ *
+ * Then the tick 0 the price starts on is the first tick crossed, so the price moves below it and only the first position stays active
+ */
+#[test]
+fn swap_down_from_used_tick() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let liq = context.get_pool_state().active_liquidity;
+    let account = context.new_account_with_moj_and_usdt(dec!("2000"), Decimal::zero());
+    context.add_pos(&account, dec!("1000"), Decimal::zero(), 0, 1000);
+    context.swap_moj_for_usdt(&account, dec!("1000"), Decimal::zero());
+
+    let pool_state = context.get_pool_state();
+    assert!(pool_state.current_tick < Tick(0));
+    assert_eq!(pool_state.active_liquidity, liq);
+}
+
+/**
+ * Tick after a swap without crossing.
+ *
+ * Given a pool with fee=0, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ *
+ * If:
+ * There is a 2000 MOJ swap, moving the price below the tick -50 without crossing any used tick.
+ * And then a position=[1000 MOJ, -50, 50] is added.
+ *
+ * Then the current tick follows the price, so the new position is out of range and the active liquidity doesn't change
+ */
+#[test]
+fn tick_after_swap_without_crossing() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("3000"), Decimal::zero());
+    context.swap_moj_for_usdt(&account, dec!("2000"), Decimal::zero());
+    let pool_state = context.get_pool_state();
+    assert!(pool_state.current_tick < Tick(-50));
+
+    context.add_pos(&account, dec!("1000"), Decimal::zero(), -50, 50);
+    assert_eq!(context.get_pool_state().active_liquidity, pool_state.active_liquidity);
+}
+
+/**
+ * Fees of a new position.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ *
+ * If:
+ * There is a 1000 MOJ swap, accumulating fees for the position.
+ * And then a position=[1000 MOJ + 1000 USDT, -1000, 1000] sharing its ticks is added.
+ *
+ * Then the new position isn't credited with the fees accumulated before it was created, so the uncollected fees don't change
+ */
+#[test]
+fn fees_of_new_position() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("3000"), dec!("1000"));
+    context.swap_moj_for_usdt(&account, dec!("1000"), Decimal::zero());
+    let tvl = context.get_tvl();
+    assert!(tvl.uncollected_fees0 > Decimal::zero());
+
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -1000, 1000);
+    let new_tvl = context.get_tvl();
+    assert_eq!(new_tvl.uncollected_fees0, tvl.uncollected_fees0);
+    assert_eq!(new_tvl.uncollected_fees1, tvl.uncollected_fees1);
+}
+
 /**
  * Add liquidity to position.
  *
@@ -827,9 +519,9 @@ fn scenario_8() {
 fn scenario_9() {
     let mut context = Context::new(
         dec!("0.01"),
-        tick_math::sqrt_price_at_tick(3000),
-        tick_math::sqrt_price_at_tick(2000),
-        tick_math::sqrt_price_at_tick(4000),
+        tick_math::sqrt_price_at_tick(Tick(3000)),
+        tick_math::sqrt_price_at_tick(Tick(2000)),
+        tick_math::sqrt_price_at_tick(Tick(4000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -843,7 +535,7 @@ fn scenario_9() {
     );
     let _add_liq_receipt = context.add_accumulated_fees_to_liq(&account);
     let _acc_fees_receipt =
-        context.collect_fees(&account, dec!("0.487730419238072792"), Decimal::zero());
+        context.collect_fees(&account, dec!("0.486127107175883714"), Decimal::zero());
     context.remove_pos(
         &account,
         dec!("7457.164320366552918974"),
@@ -866,23 +558,23 @@ fn scenario_9() {
 fn scenario_10() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     context.remove_admin_pos(
-        dec!("9999.999999999999969789"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
     let account1 = context.new_account_with_moj_and_usdt(dec!("15000"), dec!("10000"));
     context.add_pos(&account1, dec!("10000"), dec!("10000"), -1000, 1000);
     let account2 = context.new_account_with_moj_and_usdt(dec!("15000"), dec!("10000"));
     context.add_pos(&account2, dec!("10000"), dec!("10000"), 2000, 4000);
-    context.swap_moj_for_usdt(&account1, dec!("5000"), dec!("4833.322352370076335998"));
+    context.swap_moj_for_usdt(&account1, dec!("5000"), dec!("4833.322352370074371121"));
     let _acc1_fees_receipt =
-        context.collect_fees(&account1, dec!("49.999999999999999999"), Decimal::zero());
+        context.collect_fees(&account1, dec!("49.9999999999999026"), Decimal::zero());
     let _acc2_fees_receipt = context.collect_fees(&account2, Decimal::zero(), Decimal::zero());
 }
 
@@ -900,9 +592,9 @@ fn scenario_10() {
 fn scenario_11() {
     let mut context = Context::new(
         dec!("0.01"),
-        tick_math::sqrt_price_at_tick(3000),
-        tick_math::sqrt_price_at_tick(2000),
-        tick_math::sqrt_price_at_tick(4000),
+        tick_math::sqrt_price_at_tick(Tick(3000)),
+        tick_math::sqrt_price_at_tick(Tick(2000)),
+        tick_math::sqrt_price_at_tick(Tick(4000)),
         dec!("10000"),
         dec!("10000"),
     );
@@ -930,20 +622,20 @@ fn scenario_11() {
  *
  * In reality this ratios are bit skewed. The real ratio of fees is visible in the net liquidity on the positions:
  *
- * Position1 { liq: 205051.662681070198680358, low_tick: -1000, high_tick: 1000}, Position2 { liq: 105088.315200116552078393, low_tick: -2000, high_tick: 2000}]
+ * Position1 { liq: 205051.662681066435546857, low_tick: -1000, high_tick: 1000}, Position2 { liq: 105088.315200116552078393, low_tick: -2000, high_tick: 2000}]
  */
 #[test]
 fn scenario_12() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     context.remove_admin_pos(
-        dec!("9999.999999999999969789"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
     let account1 = context.new_account_with_moj_and_usdt(dec!("15000"), dec!("10000"));
@@ -973,20 +665,20 @@ fn scenario_12() {
  *
  * In reality this ratios are bit skewed. The real ratio of fees is visible in the net liquidity on the positions:
  *
- * Position1 { liq: 205051.662681070198680358, low_tick: -1000, high_tick: 1000}, Position2 { liq: 254159.202345836056361321, low_tick: -10000, high_tick: 10000}
+ * Position1 { liq: 205051.662681066435546857, low_tick: -1000, high_tick: 1000}, Position2 { liq: 254159.202345836056361321, low_tick: -10000, high_tick: 10000}
  */
 #[test]
 fn scenario_13() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     context.remove_admin_pos(
-        dec!("9999.999999999999969789"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
     let account1 = context.new_account_with_moj_and_usdt(dec!("15000"), dec!("10000"));
@@ -1020,25 +712,25 @@ fn scenario_13() {
 fn scenario_14() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     context.remove_admin_pos(
-        dec!("9999.999999999999969789"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
     let account = context.new_account_with_moj_and_usdt(dec!("100000"), dec!("100000"));
     context.add_pos(&account, dec!("10000"), dec!("10000"), -1000, 1000);
     context.add_pos(&account, dec!("10000"), Decimal::zero(), 100, 200);
     context.add_pos(&account, dec!("10000"), Decimal::zero(), 200, 300);
-    context.swap_usdt_for_moj(&account, dec!("20000"), dec!("19647.863604192115415544"));
+    context.swap_usdt_for_moj(&account, dec!("20000"), dec!("19647.863604192114214242"));
     context.swap_moj_for_usdt(
         &account,
-        dec!("19647.863604192115415544"),
-        dec!("19999.999999999994361019"),
+        dec!("19647.863604192114214242"),
+        dec!("19999.999999999993828344"),
     );
 }
 
@@ -1053,14 +745,14 @@ fn scenario_14() {
 fn scenario_15() {
     let mut context = Context::new(
         Decimal::zero(),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     context.remove_admin_pos(
-        dec!("9999.999999999999969789"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
     let account = context.new_account_with_moj_and_usdt(dec!("30000"), dec!("30000"));
@@ -1085,23 +777,594 @@ fn scenario_15() {
 fn scenario_16() {
     let mut context = Context::new(
         dec!("0.01"),
-        Decimal::one(),
-        tick_math::sqrt_price_at_tick(-1000),
-        tick_math::sqrt_price_at_tick(1000),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
         dec!("10000"),
         dec!("10000"),
     );
     context.remove_admin_pos(
-        dec!("9999.999999999999969789"),
+        dec!("9999.999999999816448552"),
         dec!("9999.999999999999999999"),
     );
     let account1 = context.new_account_with_moj_and_usdt(dec!("100000"), dec!("100000"));
     let account2 = context.new_account_with_moj_and_usdt(dec!("100000"), dec!("100000"));
     context.add_pos(&account1, dec!("10000"), dec!("10000"), -1000, 1000);
     context.add_pos(&account2, dec!("10000"), dec!("10000"), -1000, 1000);
-    context.swap_moj_for_usdt(&account1, dec!("5000"), dec!("4890.96541696510667305"));
-    context.collect_fees(&account1, dec!("24.999999999999999999"), Decimal::zero());
-    context.collect_fees(&account2, dec!("24.999999999999999999"), Decimal::zero());
+    context.swap_moj_for_usdt(&account1, dec!("5000"), dec!("4890.965416965105495766"));
+    context.collect_fees(&account1, dec!("24.9999999999999513"), Decimal::zero());
+    context.collect_fees(&account2, dec!("24.9999999999999513"), Decimal::zero());
+}
+
+/**
+ * Amount to move price.
+ *
+ * Given:
+ * - pool with fee=0.01, sqrt_price=1
+ * - position1=[10000 MOJ, 10000 USDT, -1000, 1000]
+ * - position2=[1000 MOJ, 0 USDT, 199, 200]
+ *
+ * Then:
+ * - moving the price down to tick -500 needs ~5243 MOJ
+ * - moving the price up to tick 300 needs ~4160 USDT, as the amount includes also the crossing of position2
+ */
+#[test]
+fn scenario_17() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    assert_eq!(
+        (context.moj_addr, dec!("5243.075524218243832289")),
+        context.amount_to_move_price(-500)
+    );
+
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), Decimal::zero());
+    context.add_pos(&account, dec!("1000"), Decimal::zero(), 199, 200);
+    assert_eq!(
+        (context.usdt_addr, dec!("4160.557817032208651052")),
+        context.amount_to_move_price(300)
+    );
+}
+
+/**
+ * Pool depth.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ *
+ * Test that the depth aggregated in 3 levels of 400 ticks contains on each side ~4060, ~3979 and ~1960 tokens, the last level
+ * being only partially covered by the position.
+ */
+#[test]
+fn scenario_18() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let depth = context.get_depth(3, 400);
+    assert_eq!(
+        depth.bids,
+        vec![
+            DepthLevel {
+                sqrt_price: tick_math::sqrt_price_at_tick(Tick(-400)),
+                amount: dec!("4060.093983671026951756"),
+            },
+            DepthLevel {
+                sqrt_price: tick_math::sqrt_price_at_tick(Tick(-800)),
+                amount: dec!("3979.70271573051168924"),
+            },
+            DepthLevel {
+                sqrt_price: tick_math::sqrt_price_at_tick(Tick(-1200)),
+                amount: dec!("1960.203300598461359003"),
+            },
+        ]
+    );
+    assert_eq!(
+        depth.asks,
+        vec![
+            DepthLevel {
+                sqrt_price: tick_math::sqrt_price_at_tick(Tick(400)),
+                amount: dec!("4060.093983670951183297"),
+            },
+            DepthLevel {
+                sqrt_price: tick_math::sqrt_price_at_tick(Tick(800)),
+                amount: dec!("3979.702715730439607946"),
+            },
+            DepthLevel {
+                sqrt_price: tick_math::sqrt_price_at_tick(Tick(1200)),
+                amount: dec!("1960.203300598425659787"),
+            },
+        ]
+    );
+}
+
+/**
+ * Total value locked.
+ *
+ * Given:
+ * - pool with fee=0.01, sqrt_price=1
+ * - position1=[10000 MOJ, 10000 USDT, -1000, 1000]
+ * - position2=[10000 MOJ, 0 USDT, 2000, 4000]
+ *
+ * If: there is a 5000 MOJ swap in range [-1000, 1000]
+ *
+ * Then: position1 amounts are active, position2 amounts are out of range and the 50 MOJ fee is uncollected
+ */
+#[test]
+fn scenario_19() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("15000"), dec!("10000"));
+    context.add_pos(&account, dec!("10000"), dec!("10000"), 2000, 4000);
+    context.swap_moj_for_usdt(&account, dec!("5000"), dec!("4833.322352370074371121"));
+
+    assert_eq!(
+        context.get_tvl(),
+        Tvl {
+            active0: dec!("14949.999999999816592558"),
+            active1: dec!("5166.677647629925628878"),
+            out_of_range0: dec!("10000.000000000000006128"),
+            out_of_range1: Decimal::zero(),
+            uncollected_fees0: dec!("49.9999999999999026"),
+            uncollected_fees1: Decimal::zero(),
+            total0: dec!("24999.999999999816448552"),
+            total1: dec!("5166.677647629925628879"),
+        }
+    );
+}
+
+/**
+ * Epoch statistics.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000]
+ *
+ * If: there are two 2500 MOJ swaps and a 1000 USDT swap in the same epoch
+ *
+ * Then: the epoch statistics contain 5000 MOJ + 1000 USDT volume, 50 MOJ + 10 USDT fees, the sqrt_price after the last swap,
+ * and there are no statistics for the next epoch
+ */
+#[test]
+fn scenario_20() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("5000"), dec!("1000"));
+    context.swap_moj_for_usdt(&account, dec!("2500"), Decimal::zero());
+    context.swap_moj_for_usdt(&account, dec!("2500"), Decimal::zero());
+    context.swap_usdt_for_moj(&account, dec!("1000"), Decimal::zero());
+
+    let epoch = context.runner.get_current_epoch().number();
+    let pool_state = context.get_pool_state();
+    assert_eq!(
+        context.get_epoch_stats(epoch),
+        Some(EpochStats {
+            epoch,
+            volume0: dec!("5000"),
+            volume1: dec!("1000"),
+            fees0: dec!("50"),
+            fees1: dec!("10"),
+            sqrt_price: pool_state.sqrt_price,
+            swaps: 3,
+        })
+    );
+    assert_eq!(context.get_epoch_stats(epoch + 1), None);
+}
+
+/**
+ * The state shared by the snapshot scenarios: the snapshot of a pool with fee=0.01, sqrt_price=1, a position=[10000 MOJ + 10000 USDT,
+ * -1000, 1000] and ten positions=[1000 MOJ + 1000 USDT, -100 * i, 100 * i], i = 1..10, after ten back and forth 2000 MOJ and USDT swaps.
+ * Besides the snapshot we keep the owners of the ten positions and the pool state and TVL at the time of the snapshot.
+ */
+struct SnapshotScenario {
+    snapshot: Snapshot,
+    accounts: Vec<Account>,
+    pool_state: PoolState,
+    tvl: Tvl,
+}
+
+lazy_static! {
+    static ref SNAPSHOT_SCENARIO: SnapshotScenario = {
+        let mut context = Context::new(
+            dec!("0.01"),
+            SqrtPrice(Decimal::one()),
+            tick_math::sqrt_price_at_tick(Tick(-1000)),
+            tick_math::sqrt_price_at_tick(Tick(1000)),
+            dec!("10000"),
+            dec!("10000"),
+        );
+        let mut accounts = Vec::new();
+        for i in 1..=10 {
+            let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+            context.add_pos(&account, dec!("1000"), dec!("1000"), -100 * i, 100 * i);
+            accounts.push(account);
+        }
+        let trader = context.new_account_with_moj_and_usdt(dec!("20000"), dec!("20000"));
+        for _i in 0..10 {
+            context.swap_moj_for_usdt(&trader, dec!("2000"), Decimal::zero());
+            context.swap_usdt_for_moj(&trader, dec!("2000"), Decimal::zero());
+        }
+
+        SnapshotScenario {
+            pool_state: context.get_pool_state(),
+            tvl: context.get_tvl(),
+            snapshot: context.snapshot(),
+            accounts,
+        }
+    };
+}
+
+/**
+ * Snapshot restore.
+ *
+ * Given the snapshot of the pool described by SNAPSHOT_SCENARIO
+ *
+ * If: we restore it twice and we swap 1000 MOJ in the first restored context
+ *
+ * Then: both restored contexts start with the pool state and TVL of the snapshot, and the swap doesn't affect the second context
+ */
+#[test]
+fn scenario_21() {
+    let mut context = Context::from_snapshot(&SNAPSHOT_SCENARIO.snapshot);
+    assert_eq!(context.get_pool_state(), SNAPSHOT_SCENARIO.pool_state);
+    assert_eq!(context.get_tvl(), SNAPSHOT_SCENARIO.tvl);
+
+    let trader = context.new_account_with_moj_and_usdt(dec!("1000"), Decimal::zero());
+    context.swap_moj_for_usdt(&trader, dec!("1000"), Decimal::zero());
+    assert_ne!(context.get_pool_state(), SNAPSHOT_SCENARIO.pool_state);
+
+    let mut other_context = Context::from_snapshot(&SNAPSHOT_SCENARIO.snapshot);
+    assert_eq!(other_context.get_pool_state(), SNAPSHOT_SCENARIO.pool_state);
+    assert_eq!(other_context.get_tvl(), SNAPSHOT_SCENARIO.tvl);
+}
+
+/**
+ * Collect fees from a snapshot.
+ *
+ * Given the snapshot of the pool described by SNAPSHOT_SCENARIO
+ *
+ * If: we restore it and collect the fees of all positions, the admin position included
+ *
+ * Then: the collected fees are the uncollected fees of the snapshot TVL and no fees are left uncollected
+ */
+#[test]
+fn scenario_22() {
+    let mut context = Context::from_snapshot(&SNAPSHOT_SCENARIO.snapshot);
+    let mut accounts = SNAPSHOT_SCENARIO.accounts.clone();
+    accounts.push(context.admin.clone());
+
+    let (mut collected_moj, mut collected_usdt) = (Decimal::zero(), Decimal::zero());
+    for account in &accounts {
+        let (moj_before, usdt_before) = context.get_balances(account);
+        context.collect_fees(account, Decimal::zero(), Decimal::zero());
+        let (moj_after, usdt_after) = context.get_balances(account);
+        collected_moj += moj_after - moj_before;
+        collected_usdt += usdt_after - usdt_before;
+    }
+
+    assert_eq!(collected_moj, SNAPSHOT_SCENARIO.tvl.uncollected_fees0);
+    assert_eq!(collected_usdt, SNAPSHOT_SCENARIO.tvl.uncollected_fees1);
+    let tvl = context.get_tvl();
+    assert_eq!(tvl.uncollected_fees0, Decimal::zero());
+    assert_eq!(tvl.uncollected_fees1, Decimal::zero());
+}
+
+/**
+ * Pool created from plain prices.
+ *
+ * Given a pool created with fee=0.01, price=4 and a position=[10000 MOJ + 10000 USDT, price range [1, 9]]
+ *
+ * Then: the pool sqrt_price is 2 and the position range is [1, 3] in sqrt prices, the same as for a pool created from sqrt prices
+ */
+#[test]
+fn scenario_23() {
+    let mut context = Context::new_with_price(
+        dec!("0.01"),
+        dec!("4"),
+        Decimal::one(),
+        dec!("9"),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let pool_state = context.get_pool_state();
+    assert_eq!(pool_state.sqrt_price, SqrtPrice(dec!("2")));
+    assert_eq!(pool_state.current_tick, tick_math::tick_at_sqrt_price(SqrtPrice(dec!("2"))));
+
+    let mut sqrt_price_context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(dec!("2")),
+        SqrtPrice(Decimal::one()),
+        SqrtPrice(dec!("3")),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let sqrt_price_pool_state = sqrt_price_context.get_pool_state();
+    assert_eq!(pool_state.amount0, sqrt_price_pool_state.amount0);
+    assert_eq!(pool_state.amount1, sqrt_price_pool_state.amount1);
+    assert_eq!(pool_state.active_liquidity, sqrt_price_pool_state.active_liquidity);
+    assert_eq!(context.get_tick_states(), sqrt_price_context.get_tick_states());
+}
+
+/**
+ * Collect fees as a single token.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[1000 MOJ + 1000 USDT, -1000, 1000]
+ *
+ * If there is a 500 MOJ swap and a 500 USDT swap
+ *
+ * Then the position fees are collected as USDT only, the MOJ fees being swapped, and not below the minimum output
+ */
+#[test]
+fn scenario_24() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.add_pos(&account, dec!("1000"), dec!("1000"), -1000, 1000);
+    let trader = context.new_account_with_moj_and_usdt(dec!("500"), dec!("500"));
+    context.swap_moj_for_usdt(&trader, dec!("500"), Decimal::zero());
+    context.swap_usdt_for_moj(&trader, dec!("500"), Decimal::zero());
+
+    let (moj_balance, usdt_balance) = context.get_balances(&account);
+    context.collect_fees_as(&account, context.moj_addr, dec!("1000")).expect_commit_failure();
+    context.collect_fees_as(&account, context.admin_badge_addr, Decimal::zero()).expect_commit_failure();
+    context.collect_fees_as(&account, context.usdt_addr, dec!("0.5")).expect_commit_success();
+    let (collected_moj_balance, collected_usdt_balance) = context.get_balances(&account);
+    assert_eq!(moj_balance, collected_moj_balance);
+    assert!(collected_usdt_balance - usdt_balance > dec!("0.5"));
 }
 
 // To be continued...
+
+/**
+ * Unbounded pool.
+ *
+ * Given a pool created unbounded with fee=0, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, full tick range]
+ *
+ * Test that if an account swaps 5000 MOJ, moving the price far below the ranges of the other scenarios
+ *
+ * Then: the initial position is on [MIN_TICK, MAX_TICK] and the account gets ~3333.33 USDT, as in a constant product pool
+ * (10000 - 10000 * 10000 / 15000)
+ */
+#[test]
+fn scenario_25() {
+    let mut context = Context::new_unbounded(Decimal::zero(), SqrtPrice(Decimal::one()), dec!("10000"), dec!("10000"));
+    let ticks: Vec<i32> = context.get_tick_states().iter().map(|tick_state| tick_state.tick).collect();
+    assert_eq!(ticks, vec![tick_math::MIN_TICK, tick_math::MAX_TICK]);
+
+    let account = context.new_account_with_moj_and_usdt(dec!("5000"), Decimal::zero());
+    context.try_swap_moj_for_usdt(&account, dec!("5000")).expect_commit_success();
+    let (moj_amount, usdt_amount) = context.get_balances(&account);
+    assert_eq!(moj_amount, Decimal::zero());
+    assert!(usdt_amount > dec!("3333.33") && usdt_amount < dec!("3333.34"));
+}
+
+/**
+ * List initialized ticks.
+ *
+ * We test that the used ticks are listed in pages after the cursor tick, with their liquidity_net and liquidity_gross as in their
+ * states, the last page being empty, and that the page limit is bounded.
+ */
+#[test]
+fn list_initialized_ticks() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("10000"), dec!("10000"));
+    context.add_pos(&account, dec!("10000"), dec!("10000"), -500, 2000);
+    let expected_ticks: Vec<(i32, Decimal, Decimal)> = context
+        .get_tick_states()
+        .iter()
+        .map(|state| (state.tick, state.liq_net, state.liq_gross))
+        .collect();
+    assert_eq!(expected_ticks.iter().map(|tick| tick.0).collect::<Vec<_>>(), vec![-1000, -500, 1000, 2000]);
+
+    let mut ticks: Vec<(i32, Decimal, Decimal)> = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page: Vec<(i32, Decimal, Decimal)> =
+            context.try_list_initialized_ticks(cursor, 3).expect_commit_success().output(0);
+        if page.is_empty() {
+            break;
+        }
+        assert!(page.len() <= 3);
+        cursor = page.last().map(|tick| tick.0);
+        ticks.extend(page);
+    }
+    assert_eq!(expected_ticks, ticks);
+
+    let page: Vec<(i32, Decimal, Decimal)> = context.try_list_initialized_ticks(Some(-600), 2).expect_commit_success().output(0);
+    assert_eq!(expected_ticks[1..3].to_vec(), page);
+    context.try_list_initialized_ticks(None, 0).expect_commit_failure();
+    context.try_list_initialized_ticks(None, MAX_TICKS_PAGE_SIZE + 1).expect_commit_failure();
+}
+
+/**
+ * Pool without a seed position.
+ *
+ * We test that a pool created without the initial position starts at the given price with no liquidity, that the swaps return
+ * their whole input until the first LP adds a position on any range, and that the pool swaps afterwards.
+ */
+#[test]
+fn new_without_position() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let sqrt_price = tick_math::sqrt_price_at_tick(Tick(600));
+    context.pool_addr = context.new_pool_without_position(dec!("0.003"), 60, sqrt_price);
+    let pool_state = context.get_pool_state();
+    context.position_nft_addr = pool_state.position_nft_addr;
+    assert_eq!(sqrt_price, pool_state.sqrt_price);
+    assert_eq!(Tick(600), pool_state.current_tick);
+    assert_eq!(Decimal::zero(), pool_state.active_liquidity);
+    assert!(context.get_positions().is_empty() && context.get_tick_states().is_empty());
+
+    let account = context.new_account_with_moj_and_usdt(dec!("1100"), dec!("1000"));
+    context.swap_moj_for_usdt(&account, dec!("100"), Decimal::zero());
+    assert_eq!((dec!("1100"), dec!("1000")), context.get_balances(&account));
+
+    context.add_pos(&account, dec!("1000"), dec!("1000"), 0, 1200);
+    assert_eq!(1, context.get_positions().len());
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    let (_, usdt_amount) = context.get_balances(&account);
+    assert!(usdt_amount > Decimal::zero());
+}
+
+/**
+ * Portfolio of an account.
+ *
+ * We test that the portfolio of the position NFTs of an account returns the amounts, the uncollected fees and the range status of
+ * each position, with their totals and the share of their liquidity in range.
+ */
+#[test]
+fn get_portfolio() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.try_add_pos(&account, dec!("100"), dec!("100"), -100, 100).expect_commit_success();
+    context.try_add_pos(&account, dec!("100"), dec!("100"), 200, 400).expect_commit_success();
+    let swapper = context.new_account_with_moj_and_usdt(dec!("10"), dec!("0"));
+    context.swap_moj_for_usdt(&swapper, dec!("10"), Decimal::zero());
+
+    let portfolio = context.get_portfolio(&account);
+    assert_eq!(2, portfolio.positions.len());
+    let in_range_pos = portfolio.positions.iter().find(|pos| pos.in_range).unwrap();
+    let out_of_range_pos = portfolio.positions.iter().find(|pos| !pos.in_range).unwrap();
+    assert_eq!((-100, 100), (in_range_pos.position.low_tick, in_range_pos.position.high_tick));
+    assert_eq!((200, 400), (out_of_range_pos.position.low_tick, out_of_range_pos.position.high_tick));
+    // only MOJ was swapped, in the range of the first position
+    assert!(in_range_pos.fees0 > Decimal::zero());
+    assert_eq!((Decimal::zero(), Decimal::zero()), (out_of_range_pos.fees0, out_of_range_pos.fees1));
+    // above the current price the position holds only MOJ
+    assert_eq!(Decimal::zero(), out_of_range_pos.amount1);
+
+    assert_eq!(in_range_pos.amount0 + out_of_range_pos.amount0, portfolio.amount0);
+    assert_eq!(in_range_pos.amount1 + out_of_range_pos.amount1, portfolio.amount1);
+    assert_eq!(in_range_pos.fees0, portfolio.fees0);
+    assert_eq!(
+        in_range_pos.position.liq / (in_range_pos.position.liq + out_of_range_pos.position.liq),
+        portfolio.in_range_ratio
+    );
+    assert!(context.get_positions().contains(&(in_range_pos.pos_id.clone(), in_range_pos.position.clone())));
+}
+
+/**
+ * Rescue of the foreign tokens.
+ *
+ * We test that the tokens sent to the pool other than its resources and position NFTs are kept apart and that only the admin
+ * rescues them, while the pool resources and position NFTs can neither be sent this way nor rescued.
+ */
+#[test]
+fn rescue() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let admin = context.admin.clone();
+    let (moj_addr, usdt_addr, position_nft_addr) = (context.moj_addr, context.usdt_addr, context.position_nft_addr);
+    let airdrop_addr = context.new_badge(&account, "Airdrop");
+
+    context.try_deposit_to_pool(&account, moj_addr, dec!("100")).expect_commit_failure();
+    context.try_deposit_to_pool(&admin, position_nft_addr, Decimal::one()).expect_commit_failure();
+    context.try_deposit_to_pool(&account, airdrop_addr, Decimal::one()).expect_commit_success();
+    assert_eq!(Decimal::zero(), context.runner.get_component_balance(account.addr, airdrop_addr));
+
+    let pool_state = context.get_pool_state();
+    context.try_rescue_with_badge(&account, None, airdrop_addr).expect_commit_failure();
+    for resource_addr in [moj_addr, usdt_addr, position_nft_addr] {
+        context.try_rescue_with_badge(&admin, Some(context.admin_badge_addr), resource_addr).expect_commit_failure();
+    }
+    context.try_rescue_with_badge(&admin, Some(context.admin_badge_addr), airdrop_addr).expect_commit_success();
+    assert_eq!(Decimal::one(), context.runner.get_component_balance(admin.addr, airdrop_addr));
+    assert_eq!(pool_state, context.get_pool_state());
+}
+
+/**
+ * Price band widening.
+ *
+ * We test that the positions of a pool created with a price band must be inside of it, initial position included, and that the
+ * admin widens the band with a timelocked parameter change, emitting an event, but never narrows it, while an unbounded pool has
+ * no band to widen.
+ */
+#[test]
+fn widen_price_band() {
+    let mut context = Context::new(
+        Decimal::zero(),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.apply_change(ParameterChange::WidenPriceBand(-2000, 2000)).expect_commit_failure();
+
+    let band_config = |price_band: (i32, i32)| PoolConfig {
+        initial_position: Some((tick_math::sqrt_price_at_tick(Tick(-1000)), tick_math::sqrt_price_at_tick(Tick(1000)))),
+        price_band: Some(price_band),
+        ..PoolConfig::new(Decimal::zero(), SqrtPrice(Decimal::one()), context.admin_badge_addr)
+    };
+    let (narrow_config, config) = (band_config((-500, 500)), band_config((-1000, 1000)));
+    context.new_pool_with_config(narrow_config, dec!("1000"), dec!("1000")).expect_commit_failure();
+    let pool_addr =
+        context.new_pool_with_config(config, dec!("1000"), dec!("1000")).expect_commit_success().new_component_addresses()[0];
+    context.use_pool(pool_addr);
+    assert_eq!(Some((-1000, 1000)), context.query_pool::<Option<(i32, i32)>>("get_price_band"));
+
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    context.try_add_pos(&account, dec!("100"), dec!("100"), -2000, 1000).expect_commit_failure();
+    context.try_add_pos(&account, dec!("100"), dec!("100"), -500, 500).expect_commit_success();
+
+    context.apply_change(ParameterChange::WidenPriceBand(-500, 1000)).expect_commit_failure();
+    context.apply_change(ParameterChange::WidenPriceBand(-1000, 1000)).expect_commit_failure();
+    context.apply_change(ParameterChange::WidenPriceBand(tick_math::MIN_TICK - 1, 1000)).expect_commit_failure();
+    let receipt = context.apply_change(ParameterChange::WidenPriceBand(-2000, 2000));
+    receipt.expect_commit_success();
+    let events: Vec<PriceBandWidenedEvent> = context.get_events(&receipt, "PriceBandWidenedEvent");
+    assert_eq!(vec![PriceBandWidenedEvent { low_tick: -2000, high_tick: 2000 }], events);
+    assert_eq!(Some((-2000, 2000)), context.query_pool::<Option<(i32, i32)>>("get_price_band"));
+    context.try_add_pos(&account, dec!("100"), dec!("100"), -2000, 1000).expect_commit_success();
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{FeesCollectedEvent, LiquidityAddedEvent, LiquidityRemovedEvent, PositionAddedEvent};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Position events.
+ *
+ * We test that adding a position, adding liquidity to it, compounding and collecting its fees and removing it emit typed events with
+ * the position id, the amounts, the range and the resulting liquidity, so the position can be tracked from the events only.
+ */
+#[test]
+fn position_events() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let trader = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+
+    let receipt = context.add_pos(&account, dec!("100"), dec!("100"), -1000, 1000);
+    let added: PositionAddedEvent = context.get_events(&receipt, "PositionAddedEvent")[0].clone();
+    assert_eq!((-1000, 1000), (added.low_tick, added.high_tick));
+    assert_eq!((dec!("1000") - added.amount0, dec!("1000") - added.amount1), context.get_balances(&account));
+    let pos_id = added.pos_id;
+
+    let receipt = context.add_liq(&account, dec!("100"), dec!("100"));
+    let liq_added: LiquidityAddedEvent = context.get_events(&receipt, "LiquidityAddedEvent")[0].clone();
+    assert_eq!(pos_id, liq_added.pos_id);
+    assert_eq!(added.liq + liq_added.liq, liq_added.pos_liq);
+
+    let receipt = context.add_accumulated_fees_to_liq(&account);
+    let compounded: LiquidityAddedEvent = context.get_events(&receipt, "LiquidityAddedEvent")[0].clone();
+    assert_eq!((Decimal::zero(), Decimal::zero()), (compounded.amount0, compounded.amount1));
+
+    context.try_swap_moj_for_usdt(&trader, dec!("100")).expect_commit_success();
+    let (moj_balance, _) = context.get_balances(&account);
+    let receipt = context.collect_fees(&account, Decimal::zero(), Decimal::zero());
+    let collected: FeesCollectedEvent = context.get_events(&receipt, "FeesCollectedEvent")[0].clone();
+    assert_eq!(pos_id, collected.pos_id);
+    assert!(collected.fees0 > Decimal::zero());
+    assert_eq!(moj_balance + collected.fees0, context.get_balances(&account).0);
+    assert_eq!(compounded.pos_liq, collected.pos_liq);
+
+    let receipt = context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    let removed: LiquidityRemovedEvent = context.get_events(&receipt, "LiquidityRemovedEvent")[0].clone();
+    assert_eq!(pos_id, removed.pos_id);
+    assert_eq!((-1000, 1000), (removed.low_tick, removed.high_tick));
+    assert_eq!((collected.pos_liq, Decimal::zero()), (removed.liq, removed.pos_liq));
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::ParameterChange;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Max single swap price impact.
+ *
+ * We test that the maximum price impact is set with a timelocked parameter change, that the swaps moving the price more than the
+ * maximum are aborted while the smaller ones go through, and that the limit can be removed.
+ */
+#[test]
+fn max_single_swap_price_impact() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("2000"), dec!("0"));
+
+    context.apply_change(ParameterChange::SetMaxSingleSwapPriceImpact(Some(Decimal::zero()))).expect_commit_failure();
+    context.apply_change(ParameterChange::SetMaxSingleSwapPriceImpact(Some(dec!("0.005")))).expect_commit_success();
+    assert_eq!(Some(dec!("0.005")), context.query_pool::<Option<Decimal>>("get_max_single_swap_price_impact"));
+
+    //about 1% price impact
+    context.try_swap_moj_for_usdt(&account, dec!("1000")).expect_commit_failure();
+    //about 0.1% price impact
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+
+    context.apply_change(ParameterChange::SetMaxSingleSwapPriceImpact(None)).expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("1000")).expect_commit_success();
+}
+
+/**
+ * Max single swap input.
+ *
+ * We test that the maximum input of a single swap, as a share of the active liquidity, is set with a timelocked parameter change,
+ * that the larger swaps are aborted while the smaller ones go through, and that the limit can be removed.
+ */
+#[test]
+fn max_swap_input_share() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("2000"), dec!("0"));
+
+    context.apply_change(ParameterChange::SetMaxSwapInputShare(Some(Decimal::zero()))).expect_commit_failure();
+    context.apply_change(ParameterChange::SetMaxSwapInputShare(Some(dec!("1.5")))).expect_commit_failure();
+    context.apply_change(ParameterChange::SetMaxSwapInputShare(Some(dec!("0.001")))).expect_commit_success();
+    assert_eq!(Some(dec!("0.001")), context.query_pool::<Option<Decimal>>("get_max_swap_input_share"));
+
+    //the active liquidity holds a virtual reserve of about 205000 MOJ at price 1, so at most about 205 MOJ can be swapped at once
+    context.try_swap_moj_for_usdt(&account, dec!("1000")).expect_commit_failure();
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+
+    context.apply_change(ParameterChange::SetMaxSwapInputShare(None)).expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("1000")).expect_commit_success();
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, ProtocolFeeSwitchEvent};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Protocol fee switch.
+ *
+ * We test that the protocol fee share is set with a timelocked parameter change, within the maximum share, that only the fee
+ * switcher switches the protocol fee on and off, with an event for each switch, and that the protocol part of the swap fees is
+ * accumulated in the pool state, then collected by the treasury, only while the protocol fee is switched on.
+ */
+#[test]
+fn protocol_fee_switch() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let switcher = context.new_account_with_moj_and_usdt(dec!("0"), dec!("0"));
+    let switcher_badge_addr = context.new_badge(&switcher, "Fee switcher badge");
+    context.set_role("fee_switcher", switcher_badge_addr).expect_commit_success();
+
+    context.apply_change(ParameterChange::SetProtocolFeeShare(dec!("0.5"))).expect_commit_failure();
+    context.apply_change(ParameterChange::SetProtocolFeeShare(dec!("0.25"))).expect_commit_success();
+    assert_eq!(dec!("0.25"), context.query_pool::<Decimal>("get_protocol_fee_share"));
+    assert!(!context.query_pool::<bool>("is_protocol_fee_enabled"));
+
+    context.call_as_admin("set_protocol_fee_switch", manifest_args!(true)).expect_commit_failure();
+    let receipt = context.call_with_badge(&switcher, switcher_badge_addr, "set_protocol_fee_switch", manifest_args!(true));
+    receipt.expect_commit_success();
+    let events: Vec<ProtocolFeeSwitchEvent> = context.get_events(&receipt, "ProtocolFeeSwitchEvent");
+    assert_eq!(vec![ProtocolFeeSwitchEvent { enabled: true, protocol_fee_share: dec!("0.25") }], events);
+    context.call_with_badge(&switcher, switcher_badge_addr, "set_protocol_fee_switch", manifest_args!(true)).expect_commit_failure();
+
+    let admin = context.admin.clone();
+    let (admin_moj_amount, _) = context.get_balances(&admin);
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    assert_eq!((dec!("0.25"), Decimal::zero()), context.get_pool_state().protocol_fees);
+    context.collect_protocol_fees();
+    assert_eq!(admin_moj_amount + dec!("0.25"), context.get_balances(&admin).0);
+    assert_eq!((Decimal::zero(), Decimal::zero()), context.get_pool_state().protocol_fees);
+
+    let receipt = context.call_with_badge(&switcher, switcher_badge_addr, "set_protocol_fee_switch", manifest_args!(false));
+    let events: Vec<ProtocolFeeSwitchEvent> = context.get_events(&receipt, "ProtocolFeeSwitchEvent");
+    assert_eq!(vec![ProtocolFeeSwitchEvent { enabled: false, protocol_fee_share: dec!("0.25") }], events);
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    context.collect_protocol_fees();
+    assert_eq!(admin_moj_amount + dec!("0.25"), context.get_balances(&admin).0);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::ParameterChange;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Referral fees.
+ *
+ * We test that the referred swaps need a referral registry set with a timelocked parameter change, that the referral fee share of
+ * the swap fees is paid to the payout account of the registered referrer, that a referrer id not registered aborts the swap and that
+ * only the holders of the referrer badge change its payout account.
+ */
+#[test]
+fn referral_fees() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let integrator = context.new_account_with_moj_and_usdt(dec!("0"), dec!("0"));
+    let payout = context.new_account_with_moj_and_usdt(dec!("0"), dec!("0"));
+    let integrator_badge_addr = context.new_badge(&integrator, "Integrator badge");
+    let registry_addr = context.new_referral_registry();
+    let referrer_id: u64 = context
+        .register_referrer(&integrator, registry_addr, integrator_badge_addr, payout.addr)
+        .expect_commit_success()
+        .output(2);
+
+    context.try_swap_moj_for_usdt_with_referrer(&account, dec!("100"), referrer_id).expect_commit_failure();
+
+    context.apply_change(ParameterChange::SetReferralRegistry(Some(registry_addr))).expect_commit_success();
+    context.apply_change(ParameterChange::SetReferralFeeShare(dec!("0.3"))).expect_commit_failure();
+    context.apply_change(ParameterChange::SetReferralFeeShare(dec!("0.2"))).expect_commit_success();
+    assert_eq!(Some(registry_addr), context.query_pool::<Option<ComponentAddress>>("get_referral_registry"));
+    assert_eq!(dec!("0.2"), context.query_pool::<Decimal>("get_referral_fee_share"));
+
+    //a spoofed referrer id can't get the fees
+    context.try_swap_moj_for_usdt_with_referrer(&account, dec!("100"), referrer_id + 1).expect_commit_failure();
+
+    context.try_swap_moj_for_usdt_with_referrer(&account, dec!("100"), referrer_id).expect_commit_success();
+    assert_eq!(dec!("0.2"), context.get_balances(&payout).0);
+
+    let other = context.new_account_with_moj_and_usdt(dec!("0"), dec!("0"));
+    let other_badge_addr = context.new_badge(&other, "Other badge");
+    context
+        .set_referrer_payout_account(&other, registry_addr, other_badge_addr, referrer_id, other.addr)
+        .expect_commit_failure();
+    context
+        .set_referrer_payout_account(&integrator, registry_addr, integrator_badge_addr, referrer_id, integrator.addr)
+        .expect_commit_success();
+
+    context.try_swap_moj_for_usdt_with_referrer(&account, dec!("100"), referrer_id).expect_commit_success();
+    assert_eq!(dec!("0.2"), context.get_balances(&payout).0);
+    assert_eq!(dec!("0.2"), context.get_balances(&integrator).0);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::replay::{PoolEvent, PoolReplay, ReplayMismatch};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Event replay.
+ *
+ * We test that replaying the events of the pool, from a snapshot, through adding a position, swaps crossing its ticks up and down,
+ * collecting its fees and removing it, reconstructs the on-ledger state of the pool, and that a missing event is reported as a
+ * mismatch.
+ */
+#[test]
+fn event_replay() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let trader = context.new_account_with_moj_and_usdt(dec!("5000"), dec!("5000"));
+    let mut replay =
+        PoolReplay::from_snapshot(&context.get_pool_state(), &context.get_tick_states(), &context.get_positions(), Decimal::zero());
+
+    let mut events = vec![];
+    let receipt = context.add_pos(&account, dec!("1000"), dec!("1000"), -100, 100);
+    events.extend(context.get_pool_events(&receipt));
+    let receipt = context.try_swap_usdt_for_moj(&trader, dec!("2000"));
+    events.extend(context.get_pool_events(&receipt));
+    let receipt = context.try_swap_moj_for_usdt(&trader, dec!("4000"));
+    events.extend(context.get_pool_events(&receipt));
+    let receipt = context.collect_fees(&account, Decimal::zero(), Decimal::zero());
+    events.extend(context.get_pool_events(&receipt));
+    let receipt = context.try_swap_usdt_for_moj(&trader, dec!("500"));
+    events.extend(context.get_pool_events(&receipt));
+    let receipt = context.remove_pos(&account, Decimal::zero(), Decimal::zero());
+    events.extend(context.get_pool_events(&receipt));
+
+    let (state, tick_states, positions) = (context.get_pool_state(), context.get_tick_states(), context.get_positions());
+    let mut incomplete_replay = replay.clone();
+    replay.replay(&events);
+    assert_eq!(Vec::<ReplayMismatch>::new(), replay.verify(&state, &tick_states, &positions));
+
+    //without the fees collected the vault amounts differ
+    events.retain(|event| !matches!(event, PoolEvent::FeesCollected(_)));
+    incomplete_replay.replay(&events);
+    let mismatches = incomplete_replay.verify(&state, &tick_states, &positions);
+    assert_eq!(
+        vec!["amount0", "amount1"],
+        mismatches.iter().map(|mismatch| mismatch.field.as_str()).collect::<Vec<&str>>()
+    );
+}
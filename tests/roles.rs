@@ -0,0 +1,96 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, PausableOperation, INITIAL_TIMELOCK_DELAY};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Delegated roles.
+ *
+ * We test that the admin delegates the fee changes to the holders of an operator badge with the fee_manager role, the operator
+ * getting no other parameter changes or admin rights, e.g. it can't shorten the timelock delay or execute and cancel the admin
+ * changes, while the admin keeps its rights.
+ */
+#[test]
+fn fee_manager_role() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let operator = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let operator_badge_addr = context.new_admin_badge(&operator);
+    let fee_change = ParameterChange::SetFee(dec!("0.005"));
+    let admin_change = ParameterChange::SetTimelockDelay(3600);
+
+    context
+        .call_with_badge(&operator, operator_badge_addr, "schedule_change", manifest_args!(fee_change.clone()))
+        .expect_commit_failure();
+    context.set_role("fee_manager", operator_badge_addr).expect_commit_success();
+    let receipt = context.call_with_badge(&operator, operator_badge_addr, "schedule_change", manifest_args!(fee_change.clone()));
+    let change_id: u64 = receipt.expect_commit_success().output(1);
+    context
+        .call_with_badge(&operator, operator_badge_addr, "cancel_change", manifest_args!(change_id))
+        .expect_commit_success();
+    for change in [admin_change.clone(), ParameterChange::SetProtocolFeeShare(dec!("0.1")), ParameterChange::SetSwapRoyalty(dec!("1"))] {
+        context
+            .call_with_badge(&operator, operator_badge_addr, "schedule_change", manifest_args!(change))
+            .expect_commit_failure();
+    }
+    context
+        .call_with_badge(&operator, operator_badge_addr, "propose_admin", manifest_args!(operator_badge_addr))
+        .expect_commit_failure();
+
+    let receipt = context.call_as_admin("schedule_change", manifest_args!(admin_change));
+    let change_id: u64 = receipt.expect_commit_success().output(1);
+    let current_time = context.get_current_time();
+    context.set_current_time(current_time + INITIAL_TIMELOCK_DELAY);
+    context
+        .call_with_badge(&operator, operator_badge_addr, "execute_change", manifest_args!(change_id))
+        .expect_commit_failure();
+    context
+        .call_with_badge(&operator, operator_badge_addr, "cancel_change", manifest_args!(change_id))
+        .expect_commit_failure();
+    context.call_as_admin("execute_change", manifest_args!(change_id)).expect_commit_success();
+    assert_eq!(3600, context.query_pool::<i64>("get_timelock_delay"));
+}
+
+/**
+ * Multisig admin rule.
+ *
+ * We test that a pool created with a 2 of 3 badges admin rule has no admin badge, that a single badge can't call the admin methods
+ * while two of them can, and that the multisig admin can hand the admin over to a single badge.
+ */
+#[test]
+fn multisig_admin_rule() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let admin = context.admin.clone();
+    let badge_addrs: Vec<ResourceAddress> =
+        (1..=3).map(|index| context.new_badge(&admin, &format!("Signer badge {}", index))).collect();
+    let pool_addr = context.new_pool_with_admin_rule(rule!(require_n_of(2, badge_addrs.clone())));
+    context.use_pool(pool_addr);
+    assert_eq!(None, context.query_pool::<Option<ResourceAddress>>("get_admin_badge_addr"));
+
+    let args = || manifest_args!(PausableOperation::Swap, true);
+    context.call_with_badges(&admin, &badge_addrs[..1], "set_paused", args()).expect_commit_failure();
+    context.call_with_badges(&admin, &badge_addrs[1..], "set_paused", args()).expect_commit_success();
+
+    let new_badge_addr = context.new_admin_badge(&admin);
+    context
+        .call_with_badges(&admin, &badge_addrs[..2], "propose_admin", manifest_args!(new_badge_addr))
+        .expect_commit_success();
+    context.call_with_badge(&admin, new_badge_addr, "accept_admin", manifest_args!()).expect_commit_success();
+    assert_eq!(Some(new_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_admin_badge_addr"));
+    context.call_with_badges(&admin, &badge_addrs[1..], "set_paused", args()).expect_commit_failure();
+}
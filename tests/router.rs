@@ -0,0 +1,162 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * The context pool (fee=0.003, [10000 MOJ + 10000 USDT] on [-1000, 1000]), a second MOJ/USDT pool created with the factory
+ * (fee=0.01, [1000 MOJ + 1000 USDT] on [-1200, 1200]) and an account with 1000 MOJ to swap.
+ */
+fn new_context() -> (Context, ComponentAddress, Vec<ComponentAddress>, Account) {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    let other_pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let pool_addrs = vec![context.pool_addr, other_pool_addr];
+    (context, factory_addr, pool_addrs, account)
+}
+
+fn get_moj_amounts(context: &mut Context, pool_addrs: &[ComponentAddress]) -> Vec<Decimal> {
+    let context_pool_addr = context.pool_addr;
+    let amounts = pool_addrs
+        .iter()
+        .map(|pool_addr| {
+            context.pool_addr = *pool_addr;
+            context.get_pool_state().amount0
+        })
+        .collect();
+    context.pool_addr = context_pool_addr;
+    amounts
+}
+
+/**
+ * Split swap with weights.
+ *
+ * We test that the swapped amount is split across the pools according to the weights and that the weights must match the pools.
+ */
+#[test]
+fn swap_with_weights() {
+    let (mut context, _, pool_addrs, account) = new_context();
+
+    context.swap_moj_with_weights(&account, pool_addrs.clone(), vec![dec!("1")], dec!("400")).expect_commit_failure();
+    context.swap_moj_with_weights(&account, pool_addrs.clone(), vec![dec!("0"), dec!("0")], dec!("400")).expect_commit_failure();
+
+    let initial_moj_amounts = get_moj_amounts(&mut context, &pool_addrs);
+    context.swap_moj_with_weights(&account, pool_addrs.clone(), vec![dec!("3"), dec!("1")], dec!("400")).expect_commit_success();
+    let moj_amounts = get_moj_amounts(&mut context, &pool_addrs);
+    assert_eq!(dec!("300"), moj_amounts[0] - initial_moj_amounts[0]);
+    assert_eq!(dec!("100"), moj_amounts[1] - initial_moj_amounts[1]);
+    let (moj_balance, usdt_balance) = context.get_balances(&account);
+    assert_eq!(dec!("600"), moj_balance);
+    assert!(usdt_balance > dec!("390") && usdt_balance < dec!("400"));
+}
+
+/**
+ * Greedy split swap.
+ *
+ * We test that a large swap split greedily across the pools gets more USDT than the same swap in the deepest pool alone, both pools
+ * being used.
+ */
+#[test]
+fn swap_greedy() {
+    let (mut context, _, pool_addrs, account) = new_context();
+    let initial_moj_amounts = get_moj_amounts(&mut context, &pool_addrs);
+    let snapshot = context.snapshot();
+
+    context.swap_moj_with_weights(&account, vec![pool_addrs[0]], vec![dec!("1")], dec!("1000")).expect_commit_success();
+    let (_, single_pool_usdt_balance) = context.get_balances(&account);
+
+    let mut context = Context::from_snapshot(&snapshot);
+    context.swap_moj_greedy(&account, pool_addrs.clone(), 0, dec!("1000")).expect_commit_failure();
+    context.swap_moj_greedy(&account, pool_addrs.clone(), 20, dec!("1000")).expect_commit_success();
+    let (moj_balance, greedy_usdt_balance) = context.get_balances(&account);
+    assert_eq!(Decimal::zero(), moj_balance);
+    assert!(greedy_usdt_balance > single_pool_usdt_balance);
+
+    let moj_amounts = get_moj_amounts(&mut context, &pool_addrs);
+    assert!(moj_amounts[0] > initial_moj_amounts[0] && moj_amounts[1] > initial_moj_amounts[1]);
+    assert_eq!(dec!("1000"), moj_amounts[0] + moj_amounts[1] - initial_moj_amounts[0] - initial_moj_amounts[1]);
+}
+
+/**
+ * Best quote.
+ *
+ * We test that the factory quotes a swap in the pool with the largest output, matching the output of the actual swap, and that no
+ * quote is returned for a pair without pools.
+ */
+#[test]
+fn best_quote() {
+    let (mut context, factory_addr, pool_addrs, account) = new_context();
+    let (moj_addr, usdt_addr) = (context.moj_addr, context.usdt_addr);
+    let admin = context.admin.clone();
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.0005"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("100"),
+        dec!("100"),
+    );
+    let cheap_pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    assert_eq!(None, context.best_quote(factory_addr, moj_addr, context.admin_badge_addr, dec!("100")));
+
+    // the cheaper pool gives the best output for a small swap, the deeper factory pool for a swap draining the cheaper one
+    let quote = context.best_quote(factory_addr, usdt_addr, moj_addr, dec!("1")).unwrap();
+    assert_eq!(cheap_pool_addr, quote.pool_addr);
+    let quote = context.best_quote(factory_addr, moj_addr, usdt_addr, dec!("400")).unwrap();
+    assert_eq!(pool_addrs[1], quote.pool_addr);
+    assert_eq!(Decimal::zero(), quote.remainder);
+
+    context.swap_moj_with_weights(&account, vec![quote.pool_addr], vec![dec!("1")], dec!("400")).expect_commit_success();
+    assert_eq!((dec!("600"), quote.amount_out), context.get_balances(&account));
+}
+
+/**
+ * Best quote in any resource order.
+ *
+ * We test that the factory quotes the pools of a pair created with the resources in any order.
+ */
+#[test]
+fn best_quote_any_resource_order() {
+    let (mut context, factory_addr, pool_addrs, _) = new_context();
+    let (moj_addr, usdt_addr) = (context.moj_addr, context.usdt_addr);
+    let admin = context.admin.clone();
+    let receipt = context.create_reversed_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.0005"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1200)),
+        tick_math::sqrt_price_at_tick(Tick(1200)),
+        dec!("100"),
+        dec!("100"),
+    );
+    let reversed_pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+
+    // the cheaper reversed pool gives the best output for a small swap, the deeper factory pool for a swap draining it
+    assert_eq!(reversed_pool_addr, context.best_quote(factory_addr, moj_addr, usdt_addr, dec!("1")).unwrap().pool_addr);
+    assert_eq!(reversed_pool_addr, context.best_quote(factory_addr, usdt_addr, moj_addr, dec!("1")).unwrap().pool_addr);
+    assert_eq!(pool_addrs[1], context.best_quote(factory_addr, moj_addr, usdt_addr, dec!("400")).unwrap().pool_addr);
+}
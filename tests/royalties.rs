@@ -0,0 +1,43 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, ParameterChangePreview};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Swap royalties.
+ *
+ * We test that the swaps pay no royalty by default, that they pay the royalty set with a timelocked parameter change and that the
+ * admin claims the paid royalties.
+ */
+#[test]
+fn swap_royalties() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let admin_addr = context.admin.addr;
+    let xrd_balance = context.runner.get_component_balance(admin_addr, XRD);
+
+    context.swap_moj_for_usdt(&account, dec!("10"), Decimal::zero());
+    context.claim_royalties();
+    assert_eq!(xrd_balance, context.runner.get_component_balance(admin_addr, XRD));
+
+    let badge_addr = context.new_admin_badge(&account);
+    let change = ParameterChange::SetSwapRoyalty(dec!("1"));
+    context.call_with_badge(&account, badge_addr, "schedule_change", manifest_args!(change.clone())).expect_commit_failure();
+    context.apply_change(ParameterChange::SetSwapRoyalty(dec!("-1"))).expect_commit_failure();
+    let preview: ParameterChangePreview = context.try_preview_parameter_change(change.clone()).expect_commit_success().output(0);
+    assert_eq!(dec!("1"), preview.swap_royalty);
+    context.apply_change(change).expect_commit_success();
+    context.swap_moj_for_usdt(&account, dec!("10"), Decimal::zero());
+    context.swap_moj_for_usdt(&account, dec!("10"), Decimal::zero());
+    context.claim_royalties();
+    assert_eq!(xrd_balance + dec!("2"), context.runner.get_component_balance(admin_addr, XRD));
+}
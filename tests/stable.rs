@@ -0,0 +1,113 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, STABLE_MAX_TICKS_CROSSED};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Stable pool.
+ *
+ * We test that a stable pool is created with the guards of the stable pairs: a maximum of ticks crossed by a swap, the other pools
+ * having none, and a single swap price impact capped at 0.5%, the swaps moving the price off the peg more being aborted.
+ */
+#[test]
+fn stable_pool_guards() {
+    let mut context = Context::new_stable(
+        dec!("0.0001"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-10)),
+        tick_math::sqrt_price_at_tick(Tick(10)),
+        dec!("100"),
+        dec!("100"),
+    );
+    assert_eq!(Some(STABLE_MAX_TICKS_CROSSED), context.query_pool::<Option<u32>>("get_max_ticks_crossed"));
+    assert_eq!(Some(dec!("0.005")), context.query_pool::<Option<Decimal>>("get_max_single_swap_price_impact"));
+
+    // the narrow initial position near the peg is exhausted by ~100 MOJ, then the price moves along the wide position
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("100"));
+    context.add_pos(&account, dec!("100"), dec!("100"), -200, 200);
+    context.try_swap_moj_for_usdt(&account, dec!("10")).expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("400")).expect_commit_failure();
+
+    let mut context = Context::new(
+        dec!("0.0001"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-10)),
+        tick_math::sqrt_price_at_tick(Tick(10)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    assert_eq!(None, context.query_pool::<Option<u32>>("get_max_ticks_crossed"));
+    assert_eq!(None, context.query_pool::<Option<Decimal>>("get_max_single_swap_price_impact"));
+}
+
+/**
+ * Stable pool maximum ticks crossed.
+ *
+ * We test that a swap of a stable pool crossing more used ticks than the maximum stops at the last tick it can cross, the price
+ * landing on it, and gives back the remaining amount.
+ */
+#[test]
+fn stable_max_ticks_crossed() {
+    let mut context = Context::new_stable(
+        dec!("0.0001"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-10)),
+        tick_math::sqrt_price_at_tick(Tick(10)),
+        dec!("100"),
+        dec!("100"),
+    );
+    context.apply_change(ParameterChange::SetMaxSingleSwapPriceImpact(None)).expect_commit_success();
+    // the used ticks above the current price are 10, 11, ..., 10 + STABLE_MAX_TICKS_CROSSED + 1
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("10000"));
+    let max_ticks_crossed = STABLE_MAX_TICKS_CROSSED as i32;
+    for tick in 10..=10 + max_ticks_crossed {
+        context.add_pos(&account, dec!("1"), Decimal::zero(), tick, tick + 1);
+    }
+    let (moj_amount, usdt_amount) = context.get_balances(&account);
+
+    context.try_swap_usdt_for_moj(&account, dec!("1000")).expect_commit_success();
+    let pool_state = context.get_pool_state();
+    assert_eq!(tick_math::sqrt_price_at_tick(Tick(9 + max_ticks_crossed)), pool_state.sqrt_price);
+    let (new_moj_amount, new_usdt_amount) = context.get_balances(&account);
+    // the swap took the MOJ of the initial position and of the crossed positions only, giving back most of the USDT
+    assert!(new_moj_amount - moj_amount > dec!("190") && new_moj_amount - moj_amount < dec!("200"));
+    assert!(usdt_amount - new_usdt_amount < dec!("250"));
+}
+
+/**
+ * Stable fee tier.
+ *
+ * We test that the factory enables the stable fee tier, 0.005% with a tick spacing of 1, and creates its pools as stable pools.
+ */
+#[test]
+fn stable_fee_tier() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let factory_addr = context.new_factory(dec!("100"), false);
+    let admin = context.admin.clone();
+    assert_eq!(Some(1), context.get_tick_spacing(factory_addr, dec!("0.00005")));
+    assert!(context.is_stable_fee_tier(factory_addr, dec!("0.00005")));
+    assert!(!context.is_stable_fee_tier(factory_addr, dec!("0.0001")));
+
+    let receipt = context.create_pool_with_factory(
+        factory_addr,
+        &admin,
+        dec!("0.00005"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-10)),
+        tick_math::sqrt_price_at_tick(Tick(10)),
+        dec!("1000"),
+        dec!("1000"),
+    );
+    context.pool_addr = receipt.expect_commit_success().new_component_addresses()[0];
+    assert_eq!(Some(STABLE_MAX_TICKS_CROSSED), context.query_pool::<Option<u32>>("get_max_ticks_crossed"));
+    assert_eq!(dec!("0.00005"), context.query_pool::<Decimal>("get_fee"));
+}
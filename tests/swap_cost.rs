@@ -0,0 +1,86 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * The width in ticks of each position added by the benchmark
+ */
+const POS_TICK_WIDTH: i32 = 10;
+
+/**
+ * Measures the execution cost units of a swap that crosses ticks_to_cross used ticks, in a pool holding positions + 1 positions.
+ *
+ * Given a pool with fee=0.01, sqrt_price=1 and a position=[10000 MOJ + 10000 USDT, -1000, 1000], the benchmark adds the given number of
+ * adjacent positions [10, 20], [20, 30], ... holding 100 MOJ each, then it swaps the USDT amount needed to move the price just past the
+ * ticks_to_cross-th used tick above the current price.
+ */
+fn measure_swap_cost(positions: i32, ticks_to_cross: i32) -> u32 {
+    assert!(ticks_to_cross <= positions + 1, "Not enough used ticks to cross.");
+
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000000"), dec!("1000000"));
+    for i in 1..=positions {
+        context.add_pos(
+            &account,
+            dec!("100"),
+            Decimal::zero(),
+            i * POS_TICK_WIDTH,
+            (i + 1) * POS_TICK_WIDTH,
+        );
+    }
+
+    // the used ticks above the current price are 10, 20, ..., so we move the price in the middle of the next range
+    let target_tick = ticks_to_cross * POS_TICK_WIDTH + POS_TICK_WIDTH / 2;
+    let (_resource_addr, usdt_amount) = context.amount_to_move_price(target_tick);
+
+    let swap_receipt = context.swap_usdt_for_moj(&account, usdt_amount, Decimal::zero());
+    swap_receipt.fee_summary.total_execution_cost_units_consumed
+}
+
+/**
+ * Swap cost benchmark.
+ *
+ * Prints the execution cost units of a swap as a function of the ticks crossed and of the positions open in the pool. Depending on
+ * the machine, this test can take a few minutes to run, so by default is disabled. Run it with:
+ *
+ *     scrypto test - swap_cost -- --ignored --nocapture
+ */
+#[test]
+#[ignore]
+fn swap_cost() {
+    let mut results = Vec::new();
+    for (positions, ticks_to_cross) in [
+        (0, 0),
+        (10, 0),
+        (10, 1),
+        (10, 5),
+        (10, 10),
+        (50, 0),
+        (50, 10),
+        (50, 25),
+        (50, 50),
+        (100, 0),
+        (100, 100),
+    ] {
+        results.push((
+            positions,
+            ticks_to_cross,
+            measure_swap_cost(positions, ticks_to_cross),
+        ));
+    }
+
+    println!("| positions | ticks crossed | execution cost units |");
+    println!("|-----------|---------------|----------------------|");
+    for (positions, ticks_to_cross, cost_units) in results {
+        println!("| {:>9} | {:>13} | {:>20} |", positions, ticks_to_cross, cost_units);
+    }
+}
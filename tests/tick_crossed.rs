@@ -0,0 +1,50 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{CrossDirection, PositionAddedEvent, TickCrossedEvent};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Tick crossed events.
+ *
+ * We test that the swaps emit an event for each tick crossed, with the tick net liquidity and the direction of the crossing, and
+ * none for the swaps that don't cross a tick.
+ */
+#[test]
+fn tick_crossed_events() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("1000"));
+    let receipt = context.add_pos(&account, dec!("100"), dec!("100"), -20, 20);
+    let pos_liq = context.get_events::<PositionAddedEvent>(&receipt, "PositionAddedEvent")[0].liq;
+
+    let receipt = context.try_swap_moj_for_usdt(&account, dec!("10"));
+    assert!(context.get_events::<TickCrossedEvent>(&receipt, "TickCrossedEvent").is_empty());
+
+    let receipt = context.try_swap_moj_for_usdt(&account, dec!("500"));
+    assert_eq!(
+        vec![TickCrossedEvent {
+            tick: -20,
+            liquidity_net: pos_liq,
+            direction: CrossDirection::Down,
+        }],
+        context.get_events::<TickCrossedEvent>(&receipt, "TickCrossedEvent")
+    );
+
+    let receipt = context.swap_usdt_for_moj(&account, dec!("400"), Decimal::zero());
+    assert_eq!(
+        vec![TickCrossedEvent {
+            tick: -20,
+            liquidity_net: pos_liq,
+            direction: CrossDirection::Up,
+        }],
+        context.get_events::<TickCrossedEvent>(&receipt, "TickCrossedEvent")
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * The number of sqrt prices whose ticks are computed in each benchmark transaction
+ */
+const SQRT_PRICES_PER_TX: i32 = 100;
+
+/**
+ * tick_at_sqrt_price benchmark.
+ *
+ * Prints the execution cost units of computing the ticks of 100 sqrt prices on ledger, with the division based tick_at_sqrt_price and
+ * with the log based tick_at_sqrt_price_log, for ticks around some price magnitudes, together with the cost per tick difference. The
+ * sqrt prices are half way between the sqrt prices of consecutive ticks. Depending on the machine, this test can take a minute to run,
+ * so by default is disabled. Run it with:
+ *
+ *     scrypto test - tick_math_cost -- --ignored --nocapture
+ */
+#[test]
+#[ignore]
+fn tick_math_cost() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+
+    let bench_package_addr = context.publish_tick_math_bench();
+    let mut results = Vec::new();
+    for start_tick in [-600000, -100000, -1000, 0, 1000, 100000, 900000] {
+        let ticks: Vec<Tick> = (start_tick..start_tick + SQRT_PRICES_PER_TX).map(Tick).collect();
+        let sqrt_prices: Vec<SqrtPrice> = ticks
+            .iter()
+            .map(|tick| {
+                let low_sqrt_price = tick_math::sqrt_price_at_tick(*tick).0;
+                let high_sqrt_price = tick_math::sqrt_price_at_tick(Tick(tick.0 + 1)).0;
+                SqrtPrice((low_sqrt_price + high_sqrt_price) / dec!("2"))
+            })
+            .collect();
+
+        let (division_ticks, division_cost_units) =
+            context.compute_ticks_on_ledger(bench_package_addr, "tick_at_sqrt_price", &sqrt_prices);
+        let (log_ticks, log_cost_units) =
+            context.compute_ticks_on_ledger(bench_package_addr, "tick_at_sqrt_price_log", &sqrt_prices);
+        // between ticks, the division based implementation doesn't always return the lower tick, so we compare only the log based one
+        assert_eq!(log_ticks, ticks);
+        assert_eq!(division_ticks.len(), ticks.len());
+
+        results.push((start_tick, division_cost_units, log_cost_units));
+    }
+
+    println!("| ticks            | division cost units | log cost units | cost units per tick difference |");
+    println!("|------------------|---------------------|----------------|--------------------------------|");
+    for (start_tick, division_cost_units, log_cost_units) in results {
+        println!(
+            "| {:>7}..{:<7} | {:>19} | {:>14} | {:>30} |",
+            start_tick,
+            start_tick + SQRT_PRICES_PER_TX,
+            division_cost_units,
+            log_cost_units,
+            (division_cost_units as i64 - log_cost_units as i64) / SQRT_PRICES_PER_TX as i64
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use mojitoswap_bench::tick_at_sqrt_price_log;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use mojitoswap_pool::MIN_TICK;
+use scrypto::prelude::*;
+
+/**
+ * Uniswap V3 TickMath.getSqrtRatioAtTick test vectors: the tick, the sqrtPriceX96 returned by Uniswap (a Q64.96 fixed point number,
+ * rounded up) and the same value scaled to our representation, sqrtPriceX96 * 10^18 / 2^96, truncated.
+ *
+ * The ticks are the powers of 2, which select single entries of the pre-computed PRICES / INV_PRICES tables, the bounds of the
+ * tick range shared with Uniswap [MIN_TICK, 887272] and a few ticks in between.
+ */
+const UNISWAP_SQRT_PRICES: [(i32, &str, &str); 52] = [
+    (MIN_TICK, "1572757485065785", "0.000000000000019850"),
+    (-524288, "327099227039063107", "0.000000000004128572"),
+    (-500000, "1101692437043807371", "0.000000000013905313"),
+    (-262144, "160982827401375763736069", "0.000002031888943182"),
+    (-131072, "112935262922445818024280874", "0.001425443419845977"),
+    (-123456, "165272515621987480497246433", "0.002086032420507437"),
+    (-65536, "2991262837734375505310244437", "0.037755044958865792"),
+    (-50000, "6504256538020985011912221507", "0.082095259205966698"),
+    (-32768, "15394552875315951095595078918", "0.194306574667111540"),
+    (-16384, "34923947901690145425342545399", "0.440802194489899902"),
+    (-8192, "52601903197458624361810746400", "0.663929359563123931"),
+    (-4096, "64556580881331167221767657720", "0.814818605312325898"),
+    (-2048, "71517125791179246722882903168", "0.902673033446954090"),
+    (-1024, "75273969370139069689486932538", "0.950091065870505957"),
+    (-512, "77225761753129597550065289037", "0.974726149167296094"),
+    (-256, "78220554859095770638340573244", "0.987282203408577650"),
+    (-128, "78722746600537056721934508530", "0.993620754316543878"),
+    (-64, "78975050245229982702767995060", "0.996805274021232232"),
+    (-32, "79101505139923049997807806615", "0.998401359184387445"),
+    (-16, "79164808496886665658930780292", "0.999200359880032992"),
+    (-8, "79196479170490597288862688491", "0.999600099980003499"),
+    (-7, "79200438895458472326222447580", "0.999650078735564845"),
+    (-4, "79212319258289487113226433917", "0.999800029996000499"),
+    (-2, "79220240490215316061937756561", "0.999900009999000099"),
+    (-1, "79224201403219477170569942574", "0.999950003749687527"),
+    (0, "79228162514264337593543950336", "1.000000000000000000"),
+    (1, "79232123823359799118286999568", "1.000049998750062496"),
+    (2, "79236085330515764027303304732", "1.000100000000000000"),
+    (4, "79244008939048815603706035062", "1.000200010000000000"),
+    (7, "79255895837549753882639819015", "1.000350043752187527"),
+    (8, "79259858533276714757314932306", "1.000400060004000100"),
+    (16, "79291567232598584799939703905", "1.000800280056007000"),
+    (32, "79355022692464371645785046467", "1.001601200560182043"),
+    (64, "79482085999252804386437311142", "1.003204964963598014"),
+    (128, "79736823300114093921829183327", "1.006420201727613920"),
+    (256, "80248749790819932309965073893", "1.012881622445451097"),
+    (512, "81282483887344747381513967012", "1.025929181087729343"),
+    (1024, "83390072131320151908154831282", "1.052530684607338948"),
+    (2048, "87770609709833776024991924139", "1.107820842039993613"),
+    (4096, "97234110755111693312479820774", "1.227267018058200482"),
+    (8192, "119332217159966728226237229891", "1.506184333613467388"),
+    (16384, "179736315981702064433883588728", "2.268591246822644826"),
+    (32768, "407748233172238350107850275305", "5.146506245160322222"),
+    (50000, "965075977353221155028623082916", "12.180971345630635666"),
+    (65536, "2098478828474011932436660412518", "26.486526531474198664"),
+    (123456, "37980312163600838849089827163598", "479.378934943276210474"),
+    (131072, "55581415166113811149459800483534", "701.536087702486644953"),
+    (262144, "38992368544603139932233054999993536", "492152.882348911033633683"),
+    (500000, "5697689776495288729098254600827762987878", "71914955436.073246388030734903"),
+    (524288, "19190206568837448476620805525116361302670", "242214459604.341065650571799133"),
+    (700000, "125437238852717229072740403726643244412679815", "1583240540636460.570561849024827094"),
+    (887272, "1461446703485210103287273052203988822378723970342", "18446050711097703530.314034002219689208"),
+];
+
+/**
+ * Our sqrt prices agree with the Uniswap ones either within 10 units of the 18th decimal, for the small sqrt prices which have only a
+ * few significant digits, or within a relative error of 10^-12. The relative error grows with the tick, up to ~8.3 * 10^-13 at tick
+ * 887272, because the Decimal constants are squared from √1.0001 truncated to 18 decimals, while the Uniswap values are within 2^-96
+ * of the exact ones.
+ */
+#[test]
+fn sqrt_price_at_tick_matches_uniswap() {
+    for (tick, _, uniswap_sqrt_price) in UNISWAP_SQRT_PRICES {
+        let uniswap_sqrt_price = uniswap_sqrt_price.parse::<Decimal>().unwrap();
+        let sqrt_price = tick_math::sqrt_price_at_tick(Tick(tick)).0;
+        let diff = if sqrt_price > uniswap_sqrt_price { sqrt_price - uniswap_sqrt_price } else { uniswap_sqrt_price - sqrt_price };
+        assert!(
+            diff <= dec!("0.00000000000000001") || diff <= uniswap_sqrt_price * dec!("0.000000000001"),
+            "tick: {}, sqrt price: {}, uniswap sqrt price: {}",
+            tick,
+            sqrt_price,
+            uniswap_sqrt_price
+        );
+    }
+}
+
+/**
+ * The Uniswap sqrt prices are rounded up and ours are truncated, but they are much closer than the distance to the next tick, so the
+ * tick of the Uniswap sqrt price is the same tick.
+ */
+#[test]
+fn tick_at_uniswap_sqrt_price() {
+    for (tick, _, uniswap_sqrt_price) in UNISWAP_SQRT_PRICES {
+        let uniswap_sqrt_price = SqrtPrice(uniswap_sqrt_price.parse::<Decimal>().unwrap());
+        assert_eq!(Tick(tick), tick_at_sqrt_price_log(uniswap_sqrt_price));
+    }
+}
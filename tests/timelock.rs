@@ -0,0 +1,228 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{
+    FeeRamp, ParameterChange, ParameterChangePreview, ParameterChangeScheduledEvent, ScheduledChange, INITIAL_TIMELOCK_DELAY,
+};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+// a time after the ledger genesis, rounded to minutes as the scheduled changes
+const START_TIME: i64 = 1_700_000_040;
+
+/**
+ * Timelock of the parameter changes.
+ *
+ * We test that a scheduled parameter change emits an event and can be executed only once its timelock delay passed, that a
+ * cancelled change can't be executed and that a new delay applies to the changes scheduled after it.
+ */
+#[test]
+fn timelock() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    let hook_addr = context.new_swap_limit_hook(dec!("100"));
+    assert_eq!(INITIAL_TIMELOCK_DELAY, context.query_pool::<i64>("get_timelock_delay"));
+
+    let change = ParameterChange::RegisterHook(hook_addr);
+    let receipt = context.call_as_admin("schedule_change", manifest_args!(change.clone()));
+    let events: Vec<ParameterChangeScheduledEvent> = context.get_events(&receipt, "ParameterChangeScheduledEvent");
+    assert_eq!(
+        vec![ParameterChangeScheduledEvent {
+            change_id: 0,
+            change: change.clone(),
+            execution_time: START_TIME + INITIAL_TIMELOCK_DELAY,
+        }],
+        events
+    );
+    assert_eq!(
+        vec![(0, ScheduledChange { change, execution_time: START_TIME + INITIAL_TIMELOCK_DELAY })],
+        context.query_pool::<Vec<(u64, ScheduledChange)>>("get_scheduled_changes")
+    );
+
+    context.set_current_time(START_TIME + INITIAL_TIMELOCK_DELAY - 60);
+    context.call_as_admin("execute_change", manifest_args!(0u64)).expect_commit_failure();
+    context.call_as_admin("cancel_change", manifest_args!(0u64)).expect_commit_success();
+    context.set_current_time(START_TIME + INITIAL_TIMELOCK_DELAY);
+    context.call_as_admin("execute_change", manifest_args!(0u64)).expect_commit_failure();
+    assert!(context.query_pool::<Vec<ComponentAddress>>("get_hooks").is_empty());
+
+    context.apply_change(ParameterChange::SetTimelockDelay(3600)).expect_commit_success();
+    assert_eq!(3600, context.query_pool::<i64>("get_timelock_delay"));
+    let current_time = context.get_current_time();
+    context.apply_change(ParameterChange::RegisterHook(hook_addr)).expect_commit_success();
+    assert_eq!(current_time + 3600, context.get_current_time());
+    assert_eq!(vec![hook_addr], context.query_pool::<Vec<ComponentAddress>>("get_hooks"));
+    assert!(context.query_pool::<Vec<(u64, ScheduledChange)>>("get_scheduled_changes").is_empty());
+}
+
+/**
+ * Two-step admin handover.
+ *
+ * We test that a proposed admin badge becomes the admin only once a holder of it accepts it, the previous admin badge losing access.
+ */
+#[test]
+fn admin_handover() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let admin = context.admin.clone();
+    let account = context.new_account_with_moj_and_usdt(Decimal::zero(), Decimal::zero());
+    let old_badge_addr = context.admin_badge_addr;
+    let new_badge_addr = context.new_admin_badge(&admin);
+    let change = ParameterChange::SetTimelockDelay(3600);
+
+    context.call_with_badge(&admin, new_badge_addr, "accept_admin", manifest_args!()).expect_commit_failure();
+    context.call_as_admin("propose_admin", manifest_args!(new_badge_addr)).expect_commit_success();
+    assert_eq!(Some(new_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_pending_admin_badge_addr"));
+    context.call_with_badge(&account, new_badge_addr, "accept_admin", manifest_args!()).expect_commit_failure();
+    context.call_with_badge(&admin, old_badge_addr, "accept_admin", manifest_args!()).expect_commit_failure();
+    context.call_as_admin("schedule_change", manifest_args!(change.clone())).expect_commit_success();
+
+    context.call_with_badge(&admin, new_badge_addr, "accept_admin", manifest_args!()).expect_commit_success();
+    assert_eq!(Some(new_badge_addr), context.query_pool::<Option<ResourceAddress>>("get_admin_badge_addr"));
+    assert_eq!(None, context.query_pool::<Option<ResourceAddress>>("get_pending_admin_badge_addr"));
+    context.call_with_badge(&admin, new_badge_addr, "accept_admin", manifest_args!()).expect_commit_failure();
+    context.call_as_admin("schedule_change", manifest_args!(change.clone())).expect_commit_failure();
+    context.admin_badge_addr = new_badge_addr;
+    context.call_as_admin("schedule_change", manifest_args!(change)).expect_commit_success();
+}
+
+/**
+ * Fee change.
+ *
+ * We test that the fee manager role changes the pool fee with a timelocked parameter change, within the maximum fee, and that the
+ * swaps pay the fee in force when they are done.
+ */
+#[test]
+fn fee_change() {
+    let mut context = Context::new(
+        dec!("0.003"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let fee_manager = context.new_account_with_moj_and_usdt(dec!("0"), dec!("0"));
+    let fee_manager_badge_addr = context.new_badge(&fee_manager, "Fee manager badge");
+    context.set_role("fee_manager", fee_manager_badge_addr).expect_commit_success();
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+
+    // an invalid change is rejected when scheduled, without waiting the timelock delay
+    context
+        .call_as_admin("schedule_change", manifest_args!(ParameterChange::SetFee(dec!("0.2"))))
+        .expect_commit_failure();
+    assert!(context.query_pool::<Vec<(u64, ScheduledChange)>>("get_scheduled_changes").is_empty());
+    let change = ParameterChange::SetFee(dec!("0.01"));
+    context.call_with_badge(&account, fee_manager_badge_addr, "schedule_change", manifest_args!(change.clone())).expect_commit_failure();
+    context.call_with_badge(&fee_manager, fee_manager_badge_addr, "schedule_change", manifest_args!(change)).expect_commit_success();
+    let current_time = context.get_current_time();
+    context.set_current_time(current_time + INITIAL_TIMELOCK_DELAY);
+    assert_eq!(dec!("0.003"), context.query_pool::<Decimal>("get_fee"));
+    context.call_with_badge(&fee_manager, fee_manager_badge_addr, "execute_change", manifest_args!(0u64)).expect_commit_success();
+    assert_eq!(dec!("0.01"), context.query_pool::<Decimal>("get_fee"));
+
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    let epoch = context.runner.get_current_epoch().number();
+    assert_eq!(dec!("1.3"), context.get_epoch_stats(epoch).unwrap().fees0);
+}
+
+/**
+ * Fee ramp.
+ *
+ * We test that a timelocked fee ramp moves the pool fee linearly from the current fee to the target fee across the given epochs,
+ * that the swaps pay the fee of their epoch and that the ramp ends at the target fee.
+ */
+#[test]
+fn fee_ramp() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+
+    context.apply_change(ParameterChange::RampFee(dec!("0.2"), 10)).expect_commit_failure();
+    context.apply_change(ParameterChange::RampFee(dec!("0.02"), 0)).expect_commit_failure();
+    context.apply_change(ParameterChange::RampFee(dec!("0.02"), 10)).expect_commit_success();
+    let epoch = context.runner.get_current_epoch().number();
+    assert_eq!(
+        Some(FeeRamp { start_fee: dec!("0.01"), target_fee: dec!("0.02"), start_epoch: epoch, end_epoch: epoch + 10 }),
+        context.query_pool::<Option<FeeRamp>>("get_fee_ramp")
+    );
+    assert_eq!(dec!("0.01"), context.query_pool::<Decimal>("get_fee"));
+
+    context.runner.set_current_epoch(Epoch::of(epoch + 5));
+    assert_eq!(dec!("0.015"), context.query_pool::<Decimal>("get_fee"));
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    assert_eq!(dec!("1.5"), context.get_epoch_stats(epoch + 5).unwrap().fees0);
+
+    context.runner.set_current_epoch(Epoch::of(epoch + 20));
+    assert_eq!(dec!("0.02"), context.query_pool::<Decimal>("get_fee"));
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    assert_eq!(dec!("2"), context.get_epoch_stats(epoch + 20).unwrap().fees0);
+    assert_eq!(None, context.query_pool::<Option<FeeRamp>>("get_fee_ramp"));
+}
+
+/**
+ * Parameter change preview.
+ *
+ * We test that the preview of a parameter change returns its effects if it was executed now, the ramp it replaces included, without
+ * applying it, and that the preview of an invalid change fails as its execution.
+ */
+#[test]
+fn preview_parameter_change() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    context.set_current_time(START_TIME);
+    context.apply_change(ParameterChange::RampFee(dec!("0.02"), 10)).expect_commit_success();
+    let epoch = context.runner.get_current_epoch().number();
+    let fee_ramp = FeeRamp { start_fee: dec!("0.01"), target_fee: dec!("0.02"), start_epoch: epoch, end_epoch: epoch + 10 };
+
+    let change = ParameterChange::SetFee(dec!("0.005"));
+    let preview: ParameterChangePreview =
+        context.try_preview_parameter_change(change.clone()).expect_commit_success().output(0);
+    assert_eq!(change, preview.change);
+    assert_eq!(context.get_current_time() + INITIAL_TIMELOCK_DELAY, preview.execution_time);
+    assert_eq!(1, preview.tick_spacing);
+    assert_eq!(dec!("0.005"), preview.fee_config.fee);
+    assert_eq!(None, preview.fee_config.fee_ramp);
+    assert_eq!(Some(fee_ramp.clone()), preview.replaced_fee_ramp);
+    assert_eq!(dec!("0.01"), context.query_pool::<Decimal>("get_fee"));
+    assert_eq!(Some(fee_ramp), context.query_pool::<Option<FeeRamp>>("get_fee_ramp"));
+
+    let preview: ParameterChangePreview = context
+        .try_preview_parameter_change(ParameterChange::SetMaxSingleSwapPriceImpact(Some(dec!("0.02"))))
+        .expect_commit_success()
+        .output(0);
+    assert_eq!(Some(dec!("0.02")), preview.max_single_swap_price_impact);
+    assert_eq!(dec!("0.01"), preview.fee_config.fee);
+    assert_eq!(None, preview.replaced_fee_ramp);
+
+    context.try_preview_parameter_change(ParameterChange::SetFee(dec!("0.2"))).expect_commit_failure();
+    context.try_preview_parameter_change(ParameterChange::SetTimelockDelay(-1)).expect_commit_failure();
+}
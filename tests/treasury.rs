@@ -0,0 +1,84 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::ParameterChange;
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use mojitoswap_pool::treasury::{TreasuryDepositEvent, TreasuryWithdrawalEvent};
+use scrypto::prelude::*;
+
+/**
+ * Protocol treasury.
+ *
+ * We test that anyone sweeps the protocol fees of the pools that assigned their treasury role to the treasury, in one call, with an
+ * event for each deposit, that a pool that didn't aborts the sweep and that only the admin and the withdrawer withdraw from the
+ * treasury.
+ */
+#[test]
+fn treasury() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let lp = context.new_account_with_moj_and_usdt(dec!("10000"), dec!("10000"));
+    let treasury_addr = context.new_treasury();
+    let admin_badge_addr = context.admin_badge_addr;
+    let pool_addr = context.pool_addr;
+    let other_pool_addr = context.new_pool_without_position(dec!("0.01"), 1, SqrtPrice(Decimal::one()));
+
+    for pool_addr in [pool_addr, other_pool_addr] {
+        context.use_pool(pool_addr);
+        if pool_addr == other_pool_addr {
+            context.try_add_pos(&lp, dec!("10000"), dec!("10000"), -1000, 1000).expect_commit_success();
+        }
+        context.apply_change(ParameterChange::SetProtocolFeeShare(dec!("0.25"))).expect_commit_success();
+        context.set_role("fee_switcher", admin_badge_addr).expect_commit_success();
+        context.call_as_admin("set_protocol_fee_switch", manifest_args!(true)).expect_commit_success();
+        context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    }
+
+    context.sweep_treasury(&account, treasury_addr, vec![pool_addr, other_pool_addr]).expect_commit_failure();
+    for pool_addr in [pool_addr, other_pool_addr] {
+        context
+            .set_component_role(pool_addr, "treasury", rule!(require(global_caller(treasury_addr))))
+            .expect_commit_success();
+    }
+    let receipt = context.sweep_treasury(&account, treasury_addr, vec![pool_addr, other_pool_addr]);
+    let events: Vec<TreasuryDepositEvent> = context.get_events(&receipt, "TreasuryDepositEvent");
+    let moj_addr = context.moj_addr;
+    assert_eq!(
+        vec![
+            TreasuryDepositEvent { source_addr: Some(pool_addr), resource_addr: moj_addr, amount: dec!("0.25") },
+            TreasuryDepositEvent { source_addr: Some(other_pool_addr), resource_addr: moj_addr, amount: dec!("0.25") },
+        ],
+        events
+    );
+    assert_eq!(vec![(moj_addr, dec!("0.5")), (context.usdt_addr, Decimal::zero())], context.get_treasury_balances(treasury_addr));
+
+    // nothing accrued since the last sweep
+    let receipt = context.sweep_treasury(&account, treasury_addr, vec![pool_addr]);
+    assert!(context.get_events::<TreasuryDepositEvent>(&receipt, "TreasuryDepositEvent").is_empty());
+
+    let withdrawer = context.new_account_with_moj_and_usdt(dec!("0"), dec!("0"));
+    let withdrawer_badge_addr = context.new_badge(&withdrawer, "Withdrawer badge");
+    context
+        .withdraw_from_treasury(&withdrawer, withdrawer_badge_addr, treasury_addr, moj_addr, dec!("0.1"))
+        .expect_commit_failure();
+    context
+        .set_component_role(treasury_addr, "withdrawer", rule!(require(withdrawer_badge_addr)))
+        .expect_commit_success();
+    let receipt = context.withdraw_from_treasury(&withdrawer, withdrawer_badge_addr, treasury_addr, moj_addr, dec!("0.1"));
+    let events: Vec<TreasuryWithdrawalEvent> = context.get_events(&receipt, "TreasuryWithdrawalEvent");
+    assert_eq!(vec![TreasuryWithdrawalEvent { resource_addr: moj_addr, amount: dec!("0.1") }], events);
+    assert_eq!(dec!("0.1"), context.get_balances(&withdrawer).0);
+
+    let admin = context.admin.clone();
+    context.withdraw_from_treasury(&admin, admin_badge_addr, treasury_addr, moj_addr, dec!("1")).expect_commit_failure();
+    context.withdraw_from_treasury(&admin, admin_badge_addr, treasury_addr, moj_addr, dec!("0.4")).expect_commit_success();
+    let usdt_addr = context.usdt_addr;
+    assert_eq!(vec![(moj_addr, Decimal::zero()), (usdt_addr, Decimal::zero())], context.get_treasury_balances(treasury_addr));
+}
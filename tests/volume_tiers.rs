@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use mojitoswap_pool::pool::{ParameterChange, VolumeTier, VOLUME_WINDOW_EPOCHS};
+use mojitoswap_pool::tick_math::{self, SqrtPrice, Tick};
+use scrypto::prelude::*;
+
+/**
+ * Volume tiered fee discounts.
+ *
+ * We test that the volume tiers are set with a timelocked parameter change, in ascending order, that the swaps done as a trader
+ * accrue its trailing volume, measured in USDT, and get the fee discount of the tier reached before the swap, that the plain swaps
+ * get no discount and that the volume expires after VOLUME_WINDOW_EPOCHS epochs.
+ */
+#[test]
+fn volume_tier_discounts() {
+    let mut context = Context::new(
+        dec!("0.01"),
+        SqrtPrice(Decimal::one()),
+        tick_math::sqrt_price_at_tick(Tick(-1000)),
+        tick_math::sqrt_price_at_tick(Tick(1000)),
+        dec!("10000"),
+        dec!("10000"),
+    );
+    let account = context.new_account_with_moj_and_usdt(dec!("1000"), dec!("0"));
+    let trader_id = context.new_trader_badge(&account);
+
+    let tiers = vec![
+        VolumeTier { min_volume: dec!("50"), fee_discount: dec!("0.5") },
+        VolumeTier { min_volume: dec!("1000"), fee_discount: dec!("0.75") },
+    ];
+    let mut unordered_tiers = tiers.clone();
+    unordered_tiers.reverse();
+    context.apply_change(ParameterChange::SetVolumeTiers(unordered_tiers)).expect_commit_failure();
+    context.apply_change(ParameterChange::SetVolumeTiers(tiers.clone())).expect_commit_success();
+    assert_eq!(tiers, context.query_pool::<Vec<VolumeTier>>("get_volume_tiers"));
+
+    let epoch = context.runner.get_current_epoch().number();
+    let receipt = context.try_swap_moj_for_usdt_as_trader(&account, &trader_id, dec!("100"));
+    receipt.expect_commit_success();
+    let usdt_amount = context.get_swap_events(&receipt)[0].amount_out;
+    assert_eq!(usdt_amount, context.get_trader_volume(&trader_id));
+    assert_eq!(dec!("1"), context.get_epoch_stats(epoch).unwrap().fees0);
+
+    //the first tier is reached, the fee is halved
+    let receipt = context.try_swap_moj_for_usdt_as_trader(&account, &trader_id, dec!("100"));
+    receipt.expect_commit_success();
+    let usdt_amount = usdt_amount + context.get_swap_events(&receipt)[0].amount_out;
+    assert_eq!(usdt_amount, context.get_trader_volume(&trader_id));
+    assert_eq!(dec!("1.5"), context.get_epoch_stats(epoch).unwrap().fees0);
+
+    context.try_swap_moj_for_usdt(&account, dec!("100")).expect_commit_success();
+    assert_eq!(usdt_amount, context.get_trader_volume(&trader_id));
+    assert_eq!(dec!("2.5"), context.get_epoch_stats(epoch).unwrap().fees0);
+
+    context.runner.set_current_epoch(Epoch::of(epoch + VOLUME_WINDOW_EPOCHS));
+    assert_eq!(Decimal::zero(), context.get_trader_volume(&trader_id));
+}